@@ -1,6 +1,6 @@
-use crate::SkiaWindow;
+use crate::{present_damage, SkiaWindow};
 use parking_lot::Mutex;
-use skia_safe::{ISize, ImageInfo, Surface};
+use skia_safe::{IRect, ISize, ImageInfo, Surface};
 use softbuffer::SoftBufferError;
 use std::num::NonZeroU32;
 use std::ops::Deref;
@@ -23,8 +23,8 @@ fn create_surface(size: impl Into<PhysicalSize<u32>>) -> Arc<Mutex<Surface>> {
 }
 
 impl SoftSkiaWindow {
-    pub fn new(window: Window) -> Self {
-        let window = Arc::new(window);
+    pub fn new(window: impl Into<Arc<Window>>) -> Self {
+        let window = window.into();
         let size = window.inner_size();
         let skia_surface = create_surface(size);
         let soft_buffer_context = softbuffer::Context::new(window.clone()).unwrap();
@@ -69,6 +69,10 @@ impl SkiaWindow for SoftSkiaWindow {
         );
         soft_buffer.present().unwrap();
     }
+
+    fn present_damage(&mut self, damage: &[IRect]) {
+        present_damage(&self.skia_surface, &mut self.soft_buffer_surface, damage);
+    }
 }
 
 impl Deref for SoftSkiaWindow {
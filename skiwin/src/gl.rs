@@ -25,7 +25,10 @@ pub struct GlSkiaWindow {
 }
 
 impl GlSkiaWindow {
-    pub fn new(window: Window, device_selector: Option<Box<dyn Fn(&Device) -> bool>>) -> Self {
+    pub fn new(
+        window: impl Into<Arc<Window>>,
+        device_selector: Option<Box<dyn Fn(&Device) -> bool>>,
+    ) -> Self {
         let devices = Device::query_devices()
             .expect("Failed to query devices")
             .collect::<Vec<_>>();
@@ -88,7 +91,7 @@ impl GlSkiaWindow {
 
         let mut skia_context = skia_safe::gpu::direct_contexts::make_gl(interface, None).unwrap();
 
-        let window = Arc::new(window);
+        let window = window.into();
         let size = window.inner_size();
         let skia_surface = create_surface(&mut skia_context, size);
         let soft_buffer_context = softbuffer::Context::new(window.clone()).unwrap();
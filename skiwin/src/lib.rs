@@ -12,8 +12,9 @@ pub use glutin;
 
 use parking_lot::Mutex;
 use skia_safe::gpu::{Budgeted, DirectContext, SurfaceOrigin};
-use skia_safe::{ImageInfo, Surface};
+use skia_safe::{IRect, ImageInfo, Surface};
 use softbuffer::SoftBufferError;
+use std::num::NonZeroU32;
 use std::ops::Deref;
 use std::sync::Arc;
 use winit::dpi::PhysicalSize;
@@ -24,6 +25,60 @@ pub trait SkiaWindow: Deref<Target = Window> {
     fn resize(&mut self) -> Result<(), SoftBufferError>;
     fn surface(&self) -> Arc<Mutex<Surface>>;
     fn present(&mut self);
+    /// Presents only the `damage` rectangles, in physical pixels. The rest of the window keeps
+    /// showing the previous frame.
+    fn present_damage(&mut self, damage: &[IRect]) {
+        let _ = damage;
+        self.present();
+    }
+}
+
+/// Copies the damaged rectangles of the Skia surface into the next soft buffer and presents them.
+/// The whole surface is copied if the buffer doesn't hold the previous frame.
+pub(crate) fn present_damage(
+    skia_surface: &Mutex<Surface>,
+    soft_buffer_surface: &mut softbuffer::Surface<Arc<Window>, Arc<Window>>,
+    damage: &[IRect],
+) {
+    let size = soft_buffer_surface.window().inner_size();
+    let bounds = IRect::from_wh(size.width as i32, size.height as i32);
+    let row_bytes = size.width as usize * 4;
+    let mut soft_buffer = soft_buffer_surface.buffer_mut().unwrap();
+    // A buffer of age 1 was presented last, so it only misses the damaged pixels.
+    let damage: Vec<IRect> = if soft_buffer.age() == 1 {
+        damage
+            .iter()
+            .filter_map(|rect| IRect::intersect(rect, &bounds))
+            .collect()
+    } else {
+        vec![bounds]
+    };
+    {
+        let u8_slice = bytemuck::cast_slice_mut::<u32, u8>(&mut soft_buffer);
+        let mut skia_surface = skia_surface.lock();
+        for rect in &damage {
+            let image_info = ImageInfo::new_n32_premul((rect.width(), rect.height()), None);
+            let offset = rect.top as usize * row_bytes + rect.left as usize * 4;
+            skia_surface.read_pixels(
+                &image_info,
+                &mut u8_slice[offset..],
+                row_bytes,
+                (rect.left, rect.top),
+            );
+        }
+    }
+    let damage: Vec<softbuffer::Rect> = damage
+        .iter()
+        .filter_map(|rect| {
+            Some(softbuffer::Rect {
+                x: rect.left as u32,
+                y: rect.top as u32,
+                width: NonZeroU32::new(rect.width() as u32)?,
+                height: NonZeroU32::new(rect.height() as u32)?,
+            })
+        })
+        .collect();
+    soft_buffer.present_with_damage(&damage).unwrap();
 }
 
 pub(crate) fn create_surface(
@@ -85,6 +140,10 @@ macro_rules! impl_skia_window {
                 );
                 soft_buffer.present().unwrap();
             }
+
+            fn present_damage(&mut self, damage: &[skia_safe::IRect]) {
+                $crate::present_damage(&self.skia_surface, &mut self.soft_buffer_surface, damage);
+            }
         }
 
         impl Deref for $ty {
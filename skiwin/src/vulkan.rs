@@ -26,9 +26,10 @@ pub struct VulkanSkiaWindow {
 
 impl VulkanSkiaWindow {
     pub fn new(
-        window: Window,
+        window: impl Into<Arc<Window>>,
         device_selector: Option<Box<dyn Fn(&PhysicalDevice) -> bool>>,
     ) -> Self {
+        let window = window.into();
         let vulkan_context = VulkanContext::new(window.title().as_str(), device_selector);
         let mut skia_context = {
             let get_proc = |of| unsafe {
@@ -57,7 +58,6 @@ impl VulkanSkiaWindow {
             skia_safe::gpu::direct_contexts::make_vulkan(&backend_context, None).unwrap()
         };
 
-        let window = Arc::new(window);
         let size = window.inner_size();
         let skia_surface = create_surface(&mut skia_context, size);
         let soft_buffer_context = softbuffer::Context::new(window.clone()).unwrap();
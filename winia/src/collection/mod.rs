@@ -11,6 +11,8 @@ pub enum Operation {
     Add(usize),
     Remove(usize),
     Update(usize),
+    /// An item moved from the first index to the second one.
+    Move(usize, usize),
     Clear,
     Other,
 }
\ No newline at end of file
@@ -29,6 +29,13 @@ impl<T> WVec<T> {
         self.operations.lock().push_back(Operation::Remove(index));
         self.vec.remove(index)
     }
+
+    /// Moves the item at `from` to `to`, the items in between shift by one.
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let item = self.vec.remove(from);
+        self.vec.insert(to, item);
+        self.operations.lock().push_back(Operation::Move(from, to));
+    }
     
     pub fn retain<F>(&mut self, mut f: F)
     where
@@ -0,0 +1,23 @@
+//! The `tracing` targets of the subsystems of winia, so their events can be filtered one by one,
+//! for example with `RUST_LOG=winia::layout=debug` and `tracing_subscriber::EnvFilter`.
+
+/// Window creation and the event loop.
+pub const APP: &str = "winia::app";
+/// The dispatch of window and user events to the items.
+pub const EVENT: &str = "winia::event";
+pub const LAYOUT: &str = "winia::layout";
+/// Recording the items into snapshots.
+pub const DRAW: &str = "winia::draw";
+/// Rasterizing and presenting on the render thread.
+pub const RENDER: &str = "winia::render";
+pub const FOCUS: &str = "winia::focus";
+/// The names items are found by.
+pub const NAME: &str = "winia::name";
+/// Styles parsed from TOML.
+pub const STYLE: &str = "winia::style";
+/// Observers and notifications of shared values.
+pub const SHARED: &str = "winia::shared";
+pub const PERSISTENCE: &str = "winia::persistence";
+pub const ROUTER: &str = "winia::router";
+/// Loading images and other resources.
+pub const RESOURCE: &str = "winia::resource";
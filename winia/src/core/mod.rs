@@ -1,4 +1,7 @@
+pub mod log_target;
+
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
 use lazy_static::lazy_static;
 use std::sync::Mutex;
@@ -15,17 +18,45 @@ pub fn next_id() -> usize {
     *id
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    /// The string used to bind to an id is empty.
+    Empty,
+    /// The string is already bound to another id.
+    AlreadyBound { name: String, id: usize },
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "The string used to bind to an id cannot be empty"),
+            NameError::AlreadyBound { name, id } => {
+                write!(f, "{} has already been bound to the id {}", name, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
 /// Bind a string to an id
 /// Use [get_id_by_name] to get the id by the string
-pub fn bind_str_to_id(s: &str, id: usize) {
+pub fn bind_str_to_id(s: &str, id: usize) -> Result<(), NameError> {
     if s.is_empty() {
-        panic!("The string used to bind to an id cannot be empty");
+        return Err(NameError::Empty);
     }
     let mut str_to_id = STR_TO_ID.lock().unwrap();
-    if str_to_id.contains_key(s) {
-        panic!("The string has already been bound to an id");
+    match str_to_id.get(s) {
+        Some(bound) if *bound == id => Ok(()),
+        Some(bound) => Err(NameError::AlreadyBound {
+            name: s.to_string(),
+            id: *bound,
+        }),
+        None => {
+            str_to_id.insert(s.to_string(), id);
+            Ok(())
+        }
     }
-    str_to_id.insert(s.to_string(), id);
 }
 
 /// Get the id by the string
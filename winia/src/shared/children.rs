@@ -21,34 +21,7 @@ impl Children {
     }
 
     pub fn insert_with_animation(&self, index: usize, item: Item) {
-        let visible = item.data().get_visible().clone();
-        if visible.get() {
-            visible.set(false);
-        }
-
-        {
-            let mut animated = false;
-            let layout = item.data().get_layout();
-            item.data().set_layout(move |item, w, h| {
-                if !animated {
-                    item.get_window_context()
-                        .animate(exclude_target!())
-                        .transformation({
-                            clone!(visible);
-                            move || {
-                                if !visible.get() {
-                                    visible.set(true);
-                                }
-                            }
-                        })
-                        .duration(Duration::from_millis(500))
-                        .start();
-                    animated = true;
-                }
-                let mut layout = layout.lock();
-                layout(item, w, h)
-            });
-        }
+        animate_appearance(&item, Duration::from_millis(500));
         self.lock().insert(index, item);
         self.notify();
     }
@@ -87,6 +60,37 @@ impl Children {
     }
 }
 
+/// Hides the item and makes it fade in the first time it is laid out.
+/// The item is laid out once while invisible so that the animation has a starting point.
+pub(crate) fn animate_appearance(item: &Item, duration: Duration) {
+    let visible = item.data().get_visible().clone();
+    if visible.get() {
+        visible.set(false);
+    }
+
+    let mut animated = false;
+    let layout = item.data().get_layout();
+    item.data().set_layout(move |item, w, h| {
+        if !animated {
+            item.get_window_context()
+                .animate(exclude_target!())
+                .transformation({
+                    clone!(visible);
+                    move || {
+                        if !visible.get() {
+                            visible.set(true);
+                        }
+                    }
+                })
+                .duration(duration)
+                .start();
+            animated = true;
+        }
+        let mut layout = layout.lock();
+        layout(item, w, h)
+    });
+}
+
 impl Add<Item> for Children {
     type Output = Self;
 
@@ -0,0 +1,320 @@
+use crate::shared::{Observable, Shared, WeakShared};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+lazy_static! {
+    /// The height of every computed shared, i.e. the length of the longest chain of
+    /// computed values it depends on. Plain shareds have a height of 0 and are not stored.
+    static ref HEIGHTS: Mutex<HashMap<usize, usize>> = Mutex::new(HashMap::new());
+}
+
+type Dependencies = Vec<(usize, Box<dyn Observable + Send>)>;
+type Compute<T> = Arc<Mutex<Box<dyn Fn() -> T + Send>>>;
+
+struct PendingRecomputation {
+    id: usize,
+    height: usize,
+    recompute: Box<dyn FnOnce()>,
+}
+
+#[derive(Default)]
+struct Propagation {
+    depth: usize,
+    flushing: bool,
+    pending: Vec<PendingRecomputation>,
+}
+
+thread_local! {
+    static PROPAGATION: RefCell<Propagation> = RefCell::new(Propagation::default());
+    /// One frame for every computation that is currently running on this thread.
+    static TRACKING: RefCell<Vec<Dependencies>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keeps the propagation open while notifications are sent.
+/// Computed values are recomputed once the outermost scope is dropped,
+/// in the order of their height so that every value is computed after all of its dependencies.
+pub(crate) struct PropagationScope;
+
+impl PropagationScope {
+    pub(crate) fn enter() -> Self {
+        PROPAGATION.with(|propagation| propagation.borrow_mut().depth += 1);
+        Self
+    }
+}
+
+impl Drop for PropagationScope {
+    fn drop(&mut self) {
+        let flush = PROPAGATION.with(|propagation| {
+            let mut propagation = propagation.borrow_mut();
+            propagation.depth -= 1;
+            propagation.depth == 0 && !propagation.flushing
+        });
+        if flush {
+            flush_pending();
+        }
+    }
+}
+
+fn flush_pending() {
+    PROPAGATION.with(|propagation| propagation.borrow_mut().flushing = true);
+    loop {
+        let next = PROPAGATION.with(|propagation| {
+            let mut propagation = propagation.borrow_mut();
+            // The pending list is kept in scheduling order, so the first lowest entry wins ties.
+            let index = propagation
+                .pending
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, pending)| pending.height)
+                .map(|(index, _)| index)?;
+            Some(propagation.pending.remove(index))
+        });
+        match next {
+            Some(pending) => (pending.recompute)(),
+            None => break,
+        }
+    }
+    PROPAGATION.with(|propagation| propagation.borrow_mut().flushing = false);
+}
+
+fn schedule(id: usize, height: usize, recompute: impl FnOnce() + 'static) {
+    let _scope = PropagationScope::enter();
+    PROPAGATION.with(|propagation| {
+        let mut propagation = propagation.borrow_mut();
+        if propagation.pending.iter().all(|pending| pending.id != id) {
+            propagation.pending.push(PendingRecomputation {
+                id,
+                height,
+                recompute: Box::new(recompute),
+            });
+        }
+    });
+}
+
+/// Records `shared` as a dependency of the computation running on this thread, if any.
+pub(crate) fn track_read<T: Send + 'static>(shared: &Shared<T>) {
+    TRACKING.with(|tracking| {
+        let Ok(mut tracking) = tracking.try_borrow_mut() else {
+            return;
+        };
+        if let Some(frame) = tracking.last_mut() {
+            if frame.iter().all(|(id, _)| *id != shared.id()) {
+                frame.push((shared.id(), shared.to_observable()));
+            }
+        }
+    });
+}
+
+fn track<R>(computation: impl FnOnce() -> R) -> (R, Dependencies) {
+    // Shareds set by the computation must not trigger recomputations while the frame is open.
+    let _scope = PropagationScope::enter();
+    TRACKING.with(|tracking| tracking.borrow_mut().push(Vec::new()));
+    let result = computation();
+    let dependencies = TRACKING.with(|tracking| tracking.borrow_mut().pop().unwrap_or_default());
+    (result, dependencies)
+}
+
+/// Owned by the computed shared. Dropping it, either because the shared is dropped or because
+/// the shared is given a new value, stops the subscriptions it belongs to.
+struct ComputedHandle {
+    id: usize,
+    active: Arc<AtomicBool>,
+}
+
+impl Drop for ComputedHandle {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Release);
+        HEIGHTS.lock().remove(&self.id);
+    }
+}
+
+fn subscribe<T: Send + 'static>(shared: &Shared<T>, compute: &Compute<T>, dependencies: Dependencies) {
+    let id = shared.id();
+    let height = {
+        let mut heights = HEIGHTS.lock();
+        let height = dependencies
+            .iter()
+            .map(|(dependency, _)| heights.get(dependency).copied().unwrap_or(0))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        heights.insert(id, height);
+        height
+    };
+    let active = Arc::new(AtomicBool::new(true));
+    let handle = ComputedHandle {
+        id,
+        active: active.clone(),
+    };
+    shared.add_observed_object(None, Box::new(move || drop(handle)));
+
+    for (_, mut dependency) in dependencies {
+        let weak = shared.weak();
+        let compute = compute.clone();
+        let active = active.clone();
+        let removal = dependency.add_observer(
+            id,
+            Box::new(move || {
+                if !active.load(Ordering::Acquire) {
+                    return;
+                }
+                let weak = weak.clone();
+                let compute = compute.clone();
+                let active = active.clone();
+                schedule(id, height, move || {
+                    if active.load(Ordering::Acquire) {
+                        recompute(&weak, &compute);
+                    }
+                });
+            }),
+        );
+        shared.add_observed_object(Some(dependency), removal.unwrap());
+    }
+}
+
+fn recompute<T: Send + 'static>(weak: &WeakShared<T>, compute: &Compute<T>) {
+    let Some(shared) = weak.upgrade() else {
+        return;
+    };
+    let (value, dependencies) = track(|| (compute.lock())());
+    shared.clear_observed_objects();
+    subscribe(&shared, compute, dependencies);
+    *shared.lock() = value;
+    shared.notify();
+}
+
+impl<T: Send + 'static> Shared<T> {
+    /// Creates a shared whose value is computed by `compute`.
+    ///
+    /// Every shared read while computing becomes a dependency, and the dependencies are collected
+    /// again on each recomputation, so branches that read different shareds are handled.
+    /// Changes are propagated without glitches: a computed value is recomputed once per change,
+    /// after all of its dependencies have been updated.
+    ///
+    /// Setting a new value with [`set_static`](Shared::set_static), [`set_dynamic`](Shared::set_dynamic)
+    /// or [`set_shared`](Shared::set_shared) stops the computation.
+    pub fn computed(compute: impl Fn() -> T + Send + 'static) -> Self {
+        let compute: Compute<T> = Arc::new(Mutex::new(Box::new(compute)));
+        let (value, dependencies) = track(|| (compute.lock())());
+        let shared = Self::from_static(value);
+        subscribe(&shared, &compute, dependencies);
+        shared
+    }
+
+    /// Creates a computed shared that holds `map` applied to the value of this shared.
+    pub fn map<U: Send + 'static>(&self, map: impl Fn(&T) -> U + Send + 'static) -> Shared<U> {
+        let source = self.clone();
+        Shared::computed(move || source.read(|value| map(value)))
+    }
+
+    /// Creates a computed shared that holds the result of `combine` applied to this shared and `other`.
+    pub fn combine<U: Send + 'static, R: Send + 'static>(
+        &self,
+        other: &Shared<U>,
+        combine: impl Fn(&T, &U) -> R + Send + 'static,
+    ) -> Shared<R> {
+        let source = self.clone();
+        let other = other.clone();
+        Shared::computed(move || source.read(|value| other.read(|other_value| combine(value, other_value))))
+    }
+}
+
+impl<T: Clone + Send + 'static> Shared<T> {
+    /// Creates a computed shared that holds the values of this shared and `other` as a tuple.
+    pub fn zip<U: Clone + Send + 'static>(&self, other: &Shared<U>) -> Shared<(T, U)> {
+        self.combine(other, |value, other_value| (value.clone(), other_value.clone()))
+    }
+}
+
+#[cfg(test)]
+mod computed_test {
+    use crate::shared::{Gettable, Shared};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_computed_follows_dependencies() {
+        let a = Shared::from_static(1);
+        let b = Shared::from_static(2);
+        let sum = {
+            let (a, b) = (a.clone(), b.clone());
+            Shared::computed(move || a.get() + b.get())
+        };
+        assert_eq!(sum.get(), 3);
+        a.write(|value| *value = 10);
+        assert_eq!(sum.get(), 12);
+        b.write(|value| *value = 20);
+        assert_eq!(sum.get(), 30);
+    }
+
+    #[test]
+    fn test_computed_tracks_the_branch_that_was_read() {
+        let condition = Shared::from_static(true);
+        let a = Shared::from_static(1);
+        let b = Shared::from_static(2);
+        let count = Arc::new(AtomicUsize::new(0));
+        let value = {
+            let (condition, a, b, count) = (condition.clone(), a.clone(), b.clone(), count.clone());
+            Shared::computed(move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                if condition.get() {
+                    a.get()
+                } else {
+                    b.get()
+                }
+            })
+        };
+        assert_eq!(value.get(), 1);
+        // `b` is not read yet, so it is not a dependency.
+        b.write(|value| *value = 3);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        condition.write(|value| *value = false);
+        assert_eq!(value.get(), 3);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_diamond_is_recomputed_once() {
+        let source = Shared::from_static(1);
+        let left = source.map(|value| value + 1);
+        let right = source.map(|value| value * 2);
+        let count = Arc::new(AtomicUsize::new(0));
+        let total = {
+            let count = count.clone();
+            left.combine(&right, move |left, right| {
+                count.fetch_add(1, Ordering::SeqCst);
+                left + right
+            })
+        };
+        assert_eq!(total.get(), 4);
+        source.write(|value| *value = 2);
+        assert_eq!(total.get(), 7);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_writes_inside_a_computation_are_not_dependencies() {
+        let source = Shared::from_static(1);
+        let log = Shared::from_static(0);
+        let count = Arc::new(AtomicUsize::new(0));
+        let _doubled = {
+            let (source, log, count) = (source.clone(), log.clone(), count.clone());
+            Shared::computed(move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                let doubled = source.get() * 2;
+                log.write(|value| *value = doubled);
+                doubled
+            })
+        };
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        log.write(|value| *value = 0);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        source.write(|value| *value = 2);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(log.get(), 4);
+    }
+}
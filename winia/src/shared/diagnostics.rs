@@ -0,0 +1,279 @@
+//! Bookkeeping of the observers registered on [`Shared`] values.
+//!
+//! The registry is only maintained in debug builds. In release builds every query returns
+//! empty results and the hooks used by [`Shared`] compile down to nothing.
+
+#[cfg(debug_assertions)]
+use crate::core::log_target;
+use crate::shared::Shared;
+#[cfg(debug_assertions)]
+use lazy_static::lazy_static;
+#[cfg(debug_assertions)]
+use parking_lot::Mutex;
+#[cfg(debug_assertions)]
+use std::cell::RefCell;
+#[cfg(debug_assertions)]
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObserverDiagnostics {
+    /// The number of live shareds that have at least one observer.
+    pub shared_count: usize,
+    pub observer_count: usize,
+    /// The number of observers whose owning item has been dropped.
+    pub leaked_observer_count: usize,
+    /// The number of notification cycles detected since the start of the program.
+    pub cycle_count: usize,
+}
+
+/// An observer that is still registered on a shared although the item that registered it is gone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeakedObserver {
+    pub shared_id: usize,
+    pub shared_type: &'static str,
+    pub observer_id: usize,
+}
+
+#[cfg(debug_assertions)]
+struct ObservedShared {
+    type_name: &'static str,
+    is_alive: Box<dyn Fn() -> bool + Send>,
+    observers: Vec<usize>,
+}
+
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct Registry {
+    shareds: HashMap<usize, ObservedShared>,
+    /// Ids of dropped items that still had observers registered when they were dropped.
+    detached_owners: HashSet<usize>,
+    cycle_count: usize,
+    /// Dropped shareds are pruned once the registry has grown past this size.
+    prune_threshold: usize,
+}
+
+#[cfg(debug_assertions)]
+impl Registry {
+    fn prune(&mut self) {
+        self.shareds.retain(|_, shared| (shared.is_alive)() && !shared.observers.is_empty());
+        let shareds = &self.shareds;
+        self.detached_owners
+            .retain(|owner| shareds.values().any(|shared| shared.observers.contains(owner)));
+        self.prune_threshold = (self.shareds.len() * 2).max(1024);
+    }
+}
+
+#[cfg(debug_assertions)]
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// The shareds that are currently notifying their observers on this thread.
+    static NOTIFYING: RefCell<Vec<(usize, &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn observer_added<T: Send + 'static>(shared: &Shared<T>, observer_id: usize) {
+    #[cfg(debug_assertions)]
+    {
+        let weak = shared.weak();
+        let mut registry = REGISTRY.lock();
+        if registry.shareds.len() >= registry.prune_threshold {
+            registry.prune();
+        }
+        registry
+            .shareds
+            .entry(shared.id())
+            .or_insert_with(|| ObservedShared {
+                type_name: std::any::type_name::<T>(),
+                is_alive: Box::new(move || weak.is_alive()),
+                observers: Vec::new(),
+            })
+            .observers
+            .push(observer_id);
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = (shared, observer_id);
+}
+
+/// Called when all observers registered with `observer_id` are removed.
+pub(crate) fn observers_removed(shared_id: usize, observer_id: usize) {
+    #[cfg(debug_assertions)]
+    {
+        let mut registry = REGISTRY.lock();
+        if let Some(shared) = registry.shareds.get_mut(&shared_id) {
+            shared.observers.retain(|id| *id != observer_id);
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = (shared_id, observer_id);
+}
+
+/// Called when the item with `owner_id` is dropped. Its observers are reported as leaked from now on.
+pub(crate) fn owner_detached(owner_id: usize) {
+    #[cfg(debug_assertions)]
+    {
+        let mut registry = REGISTRY.lock();
+        let has_observers = registry
+            .shareds
+            .values()
+            .any(|shared| (shared.is_alive)() && shared.observers.contains(&owner_id));
+        if has_observers {
+            registry.detached_owners.insert(owner_id);
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = owner_id;
+}
+
+/// Marks the shared as notifying until the returned guard is dropped.
+///
+/// Returns `None` if the shared is already notifying on this thread, in which case
+/// the notification would loop forever and must be skipped. The cycle is reported.
+#[cfg(debug_assertions)]
+pub(crate) fn enter_notification<T>(shared_id: usize) -> Option<NotificationGuard> {
+    let is_cycle = NOTIFYING.with(|notifying| {
+        let mut notifying = notifying.borrow_mut();
+        match notifying.iter().position(|(id, _)| *id == shared_id) {
+            Some(start) => {
+                report_cycle(&notifying[start..]);
+                true
+            }
+            None => {
+                notifying.push((shared_id, std::any::type_name::<T>()));
+                false
+            }
+        }
+    });
+    if is_cycle {
+        return None;
+    }
+    Some(NotificationGuard { shared_id })
+}
+
+/// Logs the chain of notifications that loops back to its first shared.
+#[cfg(debug_assertions)]
+fn report_cycle(cycle: &[(usize, &'static str)]) {
+    let chain = cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|(id, type_name)| format!("Shared<{}>#{}", type_name, id))
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    REGISTRY.lock().cycle_count += 1;
+    tracing::warn!(
+        target: log_target::SHARED,
+        %chain,
+        "Notification cycle detected, the notification is skipped"
+    );
+}
+
+#[cfg(debug_assertions)]
+pub(crate) struct NotificationGuard {
+    shared_id: usize,
+}
+
+#[cfg(debug_assertions)]
+impl Drop for NotificationGuard {
+    fn drop(&mut self) {
+        NOTIFYING.with(|notifying| {
+            let mut notifying = notifying.borrow_mut();
+            if let Some(index) = notifying.iter().rposition(|(id, _)| *id == self.shared_id) {
+                notifying.remove(index);
+            }
+        });
+    }
+}
+
+/// Returns the number of observers registered on the shared with `shared_id`.
+/// Always 0 in release builds.
+pub fn observer_count(shared_id: usize) -> usize {
+    #[cfg(debug_assertions)]
+    {
+        REGISTRY
+            .lock()
+            .shareds
+            .get(&shared_id)
+            .map_or(0, |shared| shared.observers.len())
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        let _ = shared_id;
+        0
+    }
+}
+
+/// Returns the observers that outlived the item that registered them.
+/// Always empty in release builds.
+pub fn leaked_observers() -> Vec<LeakedObserver> {
+    #[cfg(debug_assertions)]
+    {
+        let mut registry = REGISTRY.lock();
+        registry.prune();
+        let mut leaked = Vec::new();
+        for (shared_id, shared) in registry.shareds.iter() {
+            for observer_id in shared.observers.iter() {
+                if registry.detached_owners.contains(observer_id) {
+                    leaked.push(LeakedObserver {
+                        shared_id: *shared_id,
+                        shared_type: shared.type_name,
+                        observer_id: *observer_id,
+                    });
+                }
+            }
+        }
+        leaked
+    }
+    #[cfg(not(debug_assertions))]
+    Vec::new()
+}
+
+pub fn observer_diagnostics() -> ObserverDiagnostics {
+    #[cfg(debug_assertions)]
+    {
+        let leaked_observer_count = leaked_observers().len();
+        let registry = REGISTRY.lock();
+        ObserverDiagnostics {
+            shared_count: registry.shareds.len(),
+            observer_count: registry
+                .shareds
+                .values()
+                .map(|shared| shared.observers.len())
+                .sum(),
+            leaked_observer_count,
+            cycle_count: registry.cycle_count,
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    ObserverDiagnostics::default()
+}
+
+// Cycles are only detected in debug builds, in release builds they recurse like any notification.
+#[cfg(all(test, debug_assertions))]
+mod diagnostics_test {
+    use super::enter_notification;
+    use crate::shared::{Gettable, Shared};
+
+    #[test]
+    fn test_reentering_a_notification_is_skipped() {
+        let guard = enter_notification::<i32>(usize::MAX);
+        assert!(guard.is_some());
+        assert!(enter_notification::<i32>(usize::MAX).is_none());
+        drop(guard);
+        assert!(enter_notification::<i32>(usize::MAX).is_some());
+    }
+
+    #[test]
+    fn test_cycle_between_shareds_terminates() {
+        let first = Shared::from_static(1);
+        let second = Shared::from_dynamic([first.to_observable()].into(), {
+            let first = first.clone();
+            move || first.get() + 1
+        });
+        // Closes the loop: notifying `first` notifies `second`, which notifies `first` again.
+        first.observe(second.clone());
+        first.write(|value| *value = 2);
+        assert_eq!(second.get(), 3);
+    }
+}
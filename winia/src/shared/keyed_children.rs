@@ -0,0 +1,443 @@
+use crate::collection::{Operation, WVec};
+use crate::exclude_target;
+use crate::shared::children::animate_appearance;
+use crate::shared::{Children, Gettable, LocalShared, Observable, Settable, Shared, SharedUsize};
+use crate::ui::animation::LocalAnimationExt;
+use crate::ui::app::WindowContext;
+use crate::ui::item::CustomProperty;
+use crate::ui::Item;
+use clonelet::clone;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::DerefMut;
+use std::time::Duration;
+
+type ItemBuilder<T> = Box<dyn Fn(&WindowContext, Shared<WVec<T>>, SharedUsize) -> Item>;
+
+struct KeyedEntry<K> {
+    key: K,
+    id: usize,
+    index: SharedUsize,
+}
+
+struct KeyedState<T, K> {
+    items: Shared<WVec<T>>,
+    key: Box<dyn Fn(&T) -> K>,
+    item_builder: ItemBuilder<T>,
+    /// The entries in the same order as the items they were built from.
+    entries: Vec<KeyedEntry<K>>,
+}
+
+impl<T: Send + 'static, K: Eq + Hash + Clone + 'static> KeyedState<T, K> {
+    fn build_item(&self, window_context: &WindowContext, key: K, index: usize) -> (KeyedEntry<K>, Item) {
+        let shared_index = SharedUsize::from(index);
+        let item = (self.item_builder)(window_context, self.items.clone(), shared_index.clone());
+        item.data()
+            .custom_property("index", CustomProperty::Usize(shared_index.clone()));
+        let entry = KeyedEntry {
+            key,
+            id: item.data().get_id(),
+            index: shared_index,
+        };
+        (entry, item)
+    }
+
+    fn keys(&self) -> Vec<K> {
+        self.items
+            .read(|items| items.iter().map(|item| (self.key)(item)).collect())
+    }
+
+    /// Brings `children` in line with the current items.
+    /// Returns the ids of the items that are leaving and are still part of `children`.
+    fn reconcile(
+        &mut self,
+        window_context: &WindowContext,
+        children: &Children,
+        animated: bool,
+        duration: Duration,
+    ) -> Vec<usize> {
+        let operations = {
+            let operations = self.items.lock().operations();
+            let mut operations = operations.lock();
+            std::mem::take(operations.deref_mut())
+        };
+        let invalidated = updated_keys(&self.entries, operations.iter());
+        let new_keys = self.keys();
+
+        let mut pool: HashMap<K, (usize, KeyedEntry<K>)> = HashMap::new();
+        let mut leaving = Vec::new();
+        for (old_index, entry) in self.entries.drain(..).enumerate() {
+            if let Some((_, duplicate)) = pool.insert(entry.key.clone(), (old_index, entry)) {
+                leaving.push(duplicate.id);
+            }
+        }
+
+        let mut replaced = Vec::new();
+        // (entry, newly built item, old index)
+        let mut new_entries: Vec<(KeyedEntry<K>, Option<Item>, Option<usize>)> = Vec::new();
+        for (index, key) in new_keys.into_iter().enumerate() {
+            match pool.remove(&key) {
+                Some((old_index, entry)) if !invalidated.contains(&key) => {
+                    new_entries.push((entry, None, Some(old_index)));
+                }
+                reused => {
+                    let is_replacement = reused.is_some();
+                    if let Some((_, entry)) = reused {
+                        replaced.push(entry.id);
+                    }
+                    let (entry, item) = self.build_item(window_context, key, index);
+                    if animated && !is_replacement {
+                        animate_appearance(&item, duration);
+                    }
+                    new_entries.push((entry, Some(item), None));
+                }
+            }
+        }
+        leaving.extend(pool.into_values().map(|(_, entry)| entry.id));
+
+        // Items that keep their relative order stay where they are, all others are moved.
+        let reused_positions: Vec<usize> = new_entries
+            .iter()
+            .filter_map(|(_, _, old_index)| *old_index)
+            .collect();
+        let stable: HashSet<usize> = longest_increasing_subsequence(&reused_positions)
+            .into_iter()
+            .map(|position| reused_positions[position])
+            .collect();
+
+        {
+            let mut children_vec = children.lock();
+            for id in replaced.iter() {
+                if let Some(position) = position_of(&children_vec, *id) {
+                    children_vec.remove(position);
+                }
+            }
+
+            let mut moved_items = HashMap::new();
+            for (entry, _, old_index) in new_entries.iter() {
+                if let Some(old_index) = old_index {
+                    if !stable.contains(old_index) {
+                        if let Some(position) = position_of(&children_vec, entry.id) {
+                            moved_items.insert(entry.id, children_vec.remove(position));
+                        }
+                    }
+                }
+            }
+
+            if animated {
+                for id in leaving.iter() {
+                    if let Some(child) = children_vec.iter().find(|child| child.data().get_id() == *id) {
+                        child.data().get_visible().set(false);
+                    }
+                }
+            } else {
+                for id in leaving.drain(..) {
+                    if let Some(position) = position_of(&children_vec, id) {
+                        children_vec.remove(position);
+                    }
+                }
+            }
+
+            let mut previous: Option<usize> = None;
+            for (index, (entry, item, _)) in new_entries.iter_mut().enumerate() {
+                let item = item.take().or_else(|| moved_items.remove(&entry.id));
+                if let Some(item) = item {
+                    let position = previous
+                        .and_then(|previous| position_of(&children_vec, previous))
+                        .map_or(0, |position| position + 1);
+                    children_vec.insert(position, item);
+                }
+                if entry.index.get() != index {
+                    entry.index.set(index);
+                }
+                previous = Some(entry.id);
+            }
+        }
+        children.notify();
+
+        self.entries = new_entries
+            .into_iter()
+            .map(|(entry, _, _)| entry)
+            .collect();
+        leaving
+    }
+}
+
+/// Replays the operation log on top of the previous keys to find out
+/// which of the existing items have been updated in place.
+fn updated_keys<'a, K: Eq + Hash + Clone>(
+    entries: &[KeyedEntry<K>],
+    operations: impl Iterator<Item = &'a Operation>,
+) -> HashSet<K> {
+    let mut slots: Vec<Option<K>> = entries.iter().map(|entry| Some(entry.key.clone())).collect();
+    let mut updated = HashSet::new();
+    for operation in operations {
+        match *operation {
+            Operation::Add(index) => {
+                if index > slots.len() {
+                    break;
+                }
+                slots.insert(index, None);
+            }
+            Operation::Remove(index) => {
+                if index >= slots.len() {
+                    break;
+                }
+                slots.remove(index);
+            }
+            Operation::Update(index) => {
+                if let Some(key) = slots.get_mut(index).and_then(|slot| slot.take()) {
+                    updated.insert(key);
+                }
+            }
+            Operation::Move(from, to) => {
+                if from >= slots.len() || to >= slots.len() {
+                    break;
+                }
+                let slot = slots.remove(from);
+                slots.insert(to, slot);
+            }
+            // The positions can't be tracked any further, the keys take over from here.
+            Operation::Clear | Operation::Other => break,
+        }
+    }
+    updated
+}
+
+fn position_of(children: &WVec<Item>, id: usize) -> Option<usize> {
+    children.iter().position(|child| child.data().get_id() == id)
+}
+
+/// Returns the positions of one longest strictly increasing subsequence of `values`.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // tails[i] is the position of the smallest tail of all increasing subsequences of length i + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; values.len()];
+    for (position, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|tail| values[*tail] < *value);
+        if length > 0 {
+            predecessors[position] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(position) = current {
+        result.push(position);
+        current = predecessors[position];
+    }
+    result.reverse();
+    result
+}
+
+/// Binds a [`Shared<WVec<T>>`](crate::collection::WVec) to [`Children`].
+///
+/// Every item is identified by the key returned from `key`. When the vector changes,
+/// items whose key is still present are reused, so their state survives reordering.
+/// Only the items reported as updated through the operation log (e.g. [`WVec::get_mut`]) are rebuilt.
+/// Insertions, removals and moves are animated unless [`animated`](KeyedChildren::animated) is turned off.
+///
+/// Changes must be published with [`Shared::write`] or [`Shared::notify`], like any other shared value.
+pub struct KeyedChildren<T, K> {
+    window_context: WindowContext,
+    items: Shared<WVec<T>>,
+    key: Box<dyn Fn(&T) -> K>,
+    item_builder: ItemBuilder<T>,
+    animated: bool,
+    duration: Duration,
+}
+
+impl<T: Send + 'static, K: Eq + Hash + Clone + 'static> KeyedChildren<T, K> {
+    pub fn new(
+        window_context: &WindowContext,
+        items: impl Into<Shared<WVec<T>>>,
+        key: impl Fn(&T) -> K + 'static,
+        item_builder: impl Fn(&WindowContext, Shared<WVec<T>>, SharedUsize) -> Item + 'static,
+    ) -> Self {
+        Self {
+            window_context: window_context.clone(),
+            items: items.into(),
+            key: Box::new(key),
+            item_builder: Box::new(item_builder),
+            animated: true,
+            duration: Duration::from_millis(500),
+        }
+    }
+
+    /// Whether insertions, removals and moves are animated. Enabled by default.
+    pub fn animated(mut self, animated: bool) -> Self {
+        self.animated = animated;
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn build(self) -> Children {
+        let KeyedChildren {
+            window_context,
+            mut items,
+            key,
+            item_builder,
+            animated,
+            duration,
+        } = self;
+
+        let children = Children::new();
+        let state = LocalShared::from_static(KeyedState {
+            items: items.clone(),
+            key,
+            item_builder,
+            entries: Vec::new(),
+        });
+        {
+            let mut state = state.lock();
+            // The initial items are already reflected by the keys.
+            items.lock().operations().lock().clear();
+            for (index, key) in state.keys().into_iter().enumerate() {
+                let (entry, item) = state.build_item(&window_context, key, index);
+                state.entries.push(entry);
+                children.lock().push(item);
+            }
+        }
+
+        let dirty = Shared::from_static(false);
+        let observer_id = children.id();
+        {
+            clone!(dirty);
+            let event_loop_proxy = window_context.event_loop_proxy().clone();
+            items
+                .add_observer(
+                    observer_id,
+                    Box::new(move || {
+                        dirty.set(true);
+                        event_loop_proxy.request_layout();
+                    }),
+                )
+                .drop();
+        }
+
+        window_context.add_ui_hook({
+            let children = children.weak();
+            let window_context = window_context.clone();
+            move || {
+                let Some(children) = children.upgrade() else {
+                    items.remove_observer(observer_id);
+                    return false;
+                };
+                if !dirty.get() {
+                    return true;
+                }
+                dirty.set(false);
+
+                if !animated {
+                    state.lock().reconcile(&window_context, &children, false, duration);
+                    return true;
+                }
+
+                let leaving = LocalShared::from_static(Vec::new());
+                window_context
+                    .local_animate(exclude_target!())
+                    .transformation({
+                        clone!(state, children, leaving, window_context);
+                        move || {
+                            let ids = state
+                                .lock()
+                                .reconcile(&window_context, &children, true, duration);
+                            leaving.lock().extend(ids);
+                        }
+                    })
+                    .duration(duration)
+                    .on_finished({
+                        clone!(children);
+                        move || {
+                            for id in leaving.lock().drain(..) {
+                                children.remove_by_id(id);
+                            }
+                        }
+                    })
+                    .start();
+                true
+            }
+        });
+
+        children
+    }
+}
+
+impl<T: Send + 'static, K: Eq + Hash + Clone + 'static> From<KeyedChildren<T, K>> for Children {
+    fn from(keyed_children: KeyedChildren<T, K>) -> Self {
+        keyed_children.build()
+    }
+}
+
+pub trait ForEachKeyed<T> {
+    fn for_each_keyed<K, F, B>(&self, window_context: &WindowContext, key: F, item_builder: B) -> KeyedChildren<T, K>
+    where
+        K: Eq + Hash + Clone + 'static,
+        F: Fn(&T) -> K + 'static,
+        B: Fn(&WindowContext, Shared<WVec<T>>, SharedUsize) -> Item + 'static;
+}
+
+impl<T: Send + 'static> ForEachKeyed<T> for Shared<WVec<T>> {
+    fn for_each_keyed<K, F, B>(&self, window_context: &WindowContext, key: F, item_builder: B) -> KeyedChildren<T, K>
+    where
+        K: Eq + Hash + Clone + 'static,
+        F: Fn(&T) -> K + 'static,
+        B: Fn(&WindowContext, Shared<WVec<T>>, SharedUsize) -> Item + 'static,
+    {
+        KeyedChildren::new(window_context, self, key, item_builder)
+    }
+}
+
+#[cfg(test)]
+mod keyed_children_test {
+    use super::{longest_increasing_subsequence, updated_keys, KeyedEntry};
+    use crate::collection::Operation;
+    use crate::shared::SharedUsize;
+    use std::collections::HashSet;
+
+    fn entries(keys: &[&'static str]) -> Vec<KeyedEntry<&'static str>> {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| KeyedEntry {
+                key: *key,
+                id: index,
+                index: SharedUsize::from(index),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(longest_increasing_subsequence(&[]), Vec::<usize>::new());
+        assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), vec![0, 1, 2]);
+        let values = [3, 0, 1, 4, 2];
+        let positions = longest_increasing_subsequence(&values);
+        let subsequence: Vec<usize> = positions.iter().map(|position| values[*position]).collect();
+        assert_eq!(subsequence.len(), 3);
+        assert!(subsequence.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_updated_keys_follow_inserts_and_removals() {
+        let entries = entries(&["a", "b", "c"]);
+        // Insert before "a", then update what is now "b" and remove "a".
+        let operations = [Operation::Add(0), Operation::Update(2), Operation::Remove(1)];
+        let updated = updated_keys(&entries, operations.iter());
+        assert_eq!(updated, HashSet::from(["b"]));
+    }
+
+    #[test]
+    fn test_updated_keys_stop_at_clear() {
+        let entries = entries(&["a", "b"]);
+        let operations = [Operation::Clear, Operation::Update(0)];
+        assert!(updated_keys(&entries, operations.iter()).is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use crate::ui::app::EventLoopProxy;
 use parking_lot::{Mutex, MutexGuard};
 use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
+use std::panic::Location;
 use std::sync::{Arc, Weak};
 
 pub struct LocalRemoval {
@@ -262,23 +263,27 @@ impl<T: 'static> LocalShared<T> {
         }
     }
 
+    #[track_caller]
     pub fn redraw_when_changed(mut self, event_loop_proxy: &EventLoopProxy, id: usize) -> Self {
         let event_loop_proxy = event_loop_proxy.clone();
+        let location = Location::caller();
         self.add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_redraw();
+                event_loop_proxy.request_repaint_from(id, location);
             }),
         );
         self
     }
 
+    #[track_caller]
     pub fn layout_when_changed(mut self, event_loop_proxy: &EventLoopProxy, id: usize) -> Self {
         let event_loop_proxy = event_loop_proxy.clone();
+        let location = Location::caller();
         self.add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_layout();
+                event_loop_proxy.request_layout_from(location);
             }),
         );
         self
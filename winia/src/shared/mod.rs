@@ -13,6 +13,11 @@ pub use shared_text::*;
 mod shared_alignment;
 pub use shared_alignment::*;
 mod children;
+mod computed;
+mod diagnostics;
+mod persistence;
+mod transaction;
+mod keyed_children;
 mod shared;
 mod shared_drawable;
 mod shared_inner_position;
@@ -27,8 +32,23 @@ pub use shared_num::*;
 
 pub use children::*;
 
+pub use keyed_children::*;
+
 pub use shared::*;
 
+pub use computed::*;
+
+pub use persistence::*;
+
+pub(crate) use persistence::flush_stores;
+
+pub(crate) use diagnostics::owner_detached;
+pub use diagnostics::{
+    leaked_observers, observer_count, observer_diagnostics, LeakedObserver, ObserverDiagnostics,
+};
+
+pub use transaction::{batch, transaction};
+
 pub use local_shared::*;
 
 pub use shared_drawable::*;
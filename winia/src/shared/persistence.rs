@@ -0,0 +1,589 @@
+use crate::core::{log_target, next_id};
+use crate::shared::{Observable, Shared};
+use crate::ui::app::WindowContext;
+use lazy_static::lazy_static;
+use parking_lot::{Condvar, Mutex};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreFormat {
+    Json,
+    Toml,
+}
+
+impl StoreFormat {
+    /// Picks the format from the extension of `path`, falling back to JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => StoreFormat::Toml,
+            _ => StoreFormat::Json,
+        }
+    }
+
+    fn parse(&self, content: &str) -> Result<Value, PersistenceError> {
+        match self {
+            StoreFormat::Json => serde_json::from_str(content).map_err(PersistenceError::Json),
+            StoreFormat::Toml => toml::from_str(content).map_err(PersistenceError::TomlDeserialize),
+        }
+    }
+
+    fn format(&self, value: &Value) -> Result<String, PersistenceError> {
+        match self {
+            StoreFormat::Json => serde_json::to_string_pretty(value).map_err(PersistenceError::Json),
+            StoreFormat::Toml => toml::to_string(value).map_err(PersistenceError::TomlSerialize),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    TomlDeserialize(toml::de::Error),
+    TomlSerialize(toml::ser::Error),
+    /// The file was written by a newer version of the application.
+    UnsupportedVersion { found: u32, expected: u32 },
+}
+
+impl Display for PersistenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistenceError::Io(error) => write!(f, "I/O error: {}", error),
+            PersistenceError::Json(error) => write!(f, "JSON error: {}", error),
+            PersistenceError::TomlDeserialize(error) => write!(f, "TOML error: {}", error),
+            PersistenceError::TomlSerialize(error) => write!(f, "TOML error: {}", error),
+            PersistenceError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "The store has version {}, but at most version {} is supported",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PersistenceError {}
+
+impl From<std::io::Error> for PersistenceError {
+    fn from(error: std::io::Error) -> Self {
+        PersistenceError::Io(error)
+    }
+}
+
+type Migration = Box<dyn Fn(&mut Map<String, Value>) + Send>;
+
+pub struct StoreBuilder {
+    path: PathBuf,
+    format: StoreFormat,
+    version: u32,
+    migrations: Vec<(u32, Migration)>,
+    debounce: Duration,
+}
+
+impl StoreBuilder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            format: StoreFormat::from_path(&path),
+            path,
+            version: 0,
+            migrations: Vec::new(),
+            debounce: Duration::from_millis(500),
+        }
+    }
+
+    pub fn format(mut self, format: StoreFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The schema version of the values. Files with an older version are migrated when opened.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Adds a migration that turns the values of version `from_version` into version `from_version + 1`.
+    pub fn migration(
+        mut self,
+        from_version: u32,
+        migration: impl Fn(&mut Map<String, Value>) + Send + 'static,
+    ) -> Self {
+        self.migrations.push((from_version, Box::new(migration)));
+        self
+    }
+
+    /// How long to wait after a change before writing the file. Changes made in the meantime are written together.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn open(self) -> Result<Store, PersistenceError> {
+        let StoreBuilder {
+            path,
+            format,
+            version,
+            migrations,
+            debounce,
+        } = self;
+
+        let (mut values, stored_version) = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            let document = format.parse(&content)?;
+            let stored_version = document
+                .get("version")
+                .and_then(|version| version.as_u64())
+                .unwrap_or(0) as u32;
+            let values = match document.get("values") {
+                Some(Value::Object(values)) => values.clone(),
+                _ => Map::new(),
+            };
+            (values, stored_version)
+        } else {
+            (Map::new(), version)
+        };
+
+        if stored_version > version {
+            return Err(PersistenceError::UnsupportedVersion {
+                found: stored_version,
+                expected: version,
+            });
+        }
+        for from_version in stored_version..version {
+            for (_, migration) in migrations.iter().filter(|(from, _)| *from == from_version) {
+                migration(&mut values);
+            }
+        }
+
+        let schedule = Arc::new((Mutex::new(WriteSchedule::default()), Condvar::new()));
+        let store = Store {
+            inner: Arc::new(Mutex::new(StoreInner {
+                path,
+                format,
+                version,
+                values,
+                debounce,
+                dirty: false,
+                schedule: schedule.clone(),
+                bindings: HashMap::new(),
+            })),
+            namespace: Vec::new(),
+        };
+        let inner = Arc::downgrade(&store.inner);
+        std::thread::Builder::new()
+            .name("store-writer".to_string())
+            .spawn(move || write_loop(inner, &schedule))?;
+        {
+            let mut stores = STORES.lock();
+            stores.retain(|store| store.strong_count() > 0);
+            stores.push(Arc::downgrade(&store.inner));
+        }
+        if stored_version < version {
+            store.inner.lock().dirty = true;
+            store.flush()?;
+        }
+        Ok(store)
+    }
+}
+
+struct StoreInner {
+    path: PathBuf,
+    format: StoreFormat,
+    version: u32,
+    values: Map<String, Value>,
+    debounce: Duration,
+    dirty: bool,
+    schedule: Arc<(Mutex<WriteSchedule>, Condvar)>,
+    /// The removals of the observers added by [`Store::bind`], by namespace and key.
+    bindings: HashMap<(Vec<String>, String), Box<dyn FnOnce() + Send>>,
+}
+
+impl StoreInner {
+    fn write(&mut self) -> Result<(), PersistenceError> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut document = Map::new();
+        document.insert("version".to_string(), Value::from(self.version));
+        document.insert("values".to_string(), Value::Object(self.values.clone()));
+        let content = self.format.format(&Value::Object(document))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // The content goes to a file next to the store first, so a crash while writing leaves
+        // the previous store intact instead of a truncated one.
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        {
+            let mut file = File::create(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, &self.path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn write_or_log(&mut self) {
+        if let Err(error) = self.write() {
+            tracing::error!(
+                target: log_target::PERSISTENCE,
+                path = %self.path.display(),
+                %error,
+                "Failed to write the store"
+            );
+        }
+    }
+
+    /// The table of the namespace, `None` if nothing was stored in it yet.
+    fn find_table(&self, namespace: &[String]) -> Option<&Map<String, Value>> {
+        let mut table = &self.values;
+        for name in namespace {
+            table = table.get(name)?.as_object()?;
+        }
+        Some(table)
+    }
+
+    fn find_table_mut(&mut self, namespace: &[String]) -> Option<&mut Map<String, Value>> {
+        let mut table = &mut self.values;
+        for name in namespace {
+            table = table.get_mut(name)?.as_object_mut()?;
+        }
+        Some(table)
+    }
+
+    /// The table of the namespace, created if it doesn't exist yet.
+    fn table(&mut self, namespace: &[String]) -> &mut Map<String, Value> {
+        let mut table = &mut self.values;
+        for name in namespace {
+            let entry = table
+                .entry(name.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            table = entry.as_object_mut().unwrap();
+        }
+        table
+    }
+}
+
+impl Drop for StoreInner {
+    fn drop(&mut self) {
+        self.write_or_log();
+        let (schedule, condvar) = &*self.schedule;
+        schedule.lock().exit = true;
+        condvar.notify_one();
+    }
+}
+
+#[derive(Default)]
+struct WriteSchedule {
+    /// When the pending changes are written, pushed back by every change.
+    deadline: Option<Instant>,
+    exit: bool,
+}
+
+lazy_static! {
+    /// The open stores, flushed when the application exits.
+    static ref STORES: Mutex<Vec<Weak<Mutex<StoreInner>>>> = Mutex::new(Vec::new());
+}
+
+/// Writes the pending changes of every open store, called when the event loop exits.
+pub(crate) fn flush_stores() {
+    let stores: Vec<_> = STORES.lock().iter().filter_map(Weak::upgrade).collect();
+    for store in stores {
+        store.lock().write_or_log();
+    }
+}
+
+/// Waits on the store's own thread until the debounce delay has passed since the last change,
+/// then writes the latest values once.
+fn write_loop(inner: Weak<Mutex<StoreInner>>, schedule: &(Mutex<WriteSchedule>, Condvar)) {
+    let (schedule, condvar) = schedule;
+    loop {
+        {
+            let mut schedule = schedule.lock();
+            loop {
+                if schedule.exit {
+                    return;
+                }
+                match schedule.deadline {
+                    None => condvar.wait(&mut schedule),
+                    Some(deadline) if Instant::now() < deadline => {
+                        condvar.wait_until(&mut schedule, deadline);
+                    }
+                    Some(_) => {
+                        schedule.deadline = None;
+                        break;
+                    }
+                }
+            }
+        }
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        inner.lock().write_or_log();
+    }
+}
+
+/// A file that keeps values across restarts of the application.
+///
+/// Values are stored under string keys and can be grouped into namespaces,
+/// e.g. one per window with [`for_window`](Store::for_window).
+/// Changes are written to the file by the store's writer thread once the debounce delay has passed
+/// since the last change, when [`flush`](Store::flush) is called, when the application exits,
+/// or when the last clone of the store is dropped.
+///
+/// Note that TOML can't represent `None`, use JSON for values that contain options.
+#[derive(Clone)]
+pub struct Store {
+    inner: Arc<Mutex<StoreInner>>,
+    namespace: Vec<String>,
+}
+
+impl Store {
+    pub fn builder(path: impl Into<PathBuf>) -> StoreBuilder {
+        StoreBuilder::new(path)
+    }
+
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, PersistenceError> {
+        StoreBuilder::new(path).open()
+    }
+
+    /// Returns a view of this store whose keys live in the namespace `name`.
+    pub fn namespace(&self, name: impl Into<String>) -> Self {
+        let mut namespace = self.namespace.clone();
+        namespace.push(name.into());
+        Self {
+            inner: self.inner.clone(),
+            namespace,
+        }
+    }
+
+    /// Returns the namespace of the window, named after [`WindowAttr::name`](crate::ui::app::WindowAttr::name).
+    pub fn for_window(&self, window_context: &WindowContext) -> Self {
+        self.namespace("windows").namespace(window_context.name())
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let inner = self.inner.lock();
+        let value = inner.find_table(&self.namespace)?.get(key)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn set<T: Serialize + ?Sized>(&self, key: &str, value: &T) -> Result<(), PersistenceError> {
+        let value = serde_json::to_value(value).map_err(PersistenceError::Json)?;
+        {
+            let mut inner = self.inner.lock();
+            let table = inner.table(&self.namespace);
+            if table.get(key) == Some(&value) {
+                return Ok(());
+            }
+            table.insert(key.to_string(), value);
+        }
+        self.schedule_write();
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &str) {
+        let removed = self
+            .inner
+            .lock()
+            .find_table_mut(&self.namespace)
+            .is_some_and(|table| table.remove(key).is_some());
+        if removed {
+            self.schedule_write();
+        }
+    }
+
+    /// Writes pending changes to the file immediately.
+    pub fn flush(&self) -> Result<(), PersistenceError> {
+        self.inner.lock().write()
+    }
+
+    fn schedule_write(&self) {
+        let (schedule, debounce) = {
+            let mut inner = self.inner.lock();
+            inner.dirty = true;
+            (inner.schedule.clone(), inner.debounce)
+        };
+        let (schedule, condvar) = &*schedule;
+        schedule.lock().deadline = Some(Instant::now() + debounce);
+        condvar.notify_one();
+    }
+
+    /// Binds `shared` to `key`: the stored value, if any, is loaded into `shared`,
+    /// and every change of `shared` is written back to the store.
+    ///
+    /// The binding neither keeps the store nor `shared` alive. Binding `key` again replaces
+    /// the previous binding, see also [`unbind`](Store::unbind).
+    pub fn bind<T: Serialize + DeserializeOwned + Send + 'static>(&self, key: &str, shared: &Shared<T>) {
+        self.unbind(key);
+        if let Some(value) = self.get::<T>(key) {
+            shared.set_static(value);
+        }
+        let store = Arc::downgrade(&self.inner);
+        let namespace = self.namespace.clone();
+        let key_clone = key.to_string();
+        let weak = shared.weak();
+        let removal = shared
+            .clone()
+            .add_observer(
+                next_id(),
+                Box::new(move || {
+                    let Some(inner) = store.upgrade() else {
+                        return;
+                    };
+                    let store = Store {
+                        inner,
+                        namespace: namespace.clone(),
+                    };
+                    let key = &key_clone;
+                    if let Some(Err(error)) = weak.read(|value| store.set(key, value)) {
+                        tracing::error!(
+                            target: log_target::PERSISTENCE,
+                            %key,
+                            %error,
+                            "Failed to persist a value"
+                        );
+                    }
+                }),
+            )
+            .unwrap();
+        self.inner
+            .lock()
+            .bindings
+            .insert((self.namespace.clone(), key.to_string()), removal);
+    }
+
+    /// Stops writing the changes of the shared bound to `key` back to the store.
+    /// The stored value is kept.
+    pub fn unbind(&self, key: &str) {
+        let removal = self
+            .inner
+            .lock()
+            .bindings
+            .remove(&(self.namespace.clone(), key.to_string()));
+        // Removed outside the lock, the observer itself locks the store.
+        if let Some(removal) = removal {
+            removal();
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> Shared<T> {
+    /// Binds this shared to `key` in `store`, see [`Store::bind`].
+    pub fn persisted(self, store: &Store, key: &str) -> Self {
+        store.bind(key, &self);
+        self
+    }
+}
+
+#[cfg(test)]
+mod persistence_test {
+    use super::{Store, StoreFormat};
+    use crate::core::next_id;
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    fn temp_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "winia-store-{}-{}.{}",
+            std::process::id(),
+            next_id(),
+            extension
+        ))
+    }
+
+    fn read_values(path: &PathBuf) -> Value {
+        let content = std::fs::read_to_string(path).unwrap();
+        let document: Value = serde_json::from_str(&content).unwrap();
+        document["values"].clone()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for extension in ["json", "toml"] {
+            let path = temp_path(extension);
+            {
+                let store = Store::open(&path).unwrap();
+                store.set("count", &3).unwrap();
+                store.namespace("window").set("title", "Winia").unwrap();
+                store.flush().unwrap();
+            }
+            let store = Store::open(&path).unwrap();
+            assert_eq!(store.get::<i32>("count"), Some(3));
+            assert_eq!(
+                store.namespace("window").get::<String>("title"),
+                Some("Winia".to_string())
+            );
+            assert_eq!(store.get::<i32>("missing"), None);
+            store.remove("count");
+            store.flush().unwrap();
+            drop(store);
+            assert_eq!(Store::open(&path).unwrap().get::<i32>("count"), None);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_reads_dont_create_namespaces() {
+        let path = temp_path("json");
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.namespace("empty").get::<i32>("key"), None);
+        store.namespace("empty").remove("key");
+        store.set("key", &1).unwrap();
+        store.flush().unwrap();
+        assert_eq!(read_values(&path), serde_json::json!({ "key": 1 }));
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_replaces_the_store() {
+        let path = temp_path("json");
+        std::fs::write(&path, r#"{ "version": 0, "values": { "key": 1 } }"#).unwrap();
+        let store = Store::open(&path).unwrap();
+        store.set("key", &2).unwrap();
+        store.flush().unwrap();
+        assert_eq!(read_values(&path), serde_json::json!({ "key": 2 }));
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        assert!(!PathBuf::from(temp_path).exists());
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migration() {
+        let path = temp_path("json");
+        std::fs::write(&path, r#"{ "version": 0, "values": { "size": 10 } }"#).unwrap();
+        let store = Store::builder(&path)
+            .format(StoreFormat::Json)
+            .version(1)
+            .migration(0, |values| {
+                let size = values.remove("size").unwrap();
+                values.insert("width".to_string(), size);
+            })
+            .open()
+            .unwrap();
+        assert_eq!(store.get::<i32>("width"), Some(10));
+        assert_eq!(store.get::<i32>("size"), None);
+        drop(store);
+        let content = std::fs::read_to_string(&path).unwrap();
+        let document: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(document["version"], 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
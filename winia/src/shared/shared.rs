@@ -1,10 +1,14 @@
 use crate::core::next_id;
+use crate::shared::diagnostics;
+use crate::shared::transaction::{self, TransactionRecord};
+use crate::shared::{track_read, PropagationScope};
 use crate::ui::animation::interpolator::{Interpolator, Linear};
 use crate::ui::app::EventLoopProxy;
 use parking_lot::{ArcMutexGuard, Mutex, MutexGuard, RawMutex};
 use std::fmt::Display;
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::panic::Location;
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
@@ -53,7 +57,9 @@ macro_rules! shared {
 
 pub struct SharedGuard<'a, T> {
     outer: MutexGuard<'a, Arc<Mutex<T>>>,
-    inner: ArcMutexGuard<RawMutex, T>
+    inner: ArcMutexGuard<RawMutex, T>,
+    /// The id of the shared, reported to the running transaction on the first mutable access.
+    changed: Option<usize>,
 }
 
 impl<'a, T> SharedGuard<'a, T> {
@@ -61,7 +67,11 @@ impl<'a, T> SharedGuard<'a, T> {
         outer: MutexGuard<'a, Arc<Mutex<T>>>,
         inner: ArcMutexGuard<RawMutex, T>,
     ) -> Self {
-        Self { outer, inner }
+        Self {
+            outer,
+            inner,
+            changed: None,
+        }
     }
 }
 
@@ -75,6 +85,9 @@ impl<'a, T> Deref for SharedGuard<'a, T> {
 
 impl<'a, T> DerefMut for SharedGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        if let Some(id) = self.changed.take() {
+            transaction::changed_in_place(id);
+        }
         self.inner.deref_mut()
     }
 }
@@ -95,8 +108,7 @@ pub struct Shared<T> {
     /// A list of objects that observed by this shared.
     /// The first element of the tuple is the observable object, and the second element is the id of the observer.
     /// The id is used to remove the observer when the observable object is dropped.
-    observed_objects:
-        Arc<Mutex<Vec<(Option<Box<dyn Observable + Send>>, Box<dyn FnOnce() + Send>)>>>,
+    observed_objects: Arc<Mutex<ObservedObjects>>,
     /// A list of simple observers. The key is the id of the observer.
     simple_observers: Arc<Mutex<Vec<(usize, Box<dyn FnMut() + Send>)>>>,
     /// A list of specific observers. The key is the id of the observer. The value is the observer function.
@@ -105,6 +117,8 @@ pub struct Shared<T> {
     animation: Arc<Mutex<Option<SharedAnimation<T>>>>,
 }
 
+type ObservedObjects = Vec<(Option<Box<dyn Observable + Send>>, Box<dyn FnOnce() + Send>)>;
+
 impl<T: Send + 'static> Shared<T> {
     fn inner_new(value: T, value_generator: Option<Box<dyn Fn() -> T + Send>>) -> Self {
         let value = Arc::new(Mutex::new(Arc::new(Mutex::new(value))));
@@ -152,7 +166,20 @@ impl<T: Send + 'static> Shared<T> {
 
     /// Modifications made through this function will not send notifications to observers.
     /// If you want to notify observers after modifying the value, use the [`write`](Shared::write) method.
+    ///
+    /// Locking is not a read: computed values only depend on shareds read with
+    /// [`read`](Shared::read) or [`get`](Gettable::get).
+    ///
+    /// Inside a [`transaction`](crate::shared::transaction), mutating the value through the guard
+    /// can't be rolled back, the observers are notified even if the transaction fails.
     pub fn lock(&self) -> SharedGuard<T> {
+        let mut guard = self.lock_value();
+        guard.changed = Some(self.id);
+        guard
+    }
+
+    /// Locks the value for the shared's own bookkeeping, which is never reported to a transaction.
+    fn lock_value(&self) -> SharedGuard<T> {
         let outer = self.value.lock();
         let inner = outer.lock_arc();
         SharedGuard::new(outer, inner)
@@ -164,7 +191,9 @@ impl<T: Send + 'static> Shared<T> {
         }
         let outer = self.value.try_lock()?;
         let inner = outer.try_lock_arc()?;
-        Some(SharedGuard::new(outer, inner))
+        let mut guard = SharedGuard::new(outer, inner);
+        guard.changed = Some(self.id);
+        Some(guard)
     }
 
     pub fn is_locked(&self) -> bool {
@@ -172,7 +201,8 @@ impl<T: Send + 'static> Shared<T> {
     }
 
     pub fn read<R>(&self, mut operation: impl FnMut(&T) -> R) -> R {
-        let value = self.lock();
+        track_read(self);
+        let value = self.lock_value();
         operation(value.deref())
     }
 
@@ -200,18 +230,51 @@ impl<T: Send + 'static> Shared<T> {
         value
     }
 
+    /// Clears the observed objects before the shared is changed.
+    /// If this is the first change of the shared in the innermost transaction,
+    /// they are returned instead, so that a rollback can restore them.
+    fn begin_change(&self) -> Option<ObservedObjects> {
+        if transaction::needs_record(self.id) {
+            Some(std::mem::take(self.observed_objects.lock().deref_mut()))
+        } else {
+            self.clear_observed_objects();
+            None
+        }
+    }
+
+    fn record_change(
+        &self,
+        observed_objects: Option<ObservedObjects>,
+        previous_value: PreviousValue<T>,
+        value_generator: Option<Box<dyn Fn() -> T + Send>>,
+    ) {
+        if let Some(observed_objects) = observed_objects {
+            transaction::record(
+                self.id,
+                Box::new(SharedRecord {
+                    shared: self.clone(),
+                    previous_value,
+                    value_generator,
+                    observed_objects,
+                }),
+            );
+        }
+    }
+
     pub fn set_static(&self, value: T) {
         {
-            self.clear_observed_objects();
+            let observed_objects = self.begin_change();
             let filtered_value = self.filter(value);
-            if *self.is_from_shared.lock() {
+            let previous_value = if *self.is_from_shared.lock() {
                 let value = Arc::new(Mutex::new(filtered_value));
-                *self.value.lock() = value;
                 *self.is_from_shared.lock() = false;
+                PreviousValue::Shared(std::mem::replace(self.value.lock().deref_mut(), value))
             } else {
-                *self.lock() = self.filter(filtered_value);
-            }
-            *self.value_generator.lock() = None;
+                let value = self.filter(filtered_value);
+                PreviousValue::Value(std::mem::replace(self.lock_value().deref_mut(), value))
+            };
+            let value_generator = self.value_generator.lock().take();
+            self.record_change(observed_objects, previous_value, value_generator);
         }
         self.notify();
     }
@@ -255,9 +318,11 @@ impl<T: Send + 'static> Shared<T> {
         o: Box<[Box<dyn Observable + Send + 'static>]>,
         value_generator: impl Fn() -> T + Send + 'static,
     ) {
-        self.clear_observed_objects();
+        let observed_objects = self.begin_change();
         let value_generator: Box<dyn Fn() -> T + Send> = Box::new(value_generator);
-        *self.value_generator.lock() = Some(value_generator);
+        let previous_generator = self.value_generator.lock().replace(value_generator);
+        // The value itself only changes when the notification runs.
+        self.record_change(observed_objects, PreviousValue::Unchanged, previous_generator);
         self.notify();
 
         for observable in o {
@@ -277,11 +342,18 @@ impl<T: Send + 'static> Shared<T> {
     }
     
     pub fn set_shared(&self, shared: impl Into<Shared<T>>) {
-        *self.is_from_shared.lock() = true;
+        let was_from_shared = std::mem::replace(self.is_from_shared.lock().deref_mut(), true);
         let shared = shared.into();
-        self.clear_observed_objects();
-        *self.value.lock() = shared.value.lock().clone();
-        *self.value_generator.lock() = None;
+        let observed_objects = self.begin_change();
+        let value = shared.value.lock().clone();
+        let previous_value = std::mem::replace(self.value.lock().deref_mut(), value);
+        let previous_value = if was_from_shared {
+            PreviousValue::Shared(previous_value)
+        } else {
+            PreviousValue::Owned(previous_value)
+        };
+        let previous_generator = self.value_generator.lock().take();
+        self.record_change(observed_objects, previous_value, previous_generator);
         self.observe(shared);
         self.notify();
     }
@@ -291,6 +363,16 @@ impl<T: Send + 'static> Shared<T> {
     }
 
     pub fn notify(&self) {
+        if transaction::is_in_transaction() {
+            let shared = self.clone();
+            transaction::defer_notification(self.id, move || shared.notify());
+            return;
+        }
+        let _scope = PropagationScope::enter();
+        #[cfg(debug_assertions)]
+        let Some(_notification) = diagnostics::enter_notification::<T>(self.id) else {
+            return;
+        };
         if self.can_generate() {
             let value_generator = self.value_generator.lock();
             let generated_value = value_generator.as_ref().unwrap()();
@@ -300,7 +382,7 @@ impl<T: Send + 'static> Shared<T> {
                 *self.value.lock() = value;
                 *self.is_from_shared.lock() = false;
             } else {
-                *self.lock() = filtered_value;
+                *self.lock_value() = filtered_value;
             }
         }
 
@@ -308,13 +390,21 @@ impl<T: Send + 'static> Shared<T> {
             observer();
         }
 
-        let mut value = self.lock();
+        let mut value = self.lock_value();
         for (_, observer) in self.specific_observers.lock().iter_mut() {
             observer(&mut *value);
         }
     }
 
-    fn clear_observed_objects(&self) {
+    pub(crate) fn add_observed_object(
+        &self,
+        observable: Option<Box<dyn Observable + Send>>,
+        removal: Box<dyn FnOnce() + Send>,
+    ) {
+        self.observed_objects.lock().push((observable, removal));
+    }
+
+    pub(crate) fn clear_observed_objects(&self) {
         for (_, removal) in self.observed_objects.lock().drain(..) {
             removal();
         }
@@ -324,6 +414,7 @@ impl<T: Send + 'static> Shared<T> {
         self.specific_observers
             .lock()
             .push((id, Box::new(observer)));
+        diagnostics::observer_added(self, id);
     }
 
     pub fn observe<O: Into<Box<dyn Observable + Send + 'static>>>(&self, observable: O) {
@@ -352,6 +443,7 @@ impl<T: Send + 'static> Shared<T> {
     pub fn remove_observer(&self, id: usize) {
         self.simple_observers.lock().retain(|(i, _)| *i != id);
         self.specific_observers.lock().retain(|(i, _)| *i != id);
+        diagnostics::observers_removed(self.id, id);
     }
 
     pub fn get_animation(&self) -> Option<SharedAnimation<T>> {
@@ -372,29 +464,91 @@ impl<T: Send + 'static> Shared<T> {
         }
     }
 
+    #[track_caller]
     pub fn redraw_when_changed(mut self, event_loop_proxy: &EventLoopProxy, id: usize) -> Self {
         let event_loop_proxy = event_loop_proxy.clone();
+        let location = Location::caller();
         self.add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_redraw();
+                event_loop_proxy.request_repaint_from(id, location);
             }),
         );
         self
     }
 
+    #[track_caller]
     pub fn layout_when_changed(mut self, event_loop_proxy: &EventLoopProxy, id: usize) -> Self {
         let event_loop_proxy = event_loop_proxy.clone();
+        let location = Location::caller();
         self.add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_layout();
+                event_loop_proxy.request_layout_from(location);
             }),
         );
         self
     }
 }
 
+enum PreviousValue<T> {
+    /// The value was replaced in place.
+    Value(T),
+    /// The value was shared with another shared through [`set_shared`](Shared::set_shared).
+    Shared(Arc<Mutex<T>>),
+    /// The value was owned by the shared, and replaced by [`set_shared`](Shared::set_shared).
+    Owned(Arc<Mutex<T>>),
+    /// Only the value generator was replaced, by [`set_dynamic`](Shared::set_dynamic).
+    Unchanged,
+}
+
+/// The state of a shared before it was first set in a transaction.
+struct SharedRecord<T> {
+    shared: Shared<T>,
+    previous_value: PreviousValue<T>,
+    value_generator: Option<Box<dyn Fn() -> T + Send>>,
+    observed_objects: ObservedObjects,
+}
+
+impl<T: Send + 'static> TransactionRecord for SharedRecord<T> {
+    fn commit(self: Box<Self>) {
+        for (_, removal) in self.observed_objects {
+            removal();
+        }
+    }
+
+    fn rollback(self: Box<Self>) {
+        let SharedRecord {
+            shared,
+            previous_value,
+            value_generator,
+            observed_objects,
+        } = *self;
+        shared.clear_observed_objects();
+        match previous_value {
+            PreviousValue::Value(value) => {
+                if *shared.is_from_shared.lock() {
+                    *shared.value.lock() = Arc::new(Mutex::new(value));
+                    *shared.is_from_shared.lock() = false;
+                } else {
+                    *shared.lock_value() = value;
+                }
+            }
+            PreviousValue::Shared(value) => {
+                *shared.value.lock() = value;
+                *shared.is_from_shared.lock() = true;
+            }
+            PreviousValue::Owned(value) => {
+                *shared.value.lock() = value;
+                *shared.is_from_shared.lock() = false;
+            }
+            PreviousValue::Unchanged => {}
+        }
+        *shared.value_generator.lock() = value_generator;
+        *shared.observed_objects.lock() = observed_objects;
+    }
+}
+
 /*impl<T: Send + Observable + 'static> Shared<T> {
     pub fn from_observable(
         observable: T
@@ -436,13 +590,16 @@ impl<T> Clone for Shared<T> {
     }
 }
 
-impl<T: Send> Observable for Shared<T> {
+impl<T: Send + 'static> Observable for Shared<T> {
     fn add_observer(&mut self, id: usize, observer: Box<dyn FnMut() + Send>) -> Removal {
         self.simple_observers.lock().push((id, observer));
+        diagnostics::observer_added(self, id);
         let simple_observers = self.simple_observers.clone();
+        let shared_id = self.id;
         Removal {
             removal: Box::new(move || {
                 simple_observers.lock().retain(|(i, _)| *i != id);
+                diagnostics::observers_removed(shared_id, id);
             }),
         }
     }
@@ -494,6 +651,7 @@ impl<T: Send + 'static> Settable<T> for Shared<T> {
 
 impl<T: Clone + Send + 'static> Gettable<T> for Shared<T> {
     fn get(&self) -> T {
+        track_read(self);
         self.lock().clone()
     }
 }
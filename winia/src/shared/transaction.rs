@@ -0,0 +1,319 @@
+use crate::shared::PropagationScope;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+/// Remembers how to undo the change made to a shared during a transaction.
+pub(crate) trait TransactionRecord {
+    /// Called when the outermost transaction succeeds.
+    fn commit(self: Box<Self>);
+    fn rollback(self: Box<Self>);
+}
+
+#[derive(Default)]
+struct TransactionState {
+    /// The deferred notifications, at most one per shared.
+    notifications: Vec<(usize, Box<dyn FnOnce()>)>,
+    records: Vec<(usize, Box<dyn TransactionRecord>)>,
+    /// The shareds mutated through [`lock`](crate::shared::Shared::lock), which can't be restored.
+    changed_in_place: HashSet<usize>,
+    /// The number of records and notifications made before each nested transaction started.
+    savepoints: Vec<(usize, usize)>,
+}
+
+impl TransactionState {
+    /// Drops the notifications queued since `notifications`, except for the shareds that keep
+    /// their new value after a rollback.
+    fn discard_notifications(&mut self, notifications: usize) {
+        let discarded = self.notifications.split_off(notifications);
+        let changed_in_place = &self.changed_in_place;
+        self.notifications.extend(
+            discarded
+                .into_iter()
+                .filter(|(id, _)| changed_in_place.contains(id)),
+        );
+    }
+}
+
+thread_local! {
+    static TRANSACTION: RefCell<Option<TransactionState>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn is_in_transaction() -> bool {
+    TRANSACTION.with(|transaction| transaction.borrow().is_some())
+}
+
+/// Queues `notify` to run when the outermost transaction commits.
+/// Notifications for a shared that is already queued are dropped.
+pub(crate) fn defer_notification(id: usize, notify: impl FnOnce() + 'static) {
+    TRANSACTION.with(|transaction| {
+        if let Some(state) = transaction.borrow_mut().as_mut() {
+            if state.notifications.iter().all(|(queued, _)| *queued != id) {
+                state.notifications.push((id, Box::new(notify)));
+            }
+        }
+    });
+}
+
+/// Whether the shared with `id` is changed for the first time in the innermost transaction.
+pub(crate) fn needs_record(id: usize) -> bool {
+    TRANSACTION.with(|transaction| match transaction.borrow().as_ref() {
+        Some(state) => {
+            let (savepoint, _) = state.savepoints.last().copied().unwrap_or_default();
+            state.records[savepoint..].iter().all(|(recorded, _)| *recorded != id)
+        }
+        None => false,
+    })
+}
+
+pub(crate) fn record(id: usize, record: Box<dyn TransactionRecord>) {
+    TRANSACTION.with(|transaction| {
+        if let Some(state) = transaction.borrow_mut().as_mut() {
+            state.records.push((id, record));
+        }
+    });
+}
+
+/// Marks the shared with `id` as mutated in place, its observers are notified even if the
+/// transaction rolls back.
+pub(crate) fn changed_in_place(id: usize) {
+    TRANSACTION.with(|transaction| {
+        if let Some(state) = transaction.borrow_mut().as_mut() {
+            state.changed_in_place.insert(id);
+        }
+    });
+}
+
+/// Ends a transaction when dropped, rolling it back unless it was committed.
+/// This keeps the thread's transaction state consistent when the operation panics.
+struct TransactionScope {
+    nested: bool,
+    committed: bool,
+}
+
+impl TransactionScope {
+    fn enter() -> Self {
+        let nested = TRANSACTION.with(|transaction| {
+            let mut transaction = transaction.borrow_mut();
+            match transaction.as_mut() {
+                Some(state) => {
+                    state
+                        .savepoints
+                        .push((state.records.len(), state.notifications.len()));
+                    true
+                }
+                None => {
+                    *transaction = Some(TransactionState::default());
+                    false
+                }
+            }
+        });
+        Self {
+            nested,
+            committed: false,
+        }
+    }
+
+    fn end_nested(&self) {
+        let rolled_back = TRANSACTION.with(|transaction| {
+            let mut transaction = transaction.borrow_mut();
+            let state = transaction.as_mut()?;
+            let (records, notifications) = state.savepoints.pop().unwrap_or_default();
+            if self.committed {
+                return None;
+            }
+            state.discard_notifications(notifications);
+            Some(state.records.split_off(records))
+        });
+        // Records are rolled back outside the borrow, they may touch other shareds.
+        for (_, record) in rolled_back.into_iter().flatten().rev() {
+            record.rollback();
+        }
+    }
+
+    fn end_outermost(&self) {
+        let state = TRANSACTION
+            .with(|transaction| transaction.borrow_mut().take())
+            .unwrap_or_default();
+        if self.committed {
+            let _scope = PropagationScope::enter();
+            for (_, record) in state.records {
+                record.commit();
+            }
+            for (_, notify) in state.notifications {
+                notify();
+            }
+            return;
+        }
+        for (_, record) in state.records.into_iter().rev() {
+            record.rollback();
+        }
+        // Observers are not run while unwinding, a panicking observer would abort the process.
+        if std::thread::panicking() {
+            return;
+        }
+        let _scope = PropagationScope::enter();
+        for (id, notify) in state.notifications {
+            if state.changed_in_place.contains(&id) {
+                notify();
+            }
+        }
+    }
+}
+
+impl Drop for TransactionScope {
+    fn drop(&mut self) {
+        if self.nested {
+            self.end_nested();
+        } else {
+            self.end_outermost();
+        }
+    }
+}
+
+/// Runs `operation` as a transaction.
+///
+/// Observers of the shareds changed by `operation` are not notified until the outermost
+/// transaction ends, and each shared notifies its observers only once.
+/// If `operation` returns an error or panics, the values assigned with
+/// [`set_static`](crate::shared::Shared::set_static) (or [`Settable::set`](crate::shared::Settable::set)),
+/// [`set_dynamic`](crate::shared::Shared::set_dynamic) and [`set_shared`](crate::shared::Shared::set_shared)
+/// are restored and their observers are not notified.
+/// Changes made through [`write`](crate::shared::Shared::write) or [`lock`](crate::shared::Shared::lock)
+/// can't be restored, so their observers are still notified after the rollback.
+///
+/// Transactions can be nested, an error only rolls back the changes of the innermost transaction.
+pub fn transaction<R, E>(operation: impl FnOnce() -> Result<R, E>) -> Result<R, E> {
+    let mut scope = TransactionScope::enter();
+    let result = operation();
+    scope.committed = result.is_ok();
+    drop(scope);
+    result
+}
+
+/// Runs `operation` as a [`transaction`] that can't fail.
+/// Observers are notified once per changed shared after `operation` returns,
+/// and computed values that depend on several of the changed shareds are recomputed only once.
+pub fn batch<R>(operation: impl FnOnce() -> R) -> R {
+    match transaction(|| Ok::<R, Infallible>(operation())) {
+        Ok(result) => result,
+        Err(infallible) => match infallible {},
+    }
+}
+
+#[cfg(test)]
+mod transaction_test {
+    use crate::core::next_id;
+    use crate::shared::{batch, transaction, Gettable, Observable, Shared};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn counted(value: i32) -> (Shared<i32>, Arc<AtomicUsize>) {
+        let mut shared = Shared::from_static(value);
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        shared
+            .add_observer(
+                next_id(),
+                Box::new(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }),
+            )
+            .drop();
+        (shared, count)
+    }
+
+    #[test]
+    fn test_commit_notifies_once() {
+        let (shared, count) = counted(1);
+        batch(|| {
+            shared.set_static(2);
+            shared.set_static(3);
+            assert_eq!(count.load(Ordering::SeqCst), 0);
+        });
+        assert_eq!(shared.get(), 3);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_rollback_restores_values() {
+        let (shared, count) = counted(1);
+        let result = transaction(|| {
+            shared.set_static(2);
+            Err::<(), ()>(())
+        });
+        assert!(result.is_err());
+        assert_eq!(shared.get(), 1);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_nested_rollback_keeps_outer_changes() {
+        let (outer, outer_count) = counted(1);
+        let (inner, inner_count) = counted(10);
+        let result = transaction(|| {
+            outer.set_static(2);
+            let nested = transaction(|| {
+                inner.set_static(20);
+                outer.set_static(3);
+                Err::<(), ()>(())
+            });
+            assert!(nested.is_err());
+            assert_eq!(outer.get(), 2);
+            assert_eq!(inner.get(), 10);
+            Ok::<(), ()>(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(outer.get(), 2);
+        assert_eq!(inner.get(), 10);
+        assert_eq!(outer_count.load(Ordering::SeqCst), 1);
+        assert_eq!(inner_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_nested_commit_is_rolled_back_by_outer() {
+        let (shared, count) = counted(1);
+        let result = transaction(|| {
+            transaction(|| {
+                shared.set_static(2);
+                Ok::<(), ()>(())
+            })?;
+            assert_eq!(shared.get(), 2);
+            Err::<(), ()>(())
+        });
+        assert!(result.is_err());
+        assert_eq!(shared.get(), 1);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_rollback_notifies_changes_in_place() {
+        let (shared, count) = counted(1);
+        let result = transaction(|| {
+            shared.write(|value| *value = 5);
+            Err::<(), ()>(())
+        });
+        assert!(result.is_err());
+        assert_eq!(shared.get(), 5);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_panic_resets_transaction() {
+        let (shared, count) = counted(1);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _ = transaction(|| -> Result<(), ()> {
+                shared.set_static(2);
+                panic!("transaction failed");
+            });
+        }));
+        assert!(result.is_err());
+        assert!(!super::is_in_transaction());
+        assert_eq!(shared.get(), 1);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        shared.set_static(3);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
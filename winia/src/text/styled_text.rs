@@ -6,7 +6,7 @@ use skia_safe::textlayout::{
     FontCollection, ParagraphStyle, TextAlign, TextDecoration, TextStyle as SkiaTextStyle,
     TypefaceFontProvider,
 };
-use skia_safe::{FontMgr, FontStyle, Paint};
+use skia_safe::{FontMgr, FontStyle, Paint, Rect};
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use std::ops::{Add, Deref, Index, Range};
@@ -81,10 +81,11 @@ pub(crate) fn create_segments<'text>(
     text: &'text StyledText,
     range: &Range<usize>,
     text_style: &SkiaTextStyle,
+    bounds: Rect,
 ) -> Vec<StyleSegment<'text>> {
     let mut text_segments = Vec::new();
 
-    let first_segment = StyleSegment::new(text, range, &text_style);
+    let first_segment = StyleSegment::new(text, range, &text_style, bounds);
     text_segments.push(first_segment);
     text.get_styles(range.clone())
         .iter()
@@ -110,16 +111,19 @@ pub(crate) fn create_segments<'text>(
                             text,
                             &(text_segment.range.start..range.start),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         let middle_segment = StyleSegment::new(
                             text,
                             &(range.start..range.end),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         let right_segment = StyleSegment::new(
                             text,
                             &(range.end..text_segment.range.end),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         text_segments.remove(index);
                         text_segments.insert(index, left_segment);
@@ -133,11 +137,13 @@ pub(crate) fn create_segments<'text>(
                             text,
                             &(text_segment.range.start..range.start),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         let right_segment = StyleSegment::new(
                             text,
                             &(range.start..text_segment.range.end),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         text_segments.remove(index);
                         text_segments.insert(index, left_segment);
@@ -150,11 +156,13 @@ pub(crate) fn create_segments<'text>(
                             text,
                             &(text_segment.range.start..range.end),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         let right_segment = StyleSegment::new(
                             text,
                             &(range.end..text_segment.range.end),
                             &text_segment.text_style,
+                            text_segment.bounds,
                         );
                         text_segments.remove(index);
                         text_segments.insert(index, left_segment);
@@ -174,6 +182,8 @@ pub(crate) struct StyleSegment<'text> {
     range: Range<usize>,
     text_style: SkiaTextStyle,
     image: Option<SharedDrawable>,
+    /// What the foreground brushes are resolved against.
+    bounds: Rect,
 }
 
 impl Debug for StyleSegment<'_> {
@@ -189,6 +199,7 @@ impl<'text> StyleSegment<'text> {
         text: &'text StyledText,
         range: &Range<usize>,
         def_text_style: &SkiaTextStyle,
+        bounds: Rect,
     ) -> StyleSegment<'text> {
         let text_style = def_text_style.clone();
         StyleSegment {
@@ -196,6 +207,7 @@ impl<'text> StyleSegment<'text> {
             range: range.clone(),
             text_style,
             image: None,
+            bounds,
         }
     }
 
@@ -240,6 +252,12 @@ impl<'text> StyleSegment<'text> {
             TextStyle::TextColor(color) => {
                 self.text_style.set_color(*color);
             }
+            TextStyle::Foreground(brush) => {
+                let mut paint = Paint::default();
+                paint.set_anti_alias(true);
+                brush.apply(&mut paint, self.bounds);
+                self.text_style.set_foreground_paint(&paint);
+            }
             TextStyle::Weight(weight) => {
                 let font_style = self.text_style.font_style();
                 self.text_style.set_font_style(FontStyle::new(
@@ -349,27 +367,41 @@ impl StyledText {
         // text_style.set_font_families(&["CodeNewRoman Nerd Font"]);
         // paragraph_style.set_text_style(&text_style);
 
-        let mut paragraph_builder = ParagraphBuilder::new(&paragraph_style, font_collection());
-
-        if self.string.is_empty() {
-            let mut text = self.clone();
-            text.push(' ');
-            create_segments(&text, &(0..text.len()), default_text_style)
-                .iter()
-                .for_each(|style_segment| {
-                    paragraph_builder.add_style_segment(style_segment);
-                });
-        } else {
-            create_segments(self, &(0..self.len()), default_text_style)
-                .iter()
-                .for_each(|style_segment| {
-                    paragraph_builder.add_style_segment(style_segment);
-                });
+        let build = |bounds: Rect| {
+            let mut paragraph_builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+
+            if self.string.is_empty() {
+                let mut text = self.clone();
+                text.push(' ');
+                create_segments(&text, &(0..text.len()), default_text_style, bounds)
+                    .iter()
+                    .for_each(|style_segment| {
+                        paragraph_builder.add_style_segment(style_segment);
+                    });
+            } else {
+                create_segments(self, &(0..self.len()), default_text_style, bounds)
+                    .iter()
+                    .for_each(|style_segment| {
+                        paragraph_builder.add_style_segment(style_segment);
+                    });
+            };
+
+            let mut paragraph = paragraph_builder.build();
+            paragraph.layout(max_width);
+            paragraph
         };
 
-        let mut paragraph = paragraph_builder.build();
-        paragraph.layout(max_width);
-        paragraph
+        let paragraph = build(Rect::new_empty());
+        // Foreground brushes are resolved against the laid out paragraph, which takes a second pass.
+        let has_foreground = self
+            .styles
+            .iter()
+            .any(|(style, _, _)| matches!(style, TextStyle::Foreground(_)));
+        if has_foreground {
+            build(Rect::from_wh(paragraph.longest_line(), paragraph.height()))
+        } else {
+            paragraph
+        }
     }
 
     pub fn get_text_layout<'a>(&'a mut self, paragraph: &'a Paragraph) -> TextLayout<'a> {
@@ -3,6 +3,7 @@ use skia_safe::font_style::Weight;
 use strum_macros::{AsRefStr, Display};
 use proc_macro::AsRef;
 use crate::shared::SharedDrawable;
+use crate::ui::item::Brush;
 
 #[derive(Copy, Clone, Debug, PartialEq, AsRefStr, Display, AsRef)]
 pub enum StyleType {
@@ -13,6 +14,7 @@ pub enum StyleType {
     FontSize,
     BackgroundColor,
     TextColor,
+    Foreground,
     Weight,
     Tracking,
     Typeface,
@@ -37,6 +39,9 @@ pub enum TextStyle {
     FontSize(f32),
     BackgroundColor(Color),
     TextColor(Color),
+    /// Paints the glyphs in place of the text color, resolved against the bounds of the laid out
+    /// paragraph, as wide as its longest line.
+    Foreground(Brush),
     Weight(Weight),
     Tracking(f32),
     Typeface(Typeface),
@@ -59,6 +64,7 @@ impl TextStyle {
             TextStyle::FontSize(_) => StyleType::FontSize,
             TextStyle::BackgroundColor(_) => StyleType::BackgroundColor,
             TextStyle::TextColor(_) => StyleType::TextColor,
+            TextStyle::Foreground(_) => StyleType::Foreground,
             TextStyle::Weight(_) => StyleType::Weight,
             TextStyle::Tracking(_) => StyleType::Tracking,
             TextStyle::Typeface(_) => StyleType::Typeface,
@@ -0,0 +1,663 @@
+use crate::shared::{Settable, Shared, SharedAnimationTrait, WeakShared};
+use crate::ui::animation::interpolator::{find_segment, Interpolator, Linear, Segment};
+use crate::ui::animation::interpolate_color;
+use crate::ui::app::EventLoopProxy;
+use parking_lot::Mutex;
+use skia_safe::Color;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A leaf of a [`Motion`] that sets its values for a point in time.
+trait Track: Send {
+    fn duration(&self) -> Duration;
+    /// `time` is within `0..=duration`.
+    fn apply(&mut self, time: Duration);
+}
+
+struct Keyframe<T> {
+    time: Duration,
+    value: T,
+    /// The easing of the segment that ends at this keyframe.
+    easing: Box<dyn Interpolator + Send>,
+}
+
+/// Animates a shared through a list of keyframes, each segment with its own easing.
+///
+/// The times of the keyframes are measured from the start of the track. From there until the
+/// first keyframe the shared holds the value of the first keyframe, after the last one the value
+/// of the last keyframe. A track that hasn't started yet, because of its own
+/// [`delay`](Motion::delay) or because it comes later in a sequence, leaves the shared as it is.
+pub struct KeyframeTrack<T> {
+    shared: WeakShared<T>,
+    keyframes: Vec<Keyframe<T>>,
+    value_generator: Box<dyn Fn(&T, &T, f32) -> T + Send>,
+}
+
+impl<T: Clone + Send + 'static> KeyframeTrack<T> {
+    /// `value_generator` blends two keyframe values with the eased progress of the segment.
+    pub fn new(shared: &Shared<T>, value_generator: impl Fn(&T, &T, f32) -> T + Send + 'static) -> Self {
+        Self {
+            shared: shared.weak(),
+            keyframes: Vec::new(),
+            value_generator: Box::new(value_generator),
+        }
+    }
+
+    /// Adds a keyframe at `time`, reached from the previous keyframe linearly.
+    pub fn keyframe(self, time: Duration, value: T) -> Self {
+        self.keyframe_with(time, value, Linear::new())
+    }
+
+    /// Adds a keyframe at `time`, reached from the previous keyframe with `easing`.
+    pub fn keyframe_with(mut self, time: Duration, value: T, easing: impl Interpolator + Send + 'static) -> Self {
+        let index = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        self.keyframes.insert(
+            index,
+            Keyframe {
+                time,
+                value,
+                easing: Box::new(easing),
+            },
+        );
+        self
+    }
+
+    fn value_at(&self, time: Duration) -> Option<T> {
+        let segment = find_segment(&self.keyframes, time.as_secs_f32(), |keyframe| {
+            keyframe.time.as_secs_f32()
+        })?;
+        Some(match segment {
+            Segment::Hold(index) => self.keyframes[index].value.clone(),
+            Segment::Between { end, progress } => {
+                let (from, to) = (&self.keyframes[end - 1], &self.keyframes[end]);
+                (self.value_generator)(&from.value, &to.value, to.easing.interpolate(progress))
+            }
+        })
+    }
+}
+
+impl KeyframeTrack<f32> {
+    pub fn f32(shared: &Shared<f32>) -> Self {
+        Self::new(shared, |from, to, progress| from + (to - from) * progress)
+    }
+}
+
+impl KeyframeTrack<Color> {
+    pub fn color(shared: &Shared<Color>) -> Self {
+        Self::new(shared, interpolate_color)
+    }
+}
+
+impl<T: Clone + Send + 'static> Track for KeyframeTrack<T> {
+    fn duration(&self) -> Duration {
+        self.keyframes.last().map_or(Duration::ZERO, |keyframe| keyframe.time)
+    }
+
+    fn apply(&mut self, time: Duration) {
+        let Some(value) = self.value_at(time) else {
+            return;
+        };
+        if let Some(shared) = self.shared.upgrade() {
+            shared.set(value);
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> From<KeyframeTrack<T>> for Motion {
+    fn from(track: KeyframeTrack<T>) -> Self {
+        Motion::new(Node::Track(Box::new(track)))
+    }
+}
+
+struct Custom {
+    duration: Duration,
+    transformation: Box<dyn FnMut(f32) + Send>,
+}
+
+impl Track for Custom {
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn apply(&mut self, time: Duration) {
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            (time.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+        (self.transformation)(progress);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    Count(u32),
+    Infinite,
+}
+
+enum Node {
+    Track(Box<dyn Track>),
+    Sequence(Vec<Motion>),
+    Parallel(Vec<Motion>),
+    Wait(Duration),
+}
+
+/// A node of an animation graph.
+///
+/// Leaves are [`KeyframeTrack`]s, [`custom`](Motion::custom) transformations and [`wait`](Motion::wait)s,
+/// and they are composed with [`sequence`](Motion::sequence), [`parallel`](Motion::parallel)
+/// and [`stagger`](Motion::stagger). Every node can be delayed and repeated.
+/// The graph is run with [`start`](Motion::start), which returns a [`MotionController`].
+///
+/// A node only sets its values once its start time is reached, so nodes of a sequence
+/// may animate the same shared one after another.
+pub struct Motion {
+    node: Node,
+    delay: Duration,
+    repeat: Repeat,
+    alternate: bool,
+    /// The local time the node was last applied with, to skip values that didn't change.
+    last_time: Option<Duration>,
+}
+
+impl Motion {
+    fn new(node: Node) -> Self {
+        Self {
+            node,
+            delay: Duration::ZERO,
+            repeat: Repeat::Count(1),
+            alternate: false,
+            last_time: None,
+        }
+    }
+
+    /// Runs `motions` one after another.
+    pub fn sequence(motions: impl IntoIterator<Item = Motion>) -> Self {
+        Self::new(Node::Sequence(motions.into_iter().collect()))
+    }
+
+    /// Runs `motions` at the same time. The group lasts as long as its longest motion.
+    pub fn parallel(motions: impl IntoIterator<Item = Motion>) -> Self {
+        Self::new(Node::Parallel(motions.into_iter().collect()))
+    }
+
+    /// Builds a motion for every target and runs them in parallel, each starting `interval` after the previous one.
+    pub fn stagger<I>(
+        targets: impl IntoIterator<Item = I>,
+        interval: Duration,
+        mut builder: impl FnMut(I) -> Motion,
+    ) -> Self {
+        let motions = targets.into_iter().enumerate().map(|(index, target)| {
+            let mut motion = builder(target);
+            motion.delay += interval * index as u32;
+            motion
+        });
+        Self::parallel(motions)
+    }
+
+    /// Calls `transformation` with the linear progress from 0.0 to 1.0 over `duration`.
+    pub fn custom(duration: Duration, transformation: impl FnMut(f32) + Send + 'static) -> Self {
+        Self::new(Node::Track(Box::new(Custom {
+            duration,
+            transformation: Box::new(transformation),
+        })))
+    }
+
+    /// Does nothing for `duration`, e.g. to leave a gap in a sequence.
+    pub fn wait(duration: Duration) -> Self {
+        Self::new(Node::Wait(duration))
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn alternate(mut self, alternate: bool) -> Self {
+        self.alternate = alternate;
+        self
+    }
+
+    /// The duration of one repetition, without the delay. `None` if it never ends.
+    fn pass_duration(&self) -> Option<Duration> {
+        match &self.node {
+            Node::Track(track) => Some(track.duration()),
+            Node::Wait(duration) => Some(*duration),
+            Node::Sequence(motions) => motions
+                .iter()
+                .try_fold(Duration::ZERO, |total, motion| Some(total + motion.total_duration()?)),
+            Node::Parallel(motions) => motions
+                .iter()
+                .try_fold(Duration::ZERO, |total, motion| Some(total.max(motion.total_duration()?))),
+        }
+    }
+
+    /// The duration including the delay and all repetitions. `None` if it never ends.
+    pub fn total_duration(&self) -> Option<Duration> {
+        match self.repeat {
+            Repeat::Count(count) => Some(self.delay + self.pass_duration()? * count),
+            Repeat::Infinite => None,
+        }
+    }
+
+    /// Applies the values for `time`, measured from the start of the node including its delay.
+    fn apply(&mut self, time: Duration) {
+        if time < self.delay {
+            self.rewind();
+            return;
+        }
+        let time = time - self.delay;
+        let Some(pass) = self.pass_duration() else {
+            self.apply_local(time);
+            return;
+        };
+        if pass.is_zero() {
+            self.apply_local(Duration::ZERO);
+            return;
+        }
+        let iterations = match self.repeat {
+            Repeat::Count(count) => count.max(1),
+            Repeat::Infinite => u32::MAX,
+        };
+        let (iteration, within) = if time >= pass * iterations {
+            (iterations - 1, pass)
+        } else {
+            let iteration = (time.as_nanos() / pass.as_nanos()) as u32;
+            (iteration, time - pass * iteration)
+        };
+        let within = if self.alternate && iteration % 2 == 1 {
+            pass - within
+        } else {
+            within
+        };
+        self.apply_local(within);
+    }
+
+    /// Restores the initial values if the node was applied past its start, e.g. when seeking back before it.
+    fn rewind(&mut self) {
+        if self.last_time.is_some_and(|last_time| last_time > Duration::ZERO) {
+            self.apply_local(Duration::ZERO);
+        }
+    }
+
+    fn apply_local(&mut self, time: Duration) {
+        if self.last_time == Some(time) {
+            return;
+        }
+        self.last_time = Some(time);
+        match &mut self.node {
+            Node::Track(track) => track.apply(time),
+            Node::Wait(_) => {}
+            Node::Sequence(motions) => {
+                let mut starts = Vec::with_capacity(motions.len());
+                let mut start = Some(Duration::ZERO);
+                for motion in motions.iter() {
+                    starts.push(start);
+                    start = start.zip(motion.total_duration()).map(|(start, duration)| start + duration);
+                }
+                // Motions that haven't started yet are rewound last to first, and before the started ones
+                // are applied, so that the values of earlier motions win when they animate the same shared.
+                for (motion, start) in motions.iter_mut().zip(starts.iter()).rev() {
+                    if start.is_none_or(|start| time < start) {
+                        motion.rewind();
+                    }
+                }
+                for (motion, start) in motions.iter_mut().zip(starts) {
+                    match start {
+                        Some(start) if time >= start => motion.apply(time - start),
+                        _ => break,
+                    }
+                }
+            }
+            Node::Parallel(motions) => {
+                for motion in motions.iter_mut() {
+                    motion.apply(time);
+                }
+            }
+        }
+    }
+
+    /// Starts the motion and returns the controller of the running motion.
+    pub fn start(self, event_loop_proxy: &EventLoopProxy) -> MotionController {
+        let controller = MotionController::new(self);
+        controller.play(event_loop_proxy);
+        controller
+    }
+}
+
+struct MotionState {
+    time: Duration,
+    total_duration: Option<Duration>,
+    last_update: Instant,
+    is_reversed: bool,
+    is_paused: bool,
+    is_stopped: bool,
+    is_running: bool,
+    /// Set by [`MotionController::seek`] so that a paused motion applies the new time once.
+    needs_apply: bool,
+    event_loop_proxy: Option<EventLoopProxy>,
+    on_finish: Option<Box<dyn FnMut() + Send>>,
+}
+
+/// Controls a running [`Motion`].
+///
+/// The motion plays from its start to its end, or backwards after [`reverse`](MotionController::reverse).
+/// It's driven by the frames of the window it was started on.
+#[derive(Clone)]
+pub struct MotionController {
+    state: Arc<Mutex<MotionState>>,
+    motion: Arc<Mutex<Motion>>,
+}
+
+impl MotionController {
+    fn new(motion: Motion) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MotionState {
+                time: Duration::ZERO,
+                total_duration: motion.total_duration(),
+                last_update: Instant::now(),
+                is_reversed: false,
+                is_paused: false,
+                is_stopped: false,
+                is_running: false,
+                needs_apply: false,
+                event_loop_proxy: None,
+                on_finish: None,
+            })),
+            motion: Arc::new(Mutex::new(motion)),
+        }
+    }
+
+    fn play(&self, event_loop_proxy: &EventLoopProxy) {
+        {
+            let mut state = self.state.lock();
+            state.event_loop_proxy = Some(event_loop_proxy.clone());
+        }
+        self.ensure_running();
+    }
+
+    /// Registers the animation with the event loop unless it's already being driven.
+    fn ensure_running(&self) {
+        let event_loop_proxy = {
+            let mut state = self.state.lock();
+            if state.is_running {
+                return;
+            }
+            let Some(event_loop_proxy) = state.event_loop_proxy.clone() else {
+                return;
+            };
+            state.is_running = true;
+            state.last_update = Instant::now();
+            event_loop_proxy
+        };
+        event_loop_proxy.start_shared_animation(Box::new(self.clone()));
+    }
+
+    /// Called when the motion reaches its end, or its start when playing backwards.
+    pub fn on_finish(self, on_finish: impl FnMut() + Send + 'static) -> Self {
+        self.state.lock().on_finish = Some(Box::new(on_finish));
+        self
+    }
+
+    pub fn pause(&self) {
+        self.state.lock().is_paused = true;
+    }
+
+    pub fn resume(&self) {
+        {
+            let mut state = self.state.lock();
+            if !state.is_paused {
+                return;
+            }
+            state.is_paused = false;
+            state.is_stopped = false;
+        }
+        self.ensure_running();
+    }
+
+    /// Jumps to `time`. A paused motion stays paused at the new time.
+    pub fn seek(&self, time: Duration) {
+        {
+            let mut state = self.state.lock();
+            state.time = match state.total_duration {
+                Some(total_duration) => time.min(total_duration),
+                None => time,
+            };
+            state.needs_apply = true;
+            state.is_stopped = false;
+        }
+        self.ensure_running();
+    }
+
+    /// Changes the direction of the motion. A finished motion plays again in the new direction.
+    pub fn reverse(&self) {
+        {
+            let mut state = self.state.lock();
+            state.is_reversed = !state.is_reversed;
+            state.is_stopped = false;
+        }
+        self.ensure_running();
+    }
+
+    /// Stops the motion where it is. `on_finish` is not called.
+    pub fn stop(&self) {
+        self.state.lock().is_stopped = true;
+    }
+
+    pub fn get_time(&self) -> Duration {
+        self.state.lock().time
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.state.lock().is_reversed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().is_paused
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.lock().is_running
+    }
+}
+
+impl SharedAnimationTrait for MotionController {
+    fn is_finished(&self) -> bool {
+        let mut state = self.state.lock();
+        let is_finished = state.is_stopped || (state.is_paused && !state.needs_apply);
+        if is_finished {
+            state.is_running = false;
+        }
+        is_finished
+    }
+
+    fn update(&self) {
+        let (time, finished) = {
+            let mut state = self.state.lock();
+            if state.is_stopped {
+                return;
+            }
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last_update);
+            state.last_update = now;
+            if !state.is_paused && !state.needs_apply {
+                state.time = if state.is_reversed {
+                    state.time.saturating_sub(elapsed)
+                } else {
+                    match state.total_duration {
+                        Some(total_duration) => (state.time + elapsed).min(total_duration),
+                        None => state.time + elapsed,
+                    }
+                };
+            }
+            state.needs_apply = false;
+            let finished = !state.is_paused
+                && if state.is_reversed {
+                    state.time.is_zero()
+                } else {
+                    state.total_duration.is_some_and(|total_duration| state.time >= total_duration)
+                };
+            if finished {
+                state.is_stopped = true;
+            }
+            (state.time, finished)
+        };
+
+        // Observers of the animated shareds may control the motion, so the state must not be locked here.
+        self.motion.lock().apply(time);
+
+        if finished {
+            let on_finish = self.state.lock().on_finish.take();
+            if let Some(mut on_finish) = on_finish {
+                on_finish();
+                self.state.lock().on_finish.get_or_insert(on_finish);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod composition_test {
+    use super::{KeyframeTrack, Motion, MotionController, Repeat};
+    use crate::shared::{Gettable, Shared, SharedAnimationTrait};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Moves `shared` from `from` to `to` over 100 milliseconds.
+    fn track(shared: &Shared<f32>, from: f32, to: f32) -> Motion {
+        KeyframeTrack::f32(shared)
+            .keyframe(Duration::ZERO, from)
+            .keyframe(millis(100), to)
+            .into()
+    }
+
+    fn assert_value(shared: &Shared<f32>, expected: f32) {
+        let value = shared.get();
+        assert!((value - expected).abs() < 1e-4, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn test_sequence_offsets() {
+        let first = Shared::from_static(-1.0);
+        let second = Shared::from_static(-1.0);
+        let mut motion = Motion::sequence([
+            track(&first, 0.0, 1.0),
+            Motion::wait(millis(50)),
+            track(&second, 0.0, 1.0),
+        ]);
+        assert_eq!(motion.total_duration(), Some(millis(250)));
+        motion.apply(millis(50));
+        assert_value(&first, 0.5);
+        // The second track hasn't started and leaves its shared alone.
+        assert_value(&second, -1.0);
+        motion.apply(millis(200));
+        assert_value(&first, 1.0);
+        assert_value(&second, 0.5);
+    }
+
+    #[test]
+    fn test_stagger_offsets() {
+        let shareds = [
+            Shared::from_static(-1.0),
+            Shared::from_static(-1.0),
+            Shared::from_static(-1.0),
+        ];
+        let mut motion =
+            Motion::stagger(shareds.iter(), millis(50), |shared| track(shared, 0.0, 1.0));
+        assert_eq!(motion.total_duration(), Some(millis(200)));
+        motion.apply(millis(75));
+        assert_value(&shareds[0], 0.75);
+        assert_value(&shareds[1], 0.25);
+        assert_value(&shareds[2], -1.0);
+        motion.apply(millis(200));
+        for shared in &shareds {
+            assert_value(shared, 1.0);
+        }
+    }
+
+    #[test]
+    fn test_delay_and_seeking_back() {
+        let shared = Shared::from_static(-1.0);
+        let mut motion = track(&shared, 0.0, 1.0).delay(millis(100));
+        assert_eq!(motion.total_duration(), Some(millis(200)));
+        motion.apply(millis(50));
+        assert_value(&shared, -1.0);
+        motion.apply(millis(150));
+        assert_value(&shared, 0.5);
+        // Seeking back before the delay restores the start of the track.
+        motion.apply(millis(50));
+        assert_value(&shared, 0.0);
+    }
+
+    #[test]
+    fn test_sequence_played_backwards() {
+        let shared = Shared::from_static(-1.0);
+        let mut motion = Motion::sequence([track(&shared, 0.0, 1.0), track(&shared, 1.0, 2.0)]);
+        motion.apply(millis(150));
+        assert_value(&shared, 1.5);
+        // The second track is rewound before the first one is applied, so the first one wins.
+        motion.apply(millis(50));
+        assert_value(&shared, 0.5);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let shared = Shared::from_static(-1.0);
+        let mut motion = track(&shared, 0.0, 1.0)
+            .repeat(Repeat::Count(3))
+            .alternate(true);
+        assert_eq!(motion.total_duration(), Some(millis(300)));
+        motion.apply(millis(120));
+        assert_value(&shared, 0.8);
+        motion.apply(millis(250));
+        assert_value(&shared, 0.5);
+        // The last pass plays forwards and the motion stays at its end.
+        motion.apply(millis(400));
+        assert_value(&shared, 1.0);
+
+        let mut motion = track(&shared, 0.0, 1.0).repeat(Repeat::Infinite);
+        assert_eq!(motion.total_duration(), None);
+        motion.apply(millis(1050));
+        assert_value(&shared, 0.5);
+    }
+
+    #[test]
+    fn test_controller_reverse() {
+        let shared = Shared::from_static(-1.0);
+        let finished = Arc::new(AtomicUsize::new(0));
+        let controller = MotionController::new(track(&shared, 0.0, 1.0)).on_finish({
+            let finished = finished.clone();
+            move || {
+                finished.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        controller.seek(millis(80));
+        controller.update();
+        assert_value(&shared, 0.8);
+
+        controller.reverse();
+        controller.state.lock().last_update = Instant::now() - millis(30);
+        controller.update();
+        assert!(controller.get_time() <= millis(50));
+        assert!(shared.get() <= 0.5);
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+        controller.state.lock().last_update = Instant::now() - millis(100);
+        controller.update();
+        assert_eq!(controller.get_time(), Duration::ZERO);
+        assert_value(&shared, 0.0);
+        assert!(controller.is_finished());
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+}
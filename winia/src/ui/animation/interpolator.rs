@@ -23,6 +23,259 @@ impl Interpolator for Linear {
     }
 }
 
+/// A CSS-style `cubic-bezier(x1, y1, x2, y2)` curve from (0, 0) to (1, 1), solved at runtime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicBezier {
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+}
+
+impl CubicBezier {
+    const EPSILON: f64 = 1e-7;
+
+    /// `x1` and `x2` are clamped to [0, 1] so that the curve is a function of x.
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self {
+            x1: x1.clamp(0.0, 1.0),
+            y1,
+            x2: x2.clamp(0.0, 1.0),
+            y2,
+        }
+    }
+
+    fn sample(p1: f64, p2: f64, t: f64) -> f64 {
+        // Bernstein form with p0 = 0 and p3 = 1.
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        ((a * t + b) * t + c) * t
+    }
+
+    fn sample_derivative(p1: f64, p2: f64, t: f64) -> f64 {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        (3.0 * a * t + 2.0 * b) * t + c
+    }
+
+    /// Finds the parameter t for which the curve is at `x`.
+    fn solve_t(&self, x: f64) -> f64 {
+        let (x1, x2) = (self.x1 as f64, self.x2 as f64);
+        // Newton's method converges quickly for most curves.
+        let mut t = x;
+        for _ in 0..8 {
+            let error = Self::sample(x1, x2, t) - x;
+            if error.abs() < Self::EPSILON {
+                return t;
+            }
+            let derivative = Self::sample_derivative(x1, x2, t);
+            if derivative.abs() < 1e-6 {
+                break;
+            }
+            t -= error / derivative;
+        }
+        // Fall back to bisection where the slope is too flat.
+        let (mut low, mut high) = (0.0, 1.0);
+        t = x;
+        while low < high {
+            let value = Self::sample(x1, x2, t);
+            if (value - x).abs() < Self::EPSILON {
+                break;
+            }
+            if value < x {
+                low = t;
+            } else {
+                high = t;
+            }
+            let next = (low + high) / 2.0;
+            if next == t {
+                break;
+            }
+            t = next;
+        }
+        t
+    }
+}
+
+impl Interpolator for CubicBezier {
+    fn interpolate(&self, x: f32) -> f32 {
+        if x <= 0.0 {
+            return 0.0;
+        } else if x >= 1.0 {
+            return 1.0;
+        }
+        let t = self.solve_t(x as f64);
+        Self::sample(self.y1 as f64, self.y2 as f64, t) as f32
+    }
+}
+
+/// Where the jumps of [`Steps`] happen, as in CSS `steps()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepPosition {
+    /// The first jump happens at the start.
+    Start,
+    /// The last jump happens at the end.
+    End,
+    /// Neither at the start nor at the end, the output stays at 0 and 1 for one step each.
+    None,
+    /// Both at the start and at the end.
+    Both,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Steps {
+    count: usize,
+    position: StepPosition,
+}
+
+impl Steps {
+    pub fn new(count: usize, position: StepPosition) -> Self {
+        let minimum = if position == StepPosition::None { 2 } else { 1 };
+        Self {
+            count: count.max(minimum),
+            position,
+        }
+    }
+}
+
+impl Interpolator for Steps {
+    fn interpolate(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        let count = self.count as f32;
+        let mut step = (x * count).floor();
+        if matches!(self.position, StepPosition::Start | StepPosition::Both) {
+            step += 1.0;
+        }
+        let jumps = match self.position {
+            StepPosition::Start | StepPosition::End => count,
+            StepPosition::None => count - 1.0,
+            StepPosition::Both => count + 1.0,
+        };
+        (step / jumps).clamp(0.0, 1.0)
+    }
+}
+
+/// Moves backwards first, then accelerates towards the end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Anticipate {
+    tension: f32,
+}
+
+impl Anticipate {
+    pub fn new(tension: f32) -> Self {
+        Self { tension }
+    }
+}
+
+impl Default for Anticipate {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+impl Interpolator for Anticipate {
+    fn interpolate(&self, x: f32) -> f32 {
+        x * x * ((self.tension + 1.0) * x - self.tension)
+    }
+}
+
+/// Goes past the end and settles back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Overshoot {
+    tension: f32,
+}
+
+impl Overshoot {
+    pub fn new(tension: f32) -> Self {
+        Self { tension }
+    }
+}
+
+impl Default for Overshoot {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+impl Interpolator for Overshoot {
+    fn interpolate(&self, x: f32) -> f32 {
+        let x = x - 1.0;
+        x * x * ((self.tension + 1.0) * x + self.tension) + 1.0
+    }
+}
+
+/// Passes through a list of `(x, y)` keyframes.
+/// Each segment is eased with its own interpolator, linear by default.
+#[derive(Default)]
+pub struct Keyframes {
+    keyframes: Vec<(f32, f32, Box<dyn Interpolator + Send>)>,
+}
+
+impl Keyframes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keyframe(self, x: f32, y: f32) -> Self {
+        self.keyframe_with(x, y, Linear::new())
+    }
+
+    /// Adds a keyframe whose segment from the previous keyframe is eased with `interpolator`.
+    pub fn keyframe_with(mut self, x: f32, y: f32, interpolator: impl Interpolator + Send + 'static) -> Self {
+        let index = self.keyframes.partition_point(|(keyframe_x, _, _)| *keyframe_x <= x);
+        self.keyframes.insert(index, (x, y, Box::new(interpolator)));
+        self
+    }
+}
+
+impl Interpolator for Keyframes {
+    fn interpolate(&self, x: f32) -> f32 {
+        match find_segment(&self.keyframes, x, |(keyframe_x, _, _)| *keyframe_x) {
+            None => x,
+            Some(Segment::Hold(index)) => self.keyframes[index].1,
+            Some(Segment::Between { end, progress }) => {
+                let (_, start_y, _) = &self.keyframes[end - 1];
+                let (_, end_y, interpolator) = &self.keyframes[end];
+                start_y + (end_y - start_y) * interpolator.interpolate(progress)
+            }
+        }
+    }
+}
+
+/// Where a point lies among keyframes.
+pub(crate) enum Segment {
+    /// Before the first or after the last keyframe, which holds its value.
+    Hold(usize),
+    /// Between the keyframes at `end - 1` and `end`, `progress` is linear from 0.0 to 1.0.
+    Between { end: usize, progress: f32 },
+}
+
+/// Finds the segment of `keyframes`, sorted by `position`, that contains `x`.
+/// `None` if there are no keyframes.
+pub(crate) fn find_segment<K>(
+    keyframes: &[K],
+    x: f32,
+    position: impl Fn(&K) -> f32,
+) -> Option<Segment> {
+    let (first, last) = (keyframes.first()?, keyframes.last()?);
+    if x <= position(first) {
+        return Some(Segment::Hold(0));
+    }
+    if x >= position(last) {
+        return Some(Segment::Hold(keyframes.len() - 1));
+    }
+    // The keyframe at `end` lies after `x` and the one before it doesn't, so the segment isn't empty.
+    let end = keyframes.partition_point(|keyframe| position(keyframe) <= x);
+    let start_x = position(&keyframes[end - 1]);
+    let end_x = position(&keyframes[end]);
+    Some(Segment::Between {
+        end,
+        progress: (x - start_x) / (end_x - start_x),
+    })
+}
+
 // 使用二分法查找x所在的区间
 fn find_interval(points: &Vec<(f32, f32)>, x: f32) -> usize {
     let mut low = 0;
@@ -612,3 +865,130 @@ interpolator!(
         (1.0, 1.0),
     ]
 );
+
+#[cfg(test)]
+mod interpolator_test {
+    use super::{Anticipate, CubicBezier, Interpolator, Keyframes, Overshoot, StepPosition, Steps};
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_cubic_bezier_known_values() {
+        // The CSS `ease`, `ease-in`, `ease-in-out` curves and the Material emphasized decelerate.
+        let curves = [
+            (
+                (0.25, 0.1, 0.25, 1.0),
+                [0.094796, 0.408511, 0.802403, 0.960459, 0.994316],
+            ),
+            (
+                (0.42, 0.0, 1.0, 1.0),
+                [0.017027, 0.093465, 0.315357, 0.621862, 0.839428],
+            ),
+            (
+                (0.42, 0.0, 0.58, 1.0),
+                [0.019722, 0.129162, 0.5, 0.870838, 0.980278],
+            ),
+            (
+                (0.2, 0.0, 0.0, 1.0),
+                [0.15625, 0.60722, 0.877834, 0.97548, 0.996459],
+            ),
+        ];
+        for ((x1, y1, x2, y2), expected) in curves {
+            let curve = CubicBezier::new(x1, y1, x2, y2);
+            for (x, y) in [0.1, 0.25, 0.5, 0.75, 0.9].into_iter().zip(expected) {
+                assert_close(curve.interpolate(x), y, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cubic_bezier_endpoints() {
+        let curve = CubicBezier::new(0.68, -0.6, 0.32, 1.6);
+        assert_eq!(curve.interpolate(0.0), 0.0);
+        assert_eq!(curve.interpolate(1.0), 1.0);
+        assert_eq!(curve.interpolate(-0.5), 0.0);
+        assert_eq!(curve.interpolate(1.5), 1.0);
+    }
+
+    #[test]
+    fn test_cubic_bezier_solves_x() {
+        let curves = [
+            CubicBezier::new(0.25, 0.1, 0.25, 1.0),
+            CubicBezier::new(0.0, 0.0, 1.0, 1.0),
+            // The slope of x is 0 in the middle, where Newton's method gives up.
+            CubicBezier::new(1.0, 0.0, 0.0, 1.0),
+        ];
+        for curve in curves {
+            let (x1, x2) = (curve.x1 as f64, curve.x2 as f64);
+            let mut last_t = 0.0;
+            for step in 1..100 {
+                let x = step as f64 / 100.0;
+                let t = curve.solve_t(x);
+                assert!((CubicBezier::sample(x1, x2, t) - x).abs() < 1e-6);
+                assert!(t >= last_t, "t decreases at x = {}", x);
+                last_t = t;
+            }
+        }
+        let curve = CubicBezier::new(1.0, 0.0, 0.0, 1.0);
+        assert_close(curve.interpolate(0.3), 0.047395, 1e-4);
+        assert_close(curve.interpolate(0.49), 0.301419, 1e-2);
+    }
+
+    #[test]
+    fn test_steps() {
+        let cases = [
+            (StepPosition::End, [0.0, 0.0, 0.25, 0.75, 1.0]),
+            (StepPosition::Start, [0.25, 0.25, 0.5, 1.0, 1.0]),
+            (StepPosition::None, [0.0, 0.0, 1.0 / 3.0, 1.0, 1.0]),
+            (StepPosition::Both, [0.2, 0.2, 0.4, 0.8, 1.0]),
+        ];
+        for (position, expected) in cases {
+            let steps = Steps::new(4, position);
+            for (x, y) in [0.0, 0.2, 0.3, 0.99, 1.0].into_iter().zip(expected) {
+                assert_close(steps.interpolate(x), y, 1e-6);
+            }
+        }
+        // Without jumps at the ends, a single step couldn't move at all.
+        let steps = Steps::new(1, StepPosition::None);
+        assert_eq!(steps.interpolate(0.4), 0.0);
+        assert_eq!(steps.interpolate(0.6), 1.0);
+    }
+
+    #[test]
+    fn test_anticipate_and_overshoot() {
+        let anticipate = Anticipate::default();
+        let overshoot = Overshoot::default();
+        assert_eq!(anticipate.interpolate(0.0), 0.0);
+        assert_eq!(anticipate.interpolate(1.0), 1.0);
+        assert_eq!(overshoot.interpolate(0.0), 0.0);
+        assert_eq!(overshoot.interpolate(1.0), 1.0);
+        // The extremes lie at 4/9 and 5/9 for a tension of 2.
+        assert_close(anticipate.interpolate(4.0 / 9.0), -96.0 / 729.0, 1e-6);
+        assert_close(overshoot.interpolate(5.0 / 9.0), 1.0 + 32.0 / 243.0, 1e-6);
+        for step in 0..=100 {
+            let x = step as f32 / 100.0;
+            assert!(anticipate.interpolate(x) >= -96.0 / 729.0 - 1e-6);
+            assert!(overshoot.interpolate(x) <= 1.0 + 32.0 / 243.0 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_keyframes() {
+        let keyframes = Keyframes::new()
+            .keyframe(1.0, 0.0)
+            .keyframe(0.0, 0.0)
+            .keyframe(0.5, 1.0);
+        assert_eq!(keyframes.interpolate(-1.0), 0.0);
+        assert_close(keyframes.interpolate(0.25), 0.5, 1e-6);
+        assert_eq!(keyframes.interpolate(0.5), 1.0);
+        assert_close(keyframes.interpolate(0.75), 0.5, 1e-6);
+        assert_eq!(keyframes.interpolate(2.0), 0.0);
+    }
+}
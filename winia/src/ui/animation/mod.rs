@@ -3,6 +3,8 @@ pub mod interpolator;
 pub use interpolator::Interpolator;
 mod target;
 mod local_animation;
+mod physics;
+mod composition;
 
 use material_colors::blend::cam16_ucs;
 use material_colors::color::Argb;
@@ -10,6 +12,8 @@ use skia_safe::Color;
 pub use animation::*;
 pub use target::*;
 pub use local_animation::*;
+pub use physics::*;
+pub use composition::*;
 
 pub trait Animation {
     fn interpolate_f32(&self, start: f32, end: f32) -> f32;
@@ -0,0 +1,546 @@
+use crate::shared::{Gettable, Settable, Shared, SharedAnimationTrait, WeakShared};
+use crate::ui::app::EventLoopProxy;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames longer than this are simulated as if they took this long,
+/// so a stalled frame doesn't make the animation jump.
+const MAX_FRAME_TIME: Duration = Duration::from_millis(64);
+
+/// A damped spring with a mass of 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spring {
+    pub stiffness: f32,
+    /// 1.0 is critically damped, lower values bounce, higher values approach the target more slowly.
+    pub damping_ratio: f32,
+    /// The animation settles once it's closer to the target than this and moves slower than
+    /// [`rest_velocity`](Spring::rest_velocity).
+    pub rest_displacement: f32,
+    pub rest_velocity: f32,
+}
+
+impl Spring {
+    pub const STIFFNESS_HIGH: f32 = 10000.0;
+    pub const STIFFNESS_MEDIUM: f32 = 1500.0;
+    pub const STIFFNESS_LOW: f32 = 200.0;
+    pub const STIFFNESS_VERY_LOW: f32 = 50.0;
+    pub const DAMPING_RATIO_HIGH_BOUNCY: f32 = 0.2;
+    pub const DAMPING_RATIO_MEDIUM_BOUNCY: f32 = 0.5;
+    pub const DAMPING_RATIO_LOW_BOUNCY: f32 = 0.75;
+    pub const DAMPING_RATIO_NO_BOUNCY: f32 = 1.0;
+
+    pub fn new(stiffness: f32, damping_ratio: f32) -> Self {
+        Self {
+            stiffness: stiffness.max(f32::EPSILON),
+            damping_ratio: damping_ratio.max(0.0),
+            rest_displacement: 0.01,
+            rest_velocity: 0.1,
+        }
+    }
+
+    pub fn rest_threshold(mut self, displacement: f32, velocity: f32) -> Self {
+        self.rest_displacement = displacement;
+        self.rest_velocity = velocity;
+        self
+    }
+
+    /// Advances the spring by `time` seconds.
+    /// `displacement` is the distance from the target. Returns the new displacement and velocity.
+    pub fn step(&self, displacement: f32, velocity: f32, time: f32) -> (f32, f32) {
+        let x0 = displacement as f64;
+        let v0 = velocity as f64;
+        let t = time as f64;
+        let omega = (self.stiffness as f64).sqrt();
+        let zeta = self.damping_ratio as f64;
+        let (x, v) = if (zeta - 1.0).abs() < 1e-6 {
+            let b = v0 + omega * x0;
+            let decay = (-omega * t).exp();
+            let x = decay * (x0 + b * t);
+            let v = decay * (b - omega * (x0 + b * t));
+            (x, v)
+        } else if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            let a = x0;
+            let b = (v0 + zeta * omega * x0) / omega_d;
+            let decay = (-zeta * omega * t).exp();
+            let (sin, cos) = (omega_d * t).sin_cos();
+            let x = decay * (a * cos + b * sin);
+            let v = decay
+                * ((b * omega_d - a * zeta * omega) * cos - (a * omega_d + b * zeta * omega) * sin);
+            (x, v)
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            let c1 = (v0 - r2 * x0) / (r1 - r2);
+            let c2 = x0 - c1;
+            let x = c1 * (r1 * t).exp() + c2 * (r2 * t).exp();
+            let v = c1 * r1 * (r1 * t).exp() + c2 * r2 * (r2 * t).exp();
+            (x, v)
+        };
+        (x as f32, v as f32)
+    }
+
+    pub fn is_at_rest(&self, displacement: f32, velocity: f32) -> bool {
+        displacement.abs() < self.rest_displacement && velocity.abs() < self.rest_velocity
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self::new(Self::STIFFNESS_MEDIUM, Self::DAMPING_RATIO_NO_BOUNCY)
+    }
+}
+
+struct InnerSpringAnimation {
+    shared: WeakShared<f32>,
+    spring: Spring,
+    value: f32,
+    velocity: f32,
+    target: f32,
+    last_update: Instant,
+    is_finished: bool,
+    is_running: bool,
+    event_loop_proxy: Option<EventLoopProxy>,
+    on_finish: Option<Box<dyn FnMut() + Send>>,
+}
+
+impl InnerSpringAnimation {
+    /// Moves the value `time` seconds further and finishes once the spring is at rest.
+    fn advance(&mut self, time: f32) {
+        let (displacement, velocity) =
+            self.spring
+                .step(self.value - self.target, self.velocity, time);
+        if self.spring.is_at_rest(displacement, velocity) {
+            self.value = self.target;
+            self.velocity = 0.0;
+            self.is_finished = true;
+        } else {
+            self.value = self.target + displacement;
+            self.velocity = velocity;
+        }
+    }
+}
+
+/// Moves a [`Shared<f32>`] to a target following a [`Spring`].
+///
+/// Unlike [`SharedAnimation`](crate::shared::SharedAnimation) it has no duration,
+/// and the target can be changed with [`retarget`](SpringAnimation::retarget) while the animation runs:
+/// the current value and velocity are kept, so the motion stays continuous.
+pub struct SpringAnimation {
+    inner: Arc<Mutex<InnerSpringAnimation>>,
+}
+
+impl SpringAnimation {
+    /// Animates `shared` from its current value to `target`.
+    pub fn new(shared: &Shared<f32>, target: f32) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(InnerSpringAnimation {
+                shared: shared.weak(),
+                spring: Spring::default(),
+                value: shared.get(),
+                velocity: 0.0,
+                target,
+                last_update: Instant::now(),
+                is_finished: false,
+                is_running: false,
+                event_loop_proxy: None,
+                on_finish: None,
+            })),
+        }
+    }
+
+    pub fn spring(self, spring: Spring) -> Self {
+        self.inner.lock().spring = spring;
+        self
+    }
+
+    /// The initial velocity in units per second.
+    pub fn velocity(self, velocity: f32) -> Self {
+        self.inner.lock().velocity = velocity;
+        self
+    }
+
+    pub fn on_finish(self, on_finish: impl FnMut() + Send + 'static) -> Self {
+        self.inner.lock().on_finish = Some(Box::new(on_finish));
+        self
+    }
+
+    pub fn start(self, event_loop_proxy: &EventLoopProxy) -> Self {
+        {
+            let mut inner = self.inner.lock();
+            inner.last_update = Instant::now();
+            inner.is_finished = false;
+            inner.is_running = true;
+            inner.event_loop_proxy = Some(event_loop_proxy.clone());
+        }
+        event_loop_proxy.start_shared_animation(Box::new(self.clone()));
+        self
+    }
+
+    /// Changes the target, keeping the current value and velocity.
+    /// A finished animation is started again.
+    pub fn retarget(&self, target: f32) {
+        let restart = {
+            let mut inner = self.inner.lock();
+            inner.target = target;
+            // A running animation that just settled is kept alive by clearing the flag before it's removed.
+            inner.is_finished = false;
+            match inner.event_loop_proxy.clone() {
+                Some(event_loop_proxy) if !inner.is_running => {
+                    inner.last_update = Instant::now();
+                    inner.is_running = true;
+                    Some(event_loop_proxy)
+                }
+                _ => None,
+            }
+        };
+        if let Some(event_loop_proxy) = restart {
+            event_loop_proxy.start_shared_animation(Box::new(self.clone()));
+        }
+    }
+
+    pub fn stop(&self) {
+        self.inner.lock().is_finished = true;
+    }
+
+    pub fn get_target(&self) -> f32 {
+        self.inner.lock().target
+    }
+
+    pub fn get_velocity(&self) -> f32 {
+        self.inner.lock().velocity
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.lock().is_running
+    }
+}
+
+impl Clone for SpringAnimation {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl SharedAnimationTrait for SpringAnimation {
+    fn is_finished(&self) -> bool {
+        let mut inner = self.inner.lock();
+        if inner.is_finished {
+            inner.is_running = false;
+        }
+        inner.is_finished
+    }
+
+    fn update(&self) {
+        let mut inner = self.inner.lock();
+        if inner.is_finished {
+            return;
+        }
+        let now = Instant::now();
+        let time = now.duration_since(inner.last_update).min(MAX_FRAME_TIME);
+        inner.last_update = now;
+        inner.advance(time.as_secs_f32());
+
+        let value = inner.value;
+        let shared = inner.shared.clone();
+        let on_finish = if inner.is_finished {
+            inner.on_finish.take()
+        } else {
+            None
+        };
+        // Observers may retarget the animation, so they must not run while it's locked.
+        drop(inner);
+        if let Some(shared) = shared.upgrade() {
+            shared.set(value);
+        }
+        if let Some(mut on_finish) = on_finish {
+            on_finish();
+            self.inner.lock().on_finish.get_or_insert(on_finish);
+        }
+    }
+}
+
+struct InnerFlingAnimation {
+    shared: WeakShared<f32>,
+    value: f32,
+    velocity: f32,
+    friction: f32,
+    bounds: (f32, f32),
+    rest_velocity: f32,
+    last_update: Instant,
+    is_finished: bool,
+    on_finish: Option<Box<dyn FnMut() + Send>>,
+}
+
+impl InnerFlingAnimation {
+    /// Moves the value `time` seconds further, the velocity decays exactly over any frame time.
+    fn advance(&mut self, time: f32) {
+        let decay = (-self.friction * time).exp();
+        let travelled = self.velocity / self.friction * (1.0 - decay);
+        self.velocity *= decay;
+        let (min, max) = self.bounds;
+        self.value += travelled;
+        if self.value <= min || self.value >= max {
+            self.value = self.value.clamp(min, max);
+            self.velocity = 0.0;
+        }
+        if self.velocity.abs() < self.rest_velocity {
+            self.is_finished = true;
+        }
+    }
+}
+
+/// Lets a [`Shared<f32>`] keep moving with an initial velocity that decays exponentially,
+/// as used for kinetic scrolling.
+pub struct FlingAnimation {
+    inner: Arc<Mutex<InnerFlingAnimation>>,
+}
+
+impl FlingAnimation {
+    /// Starts from the current value of `shared` with `velocity` in units per second.
+    pub fn new(shared: &Shared<f32>, velocity: f32) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(InnerFlingAnimation {
+                shared: shared.weak(),
+                value: shared.get(),
+                velocity,
+                friction: 4.2,
+                bounds: (f32::NEG_INFINITY, f32::INFINITY),
+                rest_velocity: 5.0,
+                last_update: Instant::now(),
+                is_finished: false,
+                on_finish: None,
+            })),
+        }
+    }
+
+    /// How quickly the velocity decays, the velocity is divided by e every `1 / friction` seconds.
+    pub fn friction(self, friction: f32) -> Self {
+        self.inner.lock().friction = friction.max(f32::EPSILON);
+        self
+    }
+
+    /// The animation stops as soon as the value reaches one of the bounds.
+    pub fn bounds(self, min: f32, max: f32) -> Self {
+        self.inner.lock().bounds = (min, max);
+        self
+    }
+
+    /// The animation stops once the velocity drops below this.
+    pub fn rest_velocity(self, rest_velocity: f32) -> Self {
+        self.inner.lock().rest_velocity = rest_velocity;
+        self
+    }
+
+    pub fn on_finish(self, on_finish: impl FnMut() + Send + 'static) -> Self {
+        self.inner.lock().on_finish = Some(Box::new(on_finish));
+        self
+    }
+
+    /// The value at which the animation will come to rest, ignoring the bounds.
+    pub fn get_final_value(&self) -> f32 {
+        let inner = self.inner.lock();
+        inner.value + inner.velocity / inner.friction
+    }
+
+    pub fn get_velocity(&self) -> f32 {
+        self.inner.lock().velocity
+    }
+
+    pub fn start(self, event_loop_proxy: &EventLoopProxy) -> Self {
+        self.inner.lock().last_update = Instant::now();
+        event_loop_proxy.start_shared_animation(Box::new(self.clone()));
+        self
+    }
+
+    pub fn stop(&self) {
+        self.inner.lock().is_finished = true;
+    }
+}
+
+impl Clone for FlingAnimation {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl SharedAnimationTrait for FlingAnimation {
+    fn is_finished(&self) -> bool {
+        self.inner.lock().is_finished
+    }
+
+    fn update(&self) {
+        let mut inner = self.inner.lock();
+        if inner.is_finished {
+            return;
+        }
+        let now = Instant::now();
+        let time = now
+            .duration_since(inner.last_update)
+            .min(MAX_FRAME_TIME)
+            .as_secs_f32();
+        inner.last_update = now;
+        inner.advance(time);
+
+        let value = inner.value;
+        let shared = inner.shared.clone();
+        let on_finish = if inner.is_finished {
+            inner.on_finish.take()
+        } else {
+            None
+        };
+        drop(inner);
+        if let Some(shared) = shared.upgrade() {
+            shared.set(value);
+        }
+        if let Some(mut on_finish) = on_finish {
+            on_finish();
+        }
+    }
+}
+
+/// Estimates the velocity of a pointer from the movements of the last 100 milliseconds.
+#[derive(Clone, Debug, Default)]
+pub struct VelocityTracker {
+    samples: Vec<(Instant, f32)>,
+}
+
+impl VelocityTracker {
+    const WINDOW: Duration = Duration::from_millis(100);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_movement(&mut self, delta: f32) {
+        let now = Instant::now();
+        self.samples
+            .retain(|(time, _)| now.duration_since(*time) <= Self::WINDOW);
+        self.samples.push((now, delta));
+    }
+
+    /// The velocity in units per second.
+    pub fn velocity(&self) -> f32 {
+        let Some((first, _)) = self.samples.first() else {
+            return 0.0;
+        };
+        let elapsed = Instant::now().duration_since(*first);
+        if elapsed > Self::WINDOW || self.samples.len() < 2 {
+            return 0.0;
+        }
+        // The first sample only marks the start of the movement.
+        let distance: f32 = self.samples[1..].iter().map(|(_, delta)| delta).sum();
+        let time = self.samples.last().unwrap().0.duration_since(*first).as_secs_f32();
+        if time <= 0.0 {
+            0.0
+        } else {
+            distance / time
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod physics_test {
+    use super::{FlingAnimation, Spring, SpringAnimation};
+    use crate::shared::Shared;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    /// Steps the spring frame by frame, returns the number of frames until it is at rest and
+    /// the largest overshoot past the target.
+    fn settle(spring: &Spring, displacement: f32) -> (usize, f32) {
+        let (mut displacement, mut velocity) = (displacement, 0.0);
+        let mut overshoot: f32 = 0.0;
+        for frame in 0..10_000 {
+            if spring.is_at_rest(displacement, velocity) {
+                return (frame, overshoot);
+            }
+            (displacement, velocity) = spring.step(displacement, velocity, FRAME);
+            overshoot = overshoot.max(displacement);
+        }
+        panic!("the spring {:?} doesn't settle", spring);
+    }
+
+    #[test]
+    fn test_spring_settles() {
+        for damping_ratio in [0.2, 0.5, 1.0, 2.0] {
+            let spring = Spring::new(Spring::STIFFNESS_MEDIUM, damping_ratio);
+            let (frames, overshoot) = settle(&spring, -100.0);
+            assert!(frames > 0);
+            if damping_ratio < 1.0 {
+                assert!(overshoot > 0.0, "{} doesn't bounce", damping_ratio);
+            } else {
+                assert_eq!(overshoot, 0.0, "{} bounces", damping_ratio);
+            }
+        }
+        // A stiffer spring settles sooner.
+        let (slow, _) = settle(&Spring::new(Spring::STIFFNESS_LOW, 1.0), -100.0);
+        let (fast, _) = settle(&Spring::new(Spring::STIFFNESS_HIGH, 1.0), -100.0);
+        assert!(fast < slow);
+    }
+
+    #[test]
+    fn test_spring_step_is_continuous() {
+        let spring = Spring::new(Spring::STIFFNESS_LOW, Spring::DAMPING_RATIO_MEDIUM_BOUNCY);
+        assert_eq!(spring.step(10.0, 5.0, 0.0), (10.0, 5.0));
+        // Two half steps land where one whole step does.
+        let (displacement, velocity) = spring.step(10.0, 5.0, 0.05);
+        let (half_displacement, half_velocity) = spring.step(10.0, 5.0, 0.025);
+        let (twice_displacement, twice_velocity) =
+            spring.step(half_displacement, half_velocity, 0.025);
+        assert!((displacement - twice_displacement).abs() < 1e-3);
+        assert!((velocity - twice_velocity).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_spring_animation_ends_at_target() {
+        let shared = Shared::from_static(0.0);
+        let animation = SpringAnimation::new(&shared, 100.0).velocity(500.0);
+        let mut inner = animation.inner.lock();
+        while !inner.is_finished {
+            inner.advance(FRAME);
+        }
+        assert_eq!(inner.value, 100.0);
+        assert_eq!(inner.velocity, 0.0);
+    }
+
+    #[test]
+    fn test_fling_comes_to_rest() {
+        let shared = Shared::from_static(0.0);
+        let animation = FlingAnimation::new(&shared, 1000.0);
+        let final_value = animation.get_final_value();
+        let mut inner = animation.inner.lock();
+        // The distance doesn't depend on the frame rate.
+        inner.advance(0.1);
+        inner.advance(0.1);
+        let expected = 1000.0 / inner.friction * (1.0 - (-inner.friction * 0.2).exp());
+        assert!((inner.value - expected).abs() < 1e-2);
+        while !inner.is_finished {
+            inner.advance(FRAME);
+        }
+        assert!(inner.velocity.abs() < inner.rest_velocity);
+        assert!((inner.value - final_value).abs() <= inner.rest_velocity / inner.friction + 1e-3);
+    }
+
+    #[test]
+    fn test_fling_stops_at_bounds() {
+        let shared = Shared::from_static(0.0);
+        let animation = FlingAnimation::new(&shared, -1000.0).bounds(-50.0, 50.0);
+        let mut inner = animation.inner.lock();
+        while !inner.is_finished {
+            inner.advance(FRAME);
+        }
+        assert_eq!(inner.value, -50.0);
+        assert_eq!(inner.velocity, 0.0);
+    }
+}
@@ -1,9 +1,8 @@
-use skia_safe::textlayout::{ParagraphStyle, TextAlign};
-use skia_safe::Color;
+use skia_safe::{Color, IRect, PictureRecorder, Rect};
 use skiwin::vulkan::VulkanSkiaWindow;
-use skiwin::SkiaWindow;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use std::time::Instant;
 use winit::application::ApplicationHandler;
 use winit::dpi::{LogicalSize, PhysicalPosition, Size};
@@ -14,7 +13,7 @@ use winit::event::{
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::keyboard;
 use winit::keyboard::{ModifiersState, NamedKey};
-use winit::window::{Window, WindowAttributes, WindowId};
+use winit::window::{WindowAttributes, WindowId};
 
 macro_rules! property_get {
     ($st:ident, $($name:ident, $fn_name:ident, $ty:ty),+) =>{
@@ -43,6 +42,8 @@ macro_rules! property_set {
 
 #[derive(Clone, AsRef)]
 pub struct WindowAttr {
+    /// A stable name for the window, used to keep its persisted state apart from other windows.
+    name: Option<String>,
     title: Shared<String>,
     preferred_size: Option<(f32, f32)>,
     min_width: Shared<f32>,
@@ -50,6 +51,8 @@ pub struct WindowAttr {
     max_width: Shared<f32>,
     max_height: Shared<f32>,
     maximized: SharedBool,
+    /// Whether the inspector can be opened with Ctrl+Shift+I, by default only in debug builds.
+    inspectable: bool,
 }
 
 impl Into<WindowAttributes> for WindowAttr {
@@ -63,6 +66,7 @@ impl Into<WindowAttributes> for WindowAttr {
 impl Default for WindowAttr {
     fn default() -> Self {
         Self {
+            name: None,
             title: Shared::from_static("Winia".to_string()),
             preferred_size: None,
             min_width: 0.0.into(),
@@ -70,6 +74,7 @@ impl Default for WindowAttr {
             max_width: (u16::MAX as f32).into(),
             max_height: (u16::MAX as f32).into(),
             maximized: false.into(),
+            inspectable: cfg!(debug_assertions),
         }
     }
 }
@@ -98,6 +103,26 @@ impl WindowAttr {
         self.preferred_size = Some((width, height));
         self
     }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Registers the `inspector.open` command, bound to Ctrl+Shift+I. It is registered in
+    /// debug builds by default.
+    pub fn inspectable(mut self, inspectable: bool) -> Self {
+        self.inspectable = inspectable;
+        self
+    }
+
+    pub fn is_inspectable(&self) -> bool {
+        self.inspectable
+    }
 }
 
 property_get!(
@@ -144,16 +169,30 @@ pub struct WindowController {
     cursor_y: f32,
     pressed_mouse_buttons: Vec<MouseButton>,
     modifiers: Option<Modifiers>,
+    render_thread: RenderThread,
+    /// Whether a snapshot was submitted and not presented yet.
+    frame_in_flight: bool,
+    /// Whether a redraw arrived while a frame was in flight.
+    redraw_deferred: bool,
 }
 
 impl WindowController {
     pub fn re_layout(&mut self) {
         let (width, height) = self.window_context.window_size();
-        self.item.data().measure(
-            MeasureMode::Specified(width),
-            MeasureMode::Specified(height),
-        );
-        self.item.data().dispatch_layout(0.0, 0.0, width, height)
+        let profiler = self.window_context.profiler.clone();
+        let start = profiler.lock().start();
+        tracing::debug_span!(target: log_target::LAYOUT, "measure", width, height).in_scope(|| {
+            self.item.data().measure(
+                MeasureMode::Specified(width),
+                MeasureMode::Specified(height),
+            );
+        });
+        profiler.lock().record_phase(FramePhase::Measure, start);
+        let start = profiler.lock().start();
+        tracing::debug_span!(target: log_target::LAYOUT, "layout", width, height).in_scope(|| {
+            self.item.data().dispatch_layout(0.0, 0.0, width, height);
+        });
+        profiler.lock().record_phase(FramePhase::Layout, start);
     }
 
     pub fn add_layer(&mut self, item: Item) {
@@ -166,34 +205,34 @@ impl WindowController {
 }
 
 pub struct App {
-    on_create_window: Option<Box<dyn FnOnce(Window) -> Box<dyn SkiaWindow>>>,
+    on_create_window: Option<CreateSkiaWindow>,
     windows: HashMap<WindowId, WindowController>,
     pending_windows: Option<(
         Box<dyn FnOnce(&WindowContext) -> Item + 'static>,
         WindowAttr,
     )>,
     pub(crate) event_loop_proxy: Option<EventLoopProxy<Event>>,
-    instant: Option<Instant>,
-    second_instant: Option<Instant>,
-    fps_in_one_second: Vec<f32>,
-    average_fps: f32,
 }
 
 impl App {
+    /// Creates the app with the root item of its window.
+    ///
+    /// `on_create_window` creates the Skia window, a Vulkan window is used if it is `None`. It is
+    /// called on the render thread of the window, so it gets an `Arc<Window>` instead of the
+    /// `Window` and must be `Send`. The windows of skiwin take either, so a closure like
+    /// `|window| Box::new(SoftSkiaWindow::new(window))` works as before. A closure stored as a
+    /// `Box<dyn FnOnce(Window) -> Box<dyn SkiaWindow>>` has to be stored as a
+    /// [`CreateSkiaWindow`] instead.
     pub fn new(
         item_generator: impl FnOnce(&WindowContext) -> Item + 'static,
         window_attr: WindowAttr,
-        on_create_window: Option<Box<dyn FnOnce(Window) -> Box<dyn SkiaWindow>>>,
+        on_create_window: Option<CreateSkiaWindow>,
     ) -> Self {
         Self {
             on_create_window,
             windows: HashMap::new(),
             pending_windows: Some((Box::new(item_generator), window_attr)),
             event_loop_proxy: None,
-            instant: None,
-            second_instant: None,
-            fps_in_one_second: vec![],
-            average_fps: 0.0,
         }
     }
 
@@ -203,24 +242,21 @@ impl App {
         item_generator: impl FnOnce(&WindowContext) -> Item + 'static,
         window_attr: WindowAttr,
     ) {
-        let window = event_loop
-            .create_window(window_attr.clone().into())
-            .unwrap();
+        let window = Arc::new(
+            event_loop
+                .create_window(window_attr.clone().into())
+                .unwrap(),
+        );
         let window_id = window.id();
         let event_loop_proxy = self.event_loop_proxy.as_ref().unwrap().clone();
+        let inspectable = window_attr.is_inspectable();
         let window_attr = Shared::from_static(window_attr);
-        let window_context = WindowContext::new(
-            {
-                if let Some(on_create_window) = self.on_create_window.take() {
-                    on_create_window(window)
-                } else {
-                    Box::new(VulkanSkiaWindow::new(window, None))
-                }
-            },
-            &window_attr,
-            event_loop_proxy.clone(),
-        );
-        window_context.window.lock().resize().unwrap();
+        let create_skia_window = self.on_create_window.take().unwrap_or_else(|| {
+            Box::new(|window| Box::new(VulkanSkiaWindow::new(window, None)))
+        });
+        let render_thread =
+            RenderThread::spawn(window.clone(), create_skia_window, event_loop_proxy.clone());
+        let window_context = WindowContext::new(window, &window_attr, event_loop_proxy.clone());
         let item = item_generator(&window_context)
             .size(crate::ui::item::Size::Fill, crate::ui::item::Size::Fill);
 
@@ -230,16 +266,52 @@ impl App {
             let theme_ = window_context.theme();
             let theme = theme_.lock();
             stack.data().dispatch_apply_theme(theme.deref());
-            stack.data().set_keyboard_input(|item, input| {
-                if keyboard::Key::Named(NamedKey::Tab) == input.key_event.logical_key {
-                    if input.key_event.state.is_pressed() {
-                        item.focus_next();
-                    }
-                    return true;
-                }
-                false
-            });
         }
+        window_context.set_focus_root(stack.data_clone());
+        register_window(&window_context);
+        window_context.add_window_command(
+            "focus.next",
+            [Accelerator::from_key(
+                ModifiersState::empty(),
+                &keyboard::Key::Named(NamedKey::Tab),
+            )],
+            |window_context| {
+                window_context.move_focus(FocusDirection::Next);
+            },
+        );
+        window_context.add_window_command(
+            "profiler.toggle_hud",
+            [Accelerator::from_key(
+                ModifiersState::CONTROL | ModifiersState::SHIFT,
+                &keyboard::Key::Character("p".into()),
+            )],
+            |window_context| {
+                window_context
+                    .set_profiler_hud_visible(!window_context.is_profiler_hud_visible());
+            },
+        );
+        if inspectable {
+            window_context.add_window_command(
+                "inspector.open",
+                [Accelerator::from_key(
+                    ModifiersState::CONTROL | ModifiersState::SHIFT,
+                    &keyboard::Key::Character("i".into()),
+                )],
+                |window_context| {
+                    window_context.open_inspector();
+                },
+            );
+        }
+        window_context.add_window_command(
+            "focus.previous",
+            [Accelerator::from_key(
+                ModifiersState::SHIFT,
+                &keyboard::Key::Named(NamedKey::Tab),
+            )],
+            |window_context| {
+                window_context.move_focus(FocusDirection::Previous);
+            },
+        );
         self.windows.insert(
             window_id,
             WindowController {
@@ -252,6 +324,9 @@ impl App {
                 cursor_y: 0.0,
                 pressed_mouse_buttons: Vec::new(),
                 modifiers: None,
+                render_thread,
+                frame_in_flight: false,
+                redraw_deferred: false,
             },
         );
     }
@@ -269,6 +344,12 @@ impl ApplicationHandler<Event> for App {
     }
 
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: Event) {
+        let _span = tracing::trace_span!(
+            target: log_target::EVENT,
+            "user_event",
+            window_id = ?event.window_id
+        )
+        .entered();
         if let Some(window_controller) = self.windows.get_mut(&event.window_id) {
             match event.event {
                 EventType::RequestFocus => {
@@ -285,11 +366,40 @@ impl ApplicationHandler<Event> for App {
                     //     },
                     // );
                 }
+                // The requests were counted where they were sent.
                 EventType::RequestLayout => {
-                    window_controller.window_context.request_layout();
+                    window_controller.window_context.schedule_layout();
                 }
                 EventType::RequestRedraw => {
-                    window_controller.window_context.request_redraw();
+                    window_controller.window_context.redraw_all();
+                }
+                EventType::RequestRepaint(id) => {
+                    // The item may have been dropped since the request was sent.
+                    let is_item = window_controller
+                        .window_context
+                        .damage
+                        .lock()
+                        .contains_item(id);
+                    if is_item {
+                        window_controller.window_context.repaint_item(id);
+                    }
+                }
+                EventType::UpdateCursorIcon => {
+                    window_controller
+                        .window_context
+                        .update_cursor_icon(window_controller.item.data().deref_mut());
+                }
+                EventType::FramePresented { started, finished } => {
+                    window_controller
+                        .window_context
+                        .profiler
+                        .lock()
+                        .record_present(started, finished);
+                    window_controller.frame_in_flight = false;
+                    if window_controller.redraw_deferred {
+                        window_controller.redraw_deferred = false;
+                        window_controller.window_context.window().request_redraw();
+                    }
                 }
                 EventType::StartSharedAnimation(animation) => {
                     window_controller
@@ -297,7 +407,7 @@ impl ApplicationHandler<Event> for App {
                         .shared_animations
                         .lock()
                         .push(animation);
-                    window_controller.window_context.request_redraw();
+                    window_controller.window_context.schedule_redraw();
                 }
                 EventType::Timer(_id) => {
                     // let timers = window_controller.window_context.timers.value();
@@ -309,8 +419,8 @@ impl ApplicationHandler<Event> for App {
                     //     .write(|timers| timers.retain(|timer| timer.id != id));
                 }
                 EventType::SetWindowAttribute(set_window_attributes) => {
-                    let window = window_controller.window_context.window.lock();
-                    set_window_attributes(Some(window.deref()));
+                    let window = window_controller.window_context.window();
+                    set_window_attributes(Some(window));
                 }
                 EventType::NewWindow {
                     item_generator,
@@ -364,22 +474,24 @@ impl ApplicationHandler<Event> for App {
         window_id: WindowId,
         event: WindowEvent,
     ) {
-        fn println_item_focused(
+        fn trace_item_focused(
             item_focused: &Shared<(Option<(Shared<bool>, usize)>, Option<(Shared<bool>, usize)>)>,
         ) {
-            let item_focused = item_focused.lock();
-            if let Some((last, id)) = &item_focused.0 {
-                println!("last: {}, id: {}", last.get(), id);
-            } else {
-                println!("last: None, id: None");
-            }
-            if let Some((new, id)) = &item_focused.1 {
-                println!("new: {}, id: {}", new.get(), id);
-            } else {
-                println!("new: None, id: None");
+            if !tracing::enabled!(target: log_target::FOCUS, tracing::Level::TRACE) {
+                return;
             }
+            let item_focused = item_focused.lock();
+            tracing::trace!(
+                target: log_target::FOCUS,
+                last = ?item_focused.0.as_ref().map(|(focused, id)| (focused.get(), *id)),
+                new = ?item_focused.1.as_ref().map(|(focused, id)| (focused.get(), *id)),
+                "Focused items"
+            );
         }
 
+        let _span =
+            tracing::debug_span!(target: log_target::EVENT, "window_event", ?window_id, ?event)
+                .entered();
         let window_controller_ = self.windows.remove(&window_id);
         if window_controller_.is_none() {
             return;
@@ -387,6 +499,8 @@ impl ApplicationHandler<Event> for App {
         let mut window_controller = window_controller_.unwrap();
         let mut closed = false;
 
+        window_controller.window_context.run_ui_hooks();
+
         {// Request layout
             let request_layout = window_controller
                 .window_context
@@ -408,8 +522,9 @@ impl ApplicationHandler<Event> for App {
                         animation.update();
                     });
                     shared_animations.retain(|animation| !animation.is_finished());
+                    // The animated values repaint the items observing them.
                     if !shared_animations.is_empty() {
-                        window_controller.window_context.request_redraw()
+                        window_controller.window_context.schedule_redraw()
                     }
                 });
         }
@@ -445,19 +560,10 @@ impl ApplicationHandler<Event> for App {
                 }
             }
             WindowEvent::Resized(_size) => {
-                window_controller
-                    .window_context
-                    .window
-                    .lock()
-                    .resize()
-                    .unwrap();
-                let (width, height) = window_controller.window_context.window_size();
-                let item = &mut window_controller.item;
-                item.data().measure(
-                    MeasureMode::Specified(width),
-                    MeasureMode::Specified(height),
-                );
-                item.data().dispatch_layout(0.0, 0.0, width, height)
+                window_controller.render_thread.resize();
+                // The new surface is empty.
+                window_controller.window_context.request_redraw();
+                window_controller.re_layout();
             }
 
             WindowEvent::KeyboardInput {
@@ -465,14 +571,48 @@ impl ApplicationHandler<Event> for App {
                 event,
                 is_synthetic,
             } => {
-                window_controller
-                    .item
-                    .data()
-                    .dispatch_keyboard_input(&KeyboardInput {
-                        device_id,
-                        key_event: event,
-                        is_synthetic,
-                    });
+                // Commands bound to the key press take it from the items.
+                let command_ran = event.state.is_pressed() && {
+                    let modifiers = window_controller
+                        .modifiers
+                        .map(|modifiers| modifiers.state())
+                        .unwrap_or_default();
+                    let accelerator = Accelerator::from_key(modifiers, &event.logical_key);
+                    let window_context = &window_controller.window_context;
+                    let focus_path = window_context
+                        .get_focused_item_id()
+                        .and_then(|id| window_controller.item.data().path_to(id))
+                        .unwrap_or_default();
+                    window_context.run_command(&accelerator, &focus_path)
+                        // Shift+1 is reported as `!`, `Ctrl+Shift+1` is bound to the digit.
+                        || Accelerator::from_physical_key(modifiers, &event.physical_key)
+                            .filter(|physical| *physical != accelerator)
+                            .is_some_and(|physical| {
+                                window_context.run_command(&physical, &focus_path)
+                            })
+                };
+                if !command_ran {
+                    let pressed = event.state.is_pressed();
+                    let direction = match &event.logical_key {
+                        keyboard::Key::Named(NamedKey::ArrowUp) => Some(FocusDirection::Up),
+                        keyboard::Key::Named(NamedKey::ArrowDown) => Some(FocusDirection::Down),
+                        keyboard::Key::Named(NamedKey::ArrowLeft) => Some(FocusDirection::Left),
+                        keyboard::Key::Named(NamedKey::ArrowRight) => Some(FocusDirection::Right),
+                        _ => None,
+                    };
+                    let handled = window_controller
+                        .item
+                        .data()
+                        .dispatch_keyboard_input(&KeyboardInput {
+                            device_id,
+                            key_event: event,
+                            is_synthetic,
+                        });
+                    // The arrow keys the items don't use move the focus.
+                    if let (false, true, Some(direction)) = (handled, pressed, direction) {
+                        window_controller.window_context.move_focus(direction);
+                    }
+                }
             }
             WindowEvent::MouseInput {
                 device_id,
@@ -491,6 +631,7 @@ impl ApplicationHandler<Event> for App {
                 };
                 match state {
                     ElementState::Pressed => {
+                        window_controller.window_context.set_focus_visible(false);
                         window_controller.pressed_mouse_buttons.push(button);
                         window_controller.item.data().dispatch_mouse_input(&event);
                     }
@@ -499,6 +640,13 @@ impl ApplicationHandler<Event> for App {
                             .pressed_mouse_buttons
                             .retain(|&b| b != button);
                         window_controller.item.data().dispatch_mouse_input(&event);
+                        // A capture ends with the pointer.
+                        window_controller
+                            .window_context
+                            .release_pointer(&Pointer::Mouse { button });
+                        window_controller
+                            .window_context
+                            .update_cursor_icon(window_controller.item.data().deref_mut());
                     }
                 }
             }
@@ -514,7 +662,15 @@ impl ApplicationHandler<Event> for App {
                     .window_context
                     .cursor_position
                     .set((window_controller.cursor_x, window_controller.cursor_y));
+                move_file_hover(
+                    &window_controller.window_context,
+                    window_controller.cursor_x,
+                    window_controller.cursor_y,
+                );
                 let pressed_mouse_buttons = window_controller.pressed_mouse_buttons.clone();
+                window_controller
+                    .window_context
+                    .clear_hovered_cursor_icon();
                 window_controller
                     .item
                     .data()
@@ -524,6 +680,9 @@ impl ApplicationHandler<Event> for App {
                         y: window_controller.cursor_y,
                         is_left_window: false,
                     });
+                window_controller
+                    .window_context
+                    .update_cursor_icon(window_controller.item.data().deref_mut());
                 pressed_mouse_buttons.iter().for_each(|button| {
                     let event = MouseInput {
                         device_id,
@@ -544,6 +703,9 @@ impl ApplicationHandler<Event> for App {
                 //     pointer_state: PointerState::Cancelled,
                 // };
                 // window_controller.item.data().dispatch_mouse_input(event);
+                window_controller
+                    .window_context
+                    .clear_hovered_cursor_icon();
                 window_controller
                     .item
                     .data()
@@ -570,7 +732,16 @@ impl ApplicationHandler<Event> for App {
                     pointer_state: phase.into(),
                     force,
                 };
+                if let PointerState::Started = event.pointer_state {
+                    window_controller.window_context.set_focus_visible(false);
+                }
+                move_file_hover(&window_controller.window_context, event.x, event.y);
                 window_controller.item.data().dispatch_touch_input(&event);
+                if matches!(event.pointer_state, PointerState::Ended | PointerState::Cancelled) {
+                    window_controller
+                        .window_context
+                        .release_pointer(&Pointer::Touch { id });
+                }
             }
             WindowEvent::Ime(ime) => {
                 let id = window_controller
@@ -605,95 +776,86 @@ impl ApplicationHandler<Event> for App {
             }
             WindowEvent::RedrawRequested => {
                 window_controller.window_context.request_redraw.set(false);
-                if let Some(instant) = self.instant {
-                    let now = Instant::now();
-                    let fps = 1.0 / (now - instant).as_secs_f32();
-                    self.fps_in_one_second.push(fps);
-                    self.instant = Some(now);
+                if window_controller.frame_in_flight {
+                    // Frames are produced at the pace the render thread presents them.
+                    window_controller.redraw_deferred = true;
                 } else {
-                    self.instant = Some(Instant::now());
-                };
-                let background_color = window_controller
-                    .window_context
-                    .theme
-                    .read(|theme| *theme.get_color(color::WINDOW_BACKGROUND_COLOR).unwrap());
-                let scale_factor = window_controller.window_context.scale_factor();
-                let window = window_controller.window_context.window.lock();
-                let surface_ref = window.surface();
-                drop(window);
-                {
-                    let mut surface = surface_ref.lock();
-
-                    {
-                        let canvas = surface.canvas();
-                        canvas.clear(background_color);
-                        canvas.save();
-                        canvas.scale((scale_factor, scale_factor));
-                    }
-
-                    let instant = Instant::now();
-                    window_controller
-                        .item
-                        .data()
-                        .dispatch_draw(surface.deref_mut(), 0.0, 0.0);
-
-
-
-                    println!("Redraw completed in {:?}", instant.elapsed());
-                    let canvas = surface.canvas();
-/*
-                    let text_color = window_controller
+                    let _span = tracing::debug_span!(target: log_target::DRAW, "draw").entered();
+                    let background_color = window_controller
                         .window_context
                         .theme
-                        .read(|theme| *theme.get_color(color::ON_SURFACE).unwrap());
-
-                    if !self.fps_in_one_second.is_empty() {
-                        let fps = self.fps_in_one_second.iter().sum::<f32>()
-                            / self.fps_in_one_second.len() as f32;
-                        let now = Instant::now();
-                        if let Some(instant) = self.second_instant {
-                            if now - instant > std::time::Duration::from_secs(1) {
-                                self.fps_in_one_second.clear();
-                                self.second_instant = Some(now);
-                                self.average_fps = fps;
-                            }
-                        } else {
-                            self.second_instant = Some(now);
+                        .read(|theme| *theme.get_color(color::WINDOW_BACKGROUND_COLOR).unwrap());
+                    let scale_factor = window_controller.window_context.scale_factor();
+                    let window_size = window_controller.window_context.window().inner_size();
+                    let window_rect =
+                        IRect::from_wh(window_size.width as i32, window_size.height as i32);
+
+                    // Record the snapshot, items that didn't change replay their last recording.
+                    let damage = window_controller.window_context.damage.clone();
+                    let profiler = window_controller.window_context.profiler.clone();
+                    damage.lock().begin_frame();
+                    let mut recorder = PictureRecorder::new();
+                    let damage_rects = {
+                        let canvas = recorder.begin_recording(Rect::from_irect(window_rect), None);
+                        let start = profiler.lock().start();
+                        canvas.save();
+                        canvas.scale((scale_factor, scale_factor));
+                        window_controller
+                            .item
+                            .data()
+                            .dispatch_draw(canvas, 0.0, 0.0);
+                        window_controller.window_context.draw_inspector_overlay(
+                            window_controller.item.data().deref_mut(),
+                            canvas,
+                        );
+                        canvas.restore();
+                        let mut damage_rects = damage.lock().finish_frame(window_rect);
+                        damage.lock().draw_flashes(canvas);
+                        let mut profiler = profiler.lock();
+                        profiler.record_phase(FramePhase::Draw, start);
+                        profiler.finish_frame();
+                        if profiler.is_hud_visible() {
+                            // The HUD shows the last frames, so it changes with every frame.
+                            let hud_bounds = profiler.hud_bounds();
+                            damage_rects.push(
+                                Rect::from_ltrb(
+                                    hud_bounds.left * scale_factor,
+                                    hud_bounds.top * scale_factor,
+                                    hud_bounds.right * scale_factor,
+                                    hud_bounds.bottom * scale_factor,
+                                )
+                                .round_out(),
+                            );
+                            canvas.save();
+                            canvas.scale((scale_factor, scale_factor));
+                            profiler.draw_hud(canvas);
+                            canvas.restore();
                         }
-                    }
+                        damage_rects
+                    };
+                    let picture = recorder.finish_recording_as_picture(None);
 
-                    let fps_text = format!("FPS: {:.2}", self.average_fps);
-                    let mut styled_text = StyledText::from(fps_text);
-                    styled_text.set_style(
-                        TextStyle::TextColor(text_color),
-                        0..styled_text.len(),
-                        false,
-                    );
-                    styled_text.set_style(TextStyle::FontSize(12.0), 0..styled_text.len(), false);
-
-                    let mut paragraph_style = ParagraphStyle::default();
-                    paragraph_style.set_text_align(TextAlign::Start);
-
-                    let mut paragraph_builder =
-                        ParagraphBuilder::new(&paragraph_style, font_collection());
-
-                    create_segments(
-                        &styled_text,
-                        &(0..styled_text.len()),
-                        &skia_safe::textlayout::TextStyle::default(),
-                    )
-                        .iter()
-                        .for_each(|style_segment| {
-                            paragraph_builder.add_style_segment(style_segment);
+                    if let (Some(picture), false) = (picture, damage_rects.is_empty()) {
+                        window_controller.render_thread.submit(RenderSnapshot {
+                            picture,
+                            background_color,
+                            damage: damage_rects,
                         });
-
-                    let mut paragraph = paragraph_builder.build();
-                    paragraph.layout(100.0);
-                    paragraph.paint(canvas, 10.0, 10.0);
-*/
-                    canvas.restore();
+                        window_controller.frame_in_flight = true;
+                    }
+                    if damage.lock().has_flashes() {
+                        window_controller.window_context.schedule_redraw();
+                    }
                 }
-                window_controller.window_context.window.lock().present();
+            }
+            WindowEvent::HoveredFile(path) => {
+                hover_file(&window_controller.window_context, path);
+            }
+            WindowEvent::HoveredFileCancelled => {
+                cancel_file_hover();
+            }
+            WindowEvent::DroppedFile(path) => {
+                drop_file(&window_controller.window_context, path);
             }
             WindowEvent::ModifiersChanged(modifiers) => {
                 // println!("{:?}", modifiers);
@@ -803,8 +965,9 @@ impl ApplicationHandler<Event> for App {
                 .window_context
                 .layout_animations
                 .write(|running_animations| {
+                    // The items whose display parameters change are recorded again.
                     if !running_animations.is_empty() {
-                        window_controller.window_context.request_redraw()
+                        window_controller.window_context.schedule_redraw()
                     }
                     running_animations.iter_mut().for_each(|animation| {
                         if animation.is_finished() {
@@ -816,10 +979,7 @@ impl ApplicationHandler<Event> for App {
         }
 
         {
-            // println!("now: {:?}", Instant::now());
-            // println_item_focused(
-            //     &window_controller.window_context.item_focused,
-            // );
+            trace_item_focused(&window_controller.window_context.item_focused);
             {
                 window_controller.item.data().dispatch_focus();
                 let mut item_focused = window_controller.window_context.item_focused.lock();
@@ -828,42 +988,48 @@ impl ApplicationHandler<Event> for App {
                     item_focused.0.replace(new);
                 }
             }
-            // println!("now: {:?}", Instant::now());
-            // println_item_focused(
-            //     &window_controller.window_context.item_focused,
-            // );
+            trace_item_focused(&window_controller.window_context.item_focused);
         }
 
-        if !closed {
+        if closed {
+            unregister_window(window_id);
+        } else {
             self.windows.insert(window_id, window_controller);
         }
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        // Timer
-        // {
-        //     let timers = self.window_context.timers.read(|timers| timers.clone());
-        //     if !timers.is_empty() {
-        //         let mut most_recent_timer = timers[0].start_time + timers[0].duration;
-        //         let now = Instant::now();
-        //         if let Some(item) = &mut self.item {
-        //             for timer in timers.iter() {
-        //                 if timer.start_time + timer.duration < most_recent_timer {
-        //                     most_recent_timer = timer.start_time + timer.duration;
-        //                 }
-        //                 if now - timer.start_time >= timer.duration {
-        //                     item.data().dispatch_timer(timer.id);
-        //                 }
-        //             }
-        //         }
-        //         if most_recent_timer > now {
-        //             event_loop.set_control_flow(ControlFlow::WaitUntil(most_recent_timer));
-        //         }
-        //         self.window_context
-        //             .timers
-        //             .write(|timers| timers.retain(|timer| now - timer.start_time < timer.duration));
-        //     }
-        // }
+        // Timers: a window whose timer is due is woken up so that its UI hooks run,
+        // the event loop sleeps until the next timer is due.
+        let now = Instant::now();
+        let mut next_deadline: Option<Instant> = None;
+        for window_controller in self.windows.values() {
+            let window_context = &window_controller.window_context;
+            let is_due = {
+                let mut timers = window_context.timers.lock();
+                let count = timers.len();
+                timers.retain(|timer| {
+                    let deadline = timer.start_time + timer.duration;
+                    if deadline <= now {
+                        return false;
+                    }
+                    next_deadline = Some(next_deadline.map_or(deadline, |next| next.min(deadline)));
+                    true
+                });
+                timers.len() < count
+            };
+            if is_due {
+                window_context.window().request_redraw();
+            }
+        }
+        if let Some(next_deadline) = next_deadline {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next_deadline));
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Stores kept alive by statics are never dropped, their pending changes are written now.
+        crate::shared::flush_stores();
     }
 }
 
@@ -888,14 +1054,15 @@ pub fn run_app(app: App) {
     });
 }
 
+use crate::core::log_target;
 use crate::shared::{Children, Gettable, Settable, Shared, SharedBool};
-use crate::text::{
-    create_segments, font_collection, AddStyleSegment, ParagraphBuilder, StyledText, TextStyle,
+use crate::ui::app::{
+    register_window, unregister_window, Accelerator, CreateSkiaWindow, Event, EventType,
+    FocusDirection, FramePhase, LayerController, RenderSnapshot, RenderThread, WindowContext,
 };
-use crate::ui::app::{Event, EventType, LayerController, WindowContext};
 use crate::ui::item::{
-    CursorMove, ImeAction, ItemData, KeyboardInput, MeasureMode, MouseInput, MouseWheel,
-    PointerState, TouchInput,
+    cancel_file_hover, drop_file, hover_file, move_file_hover, CursorMove, ImeAction, ItemData,
+    KeyboardInput, MeasureMode, MouseInput, MouseWheel, Pointer, PointerState, TouchInput,
 };
 use crate::ui::layout::StackExt;
 use crate::ui::theme::color;
@@ -0,0 +1,703 @@
+use crate::core::log_target;
+use crate::shared::{Settable, Shared};
+use crate::ui::app::WindowContext;
+use crate::ui::item::ItemData;
+use crate::ui::Item;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Weak;
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+
+/// Named keys that can be written in an accelerator, with their names.
+const NAMED_KEYS: &[(&str, NamedKey)] = &[
+    ("Enter", NamedKey::Enter),
+    ("Tab", NamedKey::Tab),
+    ("Space", NamedKey::Space),
+    ("Backspace", NamedKey::Backspace),
+    ("Delete", NamedKey::Delete),
+    ("Insert", NamedKey::Insert),
+    ("Escape", NamedKey::Escape),
+    ("Up", NamedKey::ArrowUp),
+    ("Down", NamedKey::ArrowDown),
+    ("Left", NamedKey::ArrowLeft),
+    ("Right", NamedKey::ArrowRight),
+    ("Home", NamedKey::Home),
+    ("End", NamedKey::End),
+    ("PageUp", NamedKey::PageUp),
+    ("PageDown", NamedKey::PageDown),
+    ("F1", NamedKey::F1),
+    ("F2", NamedKey::F2),
+    ("F3", NamedKey::F3),
+    ("F4", NamedKey::F4),
+    ("F5", NamedKey::F5),
+    ("F6", NamedKey::F6),
+    ("F7", NamedKey::F7),
+    ("F8", NamedKey::F8),
+    ("F9", NamedKey::F9),
+    ("F10", NamedKey::F10),
+    ("F11", NamedKey::F11),
+    ("F12", NamedKey::F12),
+];
+
+/// The modifier that is used for shortcuts on the current platform,
+/// Command on macOS and Control elsewhere.
+fn primary_modifier() -> ModifiersState {
+    if cfg!(target_os = "macos") {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    }
+}
+
+/// A key combination like `Ctrl+Shift+S`.
+///
+/// When parsed, modifiers are case-insensitive and may be written as `Ctrl`/`Control`,
+/// `Shift`, `Alt`/`Option`, `Super`/`Cmd`/`Meta`/`Win` or `Primary`/`CmdOrCtrl`,
+/// which is Command on macOS and Control elsewhere.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    modifiers: ModifiersState,
+    key: Key,
+}
+
+impl Accelerator {
+    pub fn new(modifiers: ModifiersState, key: Key) -> Self {
+        let key = Self::normalize(key);
+        let modifiers = match &key {
+            // Shift is part of these symbols, e.g. `+` is Shift+= on many layouts,
+            // so it's dropped to let `Ctrl++` match the key press.
+            Key::Character(character) if Self::is_shifted_symbol(character) => {
+                modifiers - ModifiersState::SHIFT
+            }
+            _ => modifiers,
+        };
+        Self { modifiers, key }
+    }
+
+    /// The accelerator of a key press. Shift turns letters into capitals,
+    /// so letters are compared in lower case.
+    pub fn from_key(modifiers: ModifiersState, key: &Key) -> Self {
+        Self::new(modifiers, key.clone())
+    }
+
+    /// The accelerator of the digit key that was pressed, whatever the layout makes of it.
+    /// Shift turns digits into symbols, e.g. `Ctrl+Shift+1` is reported as `Ctrl+!`.
+    pub fn from_physical_key(modifiers: ModifiersState, key: &PhysicalKey) -> Option<Self> {
+        let digit = match key {
+            PhysicalKey::Code(KeyCode::Digit0) => "0",
+            PhysicalKey::Code(KeyCode::Digit1) => "1",
+            PhysicalKey::Code(KeyCode::Digit2) => "2",
+            PhysicalKey::Code(KeyCode::Digit3) => "3",
+            PhysicalKey::Code(KeyCode::Digit4) => "4",
+            PhysicalKey::Code(KeyCode::Digit5) => "5",
+            PhysicalKey::Code(KeyCode::Digit6) => "6",
+            PhysicalKey::Code(KeyCode::Digit7) => "7",
+            PhysicalKey::Code(KeyCode::Digit8) => "8",
+            PhysicalKey::Code(KeyCode::Digit9) => "9",
+            _ => return None,
+        };
+        Some(Self::new(modifiers, Key::Character(digit.into())))
+    }
+
+    fn normalize(key: Key) -> Key {
+        match key {
+            Key::Character(character) => Key::Character(character.to_lowercase().into()),
+            Key::Named(NamedKey::Space) => Key::Character(" ".into()),
+            key => key,
+        }
+    }
+
+    /// Whether the character is typed with Shift on the US layout. Digits, letters of caseless
+    /// scripts and the other symbols keep Shift, so `Ctrl+Shift+1` and `Ctrl+1` stay distinct.
+    fn is_shifted_symbol(character: &str) -> bool {
+        let mut chars = character.chars();
+        match (chars.next(), chars.next()) {
+            (Some(character), None) => "~!@#$%^&*()_+{}|:\"<>?".contains(character),
+            _ => false,
+        }
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+}
+
+impl FromStr for Accelerator {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CommandError::InvalidAccelerator(s.to_string());
+        let mut modifiers = ModifiersState::empty();
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            if part.is_empty() {
+                // The empty parts around a `+` key, which are checked below.
+                continue;
+            }
+            if key.is_some() {
+                // The key must come last.
+                return Err(invalid());
+            }
+            let modifier = match part.to_lowercase().as_str() {
+                "ctrl" | "control" => Some(ModifiersState::CONTROL),
+                "shift" => Some(ModifiersState::SHIFT),
+                "alt" | "option" => Some(ModifiersState::ALT),
+                "super" | "cmd" | "command" | "meta" | "win" => Some(ModifiersState::SUPER),
+                "primary" | "cmdorctrl" => Some(primary_modifier()),
+                _ => None,
+            };
+            match modifier {
+                Some(modifier) => modifiers |= modifier,
+                None if part.chars().count() == 1 => key = Some(Key::Character(part.into())),
+                None => {
+                    let named = NAMED_KEYS
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(part))
+                        .ok_or_else(invalid)?;
+                    key = Some(Key::Named(named.1));
+                }
+            }
+        }
+        // `Ctrl++` has a `+` key.
+        let s = s.trim();
+        if key.is_none() && (s == "+" || s.ends_with("++")) {
+            key = Some(Key::Character("+".into()));
+        }
+        key.map(|key| Accelerator::new(modifiers, key))
+            .ok_or_else(invalid)
+    }
+}
+
+impl Display for Accelerator {
+    /// Formats the accelerator for menus and tooltips, e.g. `Ctrl+Shift+S`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mac = cfg!(target_os = "macos");
+        let modifiers = [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::ALT, if mac { "Option" } else { "Alt" }),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::SUPER, if mac { "Cmd" } else { "Super" }),
+        ];
+        for (modifier, name) in modifiers {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match &self.key {
+            Key::Character(character) if character.as_str() == " " => write!(f, "Space"),
+            Key::Character(character) => write!(f, "{}", character.to_uppercase()),
+            Key::Named(named) => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|(_, key)| key == named)
+                    .map(|(name, _)| *name);
+                match name {
+                    Some(name) => write!(f, "{}", name),
+                    None => write!(f, "{:?}", named),
+                }
+            }
+            key => write!(f, "{:?}", key),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    InvalidAccelerator(String),
+    /// Two commands of the same scope would be triggered by the same accelerator.
+    Conflict {
+        accelerator: Accelerator,
+        commands: (String, String),
+    },
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    /// A keymap entry that is neither an accelerator nor a list of accelerators.
+    InvalidKeymapEntry(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::InvalidAccelerator(accelerator) => {
+                write!(f, "Invalid accelerator: {}", accelerator)
+            }
+            CommandError::Conflict {
+                accelerator,
+                commands,
+            } => write!(
+                f,
+                "{} is bound to both {} and {}",
+                accelerator, commands.0, commands.1
+            ),
+            CommandError::Io(error) => write!(f, "I/O error: {}", error),
+            CommandError::Toml(error) => write!(f, "TOML error: {}", error),
+            CommandError::InvalidKeymapEntry(command) => {
+                write!(f, "The keymap entry of {} is not an accelerator", command)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        CommandError::Io(error)
+    }
+}
+
+/// Where a command is available.
+#[derive(Clone)]
+enum Scope {
+    /// Anywhere in the window.
+    Window,
+    /// While the item or one of its descendants is focused.
+    Item {
+        id: usize,
+        item: Weak<Mutex<ItemData>>,
+    },
+}
+
+impl Scope {
+    fn id(&self) -> Option<usize> {
+        match self {
+            Scope::Window => None,
+            Scope::Item { id, .. } => Some(*id),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        match self {
+            Scope::Window => true,
+            Scope::Item { item, .. } => item.strong_count() > 0,
+        }
+    }
+}
+
+struct Command {
+    scope: Scope,
+    /// Gets the window context instead of capturing it, which would keep the window alive.
+    handler: Rc<RefCell<dyn FnMut(&WindowContext)>>,
+    default_accelerators: Vec<Accelerator>,
+    /// Increases with every registration, breaks ties between commands of the same depth.
+    order: u64,
+}
+
+/// The commands of a window and the accelerators they are bound to.
+#[derive(Default)]
+pub(crate) struct CommandRegistry {
+    commands: HashMap<String, Command>,
+    next_order: u64,
+    /// Accelerators that replace the default ones, from keymaps or [`WindowContext::bind_command`].
+    bindings: HashMap<String, Vec<Accelerator>>,
+    shortcut_texts: HashMap<String, Shared<String>>,
+}
+
+impl CommandRegistry {
+    fn accelerators(&self, name: &str) -> &[Accelerator] {
+        match self.bindings.get(name) {
+            Some(accelerators) => accelerators,
+            None => self
+                .commands
+                .get(name)
+                .map_or(&[], |command| &command.default_accelerators),
+        }
+    }
+
+    /// Finds two commands of the same scope that share an accelerator.
+    fn find_conflict(&self) -> Option<CommandError> {
+        let mut seen: HashMap<(Option<usize>, &Accelerator), &str> = HashMap::new();
+        let mut names: Vec<&String> = self.commands.keys().collect();
+        names.sort();
+        for name in names {
+            let scope = self.commands[name].scope.id();
+            for accelerator in self.accelerators(name) {
+                if let Some(other) = seen.insert((scope, accelerator), name) {
+                    return Some(CommandError::Conflict {
+                        accelerator: accelerator.clone(),
+                        commands: (other.to_string(), name.clone()),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn update_shortcut_texts(&self) {
+        for (name, text) in &self.shortcut_texts {
+            let shortcut = self
+                .accelerators(name)
+                .first()
+                .map(|accelerator| accelerator.to_string())
+                .unwrap_or_default();
+            text.set(shortcut);
+        }
+    }
+
+    fn prune(&mut self) {
+        self.commands.retain(|_, command| command.scope.is_alive());
+    }
+
+    /// The handler of the command bound to `accelerator` in the innermost scope of `focus_path`.
+    /// Of several commands in the same scope, the one registered last wins.
+    fn find_handler(
+        &mut self,
+        accelerator: &Accelerator,
+        focus_path: &[usize],
+    ) -> Option<Rc<RefCell<dyn FnMut(&WindowContext)>>> {
+        self.prune();
+        self.commands
+            .iter()
+            .filter(|(name, _)| self.accelerators(name).contains(accelerator))
+            .filter_map(|(_, command)| {
+                let depth = match command.scope.id() {
+                    None => 0,
+                    Some(id) => focus_path.iter().position(|item| *item == id)? + 1,
+                };
+                Some(((depth, command.order), command.handler.clone()))
+            })
+            .max_by_key(|(key, _)| *key)
+            .map(|(_, handler)| handler)
+    }
+
+    fn parse_keymap(content: &str) -> Result<Vec<(String, Vec<Accelerator>)>, CommandError> {
+        let table: toml::Table = toml::from_str(content).map_err(CommandError::Toml)?;
+        let mut keymap = Vec::new();
+        for (name, value) in table {
+            let accelerators: Vec<&str> = match &value {
+                toml::Value::String(accelerator) if accelerator.is_empty() => Vec::new(),
+                toml::Value::String(accelerator) => vec![accelerator.as_str()],
+                toml::Value::Array(accelerators) => accelerators
+                    .iter()
+                    .map(|accelerator| accelerator.as_str())
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| CommandError::InvalidKeymapEntry(name.clone()))?,
+                _ => return Err(CommandError::InvalidKeymapEntry(name)),
+            };
+            let accelerators = accelerators
+                .into_iter()
+                .map(Accelerator::from_str)
+                .collect::<Result<_, _>>()?;
+            keymap.push((name, accelerators));
+        }
+        Ok(keymap)
+    }
+}
+
+impl WindowContext {
+    fn register_command(
+        &self,
+        name: String,
+        scope: Scope,
+        default_accelerators: Vec<Accelerator>,
+        handler: impl FnMut(&WindowContext) + 'static,
+    ) {
+        let mut commands = self.commands.lock();
+        commands.prune();
+        let order = commands.next_order;
+        commands.next_order += 1;
+        commands.commands.insert(
+            name,
+            Command {
+                scope,
+                handler: Rc::new(RefCell::new(handler)),
+                default_accelerators,
+                order,
+            },
+        );
+        commands.update_shortcut_texts();
+    }
+
+    /// Registers a command that is available anywhere in the window.
+    /// `default_accelerators` are used until the command is bound with
+    /// [`bind_command`](WindowContext::bind_command) or a keymap.
+    /// Use [`find_command_conflict`](WindowContext::find_command_conflict) to check the defaults.
+    pub fn add_command(
+        &self,
+        name: impl Into<String>,
+        default_accelerators: impl IntoIterator<Item = Accelerator>,
+        mut handler: impl FnMut() + 'static,
+    ) {
+        self.add_window_command(name, default_accelerators, move |_| handler());
+    }
+
+    /// Like [`add_command`](WindowContext::add_command), for the handlers of the window itself.
+    pub(crate) fn add_window_command(
+        &self,
+        name: impl Into<String>,
+        default_accelerators: impl IntoIterator<Item = Accelerator>,
+        handler: impl FnMut(&WindowContext) + 'static,
+    ) {
+        self.register_command(
+            name.into(),
+            Scope::Window,
+            default_accelerators.into_iter().collect(),
+            handler,
+        );
+    }
+
+    pub fn remove_command(&self, name: &str) {
+        self.commands.lock().commands.remove(name);
+    }
+
+    /// Replaces the accelerators of a command, which may not have been registered yet.
+    /// Nothing changes if another command of the same scope is already bound to one of them.
+    pub fn bind_command(
+        &self,
+        name: impl Into<String>,
+        accelerators: impl IntoIterator<Item = Accelerator>,
+    ) -> Result<(), CommandError> {
+        self.apply_bindings(vec![(name.into(), accelerators.into_iter().collect())])
+    }
+
+    /// Goes back to the default accelerators of a command.
+    pub fn reset_command_binding(&self, name: &str) -> Result<(), CommandError> {
+        let mut commands = self.commands.lock();
+        let previous = commands.bindings.remove(name);
+        if let Some(conflict) = commands.find_conflict() {
+            if let Some(previous) = previous {
+                commands.bindings.insert(name.to_string(), previous);
+            }
+            return Err(conflict);
+        }
+        commands.update_shortcut_texts();
+        Ok(())
+    }
+
+    fn apply_bindings(
+        &self,
+        bindings: Vec<(String, Vec<Accelerator>)>,
+    ) -> Result<(), CommandError> {
+        let mut commands = self.commands.lock();
+        let previous = commands.bindings.clone();
+        commands.bindings.extend(bindings);
+        if let Some(conflict) = commands.find_conflict() {
+            commands.bindings = previous;
+            return Err(conflict);
+        }
+        commands.update_shortcut_texts();
+        Ok(())
+    }
+
+    /// Rebinds commands from a TOML keymap that maps command names to an accelerator or a list of
+    /// accelerators, an empty string unbinds a command:
+    ///
+    /// ```toml
+    /// "editor.save" = "Primary+S"
+    /// "editor.redo" = ["Ctrl+Y", "Ctrl+Shift+Z"]
+    /// "editor.delete_line" = ""
+    /// ```
+    ///
+    /// Nothing changes if the keymap is invalid or causes a conflict.
+    pub fn load_keymap(&self, content: &str) -> Result<(), CommandError> {
+        self.apply_bindings(CommandRegistry::parse_keymap(content)?)
+    }
+
+    pub fn load_keymap_file(&self, path: impl AsRef<Path>) -> Result<(), CommandError> {
+        let content = std::fs::read_to_string(path)?;
+        self.load_keymap(&content)
+    }
+
+    /// Two commands of the same scope that are bound to the same accelerator.
+    pub fn find_command_conflict(&self) -> Option<CommandError> {
+        let mut commands = self.commands.lock();
+        commands.prune();
+        commands.find_conflict()
+    }
+
+    pub fn get_command_accelerators(&self, name: &str) -> Vec<Accelerator> {
+        self.commands.lock().accelerators(name).to_vec()
+    }
+
+    /// The first accelerator of a command as text, e.g. to show it in a menu or a tooltip.
+    /// It is empty if the command is not bound and it changes when the command is rebound.
+    pub fn shortcut_text(&self, name: &str) -> Shared<String> {
+        let mut commands = self.commands.lock();
+        if let Some(text) = commands.shortcut_texts.get(name) {
+            return text.clone();
+        }
+        let text = Shared::from(String::new());
+        commands
+            .shortcut_texts
+            .insert(name.to_string(), text.clone());
+        commands.update_shortcut_texts();
+        text
+    }
+
+    /// Runs the command bound to the key press, preferring the innermost scope around the focused
+    /// item. Returns whether a command has run.
+    pub(crate) fn run_command(&self, accelerator: &Accelerator, focus_path: &[usize]) -> bool {
+        let handler = self.commands.lock().find_handler(accelerator, focus_path);
+        match handler {
+            // The registry is unlocked, so the handler may register or rebind commands.
+            Some(handler) => match handler.try_borrow_mut() {
+                Ok(mut handler) => {
+                    handler(self);
+                    true
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        target: log_target::EVENT,
+                        %accelerator,
+                        "A command ran again from its own handler, it is skipped"
+                    );
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+}
+
+impl Item {
+    /// Registers a command that is only available while this item or one of its descendants is
+    /// focused. It shadows commands of outer scopes that are bound to the same accelerator.
+    pub fn command(
+        self,
+        name: impl Into<String>,
+        default_accelerators: impl IntoIterator<Item = Accelerator>,
+        mut handler: impl FnMut() + 'static,
+    ) -> Self {
+        let (id, window_context) = {
+            let data = self.data();
+            (data.get_id(), data.get_window_context().clone())
+        };
+        window_context.register_command(
+            name.into(),
+            Scope::Item {
+                id,
+                item: self.data_clone(),
+            },
+            default_accelerators.into_iter().collect(),
+            move |_: &WindowContext| handler(),
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod command_test {
+    use super::{Accelerator, CommandRegistry};
+    use std::str::FromStr;
+    use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+
+    fn parse(accelerator: &str) -> Accelerator {
+        Accelerator::from_str(accelerator).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let accelerator = parse("ctrl + Shift + s");
+        assert_eq!(
+            accelerator.modifiers(),
+            ModifiersState::CONTROL | ModifiersState::SHIFT
+        );
+        assert_eq!(accelerator.key(), &Key::Character("s".into()));
+        assert_eq!(parse("Alt+PageDown").key(), &Key::Named(NamedKey::PageDown));
+        assert_eq!(parse("Shift+Space").modifiers(), ModifiersState::SHIFT);
+        assert!(Accelerator::from_str("S+Ctrl").is_err());
+        assert!(Accelerator::from_str("Ctrl+Unknown").is_err());
+        assert!(Accelerator::from_str("Ctrl").is_err());
+    }
+
+    #[test]
+    fn test_plus_key() {
+        let accelerator = parse("Ctrl++");
+        assert_eq!(accelerator.modifiers(), ModifiersState::CONTROL);
+        assert_eq!(accelerator.key(), &Key::Character("+".into()));
+        assert_eq!(parse("+").modifiers(), ModifiersState::empty());
+    }
+
+    #[test]
+    fn test_from_key_matches_parsed() {
+        let shift_ctrl = ModifiersState::CONTROL | ModifiersState::SHIFT;
+        // Shift turns `s` into `S`.
+        assert_eq!(
+            Accelerator::from_key(shift_ctrl, &Key::Character("S".into())),
+            parse("Ctrl+Shift+S")
+        );
+        // `+` is typed with Shift on many layouts.
+        assert_eq!(
+            Accelerator::from_key(shift_ctrl, &Key::Character("+".into())),
+            parse("Ctrl++")
+        );
+        assert_eq!(
+            Accelerator::from_key(ModifiersState::empty(), &Key::Named(NamedKey::Space)),
+            parse("Space")
+        );
+        assert_ne!(
+            Accelerator::from_key(ModifiersState::CONTROL, &Key::Character("s".into())),
+            parse("Ctrl+Shift+S")
+        );
+    }
+
+    #[test]
+    fn test_shift_with_digits() {
+        let shift_ctrl = ModifiersState::CONTROL | ModifiersState::SHIFT;
+        assert_ne!(parse("Ctrl+Shift+1"), parse("Ctrl+1"));
+        assert_eq!(parse("Ctrl+Shift+1").modifiers(), shift_ctrl);
+        // Shift turns `1` into `!`, the digit is found by the physical key.
+        let pressed = Accelerator::from_key(shift_ctrl, &Key::Character("!".into()));
+        assert_eq!(pressed, parse("Ctrl+!"));
+        assert_ne!(pressed, parse("Ctrl+Shift+1"));
+        let digit = PhysicalKey::Code(KeyCode::Digit1);
+        assert_eq!(
+            Accelerator::from_physical_key(shift_ctrl, &digit),
+            Some(parse("Ctrl+Shift+1"))
+        );
+        assert_eq!(
+            Accelerator::from_physical_key(ModifiersState::CONTROL, &digit),
+            Some(parse("Ctrl+1"))
+        );
+        let letter = PhysicalKey::Code(KeyCode::KeyA);
+        assert_eq!(Accelerator::from_physical_key(shift_ctrl, &letter), None);
+        // Letters without case and unshifted symbols keep Shift as well.
+        assert_ne!(parse("Ctrl+Shift+あ"), parse("Ctrl+あ"));
+        assert_ne!(parse("Ctrl+Shift+="), parse("Ctrl+="));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for accelerator in [
+            "Ctrl+Shift+S",
+            "Alt+F4",
+            "Ctrl++",
+            "Shift+Space",
+            "Ctrl+PageUp",
+        ] {
+            let parsed = parse(accelerator);
+            assert_eq!(parse(&parsed.to_string()), parsed);
+        }
+    }
+
+    #[test]
+    fn test_parse_keymap() {
+        let keymap = CommandRegistry::parse_keymap(
+            r#"
+            "editor.save" = "Primary+S"
+            "editor.redo" = ["Ctrl+Y", "Ctrl+Shift+Z"]
+            "editor.delete_line" = ""
+            "#,
+        )
+        .unwrap();
+        let find = |name: &str| {
+            keymap
+                .iter()
+                .find(|(command, _)| command == name)
+                .map(|(_, accelerators)| accelerators.len())
+        };
+        assert_eq!(find("editor.save"), Some(1));
+        assert_eq!(find("editor.redo"), Some(2));
+        assert_eq!(find("editor.delete_line"), Some(0));
+        assert!(CommandRegistry::parse_keymap(r#""editor.save" = 1"#).is_err());
+    }
+}
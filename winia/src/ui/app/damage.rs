@@ -0,0 +1,374 @@
+use crate::ui::app::{RequestKind, WindowContext};
+use skia_safe::{Canvas, Color, IRect, Paint, Rect};
+use std::collections::{HashMap, HashSet};
+use std::panic::Location;
+use std::time::{Duration, Instant};
+
+/// How long a repainted region stays highlighted by the debug overlay.
+const FLASH_DURATION: Duration = Duration::from_millis(300);
+/// Above this many rectangles the damage is merged into their bounds.
+const MAX_DAMAGE_RECTS: usize = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+struct ItemRecord {
+    /// The bounds in physical pixels.
+    bounds: Rect,
+    opacity: f32,
+}
+
+/// Collects the regions of the window that changed since the last frame.
+#[derive(Default)]
+pub(crate) struct DamageTracker {
+    /// The ids of the items of the window that are alive.
+    live_items: HashSet<usize>,
+    repaint_all: bool,
+    /// Items whose drawing must be recorded again in the next frame.
+    dirty_items: HashSet<usize>,
+    frame_repaint_all: bool,
+    frame_dirty_items: HashSet<usize>,
+    last_items: HashMap<usize, ItemRecord>,
+    items: HashMap<usize, ItemRecord>,
+    /// The depth of items being drawn inside a subtree whose compositing changed.
+    changed_subtree_depth: usize,
+    /// The bounds of the items blurring what is under them.
+    backdrops: Vec<Rect>,
+    /// The bounds of the focus ring drawn in this frame.
+    focus_ring: Option<Rect>,
+    last_focus_ring: Option<Rect>,
+    last_inspector_highlight: Option<Rect>,
+    damage: Vec<Rect>,
+    show_repaint_regions: bool,
+    flashes: Vec<(Rect, Instant)>,
+}
+
+impl DamageTracker {
+    pub fn add_item(&mut self, id: usize) {
+        self.live_items.insert(id);
+    }
+
+    pub fn remove_item(&mut self, id: usize) {
+        self.live_items.remove(&id);
+        self.dirty_items.remove(&id);
+    }
+
+    pub fn contains_item(&self, id: usize) -> bool {
+        self.live_items.contains(&id)
+    }
+
+    pub fn invalidate_item(&mut self, id: usize) {
+        debug_assert!(
+            self.live_items.contains(&id),
+            "{} is not an item of the window, use `request_redraw` for other changes",
+            id
+        );
+        if self.live_items.contains(&id) {
+            self.dirty_items.insert(id);
+        }
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.repaint_all = true;
+    }
+
+    /// Takes the invalidations collected so far for the frame that is about to be drawn.
+    pub fn begin_frame(&mut self) {
+        self.frame_repaint_all = std::mem::take(&mut self.repaint_all);
+        self.frame_dirty_items = std::mem::take(&mut self.dirty_items);
+        self.items.clear();
+        self.backdrops.clear();
+        self.focus_ring = None;
+        self.damage.clear();
+        self.changed_subtree_depth = 0;
+    }
+
+    pub fn needs_repaint(&self, id: usize) -> bool {
+        self.frame_repaint_all || self.frame_dirty_items.contains(&id)
+    }
+
+    /// Records where an item was drawn. Returns `true` if the compositing of the item changed, so
+    /// everything drawn by its descendants is damaged too.
+    pub fn record_item(&mut self, id: usize, bounds: Rect, opacity: f32, repainted: bool) -> bool {
+        let record = ItemRecord { bounds, opacity };
+        let last = self.last_items.get(&id).copied();
+        let changed = repainted || self.changed_subtree_depth > 0 || last != Some(record);
+        if changed {
+            self.damage.push(bounds);
+            if let Some(last) = last {
+                self.damage.push(last.bounds);
+            }
+        }
+        self.items.insert(id, record);
+        last.is_some_and(|last| last.opacity != opacity)
+    }
+
+    pub fn enter_changed_subtree(&mut self) {
+        self.changed_subtree_depth += 1;
+    }
+
+    pub fn exit_changed_subtree(&mut self) {
+        self.changed_subtree_depth = self.changed_subtree_depth.saturating_sub(1);
+    }
+
+    pub fn add_backdrop(&mut self, bounds: Rect) {
+        self.backdrops.push(bounds);
+    }
+
+    pub fn record_focus_ring(&mut self, bounds: Rect) {
+        self.focus_ring = Some(bounds);
+    }
+
+    pub fn record_inspector_highlight(&mut self, bounds: Option<Rect>) {
+        if self.last_inspector_highlight != bounds {
+            self.damage.extend(self.last_inspector_highlight);
+            self.damage.extend(bounds);
+            self.last_inspector_highlight = bounds;
+        }
+    }
+
+    /// Returns the damaged rectangles in physical pixels, clipped to `window`.
+    pub fn finish_frame(&mut self, window: IRect) -> Vec<IRect> {
+        // Items that were not drawn this time leave their last bounds behind.
+        for (id, last) in &self.last_items {
+            if !self.items.contains_key(id) {
+                self.damage.push(last.bounds);
+            }
+        }
+        // Items that were not drawn this time are recorded again once they are drawn.
+        for id in self.frame_dirty_items.drain() {
+            if !self.items.contains_key(&id) && self.live_items.contains(&id) {
+                self.dirty_items.insert(id);
+            }
+        }
+        self.last_items = std::mem::take(&mut self.items);
+        if self.last_focus_ring != self.focus_ring {
+            self.damage.extend(self.last_focus_ring);
+            self.damage.extend(self.focus_ring);
+            self.last_focus_ring = self.focus_ring;
+        }
+
+        if self.frame_repaint_all {
+            self.damage = vec![Rect::from_irect(window)];
+        }
+        // A blurred background changes with anything drawn under it.
+        for backdrop in &self.backdrops {
+            if self.damage.iter().any(|rect| rect.intersects(backdrop)) {
+                self.damage.push(*backdrop);
+            }
+        }
+        let now = Instant::now();
+        if self.show_repaint_regions {
+            self.flashes.extend(self.damage.iter().map(|rect| (*rect, now)));
+        }
+        // The highlights are damage too, so they are erased once they fade out.
+        let flashes = self.flashes.iter().map(|(rect, _)| *rect).collect::<Vec<_>>();
+        self.flashes
+            .retain(|(_, instant)| now.duration_since(*instant) < FLASH_DURATION);
+
+        let mut damage: Vec<IRect> = self
+            .damage
+            .iter()
+            .chain(flashes.iter())
+            .filter_map(|rect| IRect::intersect(&rect.round_out(), &window))
+            .filter(|rect| !rect.is_empty())
+            .collect();
+        if damage.len() > MAX_DAMAGE_RECTS {
+            let mut bounds = IRect::new_empty();
+            for rect in &damage {
+                bounds.join(rect);
+            }
+            damage = vec![bounds];
+        }
+        damage
+    }
+
+    pub fn has_flashes(&self) -> bool {
+        !self.flashes.is_empty()
+    }
+
+    /// Highlights the regions repainted recently, fading them out.
+    pub fn draw_flashes(&self, canvas: &Canvas) {
+        let now = Instant::now();
+        let mut paint = Paint::default();
+        for (rect, instant) in &self.flashes {
+            let progress =
+                now.duration_since(*instant).as_secs_f32() / FLASH_DURATION.as_secs_f32();
+            let alpha = ((1.0 - progress).clamp(0.0, 1.0) * 96.0) as u8;
+            paint.set_color(Color::from_argb(alpha, 255, 0, 255));
+            canvas.draw_rect(rect, &paint);
+        }
+    }
+}
+
+impl WindowContext {
+    /// Records the item `id` again when the window is redrawn, the other items reuse their
+    /// recordings. Use [`request_redraw`](WindowContext::request_redraw) if the change can't be
+    /// tied to one item.
+    #[track_caller]
+    pub fn request_repaint(&self, id: usize) {
+        self.event_loop_proxy
+            .count_request(RequestKind::Repaint, Location::caller());
+        self.repaint_item(id);
+    }
+
+    /// Like [`request_repaint`](WindowContext::request_repaint), without counting the request.
+    pub(crate) fn repaint_item(&self, id: usize) {
+        self.damage.lock().invalidate_item(id);
+        self.schedule_redraw();
+    }
+
+    /// Flashes the regions of the window that are repainted, to debug damage tracking.
+    pub fn show_repaint_regions(&self, show: bool) {
+        self.damage.lock().show_repaint_regions = show;
+        self.request_redraw();
+    }
+}
+
+#[cfg(test)]
+mod damage_test {
+    use super::{DamageTracker, MAX_DAMAGE_RECTS};
+    use skia_safe::{IRect, Rect};
+
+    const WINDOW: IRect = IRect {
+        left: 0,
+        top: 0,
+        right: 1000,
+        bottom: 1000,
+    };
+
+    /// Draws one frame with `items` as `(id, bounds, repainted)`.
+    fn frame(damage: &mut DamageTracker, items: &[(usize, Rect, bool)]) -> Vec<IRect> {
+        damage.begin_frame();
+        for (id, bounds, repainted) in items {
+            damage.record_item(*id, *bounds, 1.0, *repainted);
+        }
+        damage.finish_frame(WINDOW)
+    }
+
+    fn rect(x: f32, y: f32) -> Rect {
+        Rect::from_xywh(x, y, 10.0, 10.0)
+    }
+
+    #[test]
+    fn test_moved_item() {
+        let mut damage = DamageTracker::default();
+        damage.add_item(1);
+        assert_eq!(
+            frame(&mut damage, &[(1, rect(0.0, 0.0), true)]),
+            vec![IRect::from_xywh(0, 0, 10, 10)]
+        );
+        assert!(frame(&mut damage, &[(1, rect(0.0, 0.0), false)]).is_empty());
+        assert_eq!(
+            frame(&mut damage, &[(1, rect(50.0, 0.0), false)]),
+            vec![
+                IRect::from_xywh(50, 0, 10, 10),
+                IRect::from_xywh(0, 0, 10, 10)
+            ]
+        );
+        // An item that is not drawn anymore leaves its last bounds behind.
+        assert_eq!(
+            frame(&mut damage, &[]),
+            vec![IRect::from_xywh(50, 0, 10, 10)]
+        );
+    }
+
+    #[test]
+    fn test_merge_into_bounds() {
+        let mut damage = DamageTracker::default();
+        let items = (0..=MAX_DAMAGE_RECTS)
+            .map(|id| (id, rect(id as f32 * 20.0, id as f32 * 5.0), true))
+            .collect::<Vec<_>>();
+        let last = MAX_DAMAGE_RECTS as i32;
+        assert_eq!(
+            frame(&mut damage, &items),
+            vec![IRect::from_ltrb(0, 0, last * 20 + 10, last * 5 + 10)]
+        );
+        let mut damage = DamageTracker::default();
+        let items = &items[..MAX_DAMAGE_RECTS];
+        assert_eq!(frame(&mut damage, items).len(), MAX_DAMAGE_RECTS);
+    }
+
+    #[test]
+    fn test_clip_to_window() {
+        let mut damage = DamageTracker::default();
+        let items = [
+            (1, Rect::from_xywh(-5.0, 995.0, 10.0, 10.0), true),
+            (2, Rect::from_xywh(2000.0, 0.0, 10.0, 10.0), true),
+            (3, Rect::from_xywh(10.2, 10.0, 0.0, 0.0), true),
+        ];
+        assert_eq!(
+            frame(&mut damage, &items),
+            vec![IRect::from_ltrb(0, 995, 5, 1000)]
+        );
+    }
+
+    #[test]
+    fn test_repaint_all() {
+        let mut damage = DamageTracker::default();
+        damage.add_item(1);
+        frame(&mut damage, &[(1, rect(0.0, 0.0), true)]);
+        damage.invalidate_all();
+        damage.begin_frame();
+        assert!(damage.needs_repaint(1));
+        damage.record_item(1, rect(0.0, 0.0), 1.0, true);
+        assert_eq!(damage.finish_frame(WINDOW), vec![WINDOW]);
+    }
+
+    #[test]
+    fn test_backdrop() {
+        let mut damage = DamageTracker::default();
+        frame(&mut damage, &[(1, rect(0.0, 0.0), true)]);
+        damage.begin_frame();
+        damage.record_item(1, rect(0.0, 0.0), 1.0, false);
+        damage.record_item(2, rect(0.0, 0.0), 1.0, true);
+        damage.add_backdrop(Rect::from_xywh(5.0, 5.0, 100.0, 100.0));
+        damage.add_backdrop(Rect::from_xywh(500.0, 500.0, 10.0, 10.0));
+        assert_eq!(
+            damage.finish_frame(WINDOW),
+            vec![
+                IRect::from_xywh(0, 0, 10, 10),
+                IRect::from_xywh(5, 5, 100, 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dirty_item_waits_until_drawn() {
+        let mut damage = DamageTracker::default();
+        damage.add_item(1);
+        damage.add_item(2);
+        frame(&mut damage, &[(1, rect(0.0, 0.0), true)]);
+        damage.invalidate_item(1);
+        damage.invalidate_item(2);
+        damage.begin_frame();
+        assert!(damage.needs_repaint(1));
+        assert!(damage.needs_repaint(2));
+        damage.record_item(1, rect(0.0, 0.0), 1.0, true);
+        damage.finish_frame(WINDOW);
+        // Only the item that was not drawn is still dirty.
+        damage.begin_frame();
+        assert!(!damage.needs_repaint(1));
+        assert!(damage.needs_repaint(2));
+        damage.finish_frame(WINDOW);
+        damage.remove_item(2);
+        damage.begin_frame();
+        assert!(!damage.needs_repaint(2));
+    }
+
+    #[test]
+    fn test_focus_ring() {
+        let mut damage = DamageTracker::default();
+        damage.begin_frame();
+        damage.record_focus_ring(rect(0.0, 0.0));
+        assert_eq!(
+            damage.finish_frame(WINDOW),
+            vec![IRect::from_xywh(0, 0, 10, 10)]
+        );
+        damage.begin_frame();
+        damage.record_focus_ring(rect(0.0, 0.0));
+        assert!(damage.finish_frame(WINDOW).is_empty());
+        assert_eq!(
+            frame(&mut damage, &[]),
+            vec![IRect::from_xywh(0, 0, 10, 10)]
+        );
+    }
+}
@@ -0,0 +1,491 @@
+use crate::core::get_id_by_name;
+use crate::shared::{Gettable, Settable};
+use crate::ui::app::WindowContext;
+use crate::ui::item::ItemData;
+use crate::ui::theme::focus;
+use parking_lot::Mutex;
+use skia_safe::{Canvas, Color, Paint, PaintStyle, RRect, Rect};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+/// The direction the focus is moved in by [`WindowContext::move_focus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    /// The next Tab stop.
+    Next,
+    /// The previous Tab stop.
+    Previous,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+pub(crate) struct FocusState {
+    root: Weak<Mutex<ItemData>>,
+    /// Whether the focus ring is drawn, it is only shown after keyboard navigation.
+    visible: bool,
+    /// The member of each focus group that was focused last.
+    group_memory: HashMap<usize, usize>,
+}
+
+impl Default for FocusState {
+    fn default() -> Self {
+        Self {
+            root: Weak::new(),
+            visible: false,
+            group_memory: HashMap::new(),
+        }
+    }
+}
+
+struct Candidate {
+    id: usize,
+    group: Option<usize>,
+    tab_index: Option<i32>,
+    rect: Rect,
+}
+
+/// Collects the focusable items in tree order. Items inside a focus scope belong to the group of
+/// the scope unless they have a group of their own.
+fn collect_candidates(item: &mut ItemData, scope: Option<usize>, candidates: &mut Vec<Candidate>) {
+    if !item.get_visible().get() || !item.get_enabled().get() {
+        return;
+    }
+    if item.get_focusable().get() {
+        let display_parameter = item.get_display_parameter();
+        candidates.push(Candidate {
+            id: item.get_id(),
+            group: item.get_focus_group().or(scope),
+            tab_index: item.get_tab_index(),
+            rect: Rect::from_xywh(
+                display_parameter.x(),
+                display_parameter.y(),
+                display_parameter.width,
+                display_parameter.height,
+            ),
+        });
+    }
+    let scope = if item.get_focus_scope() {
+        Some(item.get_id())
+    } else {
+        scope
+    };
+    item.for_each_child_mut(|child| {
+        collect_candidates(&mut child.data(), scope, candidates);
+    });
+}
+
+/// Picks the item Tab moves to. Positive tab indices come first, a whole focus group is one stop.
+fn find_tab_target(
+    candidates: &[Candidate],
+    focused: Option<usize>,
+    group_memory: &HashMap<usize, usize>,
+    forward: bool,
+) -> Option<usize> {
+    let mut ordered: Vec<&Candidate> = candidates
+        .iter()
+        .filter(|candidate| candidate.tab_index.is_none_or(|tab_index| tab_index >= 0))
+        .collect();
+    // The sort is stable, so the tree order is kept for equal indices.
+    ordered.sort_by_key(|candidate| match candidate.tab_index {
+        Some(tab_index) if tab_index > 0 => tab_index,
+        _ => i32::MAX,
+    });
+
+    let focused_group = focused.and_then(|focused| {
+        candidates
+            .iter()
+            .find(|candidate| candidate.id == focused)
+            .and_then(|candidate| candidate.group)
+    });
+    let mut stops: Vec<usize> = Vec::new();
+    let mut current = None;
+    for candidate in &ordered {
+        let id = match candidate.group {
+            Some(group) => {
+                let seen = ordered
+                    .iter()
+                    .take_while(|other| other.id != candidate.id)
+                    .any(|other| other.group == Some(group));
+                if seen {
+                    continue;
+                }
+                if focused_group == Some(group) {
+                    focused.unwrap()
+                } else {
+                    group_memory
+                        .get(&group)
+                        .filter(|id| ordered.iter().any(|other| other.id == **id))
+                        .copied()
+                        .unwrap_or(candidate.id)
+                }
+            }
+            None => candidate.id,
+        };
+        if Some(id) == focused {
+            current = Some(stops.len());
+        }
+        stops.push(id);
+    }
+
+    if stops.is_empty() {
+        return None;
+    }
+    let index = match (current, forward) {
+        (Some(current), true) => (current + 1) % stops.len(),
+        (Some(current), false) => (current + stops.len() - 1) % stops.len(),
+        (None, true) => 0,
+        (None, false) => stops.len() - 1,
+    };
+    Some(stops[index])
+}
+
+/// Picks the nearest item in `direction` from the focused one. Inside a focus group the search
+/// stays in the group.
+fn find_directional_target(
+    candidates: &[Candidate],
+    focused: usize,
+    direction: FocusDirection,
+) -> Option<usize> {
+    let current = candidates
+        .iter()
+        .find(|candidate| candidate.id == focused)?;
+    let from = current.rect;
+    candidates
+        .iter()
+        .filter(|candidate| candidate.id != focused)
+        .filter(|candidate| current.group.is_none() || candidate.group == current.group)
+        .filter_map(|candidate| {
+            let to = candidate.rect;
+            // The distance along the direction and the misalignment across it.
+            let (major, minor) = match direction {
+                FocusDirection::Up if to.center_y() < from.center_y() => {
+                    (from.top - to.bottom, to.center_x() - from.center_x())
+                }
+                FocusDirection::Down if to.center_y() > from.center_y() => {
+                    (to.top - from.bottom, to.center_x() - from.center_x())
+                }
+                FocusDirection::Left if to.center_x() < from.center_x() => {
+                    (from.left - to.right, to.center_y() - from.center_y())
+                }
+                FocusDirection::Right if to.center_x() > from.center_x() => {
+                    (to.left - from.right, to.center_y() - from.center_y())
+                }
+                _ => return None,
+            };
+            Some((candidate.id, major.max(0.0) + minor.abs() * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(id, _)| id)
+}
+
+impl WindowContext {
+    pub(crate) fn set_focus_root(&self, root: Weak<Mutex<ItemData>>) {
+        self.focus.lock().root = root;
+    }
+
+    /// The root item of the window.
+    pub(crate) fn root_item(&self) -> Option<Arc<Mutex<ItemData>>> {
+        self.focus.lock().root.upgrade()
+    }
+
+    /// Whether the focus ring is shown, it appears on keyboard navigation and disappears when
+    /// the window is clicked or touched.
+    pub fn is_focus_visible(&self) -> bool {
+        self.focus.lock().visible
+    }
+
+    pub(crate) fn set_focus_visible(&self, visible: bool) {
+        let changed = {
+            let mut focus = self.focus.lock();
+            let changed = focus.visible != visible;
+            focus.visible = visible;
+            changed
+        };
+        if changed {
+            self.schedule_redraw();
+        }
+    }
+
+    fn focus_item(&self, root: &mut ItemData, id: usize) {
+        root.find_item_mut(id, &mut |item| {
+            if let Some(group) = item.get_focus_group() {
+                self.focus.lock().group_memory.insert(group, id);
+            }
+            item.get_focused().set(true);
+        });
+    }
+
+    /// Moves the focus with the keyboard and shows the focus ring.
+    /// Returns `false` if there is no item to move to.
+    pub fn move_focus(&self, direction: FocusDirection) -> bool {
+        let root = self.focus.lock().root.upgrade();
+        let Some(root) = root else {
+            return false;
+        };
+        let mut root = root.lock();
+        let mut candidates = Vec::new();
+        collect_candidates(&mut root, None, &mut candidates);
+        let focused = self.get_focused_item_id();
+        let target = match direction {
+            FocusDirection::Next | FocusDirection::Previous => {
+                let focus = self.focus.lock();
+                find_tab_target(
+                    &candidates,
+                    focused,
+                    &focus.group_memory,
+                    direction == FocusDirection::Next,
+                )
+            }
+            _ => {
+                focused.and_then(|focused| find_directional_target(&candidates, focused, direction))
+            }
+        };
+        let Some(target) = target else {
+            return false;
+        };
+        if let Some(group) = candidates
+            .iter()
+            .find(|candidate| candidate.id == target)
+            .and_then(|candidate| candidate.group)
+        {
+            self.focus.lock().group_memory.insert(group, target);
+        }
+        if Some(target) != focused {
+            self.focus_item(&mut root, target);
+        }
+        self.set_focus_visible(true);
+        true
+    }
+
+    /// Focuses the item bound to `name` with [`Item::name`](crate::ui::Item::name).
+    /// The focus changes before the next window event is handled.
+    pub fn request_focus(&self, name: impl AsRef<str>) {
+        let Some(id) = get_id_by_name(name) else {
+            return;
+        };
+        let window_context = self.clone();
+        self.add_ui_hook(move || {
+            let root = window_context.focus.lock().root.upgrade();
+            if let Some(root) = root {
+                window_context.focus_item(&mut root.lock(), id);
+            }
+            false
+        });
+        self.request_redraw();
+    }
+
+    /// Removes the focus from the focused item.
+    pub fn clear_focus(&self) {
+        let window_context = self.clone();
+        self.add_ui_hook(move || {
+            let root = window_context.focus.lock().root.upgrade();
+            if let (Some(root), Some(id)) = (root, window_context.get_focused_item_id()) {
+                root.lock().find_item_mut(id, &mut |item| {
+                    item.get_focused().set(false);
+                });
+            }
+            false
+        });
+        self.request_redraw();
+    }
+
+    /// Draws the theme's focus ring around `item` if it has the keyboard focus. It is called while
+    /// the item is drawn, so the clips of its ancestors apply and not its own.
+    pub(crate) fn draw_focus_ring(&self, item: &ItemData, canvas: &Canvas) {
+        let Some((rrect, paint)) = self.focus_ring(item) else {
+            return;
+        };
+        let outset = paint.stroke_width();
+        let rect = rrect.rect().with_outset((outset, outset));
+        let bounds = canvas.local_to_device_as_3x3().map_rect(rect).0;
+        let bounds = canvas
+            .device_clip_bounds()
+            .and_then(|clip| Rect::intersect(&bounds, &Rect::from_irect(clip)))
+            .unwrap_or_default();
+        self.damage.lock().record_focus_ring(bounds);
+        canvas.draw_rrect(rrect, &paint);
+    }
+
+    fn focus_ring(&self, item: &ItemData) -> Option<(RRect, Paint)> {
+        if !item.get_focus_ring() || !self.is_focus_visible() {
+            return None;
+        }
+        let display_parameter = item.get_display_parameter();
+        let rect = Rect::from_xywh(
+            display_parameter.x(),
+            display_parameter.y(),
+            display_parameter.width,
+            display_parameter.height,
+        );
+        let radius = item.get_focus_ring_radius().map(|radius| radius.get());
+        let (color, width, offset, radius) = self.theme.read(|theme| {
+            (
+                theme
+                    .get_color(focus::RING_COLOR)
+                    .copied()
+                    .unwrap_or(Color::TRANSPARENT),
+                theme
+                    .get_dimension(focus::RING_WIDTH)
+                    .copied()
+                    .unwrap_or(0.0),
+                theme
+                    .get_dimension(focus::RING_OFFSET)
+                    .copied()
+                    .unwrap_or(0.0),
+                radius
+                    .or_else(|| theme.get_dimension(focus::RING_RADIUS).copied())
+                    .unwrap_or(0.0),
+            )
+        });
+        if width <= 0.0 || color.a() == 0 {
+            return None;
+        }
+        let outset = offset + width / 2.0;
+        let rect = rect.with_outset((outset, outset));
+        let radius = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(width);
+        paint.set_color(color);
+        Some((RRect::new_rect_xy(rect, radius, radius), paint))
+    }
+}
+
+#[cfg(test)]
+mod focus_test {
+    use super::{find_directional_target, find_tab_target, Candidate, FocusDirection};
+    use skia_safe::Rect;
+    use std::collections::HashMap;
+
+    fn candidate(id: usize, group: Option<usize>, tab_index: Option<i32>) -> Candidate {
+        Candidate {
+            id,
+            group,
+            tab_index,
+            rect: Rect::from_xywh(id as f32 * 100.0, 0.0, 50.0, 50.0),
+        }
+    }
+
+    fn tab_order(candidates: &[Candidate], memory: &HashMap<usize, usize>) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut focused = None;
+        for _ in 0..candidates.len() {
+            focused = find_tab_target(candidates, focused, memory, true);
+            if focused.is_none() || order.contains(&focused.unwrap()) {
+                break;
+            }
+            order.push(focused.unwrap());
+        }
+        order
+    }
+
+    #[test]
+    fn test_tree_order_wraps() {
+        let candidates = [
+            candidate(1, None, None),
+            candidate(2, None, None),
+            candidate(3, None, None),
+        ];
+        let memory = HashMap::new();
+        assert_eq!(find_tab_target(&candidates, None, &memory, true), Some(1));
+        assert_eq!(find_tab_target(&candidates, None, &memory, false), Some(3));
+        assert_eq!(
+            find_tab_target(&candidates, Some(3), &memory, true),
+            Some(1)
+        );
+        assert_eq!(
+            find_tab_target(&candidates, Some(1), &memory, false),
+            Some(3)
+        );
+        assert_eq!(
+            find_tab_target(&candidates, Some(2), &memory, true),
+            Some(3)
+        );
+        assert_eq!(find_tab_target(&[], None, &memory, true), None);
+    }
+
+    #[test]
+    fn test_tab_index() {
+        let candidates = [
+            candidate(1, None, None),
+            candidate(2, None, Some(2)),
+            candidate(3, None, Some(-1)),
+            candidate(4, None, Some(1)),
+            candidate(5, None, Some(0)),
+        ];
+        assert_eq!(tab_order(&candidates, &HashMap::new()), vec![4, 2, 1, 5]);
+        // A skipped item can still be focused, Tab goes on from the start.
+        assert_eq!(
+            find_tab_target(&candidates, Some(3), &HashMap::new(), true),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_focus_group_is_one_stop() {
+        let candidates = [
+            candidate(1, None, None),
+            candidate(2, Some(10), None),
+            candidate(3, Some(10), None),
+            candidate(4, None, None),
+        ];
+        let mut memory = HashMap::new();
+        assert_eq!(tab_order(&candidates, &memory), vec![1, 2, 4]);
+        // Tab leaves the group from any member.
+        assert_eq!(
+            find_tab_target(&candidates, Some(3), &memory, true),
+            Some(4)
+        );
+        assert_eq!(
+            find_tab_target(&candidates, Some(3), &memory, false),
+            Some(1)
+        );
+        // The member focused last is the stop of the group.
+        memory.insert(10, 3);
+        assert_eq!(tab_order(&candidates, &memory), vec![1, 3, 4]);
+        // A member that is gone is forgotten.
+        memory.insert(10, 9);
+        assert_eq!(
+            find_tab_target(&candidates, Some(1), &memory, true),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_directional_target() {
+        let mut candidates = vec![
+            candidate(0, None, None),
+            candidate(1, None, None),
+            candidate(2, None, None),
+        ];
+        candidates.push(Candidate {
+            id: 3,
+            group: None,
+            tab_index: None,
+            rect: Rect::from_xywh(100.0, 100.0, 50.0, 50.0),
+        });
+        assert_eq!(
+            find_directional_target(&candidates, 1, FocusDirection::Right),
+            Some(2)
+        );
+        assert_eq!(
+            find_directional_target(&candidates, 1, FocusDirection::Left),
+            Some(0)
+        );
+        assert_eq!(
+            find_directional_target(&candidates, 1, FocusDirection::Down),
+            Some(3)
+        );
+        assert_eq!(
+            find_directional_target(&candidates, 3, FocusDirection::Up),
+            Some(1)
+        );
+        assert_eq!(
+            find_directional_target(&candidates, 0, FocusDirection::Left),
+            None
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::shared::Gettable;
+use crate::ui::app::WindowContext;
+use crate::ui::item::ItemData;
+use std::ops::DerefMut;
+
+/// An item as the hit traversal sees it.
+pub(crate) trait HitTarget {
+    fn id(&self) -> usize;
+    /// Whether the item and its children are shown at all.
+    fn is_shown(&self) -> bool;
+    /// Whether the point is inside the item as it was last drawn.
+    fn contains_point(&mut self, x: f32, y: f32) -> bool;
+    /// Whether the children are clipped to the shape of the item.
+    fn is_clipped(&self) -> bool;
+    /// Calls `f` with the children, the topmost first, until it returns true.
+    fn any_child(&mut self, f: &mut dyn FnMut(&mut Self) -> bool) -> bool;
+}
+
+impl HitTarget for ItemData {
+    fn id(&self) -> usize {
+        self.get_id()
+    }
+
+    fn is_shown(&self) -> bool {
+        ItemData::is_shown(self)
+    }
+
+    fn contains_point(&mut self, x: f32, y: f32) -> bool {
+        ItemData::contains_point(self, x, y)
+    }
+
+    fn is_clipped(&self) -> bool {
+        self.get_clip().get()
+    }
+
+    fn any_child(&mut self, f: &mut dyn FnMut(&mut Self) -> bool) -> bool {
+        // Later children are drawn over the earlier ones.
+        self.get_children()
+            .lock()
+            .iter_mut()
+            .rev()
+            .any(|child| f(child.data().deref_mut()))
+    }
+}
+
+/// Visits the items under the point, the topmost first and the children before their parents,
+/// until `visit` returns true. Children that overflow an unclipped parent are hit outside of it,
+/// clipped ones only inside. Returns whether `visit` returned true.
+pub(crate) fn visit_hits<T: HitTarget>(
+    item: &mut T,
+    x: f32,
+    y: f32,
+    visit: &mut dyn FnMut(&mut T) -> bool,
+) -> bool {
+    if !item.is_shown() {
+        return false;
+    }
+    let inside = item.contains_point(x, y);
+    if item.is_clipped() && !inside {
+        return false;
+    }
+    if item.any_child(&mut |child| visit_hits(child, x, y, visit)) {
+        return true;
+    }
+    inside && visit(item)
+}
+
+/// Whether the point hits the item or one of its descendants. Pointer events that go down are
+/// dispatched to the topmost child that is hit, and from there on down to the topmost item,
+/// so a child that overflows its parent passes through the parent like any other descendant.
+pub(crate) fn is_hit<T: HitTarget>(item: &mut T, x: f32, y: f32) -> bool {
+    visit_hits(item, x, y, &mut |_| true)
+}
+
+/// The ids of the items under the point, the topmost first.
+pub(crate) fn hits<T: HitTarget>(item: &mut T, x: f32, y: f32) -> Vec<usize> {
+    let mut hits = Vec::new();
+    visit_hits(item, x, y, &mut |item| {
+        hits.push(item.id());
+        false
+    });
+    hits
+}
+
+impl WindowContext {
+    /// Returns the ids of the items under the point in logical window coordinates, from the
+    /// topmost to the root. Later children are drawn over the earlier ones and layers over the
+    /// window content. The clip shapes, transformations and visibility of the items are taken into
+    /// account with the positions the items were last drawn at, by the same traversal that
+    /// finds the item a pointer goes down on.
+    pub fn hit_test(&self, x: f32, y: f32) -> Vec<usize> {
+        match self.root_item() {
+            Some(root) => hits(root.lock().deref_mut(), x, y),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod hit_test_test {
+    use super::{hits, is_hit, HitTarget};
+    use crate::ui::item::HitRegion;
+    use skia_safe::{Matrix, Path, Rect};
+
+    struct Node {
+        id: usize,
+        region: HitRegion,
+        clip: bool,
+        children: Vec<Node>,
+    }
+
+    impl Node {
+        /// A rectangle in window coordinates.
+        fn new(id: usize, x: f32, y: f32, width: f32, height: f32) -> Self {
+            let rect = Rect::from_xywh(x, y, width, height);
+            Self::transformed(id, rect, &Matrix::new_identity())
+        }
+
+        /// A rectangle drawn with `to_window`.
+        fn transformed(id: usize, rect: Rect, to_window: &Matrix) -> Self {
+            Self {
+                id,
+                region: HitRegion::new(to_window, Path::rect(rect, None), 1.0).unwrap(),
+                clip: false,
+                children: Vec::new(),
+            }
+        }
+
+        fn clip(mut self) -> Self {
+            self.clip = true;
+            self
+        }
+
+        fn child(mut self, child: Node) -> Self {
+            self.children.push(child);
+            self
+        }
+    }
+
+    impl HitTarget for Node {
+        fn id(&self) -> usize {
+            self.id
+        }
+
+        fn is_shown(&self) -> bool {
+            self.region.is_shown()
+        }
+
+        fn contains_point(&mut self, x: f32, y: f32) -> bool {
+            self.region.contains(x, y)
+        }
+
+        fn is_clipped(&self) -> bool {
+            self.clip
+        }
+
+        fn any_child(&mut self, f: &mut dyn FnMut(&mut Self) -> bool) -> bool {
+            self.children.iter_mut().rev().any(|child| f(child))
+        }
+    }
+
+    /// The root covers 100x100, its child 1 covers the left half and overflows it by 20 with
+    /// its own child 2.
+    fn tree(clip: bool) -> Node {
+        let parent = Node::new(1, 0.0, 0.0, 50.0, 50.0).child(Node::new(2, 40.0, 40.0, 30.0, 30.0));
+        let parent = if clip { parent.clip() } else { parent };
+        Node::new(0, 0.0, 0.0, 100.0, 100.0).child(parent)
+    }
+
+    #[test]
+    fn test_nested() {
+        let mut root = tree(false);
+        assert_eq!(hits(&mut root, 45.0, 45.0), vec![2, 1, 0]);
+        assert_eq!(hits(&mut root, 10.0, 10.0), vec![1, 0]);
+        assert_eq!(hits(&mut root, 90.0, 10.0), vec![0]);
+        assert_eq!(hits(&mut root, 200.0, 10.0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_unclipped_overflow() {
+        let mut root = tree(false);
+        // Outside of its parent, the overflowing child is still hit.
+        assert_eq!(hits(&mut root, 60.0, 60.0), vec![2, 0]);
+        assert!(is_hit(&mut root.children[0], 60.0, 60.0));
+        assert!(!is_hit(&mut root.children[0], 10.0, 90.0));
+    }
+
+    #[test]
+    fn test_clipped_parent() {
+        let mut root = tree(true);
+        assert_eq!(hits(&mut root, 60.0, 60.0), vec![0]);
+        assert!(!is_hit(&mut root.children[0], 60.0, 60.0));
+        assert_eq!(hits(&mut root, 45.0, 45.0), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_transformed() {
+        // Scaled by 2 around the origin and moved by 100, 0.
+        let mut to_window = Matrix::translate((100.0, 0.0));
+        to_window.pre_scale((2.0, 2.0), None);
+        let mut root = Node::new(0, 0.0, 0.0, 300.0, 300.0).child(Node::transformed(
+            1,
+            Rect::from_wh(10.0, 10.0),
+            &to_window,
+        ));
+        assert_eq!(hits(&mut root, 115.0, 15.0), vec![1, 0]);
+        // Where the item would be without its transformation.
+        assert_eq!(hits(&mut root, 5.0, 5.0), vec![0]);
+        assert_eq!(hits(&mut root, 125.0, 5.0), vec![0]);
+
+        // Rotated by 45 degrees around its center.
+        let rotation = Matrix::rotate_deg_pivot(45.0, (50.0, 50.0));
+        let mut rotated = Node::transformed(2, Rect::from_xywh(0.0, 0.0, 100.0, 100.0), &rotation);
+        assert!(!rotated.contains_point(5.0, 5.0));
+        assert!(rotated.contains_point(50.0, -15.0));
+        assert!(is_hit(&mut rotated, 50.0, 50.0));
+    }
+
+    #[test]
+    fn test_overlapping_siblings() {
+        let mut root = Node::new(0, 0.0, 0.0, 100.0, 100.0)
+            .child(Node::new(1, 0.0, 0.0, 60.0, 60.0).child(Node::new(3, 0.0, 0.0, 60.0, 60.0)))
+            .child(Node::new(2, 40.0, 40.0, 60.0, 60.0));
+        // The later sibling is drawn over the earlier one and its children.
+        assert_eq!(hits(&mut root, 50.0, 50.0), vec![2, 3, 1, 0]);
+        assert_eq!(hits(&mut root, 10.0, 10.0), vec![3, 1, 0]);
+        // A small later sibling is still on top of a larger earlier one.
+        let mut root = Node::new(0, 0.0, 0.0, 100.0, 100.0)
+            .child(Node::new(1, 40.0, 40.0, 20.0, 20.0))
+            .child(Node::new(2, 0.0, 0.0, 100.0, 100.0));
+        assert_eq!(hits(&mut root, 50.0, 50.0), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_hidden() {
+        let mut root = tree(false);
+        root.children[0].region = HitRegion::new(
+            &Matrix::new_identity(),
+            Path::rect(Rect::from_wh(50.0, 50.0), None),
+            0.0,
+        )
+        .unwrap();
+        // A transparent item hides its children.
+        assert_eq!(hits(&mut root, 45.0, 45.0), vec![0]);
+        assert!(!is_hit(&mut root.children[0], 45.0, 45.0));
+    }
+}
@@ -0,0 +1,577 @@
+use crate::collection::WVec;
+use crate::core::next_id;
+use crate::shared::{Gettable, Settable, Shared, SharedBool, SharedF32, SharedText};
+use crate::text::StyledText;
+use crate::ui::app::{EventLoopProxy, WindowAttr, WindowContext};
+use crate::ui::component::TextExt;
+use crate::ui::item::{DisplayParameter, ItemData, Size};
+use crate::ui::layout::{ColumnExt, FlexGrow, ListExt, RowExt, ScrollAreaExt};
+use crate::ui::Item;
+use clonelet::clone;
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Path, Rect};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::DerefMut;
+use winit::window::WindowId;
+
+/// The properties the inspector can edit.
+const PROPERTIES: [(&str, fn(&ItemData) -> &SharedF32); 13] = [
+    ("margin_start", ItemData::get_margin_start),
+    ("margin_end", ItemData::get_margin_end),
+    ("margin_top", ItemData::get_margin_top),
+    ("margin_bottom", ItemData::get_margin_bottom),
+    ("padding_start", ItemData::get_padding_start),
+    ("padding_end", ItemData::get_padding_end),
+    ("padding_top", ItemData::get_padding_top),
+    ("padding_bottom", ItemData::get_padding_bottom),
+    ("offset_x", ItemData::get_offset_x),
+    ("offset_y", ItemData::get_offset_y),
+    ("opacity", ItemData::get_opacity),
+    ("rotation", ItemData::get_rotation),
+    ("elevation", ItemData::get_elevation),
+];
+
+thread_local! {
+    /// The open windows by their id, all windows live on the thread of the event loop.
+    static WINDOWS: RefCell<HashMap<WindowId, WindowContext>> = RefCell::new(HashMap::new());
+}
+
+pub(crate) fn register_window(window_context: &WindowContext) {
+    WINDOWS.with_borrow_mut(|windows| {
+        windows.insert(window_context.window_id(), window_context.clone());
+    });
+}
+
+/// Forgets a closed window and stops the inspections it was showing.
+pub(crate) fn unregister_window(window_id: WindowId) {
+    let windows: Vec<WindowContext> = WINDOWS.with_borrow_mut(|windows| {
+        windows.remove(&window_id);
+        windows.values().cloned().collect()
+    });
+    for window_context in windows {
+        let detached = {
+            let mut inspection = window_context.inspection.lock();
+            let detached = inspection
+                .as_ref()
+                .is_some_and(|inspection| inspection.inspector == window_id);
+            if detached {
+                *inspection = None;
+            }
+            detached
+        };
+        if detached {
+            // Removes the highlight.
+            window_context.schedule_redraw();
+        }
+    }
+}
+
+fn find_window(window_id: WindowId) -> Option<WindowContext> {
+    WINDOWS.with_borrow(|windows| windows.get(&window_id).cloned())
+}
+
+/// One item of the inspected tree.
+#[derive(Clone, Debug, PartialEq)]
+struct InspectedItem {
+    id: usize,
+    depth: usize,
+    name: String,
+    display_parameter: DisplayParameter,
+    /// Where the item was last drawn in window coordinates, `None` if it wasn't drawn.
+    outline: Option<Path>,
+    properties: [f32; PROPERTIES.len()],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct InspectedTree {
+    items: Vec<InspectedItem>,
+    /// The theme keys with their values, sorted by key.
+    theme: Vec<(String, String)>,
+}
+
+/// A row of the tree view.
+#[derive(Clone, PartialEq)]
+struct TreeRow {
+    id: usize,
+    depth: usize,
+    name: String,
+}
+
+struct PropertyEdit {
+    id: usize,
+    property: usize,
+    value: f32,
+}
+
+/// What the inspected window and its inspector exchange.
+#[derive(Clone, Default)]
+struct InspectorLink {
+    /// The tree after the last frame of the inspected window that changed it.
+    tree: Shared<Option<InspectedTree>>,
+    /// The changes made in the inspector, applied by the inspected window.
+    edits: Shared<Vec<PropertyEdit>>,
+    /// The item whose bounds are highlighted in the inspected window.
+    highlighted: Shared<Option<usize>>,
+}
+
+/// The inspector attached to a window.
+pub(crate) struct Inspection {
+    inspector: WindowId,
+    inspector_proxy: EventLoopProxy,
+    link: InspectorLink,
+    published: Option<InspectedTree>,
+}
+
+fn collect_items(item: &mut ItemData, depth: usize, items: &mut Vec<InspectedItem>) {
+    let mut properties = [0.0; PROPERTIES.len()];
+    for (value, (_, property)) in properties.iter_mut().zip(PROPERTIES) {
+        *value = property(item).get();
+    }
+    items.push(InspectedItem {
+        id: item.get_id(),
+        depth,
+        name: item.get_name().to_string(),
+        display_parameter: item.get_display_parameter(),
+        outline: item.drawn_shape(),
+        properties,
+    });
+    item.for_each_child_mut(|child| {
+        collect_items(&mut child.data(), depth + 1, items);
+    });
+}
+
+/// The outline of the item as it was drawn, rotated or scaled items included.
+/// Items that haven't been drawn fall back to their layout bounds.
+fn outline(item: &InspectedItem) -> Path {
+    item.outline.clone().unwrap_or_else(|| {
+        let display_parameter = &item.display_parameter;
+        Path::rect(
+            Rect::from_xywh(
+                display_parameter.x(),
+                display_parameter.y(),
+                display_parameter.width,
+                display_parameter.height,
+            ),
+            None,
+        )
+    })
+}
+
+impl WindowContext {
+    /// Opens a window showing the item tree of this window. The selected item's properties can
+    /// be edited there, the hovered one is highlighted here.
+    pub fn open_inspector(&self) {
+        let inspected = self.window_id();
+        let title = format!(
+            "Inspector - {}",
+            self.window_attr().lock().get_title().get()
+        );
+        self.event_loop_proxy().new_window(
+            move |w| inspector(w, inspected),
+            WindowAttr::default()
+                .name("inspector")
+                .title(title)
+                .preferred_size(720.0, 640.0)
+                .inspectable(false),
+        );
+    }
+
+    fn attach_inspector(&self, inspector: &WindowContext, link: InspectorLink) {
+        let inspector_proxy = inspector.event_loop_proxy().clone();
+        let inspector = inspector.window_id();
+        *self.inspection.lock() = Some(Inspection {
+            inspector,
+            inspector_proxy,
+            link: link.clone(),
+            published: None,
+        });
+        // The edits are applied before the next event, when no item is borrowed.
+        let window_context = self.clone();
+        self.add_ui_hook(move || {
+            let attached = window_context
+                .inspection
+                .lock()
+                .as_ref()
+                .is_some_and(|inspection| inspection.inspector == inspector);
+            if !attached {
+                return false;
+            }
+            let edits = std::mem::take(link.edits.lock().deref_mut());
+            if let (false, Some(root)) = (edits.is_empty(), window_context.root_item()) {
+                let mut root = root.lock();
+                for edit in edits {
+                    root.find_item_mut(edit.id, &mut |item| {
+                        (PROPERTIES[edit.property].1)(item).set(edit.value);
+                    });
+                }
+            }
+            true
+        });
+        // Publishes the tree.
+        self.redraw_all();
+    }
+
+    /// Sends the tree to the attached inspector if it changed and highlights the item hovered
+    /// in it.
+    pub(crate) fn draw_inspector_overlay(&self, root: &mut ItemData, canvas: &Canvas) {
+        let mut inspection = self.inspection.lock();
+        let Some(inspection) = inspection.as_mut() else {
+            self.damage.lock().record_inspector_highlight(None);
+            return;
+        };
+
+        let mut items = Vec::new();
+        collect_items(root, 0, &mut items);
+        let theme = self.theme.read(|theme| {
+            let mut keys: Vec<(String, String)> = theme
+                .color_keys()
+                .map(|key| {
+                    let color = theme.get_color(key).copied().unwrap_or(Color::TRANSPARENT);
+                    let value = format!(
+                        "#{:02X}{:02X}{:02X}{:02X}",
+                        color.a(),
+                        color.r(),
+                        color.g(),
+                        color.b()
+                    );
+                    (key.to_string(), value)
+                })
+                .chain(theme.dimension_keys().map(|key| {
+                    (
+                        key.to_string(),
+                        theme
+                            .get_dimension(key)
+                            .map_or_else(String::new, f32::to_string),
+                    )
+                }))
+                .chain(theme.bool_keys().map(|key| {
+                    (
+                        key.to_string(),
+                        theme
+                            .get_bool(key)
+                            .map_or_else(String::new, bool::to_string),
+                    )
+                }))
+                .chain(theme.string_keys().map(|key| {
+                    (
+                        key.to_string(),
+                        format!("{:?}", theme.get_string(key).map_or("", String::as_str)),
+                    )
+                }))
+                .collect();
+            keys.sort();
+            keys
+        });
+        let tree = InspectedTree { items, theme };
+        if inspection.published.as_ref() != Some(&tree) {
+            inspection.link.tree.lock().replace(tree.clone());
+            inspection.published = Some(tree);
+            inspection.inspector_proxy.request_redraw();
+        }
+
+        let highlighted = inspection.link.highlighted.get().and_then(|id| {
+            let tree = inspection.published.as_ref()?;
+            let item = tree.items.iter().find(|item| item.id == id)?;
+            Some(outline(item))
+        });
+        let device_bounds = highlighted.as_ref().map(|path| {
+            canvas
+                .local_to_device_as_3x3()
+                .map_rect(path.bounds().with_outset((1.0, 1.0)))
+                .0
+        });
+        self.damage.lock().record_inspector_highlight(device_bounds);
+        if let Some(path) = highlighted {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(Color::from_argb(64, 66, 133, 244));
+            canvas.draw_path(&path, &paint);
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_width(1.0);
+            paint.set_color(Color::from_rgb(66, 133, 244));
+            canvas.draw_path(&path, &paint);
+        }
+    }
+}
+
+/// The editor of one entry of [`PROPERTIES`].
+struct PropertyEditor {
+    text: SharedText,
+    focused: SharedBool,
+    /// The value of the selected item, `None` without a selection.
+    value: Shared<Option<f32>>,
+}
+
+/// The state of an inspector window.
+struct InspectorView {
+    link: InspectorLink,
+    tree: Option<InspectedTree>,
+    rows: Shared<WVec<TreeRow>>,
+    selected: Shared<Option<usize>>,
+    shown_selection: Option<usize>,
+    details: SharedText,
+    theme: SharedText,
+    editors: Vec<PropertyEditor>,
+}
+
+impl InspectorView {
+    fn update(&mut self) {
+        let tree = self.link.tree.lock().take();
+        let changed = tree.is_some();
+        if let Some(tree) = tree {
+            let rows: Vec<TreeRow> = tree
+                .items
+                .iter()
+                .map(|item| TreeRow {
+                    id: item.id,
+                    depth: item.depth,
+                    name: item.name.clone(),
+                })
+                .collect();
+            let rows_changed = self.rows.read(|current| {
+                current.len() != rows.len()
+                    || rows
+                        .iter()
+                        .enumerate()
+                        .any(|(index, row)| current.get(index) != Some(row))
+            });
+            if rows_changed {
+                {
+                    let mut current = self.rows.lock();
+                    current.clear();
+                    for row in rows {
+                        current.push(row);
+                    }
+                }
+                self.rows.notify();
+            }
+            if self.tree.as_ref().map(|current| &current.theme) != Some(&tree.theme) {
+                let mut theme = String::new();
+                for (key, value) in &tree.theme {
+                    let _ = writeln!(theme, "{} = {}", key, value);
+                }
+                self.theme.set(StyledText::from(theme));
+            }
+            self.tree = Some(tree);
+        }
+
+        let selected = self.selected.get();
+        if changed || selected != self.shown_selection {
+            self.shown_selection = selected;
+            self.show_selection();
+        }
+    }
+
+    fn show_selection(&self) {
+        let item = self
+            .shown_selection
+            .and_then(|id| self.tree.as_ref()?.items.iter().find(|item| item.id == id));
+        let Some(item) = item else {
+            self.details.set(StyledText::from("No item selected"));
+            for editor in &self.editors {
+                editor.value.set(None);
+                editor.text.set(StyledText::from(""));
+            }
+            return;
+        };
+
+        let p = &item.display_parameter;
+        let mut details = String::new();
+        let _ = writeln!(details, "{} #{}", item.name, item.id);
+        let _ = writeln!(details, "visible: {}", p.visible);
+        let _ = writeln!(details, "position: {}, {}", p.x(), p.y());
+        let _ = writeln!(details, "size: {} x {}", p.width, p.height);
+        let _ = writeln!(
+            details,
+            "margin: {} {} {} {}",
+            p.margin_start, p.margin_top, p.margin_end, p.margin_bottom
+        );
+        let _ = writeln!(
+            details,
+            "padding: {} {} {} {}",
+            p.padding_start, p.padding_top, p.padding_end, p.padding_bottom
+        );
+        let _ = writeln!(details, "offset: {}, {}", p.offset_x, p.offset_y);
+        let _ = writeln!(details, "opacity: {}", p.opacity);
+        let _ = writeln!(
+            details,
+            "rotation: {} around {}, {}",
+            p.rotation, p.rotation_center_x, p.rotation_center_y
+        );
+        let _ = writeln!(
+            details,
+            "scale: {} x {} around {}, {}",
+            p.scale_x, p.scale_y, p.scale_center_x, p.scale_center_y
+        );
+        let _ = writeln!(
+            details,
+            "skew: {} x {} around {}, {}",
+            p.skew_x, p.skew_y, p.skew_center_x, p.skew_center_y
+        );
+        let mut float_params: Vec<_> = p.float_params.iter().collect();
+        float_params.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in float_params {
+            let _ = writeln!(details, "{}: {}", key, value);
+        }
+        self.details.set(StyledText::from(details));
+
+        for (editor, value) in self.editors.iter().zip(item.properties) {
+            editor.value.set(Some(value));
+            // What is being typed is not overwritten.
+            if !editor.focused.get() {
+                let text = value.to_string();
+                if editor.text.read(|current| current.as_str() != text) {
+                    editor.text.set(StyledText::from(text));
+                }
+            }
+        }
+    }
+}
+
+fn inspector(w: &WindowContext, inspected: WindowId) -> Item {
+    let Some(inspected) = find_window(inspected) else {
+        return w.text("The window was closed").editable(false).item();
+    };
+    let inspected_proxy = inspected.event_loop_proxy().clone();
+    let link = InspectorLink::default();
+    let rows: Shared<WVec<TreeRow>> = Shared::from_static(WVec::new());
+    let selected: Shared<Option<usize>> = Shared::from_static(None);
+    let details: SharedText = Shared::from_static(StyledText::from("No item selected"));
+    let theme: SharedText = Shared::from_static(StyledText::from(""));
+
+    let tree_view = w
+        .list(rows.clone(), {
+            clone!(link, selected, inspected_proxy);
+            move |w, rows, index| {
+                let row = rows.lock().get(index.get()).cloned().unwrap();
+                let id = row.id;
+                let label = if row.name.is_empty() {
+                    format!("#{}", id)
+                } else {
+                    format!("{} #{}", row.name, id)
+                };
+                w.text(label)
+                    .editable(false)
+                    .font_size(14)
+                    .item()
+                    .size(Size::Fill, Size::Auto)
+                    .padding_start(8.0 + row.depth as f32 * 16.0)
+                    .on_click({
+                        clone!(selected);
+                        let event_loop_proxy = w.event_loop_proxy().clone();
+                        move |_| {
+                            selected.set(Some(id));
+                            event_loop_proxy.request_redraw();
+                        }
+                    })
+                    .on_hover({
+                        clone!(link, inspected_proxy);
+                        move |hovered| {
+                            let highlighted = link.highlighted.get();
+                            if hovered && highlighted != Some(id) {
+                                link.highlighted.set(Some(id));
+                            } else if !hovered && highlighted == Some(id) {
+                                link.highlighted.set(None);
+                            } else {
+                                return;
+                            }
+                            inspected_proxy.request_redraw();
+                        }
+                    })
+            }
+        })
+        .item();
+
+    let mut editors = Vec::new();
+    let mut editor_rows: Vec<Item> = Vec::new();
+    for (index, (name, _)) in PROPERTIES.iter().enumerate() {
+        let text: SharedText = Shared::from_static(StyledText::from(""));
+        let value: Shared<Option<f32>> = Shared::from_static(None);
+        text.add_specific_observer(next_id(), {
+            clone!(link, selected, value, inspected_proxy);
+            move |text: &mut StyledText| {
+                let (Some(id), Some(current)) = (selected.get(), value.get()) else {
+                    return;
+                };
+                let Ok(new) = text.as_str().trim().parse::<f32>() else {
+                    return;
+                };
+                if new != current {
+                    link.edits.lock().push(PropertyEdit {
+                        id,
+                        property: index,
+                        value: new,
+                    });
+                    inspected_proxy.request_redraw();
+                }
+            }
+        });
+        let editor = w.text(text.clone()).editable(true).font_size(14).item();
+        editors.push(PropertyEditor {
+            text,
+            focused: editor.data().get_focused().clone(),
+            value,
+        });
+        editor_rows.push(
+            w.row(
+                w.text(name.to_string())
+                    .editable(false)
+                    .font_size(14)
+                    .item()
+                    .size(140, Size::Auto)
+                    + editor.size(Size::Fill, Size::Auto),
+            )
+            .item()
+            .size(Size::Fill, Size::Auto),
+        );
+    }
+
+    let mut view = InspectorView {
+        link: link.clone(),
+        tree: None,
+        rows,
+        selected,
+        shown_selection: None,
+        details: details.clone(),
+        theme: theme.clone(),
+        editors,
+    };
+    // Stops once either window has been closed.
+    let inspector_id = w.window_id();
+    let inspected_id = inspected.window_id();
+    w.add_ui_hook(move || {
+        if find_window(inspector_id).is_none() {
+            return false;
+        }
+        if find_window(inspected_id).is_none() {
+            view.details.set(StyledText::from("The window was closed"));
+            return false;
+        }
+        view.update();
+        true
+    });
+    inspected.attach_inspector(w, link);
+
+    let mut details_column = w.text(details).editable(false).font_size(14).item()
+        + w.text("Properties").editable(false).font_size(16).item();
+    for editor_row in editor_rows {
+        details_column = details_column + editor_row;
+    }
+    details_column = details_column
+        + w.text("Theme").editable(false).font_size(16).item()
+        + w.text(theme).editable(false).font_size(12).item();
+
+    w.row(
+        w.scroll_area(tree_view)
+            .vertical_scrollable(true)
+            .item()
+            .size(280, Size::Fill)
+            + w.scroll_area(w.column(details_column).item().size(Size::Fill, Size::Auto))
+                .vertical_scrollable(true)
+                .item()
+                .size(Size::Auto, Size::Fill)
+                .flex_grow(1),
+    )
+    .item()
+}
@@ -1,4 +1,18 @@
 mod app;
+mod command;
+mod damage;
+mod focus;
+mod hit_test;
+mod inspector;
+mod profiler;
+mod render;
 mod window_context;
 pub use app::*;
+pub use command::*;
+pub(crate) use damage::*;
+pub use focus::*;
+pub(crate) use hit_test::*;
+pub(crate) use inspector::*;
+pub use profiler::*;
+pub use render::*;
 pub use window_context::*;
@@ -0,0 +1,713 @@
+use crate::text::font_collection;
+use crate::ui::app::WindowContext;
+use skia_safe::textlayout::{ParagraphBuilder, ParagraphStyle, TextStyle};
+use skia_safe::{Canvas, Color, Paint, Rect};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::panic::Location;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The number of frames kept for the HUD and the trace.
+const MAX_FRAMES: usize = 600;
+/// The number of trace spans kept, the oldest ones are dropped first.
+const MAX_SPANS: usize = 200_000;
+/// The number of items listed as the slowest of a frame.
+const SLOWEST_ITEMS: usize = 5;
+/// The number of frames drawn in the graph of the HUD.
+const HUD_FRAMES: usize = 120;
+/// The size of the HUD in logical pixels.
+const HUD_WIDTH: f32 = 320.0;
+const HUD_HEIGHT: f32 = 250.0;
+/// The frame budget drawn as a line in the graph of the HUD.
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// The phases of a frame that are timed by the profiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    Measure,
+    Layout,
+    /// Recording the items into a snapshot.
+    Draw,
+    /// Rasterizing and presenting the snapshot on the render thread.
+    Present,
+}
+
+impl FramePhase {
+    fn name(&self) -> &'static str {
+        match self {
+            FramePhase::Measure => "Measure",
+            FramePhase::Layout => "Layout",
+            FramePhase::Draw => "Draw",
+            FramePhase::Present => "Present",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            FramePhase::Measure => Color::from_rgb(0x42, 0xa5, 0xf5),
+            FramePhase::Layout => Color::from_rgb(0x66, 0xbb, 0x6a),
+            FramePhase::Draw => Color::from_rgb(0xff, 0xa7, 0x26),
+            FramePhase::Present => Color::from_rgb(0xab, 0x47, 0xbc),
+        }
+    }
+}
+
+/// What a request asked the window to do again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Layout,
+    Redraw,
+    Repaint,
+}
+
+/// How many times a request was made from one place in the code. Requests of property observers
+/// are counted where the property was set, e.g. the call to `Item::width`.
+#[derive(Clone, Debug)]
+pub struct RequestCount {
+    pub kind: RequestKind,
+    pub location: &'static Location<'static>,
+    pub count: usize,
+}
+
+/// The time an item took to record its drawing.
+#[derive(Clone, Debug)]
+pub struct ItemCost {
+    pub id: usize,
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// The timings of one frame.
+#[derive(Clone, Debug, Default)]
+pub struct FrameProfile {
+    pub number: u64,
+    /// When the frame started, relative to when profiling started.
+    pub start: Duration,
+    pub measure: Duration,
+    pub layout: Duration,
+    pub draw: Duration,
+    pub present: Duration,
+    /// The items that took the longest to record, the slowest first.
+    pub slowest_items: Vec<ItemCost>,
+}
+
+impl FrameProfile {
+    pub fn phase(&self, phase: FramePhase) -> Duration {
+        match phase {
+            FramePhase::Measure => self.measure,
+            FramePhase::Layout => self.layout,
+            FramePhase::Draw => self.draw,
+            FramePhase::Present => self.present,
+        }
+    }
+
+    fn phase_mut(&mut self, phase: FramePhase) -> &mut Duration {
+        match phase {
+            FramePhase::Measure => &mut self.measure,
+            FramePhase::Layout => &mut self.layout,
+            FramePhase::Draw => &mut self.draw,
+            FramePhase::Present => &mut self.present,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.measure + self.layout + self.draw + self.present
+    }
+}
+
+struct TraceSpan {
+    name: String,
+    category: &'static str,
+    /// Spans of the render thread are shown on a track of their own.
+    render_thread: bool,
+    start: Duration,
+    duration: Duration,
+    frame: u64,
+}
+
+/// Collects the timings of the frames of a window.
+pub(crate) struct Profiler {
+    /// Shared with the [`EventLoopProxy`](crate::ui::app::EventLoopProxy) of the window, so
+    /// requests are only counted after checking it, without locking the profiler.
+    enabled: Arc<AtomicBool>,
+    hud_visible: bool,
+    epoch: Instant,
+    current: FrameProfile,
+    /// Whether anything was timed for the current frame yet.
+    current_started: bool,
+    frames: VecDeque<FrameProfile>,
+    spans: VecDeque<TraceSpan>,
+    requests: HashMap<(RequestKind, &'static Location<'static>), usize>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            hud_visible: false,
+            epoch: Instant::now(),
+            current: FrameProfile::default(),
+            current_started: false,
+            frames: VecDeque::new(),
+            spans: VecDeque::new(),
+            requests: HashMap::new(),
+        }
+    }
+}
+
+impl Profiler {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn enabled_flag(&self) -> Arc<AtomicBool> {
+        self.enabled.clone()
+    }
+
+    /// Returns the start of a timing if the profiler is enabled.
+    pub fn start(&self) -> Option<Instant> {
+        self.is_enabled().then(Instant::now)
+    }
+
+    fn push_span(&mut self, span: TraceSpan) {
+        if self.spans.len() >= MAX_SPANS {
+            self.spans.pop_front();
+        }
+        self.spans.push_back(span);
+    }
+
+    fn start_frame(&mut self, start: Instant) {
+        if !self.current_started {
+            self.current_started = true;
+            self.current.start = start.saturating_duration_since(self.epoch);
+        }
+    }
+
+    /// Adds the time since `start` to `phase` of the current frame.
+    pub fn record_phase(&mut self, phase: FramePhase, start: Option<Instant>) {
+        let Some(start) = start else {
+            return;
+        };
+        let duration = start.elapsed();
+        self.start_frame(start);
+        *self.current.phase_mut(phase) += duration;
+        self.push_span(TraceSpan {
+            name: phase.name().to_string(),
+            category: "frame",
+            render_thread: false,
+            start: start.saturating_duration_since(self.epoch),
+            duration,
+            frame: self.current.number,
+        });
+    }
+
+    /// Records the time since `start` it took the item `id` to record its drawing.
+    pub fn record_item(&mut self, id: usize, name: &str, start: Option<Instant>) {
+        let Some(start) = start else {
+            return;
+        };
+        let duration = start.elapsed();
+        let name = if name.is_empty() {
+            format!("#{}", id)
+        } else {
+            name.to_string()
+        };
+        self.push_span(TraceSpan {
+            name: name.clone(),
+            category: "item",
+            render_thread: false,
+            start: start.saturating_duration_since(self.epoch),
+            duration,
+            frame: self.current.number,
+        });
+        let slowest_items = &mut self.current.slowest_items;
+        let index = slowest_items.partition_point(|item| item.duration >= duration);
+        if index < SLOWEST_ITEMS {
+            slowest_items.insert(index, ItemCost { id, name, duration });
+            slowest_items.truncate(SLOWEST_ITEMS);
+        }
+    }
+
+    /// Closes the current frame once it is drawn. It is presented later on the render thread.
+    pub fn finish_frame(&mut self) {
+        if !self.is_enabled() || !self.current_started {
+            return;
+        }
+        let number = self.current.number;
+        let frame = std::mem::replace(
+            &mut self.current,
+            FrameProfile {
+                number: number + 1,
+                ..FrameProfile::default()
+            },
+        );
+        self.current_started = false;
+        if self.frames.len() >= MAX_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Records the presentation of the last frame that was drawn.
+    pub fn record_present(&mut self, started: Instant, finished: Instant) {
+        if !self.is_enabled() {
+            return;
+        }
+        let duration = finished.saturating_duration_since(started);
+        let Some(frame) = self.frames.back_mut() else {
+            return;
+        };
+        frame.present = duration;
+        let number = frame.number;
+        self.push_span(TraceSpan {
+            name: FramePhase::Present.name().to_string(),
+            category: "frame",
+            render_thread: true,
+            start: started.saturating_duration_since(self.epoch),
+            duration,
+            frame: number,
+        });
+    }
+
+    pub fn count_request(&mut self, kind: RequestKind, location: &'static Location<'static>) {
+        if self.is_enabled() {
+            *self.requests.entry((kind, location)).or_default() += 1;
+        }
+    }
+
+    fn request_counts(&self) -> Vec<RequestCount> {
+        let mut counts: Vec<RequestCount> = self
+            .requests
+            .iter()
+            .map(|((kind, location), count)| RequestCount {
+                kind: *kind,
+                location: *location,
+                count: *count,
+            })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count));
+        counts
+    }
+
+    pub fn is_hud_visible(&self) -> bool {
+        self.hud_visible
+    }
+
+    /// The region covered by the HUD, in logical pixels.
+    pub fn hud_bounds(&self) -> Rect {
+        Rect::from_xywh(8.0, 8.0, HUD_WIDTH, HUD_HEIGHT)
+    }
+
+    /// Draws the timings of the last frames. The canvas is in logical pixels.
+    pub fn draw_hud(&self, canvas: &Canvas) {
+        let bounds = self.hud_bounds();
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Color::from_argb(0xd0, 0x20, 0x20, 0x20));
+        canvas.draw_round_rect(bounds, 6.0, 6.0, &paint);
+
+        let frames: Vec<&FrameProfile> = self.frames.iter().rev().take(HUD_FRAMES).collect();
+        let average = |phase: FramePhase| average_millis(&frames, phase);
+        let fps = frame_rate(&frames);
+
+        let mut text = format!(
+            "{:.1} fps\nmeasure {:.2} ms  layout {:.2} ms\ndraw {:.2} ms  present {:.2} ms\n",
+            fps,
+            average(FramePhase::Measure),
+            average(FramePhase::Layout),
+            average(FramePhase::Draw),
+            average(FramePhase::Present),
+        );
+        if let Some(frame) = self.frames.back() {
+            for item in &frame.slowest_items {
+                text.push_str(&format!(
+                    "  {} {:.2} ms\n",
+                    item.name,
+                    item.duration.as_secs_f32() * 1000.0
+                ));
+            }
+        }
+        for request in self.request_counts().iter().take(3) {
+            let file = request.location.file().rsplit(['/', '\\']).next().unwrap_or_default();
+            text.push_str(&format!(
+                "{:?} x{} {}:{}\n",
+                request.kind,
+                request.count,
+                file,
+                request.location.line()
+            ));
+        }
+
+        let mut text_style = TextStyle::new();
+        text_style.set_color(Color::WHITE);
+        text_style.set_font_size(11.0);
+        let mut paragraph_style = ParagraphStyle::new();
+        paragraph_style.set_text_style(&text_style);
+        let mut paragraph_builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+        paragraph_builder.push_style(&text_style);
+        paragraph_builder.add_text(text);
+        let mut paragraph = paragraph_builder.build();
+        paragraph.layout(bounds.width() - 16.0);
+        paragraph.paint(canvas, (bounds.left + 8.0, bounds.top + 8.0));
+
+        // One stacked bar per frame, the newest on the right.
+        let graph = Rect::from_ltrb(
+            bounds.left + 8.0,
+            bounds.bottom - 60.0,
+            bounds.right - 8.0,
+            bounds.bottom - 8.0,
+        );
+        let scale = graph.height() / (FRAME_BUDGET.as_secs_f32() * 2.0);
+        let bar_width = graph.width() / HUD_FRAMES as f32;
+        for (index, frame) in frames.iter().enumerate() {
+            let x = graph.right - (index + 1) as f32 * bar_width;
+            let mut bottom = graph.bottom;
+            for phase in [
+                FramePhase::Measure,
+                FramePhase::Layout,
+                FramePhase::Draw,
+                FramePhase::Present,
+            ] {
+                let height = (frame.phase(phase).as_secs_f32() * scale).min(bottom - graph.top);
+                paint.set_color(phase.color());
+                canvas.draw_rect(
+                    Rect::from_ltrb(x, bottom - height, x + bar_width * 0.8, bottom),
+                    &paint,
+                );
+                bottom -= height;
+            }
+        }
+        let budget = graph.bottom - FRAME_BUDGET.as_secs_f32() * scale;
+        paint.set_color(Color::from_argb(0xa0, 0xff, 0x52, 0x52));
+        canvas.draw_line((graph.left, budget), (graph.right, budget), &paint);
+    }
+
+    /// Writes the recorded spans in the Chrome trace event format.
+    fn write_chrome_trace(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let micros = |duration: Duration| duration.as_secs_f64() * 1_000_000.0;
+        let mut events = vec![
+            serde_json::json!({
+                "name": "thread_name", "ph": "M", "pid": 1, "tid": 1,
+                "args": { "name": "UI" },
+            }),
+            serde_json::json!({
+                "name": "thread_name", "ph": "M", "pid": 1, "tid": 2,
+                "args": { "name": "Render" },
+            }),
+        ];
+        events.extend(self.spans.iter().map(|span| {
+            serde_json::json!({
+                "name": span.name,
+                "cat": span.category,
+                "ph": "X",
+                "ts": micros(span.start),
+                "dur": micros(span.duration),
+                "pid": 1,
+                "tid": if span.render_thread { 2 } else { 1 },
+                "args": { "frame": span.frame },
+            })
+        }));
+        let requests: Vec<serde_json::Value> = self
+            .request_counts()
+            .iter()
+            .map(|request| {
+                serde_json::json!({
+                    "kind": format!("{:?}", request.kind),
+                    "location": request.location.to_string(),
+                    "count": request.count,
+                })
+            })
+            .collect();
+        let trace = serde_json::json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "metadata": { "requests": requests },
+        });
+        serde_json::to_writer(writer, &trace)?;
+        Ok(())
+    }
+}
+
+/// The average time of `phase` over the frames, in milliseconds.
+fn average_millis(frames: &[&FrameProfile], phase: FramePhase) -> f32 {
+    if frames.is_empty() {
+        return 0.0;
+    }
+    frames
+        .iter()
+        .map(|frame| frame.phase(phase).as_secs_f32())
+        .sum::<f32>()
+        / frames.len() as f32
+        * 1000.0
+}
+
+/// The frames per second between the oldest and the newest of the frames, in any order.
+fn frame_rate(frames: &[&FrameProfile]) -> f32 {
+    let first = frames.iter().map(|frame| frame.start).min();
+    let last = frames.iter().map(|frame| frame.start).max();
+    match (first, last) {
+        (Some(first), Some(last)) if last > first => {
+            (frames.len() - 1) as f32 / (last - first).as_secs_f32()
+        }
+        _ => 0.0,
+    }
+}
+
+impl WindowContext {
+    /// Starts or stops collecting frame timings and request counts.
+    pub fn set_profiling(&self, enabled: bool) {
+        self.profiler.lock().set_enabled(enabled);
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.event_loop_proxy.is_profiling()
+    }
+
+    /// Shows the timings of the last frames over the window. Showing the HUD starts profiling.
+    /// It can be toggled with the `profiler.toggle_hud` command.
+    pub fn set_profiler_hud_visible(&self, visible: bool) {
+        {
+            let mut profiler = self.profiler.lock();
+            profiler.hud_visible = visible;
+            if visible {
+                profiler.set_enabled(true);
+            }
+        }
+        self.request_redraw();
+    }
+
+    pub fn is_profiler_hud_visible(&self) -> bool {
+        self.profiler.lock().hud_visible
+    }
+
+    /// The timings of the last frames, the oldest first.
+    pub fn frame_profiles(&self) -> Vec<FrameProfile> {
+        self.profiler.lock().frames.iter().cloned().collect()
+    }
+
+    /// Where layouts, redraws and repaints were requested from, the most frequent first.
+    pub fn request_counts(&self) -> Vec<RequestCount> {
+        self.profiler.lock().request_counts()
+    }
+
+    /// Forgets the recorded frames and requests.
+    pub fn clear_profile(&self) {
+        let mut profiler = self.profiler.lock();
+        profiler.frames.clear();
+        profiler.spans.clear();
+        profiler.requests.clear();
+    }
+
+    /// Writes the recorded frames to `path` as a Chrome trace, which can be opened in
+    /// `chrome://tracing` or Perfetto.
+    pub fn export_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.profiler.lock().write_chrome_trace(&mut writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod profiler_test {
+    use super::{
+        average_millis, frame_rate, FramePhase, FrameProfile, Profiler, RequestKind, MAX_FRAMES,
+        SLOWEST_ITEMS,
+    };
+    use std::panic::Location;
+    use std::time::{Duration, Instant};
+
+    fn enabled() -> Profiler {
+        let profiler = Profiler::default();
+        profiler.set_enabled(true);
+        profiler
+    }
+
+    fn micros(duration: Duration) -> f64 {
+        duration.as_secs_f64() * 1_000_000.0
+    }
+
+    fn ago(millis: u64) -> Option<Instant> {
+        Some(Instant::now() - Duration::from_millis(millis))
+    }
+
+    fn frame(start: u64, draw: u64) -> FrameProfile {
+        FrameProfile {
+            start: Duration::from_millis(start),
+            draw: Duration::from_millis(draw),
+            ..FrameProfile::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut profiler = Profiler::default();
+        assert_eq!(profiler.start(), None);
+        profiler.record_phase(FramePhase::Draw, profiler.start());
+        profiler.count_request(RequestKind::Redraw, Location::caller());
+        profiler.finish_frame();
+        assert!(profiler.frames.is_empty());
+        assert!(profiler.spans.is_empty());
+        assert!(profiler.request_counts().is_empty());
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut profiler = enabled();
+        profiler.record_phase(FramePhase::Measure, ago(2));
+        profiler.record_phase(FramePhase::Layout, ago(3));
+        profiler.record_phase(FramePhase::Measure, ago(2));
+        profiler.finish_frame();
+        // Nothing was timed, so there is no frame to close.
+        profiler.finish_frame();
+        profiler.record_phase(FramePhase::Draw, ago(1));
+        profiler.finish_frame();
+
+        assert_eq!(profiler.frames.len(), 2);
+        let first = &profiler.frames[0];
+        assert_eq!(first.number, 0);
+        assert!(first.measure >= Duration::from_millis(4));
+        assert!(first.layout >= Duration::from_millis(3));
+        assert_eq!(first.draw, Duration::ZERO);
+        assert_eq!(first.total(), first.measure + first.layout);
+        let second = &profiler.frames[1];
+        assert_eq!(second.number, 1);
+        assert!(second.draw >= Duration::from_millis(1));
+        assert!(second.start >= first.start);
+    }
+
+    #[test]
+    fn test_present() {
+        let mut profiler = enabled();
+        let started = Instant::now();
+        // Nothing has been drawn yet.
+        profiler.record_present(started, started + Duration::from_millis(5));
+        assert!(profiler.spans.is_empty());
+        profiler.record_phase(FramePhase::Draw, profiler.start());
+        profiler.finish_frame();
+        profiler.record_present(started, started + Duration::from_millis(5));
+        assert_eq!(profiler.frames[0].present, Duration::from_millis(5));
+        let span = profiler.spans.back().unwrap();
+        assert!(span.render_thread);
+        assert_eq!(span.frame, 0);
+    }
+
+    #[test]
+    fn test_max_frames() {
+        let mut profiler = enabled();
+        for _ in 0..MAX_FRAMES + 10 {
+            profiler.record_phase(FramePhase::Draw, profiler.start());
+            profiler.finish_frame();
+        }
+        assert_eq!(profiler.frames.len(), MAX_FRAMES);
+        assert_eq!(profiler.frames[0].number, 10);
+    }
+
+    #[test]
+    fn test_slowest_items() {
+        let mut profiler = enabled();
+        for (id, millis) in [(1, 3), (2, 9), (3, 1), (4, 7), (5, 5), (6, 2), (7, 8)] {
+            profiler.record_item(id, if id == 2 { "" } else { "item" }, ago(millis));
+        }
+        let ids: Vec<usize> = profiler
+            .current
+            .slowest_items
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        assert_eq!(ids.len(), SLOWEST_ITEMS);
+        assert_eq!(ids, vec![2, 7, 4, 5, 1]);
+        assert_eq!(profiler.current.slowest_items[0].name, "#2");
+        profiler.finish_frame();
+        // Items alone don't start a frame.
+        assert!(profiler.frames.is_empty());
+        profiler.record_phase(FramePhase::Draw, profiler.start());
+        profiler.finish_frame();
+        assert_eq!(profiler.frames[0].slowest_items.len(), SLOWEST_ITEMS);
+        assert!(profiler.current.slowest_items.is_empty());
+    }
+
+    #[test]
+    fn test_request_counts() {
+        let mut profiler = enabled();
+        let layout = Location::caller();
+        let redraw = Location::caller();
+        for _ in 0..3 {
+            profiler.count_request(RequestKind::Redraw, redraw);
+        }
+        profiler.count_request(RequestKind::Layout, layout);
+        let counts = profiler.request_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].kind, RequestKind::Redraw);
+        assert_eq!(counts[0].count, 3);
+        assert_eq!(counts[1].kind, RequestKind::Layout);
+        assert_eq!(counts[1].count, 1);
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let frames = [frame(0, 2), frame(20, 4), frame(40, 6)];
+        let newest_first: Vec<&FrameProfile> = frames.iter().rev().collect();
+        assert!((frame_rate(&newest_first) - 50.0).abs() < 1e-3);
+        assert!((average_millis(&newest_first, FramePhase::Draw) - 4.0).abs() < 1e-3);
+        assert_eq!(average_millis(&newest_first, FramePhase::Layout), 0.0);
+        assert_eq!(frame_rate(&newest_first[..1]), 0.0);
+        assert_eq!(frame_rate(&[]), 0.0);
+        assert_eq!(average_millis(&[], FramePhase::Draw), 0.0);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let mut profiler = enabled();
+        let start = Instant::now() - Duration::from_millis(2);
+        profiler.record_phase(FramePhase::Layout, Some(start));
+        profiler.record_item(7, "button", profiler.start());
+        profiler.finish_frame();
+        let started = Instant::now();
+        profiler.record_present(started, started + Duration::from_micros(1500));
+        profiler.count_request(RequestKind::Repaint, Location::caller());
+
+        let mut buffer = Vec::new();
+        profiler.write_chrome_trace(&mut buffer).unwrap();
+        let trace: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(trace["displayTimeUnit"], "ms");
+        let events = trace["traceEvents"].as_array().unwrap();
+        // The names of the two threads and the three spans.
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0]["ph"], "M");
+        assert_eq!(events[1]["args"]["name"], "Render");
+
+        let layout = &events[2];
+        assert_eq!(layout["name"], "Layout");
+        assert_eq!(layout["cat"], "frame");
+        assert_eq!(layout["ph"], "X");
+        assert_eq!(layout["tid"], 1);
+        assert_eq!(layout["args"]["frame"], 0);
+        assert!(layout["dur"].as_f64().unwrap() >= 2000.0);
+        let expected = micros(start.saturating_duration_since(profiler.epoch));
+        assert!((layout["ts"].as_f64().unwrap() - expected).abs() < 1.0);
+
+        assert_eq!(events[3]["name"], "button");
+        assert_eq!(events[3]["cat"], "item");
+        let present = &events[4];
+        assert_eq!(present["name"], "Present");
+        assert_eq!(present["tid"], 2);
+        assert!((present["dur"].as_f64().unwrap() - 1500.0).abs() < 1e-6);
+
+        let requests = trace["metadata"]["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["kind"], "Repaint");
+        assert_eq!(requests[0]["count"], 1);
+        assert!(requests[0]["location"]
+            .as_str()
+            .unwrap()
+            .contains("profiler.rs"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::core::log_target;
+use crate::ui::app::{Event, EventType};
+use parking_lot::{Condvar, Mutex};
+use skia_safe::region::RegionOp;
+use skia_safe::{Color, IRect, Picture, Region};
+use skiwin::SkiaWindow;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+use winit::event_loop::EventLoopProxy;
+use winit::window::Window;
+
+/// Creates the Skia window a window is rendered with. It is called on the render thread.
+pub type CreateSkiaWindow = Box<dyn FnOnce(Arc<Window>) -> Box<dyn SkiaWindow> + Send>;
+
+/// An immutable recording of the laid out window, produced by the UI thread.
+pub(crate) struct RenderSnapshot {
+    /// The whole window in physical pixels.
+    pub picture: Picture,
+    pub background_color: Color,
+    /// The regions that changed since the last snapshot, in physical pixels.
+    pub damage: Vec<IRect>,
+}
+
+#[derive(Default)]
+struct Mailbox {
+    snapshot: Option<RenderSnapshot>,
+    resized: bool,
+    exit: bool,
+}
+
+/// Rasterizes and presents the snapshots of one window on a thread of its own, so the UI thread
+/// never waits for the GPU or the compositor.
+pub(crate) struct RenderThread {
+    mailbox: Arc<(Mutex<Mailbox>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RenderThread {
+    pub fn spawn(
+        window: Arc<Window>,
+        create_skia_window: CreateSkiaWindow,
+        event_loop_proxy: EventLoopProxy<Event>,
+    ) -> Self {
+        let mailbox = Arc::new((Mutex::new(Mailbox::default()), Condvar::new()));
+        let handle = std::thread::Builder::new()
+            .name(format!("render-{:?}", window.id()))
+            .spawn({
+                let mailbox = mailbox.clone();
+                move || render_loop(window, create_skia_window, &mailbox, event_loop_proxy)
+            })
+            .unwrap();
+        Self {
+            mailbox,
+            handle: Some(handle),
+        }
+    }
+
+    /// Hands a snapshot to the render thread. A snapshot that wasn't rasterized yet is replaced,
+    /// its damage is kept.
+    pub fn submit(&self, snapshot: RenderSnapshot) {
+        let (mailbox, condvar) = &*self.mailbox;
+        let mut mailbox = mailbox.lock();
+        let snapshot = match mailbox.snapshot.take() {
+            Some(previous) => {
+                let mut damage = previous.damage;
+                damage.extend(snapshot.damage);
+                RenderSnapshot { damage, ..snapshot }
+            }
+            None => snapshot,
+        };
+        mailbox.snapshot = Some(snapshot);
+        condvar.notify_one();
+    }
+
+    /// Resizes the surface before the next snapshot is rasterized.
+    pub fn resize(&self) {
+        self.mailbox.0.lock().resized = true;
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        {
+            let (mailbox, condvar) = &*self.mailbox;
+            mailbox.lock().exit = true;
+            condvar.notify_one();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn render_loop(
+    window: Arc<Window>,
+    create_skia_window: CreateSkiaWindow,
+    mailbox: &(Mutex<Mailbox>, Condvar),
+    event_loop_proxy: EventLoopProxy<Event>,
+) {
+    let window_id = window.id();
+    // The GPU context lives and dies on this thread.
+    let mut skia_window = create_skia_window(window.clone());
+    let mut needs_resize = true;
+    loop {
+        let snapshot = {
+            let (mailbox, condvar) = mailbox;
+            let mut mailbox = mailbox.lock();
+            while mailbox.snapshot.is_none() && !mailbox.exit {
+                condvar.wait(&mut mailbox);
+            }
+            if mailbox.exit {
+                return;
+            }
+            needs_resize |= std::mem::take(&mut mailbox.resized);
+            mailbox.snapshot.take().unwrap()
+        };
+
+        let started = Instant::now();
+        let _span =
+            tracing::debug_span!(target: log_target::RENDER, "present", ?window_id).entered();
+        let size = window.inner_size();
+        // A minimized window has no surface to draw on.
+        if size.width > 0 && size.height > 0 {
+            let bounds = IRect::from_wh(size.width as i32, size.height as i32);
+            let damage = if needs_resize {
+                needs_resize = false;
+                if let Err(error) = skia_window.resize() {
+                    tracing::warn!(
+                        target: log_target::RENDER,
+                        %error,
+                        "Failed to resize the surface"
+                    );
+                }
+                // The new surface doesn't hold the previous frame.
+                vec![bounds]
+            } else {
+                snapshot.damage
+            };
+
+            let surface = skia_window.surface();
+            {
+                let mut surface = surface.lock();
+                let canvas = surface.canvas();
+                let mut region = Region::new();
+                for rect in &damage {
+                    region.op_rect(rect, RegionOp::Union);
+                }
+                canvas.save();
+                canvas.clip_region(&region, None);
+                canvas.clear(snapshot.background_color);
+                canvas.draw_picture(&snapshot.picture, None, None);
+                canvas.restore();
+            }
+            window.pre_present_notify();
+            skia_window.present_damage(&damage);
+        }
+
+        let _ = event_loop_proxy.send_event(Event {
+            window_id,
+            event: EventType::FramePresented {
+                started,
+                finished: Instant::now(),
+            },
+        });
+    }
+}
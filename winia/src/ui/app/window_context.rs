@@ -1,15 +1,20 @@
 use crate::shared::{Gettable, LocalShared, Settable, Shared, SharedAnimationTrait, SharedBool};
-use crate::ui::app::WindowAttr;
+use crate::ui::app::{
+    CommandRegistry, DamageTracker, FocusState, Inspection, Profiler, RequestKind, WindowAttr,
+};
+use crate::ui::item::{ItemData, Pointer};
 use crate::ui::theme::material_theme;
 use crate::ui::{Item, LayoutAnimation, Theme};
-use parking_lot::MutexGuard;
 use proc_macro::AsRef;
 use skia_safe::Color;
-use skiwin::SkiaWindow;
-use std::collections::{BTreeSet, LinkedList};
+use std::collections::{BTreeSet, HashMap, LinkedList};
+use std::ops::DerefMut;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use winit::event_loop::EventLoopProxy as WinitEventLoopProxy;
-use winit::window::{Window, WindowId};
+use winit::window::{CursorIcon, Window, WindowId};
 use crate::ui::animation::{Animation, LocalLayoutAnimation};
 
 #[derive(Clone, Debug)]
@@ -54,6 +59,14 @@ pub enum EventType {
     RequestFocus,
     RequestLayout,
     RequestRedraw,
+    RequestRepaint(usize),
+    /// The cursor icon of the hovered item has changed.
+    UpdateCursorIcon,
+    /// The render thread presented the last snapshot it was given.
+    FramePresented {
+        started: Instant,
+        finished: Instant,
+    },
     StartSharedAnimation(Box<dyn SharedAnimationTrait + Send>),
     StartLayoutAnimation(LayoutAnimation),
     Timer(usize),
@@ -70,13 +83,33 @@ pub enum EventType {
 pub struct EventLoopProxy {
     window_id: WindowId,
     event_loop_proxy: WinitEventLoopProxy<Event>,
+    profiler: Shared<Profiler>,
+    profiling: Arc<AtomicBool>,
 }
 
 impl EventLoopProxy {
-    pub fn new(window_id: WindowId, event_loop_proxy: WinitEventLoopProxy<Event>) -> Self {
+    pub(crate) fn new(
+        window_id: WindowId,
+        event_loop_proxy: WinitEventLoopProxy<Event>,
+        profiler: Shared<Profiler>,
+    ) -> Self {
+        let profiling = profiler.lock().enabled_flag();
         Self {
             window_id,
             event_loop_proxy,
+            profiler,
+            profiling,
+        }
+    }
+
+    pub(crate) fn is_profiling(&self) -> bool {
+        self.profiling.load(Ordering::Relaxed)
+    }
+
+    /// Counts a request made at `location` if the window is profiled.
+    pub(crate) fn count_request(&self, kind: RequestKind, location: &'static Location<'static>) {
+        if self.is_profiling() {
+            self.profiler.lock().count_request(kind, location);
         }
     }
 
@@ -89,20 +122,50 @@ impl EventLoopProxy {
         }
     }
 
+    #[track_caller]
     pub fn request_redraw(&self) {
+        self.request_redraw_from(Location::caller());
+    }
+
+    /// Like [`request_redraw`](EventLoopProxy::request_redraw), counted at `location` by the
+    /// profiler. Observers can't be `#[track_caller]`, so they request from where they were added.
+    pub fn request_redraw_from(&self, location: &'static Location<'static>) {
+        self.count_request(RequestKind::Redraw, location);
         self.send_event(Event {
             window_id: self.window_id,
             event: EventType::RequestRedraw,
         });
     }
 
+    #[track_caller]
     pub fn request_layout(&self) {
+        self.request_layout_from(Location::caller());
+    }
+
+    /// See [`request_redraw_from`](EventLoopProxy::request_redraw_from).
+    pub fn request_layout_from(&self, location: &'static Location<'static>) {
+        self.count_request(RequestKind::Layout, location);
         self.send_event(Event {
             window_id: self.window_id,
             event: EventType::RequestLayout,
         });
     }
 
+    /// See [`WindowContext::request_repaint`].
+    #[track_caller]
+    pub fn request_repaint(&self, id: usize) {
+        self.request_repaint_from(id, Location::caller());
+    }
+
+    /// See [`request_redraw_from`](EventLoopProxy::request_redraw_from).
+    pub fn request_repaint_from(&self, id: usize, location: &'static Location<'static>) {
+        self.count_request(RequestKind::Repaint, location);
+        self.send_event(Event {
+            window_id: self.window_id,
+            event: EventType::RequestRepaint(id),
+        });
+    }
+
     pub fn set_window_attribute(&self, f: impl FnOnce(Option<&Window>) + Send + 'static) {
         self.send_event(Event {
             window_id: self.window_id,
@@ -130,6 +193,13 @@ impl EventLoopProxy {
             event: EventType::RequestFocus,
         });
     }
+
+    pub(crate) fn request_cursor_icon_update(&self) {
+        self.send_event(Event {
+            window_id: self.window_id,
+            event: EventType::UpdateCursorIcon,
+        });
+    }
     
     pub fn new_window(&self, item_generator: impl FnOnce(&WindowContext) -> Item + Send + 'static, window_attr: WindowAttr) {
         self.send_event(Event {
@@ -159,7 +229,7 @@ impl EventLoopProxy {
 #[derive(Clone)]
 pub struct WindowContext {
     pub(crate) theme: Shared<Theme>,
-    pub(crate) window: LocalShared<Box<dyn SkiaWindow>>,
+    pub(crate) window: Arc<Window>,
     pub(crate) window_attr: Shared<WindowAttr>,
     pub(crate) event_loop_proxy: EventLoopProxy,
     pub(crate) request_layout: Shared<bool>,
@@ -167,11 +237,24 @@ pub struct WindowContext {
     pub(crate) layout_animations: LocalShared<Vec<Box<dyn Animation>>>,
     pub(crate) starting_local_animations: LocalShared<LinkedList<LocalLayoutAnimation>>,
     pub(crate) shared_animations: LocalShared<Vec<Box<dyn SharedAnimationTrait + Send>>>,
+    pub(crate) ui_hooks: LocalShared<Vec<Box<dyn FnMut() -> bool>>>,
+    pub(crate) commands: LocalShared<CommandRegistry>,
+    pub(crate) damage: LocalShared<DamageTracker>,
+    pub(crate) profiler: Shared<Profiler>,
     /// ((last focused item, id), (new focused item, id))
     pub(crate) item_focused: Shared<(Option<(SharedBool, usize)>, Option<(SharedBool, usize)>)>,
+    pub(crate) focus: LocalShared<FocusState>,
+    pub(crate) inspection: LocalShared<Option<Inspection>>,
     ime_allowed: Shared<BTreeSet<usize>>,
     pub(crate) timers: Shared<Vec<Timer>>,
     pub(crate) cursor_position: Shared<(f32, f32)>,
+    /// The cursor icon of the innermost hovered item, collected while the cursor move is dispatched.
+    pub(crate) hovered_cursor_icon: Shared<Option<CursorIcon>>,
+    /// The id of the item whose cursor icon is in `hovered_cursor_icon`.
+    pub(crate) hovered_cursor_item: Shared<Option<usize>>,
+    cursor_icon: Shared<CursorIcon>,
+    /// The item that has captured each pointer.
+    pointer_captures: Shared<HashMap<Pointer, usize>>,
     pub(crate) title: Shared<String>,
     pub(crate) min_width: Shared<f32>,
     pub(crate) min_height: Shared<f32>,
@@ -180,24 +263,35 @@ pub struct WindowContext {
 }
 
 impl WindowContext {
-    pub(crate) fn new(window: Box<dyn SkiaWindow + 'static>, window_attr: &Shared<WindowAttr>, event_loop_proxy: winit::event_loop::EventLoopProxy<Event>) -> Self {
+    pub(crate) fn new(window: Arc<Window>, window_attr: &Shared<WindowAttr>, event_loop_proxy: winit::event_loop::EventLoopProxy<Event>) -> Self {
         let window_id = window.id();
+        let profiler: Shared<Profiler> = Profiler::default().into();
         Self {
             theme: material_theme(Color::from_rgb(255, 0, 0), dark_light::detect().map_or(false,|mode|{
                 mode != dark_light::Mode::Dark
             })).into(),
-            window: LocalShared::from_static(window),
+            window,
             window_attr: window_attr.clone(),
-            event_loop_proxy: EventLoopProxy::new(window_id, event_loop_proxy),
+            event_loop_proxy: EventLoopProxy::new(window_id, event_loop_proxy, profiler.clone()),
             request_layout: false.into(),
             request_redraw: false.into(),
             layout_animations: Vec::new().into(),
             starting_local_animations: LinkedList::new().into(),
             shared_animations: Vec::new().into(),
+            ui_hooks: Vec::new().into(),
+            commands: CommandRegistry::default().into(),
+            damage: DamageTracker::default().into(),
+            profiler,
             item_focused: (None, None).into(),
+            focus: FocusState::default().into(),
+            inspection: None.into(),
             ime_allowed: BTreeSet::new().into(),
             timers: Vec::new().into(),
             cursor_position: (0.0, 0.0).into(),
+            hovered_cursor_icon: None.into(),
+            hovered_cursor_item: None.into(),
+            cursor_icon: CursorIcon::Default.into(),
+            pointer_captures: HashMap::new().into(),
             title: "Title".to_string().into(),
             min_width: 0.0.into(),
             min_height: 0.0.into(),
@@ -206,8 +300,8 @@ impl WindowContext {
         }
     }
 
-    pub(crate) fn window(&self) -> MutexGuard<'_, Box<dyn SkiaWindow>> {
-        self.window.lock()
+    pub(crate) fn window(&self) -> &Window {
+        &self.window
     }
     
     pub fn window_attr(&self) -> &Shared<WindowAttr> {
@@ -241,10 +335,77 @@ impl WindowContext {
         }
     }
 
+    /// The id of the focused item.
+    pub fn get_focused_item_id(&self) -> Option<usize> {
+        self.item_focused.read(|(last, _new)| {
+            last.as_ref()
+                .filter(|(focused, _)| focused.get())
+                .map(|(_, id)| *id)
+        })
+    }
+
     pub fn get_cursor_position(&self) -> (f32, f32) {
         *self.cursor_position.lock()
     }
 
+    /// The cursor icon shown in the window.
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon.get()
+    }
+
+    /// Forgets the cursor icon of the hovered item, before the cursor move is dispatched again.
+    pub(crate) fn clear_hovered_cursor_icon(&self) {
+        self.hovered_cursor_icon.set(None);
+        self.hovered_cursor_item.set(None);
+    }
+
+    /// Shows the cursor icon of the item capturing the mouse, or of the innermost hovered item.
+    pub(crate) fn update_cursor_icon(&self, item: &mut ItemData) {
+        let captured = self
+            .pointer_captures
+            .read(|captures| {
+                captures
+                    .iter()
+                    .find(|(pointer, _)| matches!(pointer, Pointer::Mouse { .. }))
+                    .map(|(_, id)| *id)
+            });
+        let mut cursor_icon = None;
+        if let Some(id) = captured {
+            item.find_item(id, &mut |item| {
+                cursor_icon = item.get_cursor_icon().map(|cursor_icon| cursor_icon.get());
+            });
+        }
+        let cursor_icon = cursor_icon
+            .or_else(|| self.hovered_cursor_icon.get())
+            .unwrap_or_default();
+        if self.cursor_icon.get() != cursor_icon {
+            self.cursor_icon.set(cursor_icon);
+            self.window().set_cursor(cursor_icon);
+        }
+    }
+
+    /// See [`ItemData::capture_pointer`].
+    pub fn capture_pointer(&self, id: usize, pointer: Pointer) {
+        self.pointer_captures.lock().insert(pointer, id);
+    }
+
+    pub fn release_pointer(&self, pointer: &Pointer) {
+        self.pointer_captures.lock().remove(pointer);
+    }
+
+    /// The id of the item that has captured `pointer`.
+    pub fn get_pointer_capture(&self, pointer: &Pointer) -> Option<usize> {
+        self.pointer_captures.lock().get(pointer).copied()
+    }
+
+    /// The name given with [`WindowAttr::name`], or the title of the window if it has no name.
+    pub fn name(&self) -> String {
+        let name = self
+            .window_attr
+            .read(|window_attr| window_attr.get_name().map(|name| name.to_string()));
+        name.unwrap_or_else(|| self.title.get())
+    }
+
     pub fn title(&self) -> &Shared<String> {
         &self.title
     }
@@ -274,6 +435,21 @@ impl WindowContext {
         self.timers.write(|timers| timers.push(timer.clone()));
     }
 
+    /// Registers a closure that runs on the UI thread before each window event is handled.
+    /// The hook is dropped as soon as it returns `false`.
+    pub fn add_ui_hook(&self, hook: impl FnMut() -> bool + 'static) {
+        self.ui_hooks.lock().push(Box::new(hook));
+    }
+
+    pub(crate) fn run_ui_hooks(&self) {
+        // Hooks may register new hooks, so they must not run while the list is locked.
+        let mut hooks = std::mem::take(self.ui_hooks.lock().deref_mut());
+        hooks.retain_mut(|hook| hook());
+        let mut ui_hooks = self.ui_hooks.lock();
+        hooks.append(ui_hooks.deref_mut());
+        *ui_hooks = hooks;
+    }
+
     // pub fn send_event(&self, event: Event) {
     //     self.event_loop_proxy.send_event(event);
     // }
@@ -293,7 +469,23 @@ impl WindowContext {
         // 1.0
     }
 
+    /// Redraws the whole window, recording every item again.
+    /// Prefer [`request_repaint`](WindowContext::request_repaint) when only one item changed.
+    #[track_caller]
     pub fn request_redraw(&self) {
+        self.event_loop_proxy
+            .count_request(RequestKind::Redraw, Location::caller());
+        self.redraw_all();
+    }
+
+    /// Like [`request_redraw`](WindowContext::request_redraw), without counting the request.
+    pub(crate) fn redraw_all(&self) {
+        self.damage.lock().invalidate_all();
+        self.schedule_redraw();
+    }
+
+    /// Redraws the window, only the items that were invalidated are recorded again.
+    pub(crate) fn schedule_redraw(&self) {
         if self.request_redraw.get() {
             return;
         }
@@ -301,12 +493,20 @@ impl WindowContext {
         self.window().request_redraw();
     }
 
+    #[track_caller]
     pub fn request_layout(&self) {
+        self.event_loop_proxy
+            .count_request(RequestKind::Layout, Location::caller());
+        self.schedule_layout();
+    }
+
+    /// Like [`request_layout`](WindowContext::request_layout), without counting the request.
+    pub(crate) fn schedule_layout(&self) {
         if self.request_layout.get() {
             return;
         }
         self.request_layout.set(true);
-        self.request_redraw();
+        self.schedule_redraw();
     }
 
     // pub fn request_focus(&self, id: usize, focused: bool) {
@@ -13,6 +13,7 @@ use proc_macro::item;
 use skia_safe::Color;
 use std::fmt::Display;
 use std::time::Duration;
+use winit::window::CursorIcon;
 use strum_macros::EnumString;
 
 #[derive(Clone, EnumString, Debug)]
@@ -120,8 +121,6 @@ impl Button {
 
         let layer_state_color = Shared::from(Color::TRANSPARENT);
 
-        let focus_indicator_color = Shared::from(Color::TRANSPARENT);
-
         let property_ = property.lock();
         let icon = property_.icon.clone();
         let label = property_.label.clone();
@@ -176,14 +175,6 @@ impl Button {
                             .item()
                     ),
             )
-            .foreground(
-                w.rectangle(Color::TRANSPARENT)
-                    .outline_width(3)
-                    .outline_offset(5)
-                    .outline_color(&focus_indicator_color)
-                    .radius(f32::MAX)
-                    .item(),
-            )
             .align_content(Alignment::CenterStart)
             .width(Size::Auto)
             .height(&container_height)
@@ -294,30 +285,12 @@ impl Button {
                 }
             );
             state.notify()
-            // focus_indicator_color.set_dynamic(
-            //     [state.to_observable(), theme.to_observable()].into(),
-            //     move || {
-            //         if state.get() == ItemState::Focused {
-            //             theme.lock().get_color(color::SECONDARY).cloned().unwrap()
-            //         } else {
-            //             Color::TRANSPARENT
-            //         }
-            //     },
-            // );
         }
 
-        item.data().set_focus_next(|item| {
-            if !item.get_enabled().get() {
-                return true;
-            }
-            let focused = item.get_focused();
-            if !focused.get() {
-                focused.set(true);
-                false
-            } else {
-                true
-            }
-        });
+        item.data().set_cursor_icon(CursorIcon::Pointer);
+
+        item.data().set_focusable(true);
+        item.data().set_focus_ring_radius(f32::MAX);
 
         Self { item, property }
     }
@@ -0,0 +1,510 @@
+use crate::shared::{Children, Gettable, LocalShared, Shared, SharedF32};
+use crate::ui::app::{EventLoopProxy, WindowContext};
+use crate::ui::item::{Brush, DisplayParameter};
+use crate::ui::Item;
+use proc_macro::item;
+use skia_safe::paint::{Cap, Join, Style};
+use skia_safe::{Canvas, Color, Paint, Path, PathEffect, RRect, Rect};
+
+#[derive(Clone)]
+enum Geometry {
+    Rect,
+    RoundedRect {
+        radius: SharedF32,
+    },
+    Oval,
+    /// The angles are in degrees, 0.0 points right and positive angles turn clockwise.
+    Arc {
+        start_angle: SharedF32,
+        sweep_angle: SharedF32,
+        use_center: bool,
+    },
+    Path(Shared<Path>),
+}
+
+/// A vector shape drawn by a [`CanvasView`], in the coordinates of the canvas content.
+///
+/// The numbers and brushes are animated by [`LayoutAnimation`](crate::ui::LayoutAnimation)
+/// like the other properties of an item, paths and dash intervals change at once. The brushes
+/// fit the bounds of the shape.
+#[derive(Clone)]
+pub struct Shape {
+    geometry: Geometry,
+    x: SharedF32,
+    y: SharedF32,
+    width: SharedF32,
+    height: SharedF32,
+    fill: Shared<Brush>,
+    stroke: Shared<Brush>,
+    stroke_width: SharedF32,
+    stroke_cap: Cap,
+    stroke_join: Join,
+    dash_intervals: Shared<Vec<f32>>,
+    dash_phase: SharedF32,
+}
+
+impl Shape {
+    fn new(
+        geometry: Geometry,
+        x: impl Into<SharedF32>,
+        y: impl Into<SharedF32>,
+        width: impl Into<SharedF32>,
+        height: impl Into<SharedF32>,
+    ) -> Self {
+        Self {
+            geometry,
+            x: x.into(),
+            y: y.into(),
+            width: width.into(),
+            height: height.into(),
+            fill: Color::TRANSPARENT.into(),
+            stroke: Color::TRANSPARENT.into(),
+            stroke_width: 0.0.into(),
+            stroke_cap: Cap::Butt,
+            stroke_join: Join::Miter,
+            dash_intervals: Vec::new().into(),
+            dash_phase: 0.0.into(),
+        }
+    }
+
+    pub fn rect(
+        x: impl Into<SharedF32>,
+        y: impl Into<SharedF32>,
+        width: impl Into<SharedF32>,
+        height: impl Into<SharedF32>,
+    ) -> Self {
+        Self::new(Geometry::Rect, x, y, width, height)
+    }
+
+    pub fn rounded_rect(
+        x: impl Into<SharedF32>,
+        y: impl Into<SharedF32>,
+        width: impl Into<SharedF32>,
+        height: impl Into<SharedF32>,
+        radius: impl Into<SharedF32>,
+    ) -> Self {
+        let radius = radius.into();
+        Self::new(Geometry::RoundedRect { radius }, x, y, width, height)
+    }
+
+    pub fn oval(
+        x: impl Into<SharedF32>,
+        y: impl Into<SharedF32>,
+        width: impl Into<SharedF32>,
+        height: impl Into<SharedF32>,
+    ) -> Self {
+        Self::new(Geometry::Oval, x, y, width, height)
+    }
+
+    /// An arc of the oval in the bounds, the angles are in degrees and 0.0 points right.
+    /// With `use_center` the arc is closed through the center like a pie slice.
+    pub fn arc(
+        x: impl Into<SharedF32>,
+        y: impl Into<SharedF32>,
+        width: impl Into<SharedF32>,
+        height: impl Into<SharedF32>,
+        start_angle: impl Into<SharedF32>,
+        sweep_angle: impl Into<SharedF32>,
+        use_center: bool,
+    ) -> Self {
+        let geometry = Geometry::Arc {
+            start_angle: start_angle.into(),
+            sweep_angle: sweep_angle.into(),
+            use_center,
+        };
+        Self::new(geometry, x, y, width, height)
+    }
+
+    /// A path in the coordinates of the canvas content.
+    pub fn path(path: impl Into<Shared<Path>>) -> Self {
+        Self::new(Geometry::Path(path.into()), 0.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn fill(mut self, brush: impl Into<Shared<Brush>>) -> Self {
+        self.fill = brush.into();
+        self
+    }
+
+    pub fn stroke(mut self, brush: impl Into<Shared<Brush>>, width: impl Into<SharedF32>) -> Self {
+        self.stroke = brush.into();
+        self.stroke_width = width.into();
+        self
+    }
+
+    pub fn stroke_cap(mut self, cap: Cap) -> Self {
+        self.stroke_cap = cap;
+        self
+    }
+
+    pub fn stroke_join(mut self, join: Join) -> Self {
+        self.stroke_join = join;
+        self
+    }
+
+    /// Dashes the stroke, `intervals` alternates the lengths of the dashes and the gaps.
+    pub fn dash(
+        mut self,
+        intervals: impl Into<Shared<Vec<f32>>>,
+        phase: impl Into<SharedF32>,
+    ) -> Self {
+        self.dash_intervals = intervals.into();
+        self.dash_phase = phase.into();
+        self
+    }
+
+    /// The animated values of the shape, by the suffix of their parameter key.
+    fn float_values(&self) -> Vec<(&'static str, &SharedF32)> {
+        let mut values = vec![
+            ("x", &self.x),
+            ("y", &self.y),
+            ("width", &self.width),
+            ("height", &self.height),
+            ("stroke_width", &self.stroke_width),
+            ("dash_phase", &self.dash_phase),
+        ];
+        match &self.geometry {
+            Geometry::RoundedRect { radius } => values.push(("radius", radius)),
+            Geometry::Arc {
+                start_angle,
+                sweep_angle,
+                ..
+            } => {
+                values.push(("start_angle", start_angle));
+                values.push(("sweep_angle", sweep_angle));
+            }
+            _ => {}
+        }
+        values
+    }
+
+    fn brush_values(&self) -> [(&'static str, &Shared<Brush>); 2] {
+        [("fill", &self.fill), ("stroke", &self.stroke)]
+    }
+
+    /// Lays the canvas out again when an animated value changes and records it again when
+    /// the rest does.
+    fn observe(&self, event_loop_proxy: &EventLoopProxy, id: usize) {
+        for (_, value) in self.float_values() {
+            value.clone().layout_when_changed(event_loop_proxy, id);
+        }
+        for (_, value) in self.brush_values() {
+            value.clone().layout_when_changed(event_loop_proxy, id);
+        }
+        if let Geometry::Path(path) = &self.geometry {
+            path.clone().redraw_when_changed(event_loop_proxy, id);
+        }
+        self.dash_intervals
+            .clone()
+            .redraw_when_changed(event_loop_proxy, id);
+    }
+
+    fn set_target(&self, index: usize, target_parameter: &mut DisplayParameter) {
+        for (key, value) in self.float_values() {
+            target_parameter.set_float_param(format!("shape{}.{}", index, key), value.get());
+        }
+        for (key, value) in self.brush_values() {
+            target_parameter.set_brush_param(format!("shape{}.{}", index, key), value.get());
+        }
+    }
+
+    /// The outline of the shape, `float` returns the animated value of a parameter by its suffix.
+    fn build_path(&self, float: impl Fn(&str) -> f32) -> Path {
+        let rect = Rect::from_xywh(float("x"), float("y"), float("width"), float("height"));
+        match &self.geometry {
+            Geometry::Rect => Path::rect(rect, None),
+            Geometry::RoundedRect { .. } => {
+                let radius = float("radius");
+                Path::rrect(RRect::new_rect_xy(rect, radius, radius), None)
+            }
+            Geometry::Oval => Path::oval(rect, None),
+            Geometry::Arc { use_center, .. } => {
+                let mut path = Path::new();
+                if *use_center {
+                    path.move_to(rect.center());
+                }
+                path.arc_to(
+                    rect,
+                    float("start_angle"),
+                    float("sweep_angle"),
+                    !use_center,
+                );
+                if *use_center {
+                    path.close();
+                }
+                path
+            }
+            Geometry::Path(path) => path.get(),
+        }
+    }
+
+    fn draw(&self, index: usize, canvas: &Canvas, display_parameter: &DisplayParameter) {
+        let float = |key: &str| {
+            display_parameter
+                .get_float_param(&format!("shape{}.{}", index, key))
+                .unwrap_or(0.0)
+        };
+        let transparent = Brush::Solid(Color::TRANSPARENT);
+        let brush = |key: &str| {
+            display_parameter
+                .get_brush_param(&format!("shape{}.{}", index, key))
+                .unwrap_or(&transparent)
+        };
+        let is_visible = |brush: &Brush| !matches!(brush, Brush::Solid(color) if color.a() == 0);
+
+        let path = self.build_path(&float);
+
+        let bounds = *path.bounds();
+
+        let fill = brush("fill");
+        if is_visible(fill) {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            fill.apply(&mut paint, bounds);
+            canvas.draw_path(&path, &paint);
+        }
+
+        let stroke_width = float("stroke_width");
+        let stroke = brush("stroke");
+        if stroke_width > 0.0 && is_visible(stroke) {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_style(Style::Stroke);
+            stroke.apply(&mut paint, bounds);
+            paint.set_stroke_width(stroke_width);
+            paint.set_stroke_cap(self.stroke_cap);
+            paint.set_stroke_join(self.stroke_join);
+            let dash_intervals = self.dash_intervals.get();
+            if is_valid_dash(&dash_intervals) {
+                paint.set_path_effect(PathEffect::dash(&dash_intervals, float("dash_phase")));
+            }
+            canvas.draw_path(&path, &paint);
+        }
+    }
+}
+
+/// Skia needs an even number of positive intervals.
+fn is_valid_dash(intervals: &[f32]) -> bool {
+    intervals.len() >= 2
+        && intervals.len() % 2 == 0
+        && intervals.iter().all(|interval| *interval > 0.0)
+}
+
+type Painter = Box<dyn FnMut(&Canvas, f32, f32)>;
+
+/// Draws [`Shape`]s and then calls a painter with the Skia canvas, both in the coordinates of
+/// the content inside the padding.
+#[item]
+pub struct CanvasView {
+    item: Item,
+    shapes: Shared<Vec<Shape>>,
+    painter: LocalShared<Option<Painter>>,
+}
+
+impl CanvasView {
+    pub fn new(window_context: &WindowContext) -> Self {
+        let item = Item::new(window_context, Children::new());
+        let shapes: Shared<Vec<Shape>> = Shared::from_static(Vec::new());
+        let painter: LocalShared<Option<Painter>> = None.into();
+
+        item.data()
+            .set_layout({
+                let shapes = shapes.clone();
+                move |item, width, height| {
+                    let padding_left = item.get_padding_left();
+                    let padding_right = item.get_padding_right();
+                    let padding_top = item.get_padding_top().get();
+                    let padding_bottom = item.get_padding_bottom().get();
+                    let target_parameter = item.get_target_parameter();
+                    target_parameter.set_float_param("content_x", padding_left);
+                    target_parameter.set_float_param("content_y", padding_top);
+                    target_parameter.set_float_param(
+                        "content_width",
+                        (width - padding_left - padding_right).max(0.0),
+                    );
+                    target_parameter.set_float_param(
+                        "content_height",
+                        (height - padding_top - padding_bottom).max(0.0),
+                    );
+                    shapes.read(|shapes| {
+                        for (index, shape) in shapes.iter().enumerate() {
+                            shape.set_target(index, target_parameter);
+                        }
+                    });
+                }
+            })
+            .set_draw({
+                let shapes = shapes.clone();
+                let painter = painter.clone();
+                move |item, canvas| {
+                    let display_parameter = item.get_display_parameter();
+                    let param = |key: &str| display_parameter.get_float_param(key).unwrap_or(0.0);
+                    canvas.save();
+                    canvas.translate((
+                        display_parameter.x() + param("content_x"),
+                        display_parameter.y() + param("content_y"),
+                    ));
+                    shapes.read(|shapes| {
+                        for (index, shape) in shapes.iter().enumerate() {
+                            shape.draw(index, canvas, &display_parameter);
+                        }
+                    });
+                    if let Some(painter) = painter.lock().as_mut() {
+                        painter(canvas, param("content_width"), param("content_height"));
+                    }
+                    canvas.restore();
+                }
+            });
+        Self {
+            item,
+            shapes,
+            painter,
+        }
+    }
+
+    /// Adds a shape over the shapes added before.
+    pub fn shape(self, shape: Shape) -> Self {
+        let id = self.item.data().get_id();
+        shape.observe(self.item.data().get_window_context().event_loop_proxy(), id);
+        self.shapes.lock().push(shape);
+        self.item.data().get_window_context().request_layout();
+        self
+    }
+
+    /// Draws with Skia after the shapes, with the width and height of the content. Values the
+    /// painter reads aren't observed, call
+    /// [`request_repaint`](WindowContext::request_repaint) when they change.
+    pub fn painter(self, painter: impl FnMut(&Canvas, f32, f32) + 'static) -> Self {
+        *self.painter.lock() = Some(Box::new(painter));
+        let id = self.item.data().get_id();
+        self.item.data().get_window_context().request_repaint(id);
+        self
+    }
+}
+
+#[cfg(test)]
+mod canvas_test {
+    use super::{is_valid_dash, Shape};
+    use crate::shared::Shared;
+    use crate::ui::item::{Brush, DisplayParameter};
+    use skia_safe::{Color, Path, Point, Rect};
+
+    /// Builds the path of the shape from the parameters it sets, like drawing does.
+    fn build(shape: &Shape) -> Path {
+        let mut display_parameter = DisplayParameter::default();
+        shape.set_target(0, &mut display_parameter);
+        shape.build_path(|key| {
+            display_parameter
+                .get_float_param(&format!("shape0.{}", key))
+                .unwrap_or(0.0)
+        })
+    }
+
+    fn assert_point(point: Option<Point>, x: f32, y: f32) {
+        let point = point.unwrap();
+        assert!(
+            (point.x - x).abs() < 1e-3 && (point.y - y).abs() < 1e-3,
+            "{:?} is not ({}, {})",
+            point,
+            x,
+            y
+        );
+    }
+
+    #[test]
+    fn test_rect() {
+        let path = build(&Shape::rect(10.0, 20.0, 30.0, 40.0));
+        assert_eq!(*path.bounds(), Rect::from_xywh(10.0, 20.0, 30.0, 40.0));
+        assert!(path.is_oval().is_none());
+    }
+
+    #[test]
+    fn test_rounded_rect() {
+        let path = build(&Shape::rounded_rect(0.0, 0.0, 100.0, 50.0, 8.0));
+        let rrect = path.is_rrect().unwrap();
+        assert_eq!(*rrect.rect(), Rect::from_wh(100.0, 50.0));
+        assert_eq!(rrect.simple_radii(), Point::new(8.0, 8.0));
+    }
+
+    #[test]
+    fn test_oval() {
+        let path = build(&Shape::oval(5.0, 5.0, 20.0, 10.0));
+        assert_eq!(path.is_oval(), Some(Rect::from_xywh(5.0, 5.0, 20.0, 10.0)));
+    }
+
+    #[test]
+    fn test_arc() {
+        // A quarter from the right of the circle to its bottom.
+        let path = build(&Shape::arc(0.0, 0.0, 100.0, 100.0, 0.0, 90.0, false));
+        assert_point(path.get_point(0), 100.0, 50.0);
+        assert_point(path.last_pt(), 50.0, 100.0);
+        assert!(!path.is_last_contour_closed());
+
+        // The pie slice starts at the center and is closed.
+        let path = build(&Shape::arc(0.0, 0.0, 100.0, 100.0, 0.0, 90.0, true));
+        assert_point(path.get_point(0), 50.0, 50.0);
+        assert_point(path.get_point(1), 100.0, 50.0);
+        assert_point(path.last_pt(), 50.0, 100.0);
+        assert!(path.is_last_contour_closed());
+    }
+
+    #[test]
+    fn test_path() {
+        let mut triangle = Path::new();
+        triangle.move_to((0.0, 0.0));
+        triangle.line_to((10.0, 0.0));
+        triangle.line_to((0.0, 10.0));
+        triangle.close();
+        let shared = Shared::from(triangle.clone());
+        let shape = Shape::path(shared.clone());
+        assert_eq!(build(&shape), triangle);
+        // A changed path is drawn as it is.
+        let mut line = Path::new();
+        line.move_to((0.0, 0.0));
+        line.line_to((5.0, 5.0));
+        shared.set(line.clone());
+        assert_eq!(build(&shape), line);
+    }
+
+    #[test]
+    fn test_target_parameters() {
+        let shapes = [
+            Shape::rect(0.0, 0.0, 10.0, 10.0).fill(Color::RED),
+            Shape::rounded_rect(0.0, 0.0, 10.0, 10.0, 4.0).stroke(Color::BLUE, 2.0),
+            Shape::arc(0.0, 0.0, 10.0, 10.0, 45.0, 180.0, true),
+        ];
+        let mut display_parameter = DisplayParameter::default();
+        for (index, shape) in shapes.iter().enumerate() {
+            shape.set_target(index, &mut display_parameter);
+        }
+        let float = |key: &str| display_parameter.get_float_param(key);
+        assert_eq!(float("shape1.radius"), Some(4.0));
+        assert_eq!(float("shape1.stroke_width"), Some(2.0));
+        assert_eq!(float("shape2.start_angle"), Some(45.0));
+        assert_eq!(float("shape2.sweep_angle"), Some(180.0));
+        assert_eq!(float("shape0.radius"), None);
+        assert_eq!(float("shape0.start_angle"), None);
+        assert_eq!(
+            display_parameter.get_brush_param("shape0.fill"),
+            Some(&Brush::Solid(Color::RED))
+        );
+        assert_eq!(
+            display_parameter.get_brush_param("shape1.stroke"),
+            Some(&Brush::Solid(Color::BLUE))
+        );
+        assert_eq!(
+            display_parameter.get_brush_param("shape2.fill"),
+            Some(&Brush::Solid(Color::TRANSPARENT))
+        );
+    }
+
+    #[test]
+    fn test_dash() {
+        assert!(is_valid_dash(&[4.0, 2.0]));
+        assert!(is_valid_dash(&[4.0, 2.0, 1.0, 2.0]));
+        assert!(!is_valid_dash(&[]));
+        assert!(!is_valid_dash(&[4.0]));
+        assert!(!is_valid_dash(&[4.0, 2.0, 1.0]));
+        assert!(!is_valid_dash(&[4.0, 0.0]));
+        assert!(!is_valid_dash(&[4.0, -2.0]));
+    }
+}
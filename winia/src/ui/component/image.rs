@@ -1,3 +1,4 @@
+use crate::core::log_target;
 use crate::impl_property_layout;
 use crate::shared::{Children, Gettable, Shared, SharedBool, SharedDrawable};
 use crate::ui::app::WindowContext;
@@ -252,7 +253,12 @@ impl ImageDrawable {
         let response = match response {
             Ok(response) => response,
             Err(e) => {
-                println!("Error: {:?}", e);
+                tracing::warn!(
+                    target: log_target::RESOURCE,
+                    url = %url.display(),
+                    error = %e,
+                    "Failed to download an image"
+                );
                 return None;
             }
         };
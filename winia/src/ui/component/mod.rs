@@ -1,3 +1,4 @@
+mod canvas;
 mod image;
 mod rectangle;
 mod ripple;
@@ -16,10 +17,13 @@ mod switch;
 mod progress_indicator;
 mod icon;
 mod page;
+mod page_transition;
+mod router;
 
 pub use radio::*;
 // pub use divider::DividerExt;
 
+pub use canvas::*;
 pub use image::*;
 pub use rectangle::*;
 pub use ripple::*;
@@ -33,3 +37,5 @@ pub use switch::*;
 pub use progress_indicator::*;
 pub use icon::*;
 pub use page::*;
+pub use page_transition::*;
+pub use router::*;
@@ -1,44 +1,304 @@
-use std::collections::LinkedList;
+use std::collections::{HashMap, HashSet, LinkedList};
 use std::ops::Add;
 use clonelet::clone;
-use winit::event::ElementState;
+use winit::event::{ElementState, MouseButton};
 use winit::keyboard::{Key, NamedKey};
 use proc_macro::item;
-use crate::shared::{Children, Shared};
+use crate::shared::{Children, Gettable, Settable, Shared};
 use crate::ui::app::{EventLoopProxy, WindowContext};
-use crate::ui::component::RectangleExt;
+use crate::ui::component::{start_page_transition, PageState, PageTransition, RectangleExt, TransitionDirection};
 use crate::ui::Item;
-use crate::ui::item::Size;
+use crate::ui::item::{ItemData, PointerState, Size};
 use crate::ui::layout::StackExt;
 use crate::ui::theme::color;
 
+pub type PageFn = Box<dyn Fn(&WindowContext, PageManager) -> Item + Send>;
+
 enum PageAction {
-    Push(Box<dyn Fn(&WindowContext, PageManager) -> Item + Send>),
+    Push {
+        page_fn: PageFn,
+        transition: Option<PageTransition>,
+        /// Whether the page below is removed once the new page is shown.
+        replace: bool,
+    },
     Pop,
+    /// Pops pages until only the given number of pages is left.
+    PopTo(usize),
+    /// Replaces all pages without a transition.
+    Reset(Vec<PageFn>),
+    /// Removes the page with the id once its transition has finished.
+    Remove(usize),
 }
 
 struct PageStackProperty {
     actions: Shared<LinkedList<PageAction>>,
+    transition: Shared<PageTransition>,
 }
 
+type BackHandler = Box<dyn FnMut() -> bool + Send>;
+
 #[derive(Clone)]
 pub struct PageManager {
     actions: Shared<LinkedList<PageAction>>,
+    back_handler: Shared<Option<BackHandler>>,
     event_loop_proxy: EventLoopProxy
 }
 
 impl PageManager {
+    fn send(&self, action: PageAction) {
+        self.actions.lock().push_back(action);
+        self.event_loop_proxy.request_layout();
+    }
+
     pub fn push<F>(&self, page_fn: F)
     where
         F: Fn(&WindowContext, PageManager) -> Item + Send + 'static,
     {
-        self.actions.lock().push_back(PageAction::Push(Box::new(page_fn)));
-        self.event_loop_proxy.request_layout();
+        self.send(PageAction::Push {
+            page_fn: Box::new(page_fn),
+            transition: None,
+            replace: false,
+        });
+    }
+
+    /// Pushes a page with `transition` instead of the transition of the stack.
+    /// The same transition is used when the page is popped.
+    pub fn push_with_transition<F>(&self, transition: PageTransition, page_fn: F)
+    where
+        F: Fn(&WindowContext, PageManager) -> Item + Send + 'static,
+    {
+        self.send(PageAction::Push {
+            page_fn: Box::new(page_fn),
+            transition: Some(transition),
+            replace: false,
+        });
+    }
+
+    /// Pushes a page that takes the place of the current page.
+    pub fn replace<F>(&self, page_fn: F)
+    where
+        F: Fn(&WindowContext, PageManager) -> Item + Send + 'static,
+    {
+        self.send(PageAction::Push {
+            page_fn: Box::new(page_fn),
+            transition: None,
+            replace: true,
+        });
     }
 
     pub fn pop(&self) {
-        self.actions.lock().push_back(PageAction::Pop);
-        self.event_loop_proxy.request_layout();
+        self.send(PageAction::Pop);
+    }
+
+    /// Pops pages until `depth` pages are left. Only the current page is animated, the pages
+    /// between it and the new current page are removed at once. The first page is never popped.
+    pub fn pop_to(&self, depth: usize) {
+        self.send(PageAction::PopTo(depth));
+    }
+
+    /// Replaces all pages with `page_fns` without a transition, the last one becomes the current page.
+    pub fn reset(&self, page_fns: Vec<PageFn>) {
+        if !page_fns.is_empty() {
+            self.send(PageAction::Reset(page_fns));
+        }
+    }
+
+    /// Handles Escape and the back button of the mouse.
+    /// The back handler decides what happens if one is set, otherwise the current page is popped.
+    pub fn back(&self) {
+        // Called without holding the lock, the handler may navigate or set another handler.
+        let back_handler = self.back_handler.lock().take();
+        let handled = match back_handler {
+            Some(mut back_handler) => {
+                let handled = back_handler();
+                let mut slot = self.back_handler.lock();
+                if slot.is_none() {
+                    *slot = Some(back_handler);
+                }
+                handled
+            }
+            None => false,
+        };
+        if !handled {
+            self.pop();
+        }
+    }
+
+    /// Sets the function called by [`back`](PageManager::back). It returns whether it has handled the event.
+    pub fn set_back_handler(&self, back_handler: impl FnMut() -> bool + Send + 'static) {
+        self.back_handler.lock().replace(Box::new(back_handler));
+    }
+}
+
+/// Wraps a page into a full-size item with the background color, which catches the clicks
+/// that would otherwise reach the pages below.
+fn wrap_page(window_context: &WindowContext, page: Item) -> Item {
+    window_context.stack(
+        page
+    ).item().size(Size::Fill, Size::Fill).background(window_context.rectangle(
+        *window_context.theme().lock().get_color(color::BACKGROUND).unwrap()
+    ).item()).on_click(|_| {})
+}
+
+fn capture_page(page: &Item) -> (PageState, f32, f32) {
+    let mut page = page.data();
+    let (parent_x, parent_y) = {
+        let target_parameter = page.get_target_parameter();
+        (target_parameter.parent_x, target_parameter.parent_y)
+    };
+    (PageState::capture(&mut page, parent_x, parent_y), parent_x, parent_y)
+}
+
+struct PageStackState {
+    children: Children,
+    actions: Shared<LinkedList<PageAction>>,
+    transition: Shared<PageTransition>,
+    /// The transition each pushed page was pushed with, by page id.
+    page_transitions: Shared<HashMap<usize, PageTransition>>,
+    /// The pages that are transitioning out and are removed once their transition has finished.
+    exiting: Shared<HashSet<usize>>,
+    page_manager: PageManager,
+    event_loop_proxy: EventLoopProxy,
+}
+
+impl PageStackState {
+    /// The ids of the pages that are not leaving the stack, from the bottom up.
+    fn live_ids(&self) -> Vec<usize> {
+        let exiting = self.exiting.lock();
+        self.children
+            .lock()
+            .iter()
+            .map(|page| page.data().get_id())
+            .filter(|id| !exiting.contains(id))
+            .collect()
+    }
+
+    fn capture(&self, id: usize) -> Option<(PageState, f32, f32)> {
+        self.children
+            .lock()
+            .iter()
+            .find(|page| page.data().get_id() == id)
+            .map(capture_page)
+    }
+
+    fn remove(&self, id: usize) {
+        self.page_transitions.lock().remove(&id);
+        self.exiting.lock().remove(&id);
+        self.children.remove_by_id(id);
+    }
+
+    /// Returns a function that removes the page with `id` after the current measure pass.
+    fn remove_later(&self, id: usize) -> impl FnMut() + Send + 'static {
+        let actions = self.actions.clone();
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        move || {
+            actions.lock().push_back(PageAction::Remove(id));
+            event_loop_proxy.request_layout();
+        }
+    }
+
+    fn push(&self, item: &ItemData, page_fn: PageFn, page_transition: Option<PageTransition>, replace: bool) {
+        let window_context = item.get_window_context();
+        let background = wrap_page(window_context, page_fn(window_context, self.page_manager.clone()));
+        let page_transition = page_transition.unwrap_or_else(|| self.transition.get());
+        self.page_transitions.lock().insert(background.data().get_id(), page_transition.clone());
+
+        let outgoing = self.live_ids().last().and_then(|id| self.capture(*id));
+        let Some((outgoing, parent_x, parent_y)) = outgoing else {
+            self.children.add_item(background);
+            return;
+        };
+        let replaced = outgoing.id();
+        if page_transition == PageTransition::None {
+            self.children.add_item(background);
+            if replace {
+                self.remove(replaced);
+            }
+            return;
+        }
+        if replace {
+            self.exiting.lock().insert(replaced);
+        }
+
+        // Hidden until the transition starts, once the page has been laid out.
+        background.data().get_opacity().set(0.0);
+        let mut outgoing = Some(outgoing);
+        let mut on_finish = replace.then(|| self.remove_later(replaced));
+        let layout = background.data().get_layout();
+        background.data().set_layout(move |item, width, height| {
+            layout.lock()(item, width, height);
+            if let Some(outgoing) = outgoing.take() {
+                let incoming = PageState::capture(item, parent_x, parent_y);
+                let mut on_finish = on_finish.take();
+                start_page_transition(
+                    item.get_window_context(),
+                    &page_transition,
+                    TransitionDirection::Forward,
+                    outgoing,
+                    incoming,
+                    move || {
+                        if let Some(on_finish) = on_finish.as_mut() {
+                            on_finish();
+                        }
+                    },
+                );
+            }
+        });
+        self.children.add_item(background);
+    }
+
+    fn pop_to(&self, item: &ItemData, depth: usize) {
+        let depth = depth.max(1);
+        // Pages that are already transitioning out don't count, they are removed on their own.
+        let live = self.live_ids();
+        if live.len() <= depth {
+            return;
+        }
+        let id = live[live.len() - 1];
+        for skipped in &live[depth..live.len() - 1] {
+            self.remove(*skipped);
+        }
+
+        let pages = self.capture(id).zip(self.capture(live[depth - 1]));
+        let Some(((outgoing, _, _), (incoming, _, _))) = pages else {
+            return;
+        };
+        self.exiting.lock().insert(id);
+        let page_transition = self
+            .page_transitions
+            .lock()
+            .remove(&id)
+            .unwrap_or_else(|| self.transition.get());
+        let started = start_page_transition(
+            item.get_window_context(),
+            &page_transition,
+            TransitionDirection::Backward,
+            outgoing,
+            incoming,
+            self.remove_later(id),
+        );
+        if started.is_none() {
+            self.remove(id);
+        }
+    }
+
+    fn reset(&self, item: &ItemData, page_fns: Vec<PageFn>) {
+        let window_context = item.get_window_context();
+        self.page_transitions.lock().clear();
+        self.exiting.lock().clear();
+        {
+            let mut children = self.children.lock();
+            children.clear();
+            for (index, page_fn) in page_fns.iter().enumerate() {
+                let page = page_fn(window_context, self.page_manager.clone());
+                if index == 0 {
+                    children.push(page);
+                } else {
+                    children.push(wrap_page(window_context, page));
+                }
+            }
+        }
+        self.children.notify();
     }
 }
 
@@ -58,39 +318,46 @@ impl PageStack {
         let item = w.stack(children.clone()).item();
         let id = item.data().get_id();
         let actions = Shared::from(LinkedList::new()).redraw_when_changed(&e, id);
+        let transition = Shared::from(PageTransition::default());
         let page_manager = PageManager {
             actions: actions.clone(),
-            event_loop_proxy: e,
+            back_handler: Shared::from(None),
+            event_loop_proxy: e.clone(),
         };
         let first_page_item = first_page(w, page_manager.clone());
         children.add_item(first_page_item);
 
+        let state = PageStackState {
+            children,
+            actions: actions.clone(),
+            transition: transition.clone(),
+            page_transitions: Shared::from(HashMap::new()),
+            exiting: Shared::from(HashSet::new()),
+            page_manager: page_manager.clone(),
+            event_loop_proxy: e,
+        };
+
         let measure = item.data().get_measure();
         item.data().set_measure({
-            clone!(actions, page_manager, children);
             move |item, width_mode, height_mode| {
-                if let Some(action) = actions.lock().pop_front() {
+                let action = state.actions.lock().pop_front();
+                if let Some(action) = action {
                     match action {
-                        PageAction::Push(page_fn) => {
-                            let window_context = item.get_window_context();
-                            let new_item = page_fn(item.get_window_context(), page_manager.clone());
-                            let background = window_context.stack(
-                                new_item
-                            ).item().size(Size::Fill, Size::Fill).background(window_context.rectangle(
-                                *window_context.theme().lock().get_color(color::BACKGROUND).unwrap()
-                            ).item()).on_click(|_| {});
-                            children.insert_with_animation(
-                                children.len(),
-                                background
-                            );
+                        PageAction::Push { page_fn, transition, replace } => {
+                            state.push(item, page_fn, transition, replace);
                         }
                         PageAction::Pop => {
-                            if children.len() > 1 {
-                                let last_item_id = children.lock().last().map(|item| item.data().get_id());
-                                if let Some(id) = last_item_id {
-                                    children.remove_with_animation(id);
-                                }
-                            }
+                            let depth = state.live_ids().len().saturating_sub(1);
+                            state.pop_to(item, depth);
+                        }
+                        PageAction::PopTo(depth) => {
+                            state.pop_to(item, depth);
+                        }
+                        PageAction::Reset(page_fns) => {
+                            state.reset(item, page_fns);
+                        }
+                        PageAction::Remove(id) => {
+                            state.remove(id);
                         }
                     }
                 }
@@ -102,7 +369,7 @@ impl PageStack {
                 if input.key_event.state == ElementState::Pressed {
                     match input.key_event.logical_key {
                         Key::Named(NamedKey::Escape) => {
-                            page_manager.pop();
+                            page_manager.back();
                             true
                         }
                         _=> {false}
@@ -111,12 +378,25 @@ impl PageStack {
                     false
                 }
             }
+        }).set_mouse_input({
+            clone!(page_manager);
+            move |_item, event| {
+                if event.button == MouseButton::Back && event.pointer_state == PointerState::Ended {
+                    page_manager.back();
+                }
+            }
         });
 
-        let property = Shared::from(PageStackProperty { actions });
+        let property = Shared::from(PageStackProperty { actions, transition });
         PageStack {
             item,
             property,
         }
     }
-}
\ No newline at end of file
+
+    /// The transition used when pages are pushed and popped, [`PageTransition::Fade`] by default.
+    pub fn transition(self, transition: PageTransition) -> Self {
+        self.property.lock().transition.set(transition);
+        self
+    }
+}
@@ -0,0 +1,407 @@
+use crate::shared::{Gettable, Settable, SharedColor, SharedF32, SharedInnerPosition};
+use crate::ui::animation::interpolator::CubicBezier;
+use crate::ui::animation::{KeyframeTrack, Motion, MotionController};
+use crate::ui::app::WindowContext;
+use crate::ui::item::{CustomProperty, InnerPosition, ItemData};
+use crate::ui::theme::motion;
+use crate::ui::Item;
+use skia_safe::Rect;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How a [`PageStack`](crate::ui::component::PageStack) moves from one page to another.
+///
+/// Except for [`None`](PageTransition::None), every transition also morphs the [`SharedElement`]s
+/// that appear on both pages.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PageTransition {
+    None,
+    /// The new page fades in on top of the previous one.
+    #[default]
+    Fade,
+    /// The previous page fades out, then the new page fades in while scaling up slightly.
+    FadeThrough,
+    /// The pages slide horizontally while fading, for pages that are siblings or steps of a flow.
+    SharedAxisX,
+    /// The pages slide vertically while fading.
+    SharedAxisY,
+    /// The pages scale while fading, for pages that are parent and child.
+    SharedAxisZ,
+    /// The new page grows out of the shared element with the given name on the previous page,
+    /// and shrinks back into it when popped.
+    ContainerTransform(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TransitionDirection {
+    /// A page is pushed.
+    Forward,
+    /// A page is popped.
+    Backward,
+}
+
+/// Marks an item as an element that is shared between pages.
+///
+/// When a page transition runs, every shared element of the incoming page that has a counterpart
+/// with the same name on the outgoing page morphs from the bounds of that counterpart to its own bounds.
+/// If both elements have a corner radius or a color, these are morphed as well.
+#[derive(Clone)]
+pub struct SharedElement {
+    name: String,
+    corner_radius: Option<SharedF32>,
+    color: Option<SharedColor>,
+}
+
+impl SharedElement {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            corner_radius: None,
+            color: None,
+        }
+    }
+
+    /// The corner radius of the element, e.g. the radius of its background rectangle.
+    pub fn corner_radius(mut self, corner_radius: &SharedF32) -> Self {
+        self.corner_radius = Some(corner_radius.clone());
+        self
+    }
+
+    /// The color of the element, e.g. the color of its background rectangle.
+    pub fn color(mut self, color: &SharedColor) -> Self {
+        self.color = Some(color.clone());
+        self
+    }
+}
+
+impl From<&str> for SharedElement {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for SharedElement {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+const SHARED_ELEMENT: &str = "shared_element";
+
+impl Item {
+    /// Marks the item as a [`SharedElement`].
+    pub fn shared_element(self, shared_element: impl Into<SharedElement>) -> Self {
+        self.data()
+            .custom_property(SHARED_ELEMENT, CustomProperty::Any(Box::new(shared_element.into())));
+        self
+    }
+}
+
+/// The animatable properties of an item, together with its bounds when it was captured.
+#[derive(Clone)]
+struct Transform {
+    bounds: Rect,
+    opacity: SharedF32,
+    offset_x: SharedF32,
+    offset_y: SharedF32,
+    scale_x: SharedF32,
+    scale_y: SharedF32,
+    scale_center_x: SharedInnerPosition,
+    scale_center_y: SharedInnerPosition,
+}
+
+impl Transform {
+    /// `parent_x` and `parent_y` are the position of the parent in the window.
+    fn capture(item: &mut ItemData, parent_x: f32, parent_y: f32) -> Self {
+        let display_parameter = item.get_display_parameter();
+        Self {
+            bounds: Rect::from_xywh(
+                parent_x + display_parameter.relative_x + display_parameter.offset_x,
+                parent_y + display_parameter.relative_y + display_parameter.offset_y,
+                display_parameter.width,
+                display_parameter.height,
+            ),
+            opacity: item.get_opacity().clone(),
+            offset_x: item.get_offset_x().clone(),
+            offset_y: item.get_offset_y().clone(),
+            scale_x: item.get_scale_x().clone(),
+            scale_y: item.get_scale_y().clone(),
+            scale_center_x: item.get_scale_center_x().clone(),
+            scale_center_y: item.get_scale_center_y().clone(),
+        }
+    }
+
+    fn reset(&self) {
+        self.opacity.set(1.0);
+        self.offset_x.set(0.0);
+        self.offset_y.set(0.0);
+        self.scale_x.set(1.0);
+        self.scale_y.set(1.0);
+    }
+
+    /// Returns a motion that moves the item from `from` to its own bounds over `duration`.
+    /// The item is scaled from its top left corner until the motion finishes.
+    fn morph_from(&self, from: Rect, duration: Duration, easing: CubicBezier) -> Motion {
+        let scale_x = if self.bounds.width() > 0.0 {
+            from.width() / self.bounds.width()
+        } else {
+            1.0
+        };
+        let scale_y = if self.bounds.height() > 0.0 {
+            from.height() / self.bounds.height()
+        } else {
+            1.0
+        };
+        self.scale_center_x.set(InnerPosition::Start(0.0));
+        self.scale_center_y.set(InnerPosition::Start(0.0));
+        Motion::parallel([
+            tween(&self.offset_x, from.left - self.bounds.left, 0.0, Duration::ZERO, duration, easing),
+            tween(&self.offset_y, from.top - self.bounds.top, 0.0, Duration::ZERO, duration, easing),
+            tween(&self.scale_x, scale_x, 1.0, Duration::ZERO, duration, easing),
+            tween(&self.scale_y, scale_y, 1.0, Duration::ZERO, duration, easing),
+        ])
+    }
+
+    /// Returns a motion that moves the item from its own bounds to `to` over `duration`.
+    fn morph_to(&self, to: Rect, duration: Duration, easing: CubicBezier) -> Motion {
+        let scale_x = if self.bounds.width() > 0.0 {
+            to.width() / self.bounds.width()
+        } else {
+            1.0
+        };
+        let scale_y = if self.bounds.height() > 0.0 {
+            to.height() / self.bounds.height()
+        } else {
+            1.0
+        };
+        self.scale_center_x.set(InnerPosition::Start(0.0));
+        self.scale_center_y.set(InnerPosition::Start(0.0));
+        Motion::parallel([
+            tween(&self.offset_x, 0.0, to.left - self.bounds.left, Duration::ZERO, duration, easing),
+            tween(&self.offset_y, 0.0, to.top - self.bounds.top, Duration::ZERO, duration, easing),
+            tween(&self.scale_x, 1.0, scale_x, Duration::ZERO, duration, easing),
+            tween(&self.scale_y, 1.0, scale_y, Duration::ZERO, duration, easing),
+        ])
+    }
+}
+
+struct SharedElementState {
+    transform: Transform,
+    scale_center: (InnerPosition, InnerPosition),
+    element: SharedElement,
+}
+
+/// A page that takes part in a transition.
+pub(crate) struct PageState {
+    id: usize,
+    transform: Transform,
+    shared_elements: HashMap<String, SharedElementState>,
+}
+
+impl PageState {
+    pub(crate) fn capture(page: &mut ItemData, parent_x: f32, parent_y: f32) -> Self {
+        let transform = Transform::capture(page, parent_x, parent_y);
+        let mut shared_elements = HashMap::new();
+        collect_shared_elements(page, transform.bounds.left, transform.bounds.top, &mut shared_elements);
+        Self {
+            id: page.get_id(),
+            transform,
+            shared_elements,
+        }
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+}
+
+fn collect_shared_elements(
+    item: &ItemData,
+    x: f32,
+    y: f32,
+    shared_elements: &mut HashMap<String, SharedElementState>,
+) {
+    for child in item.get_children().lock().iter() {
+        let mut child = child.data();
+        let transform = Transform::capture(&mut child, x, y);
+        let (child_x, child_y) = (transform.bounds.left, transform.bounds.top);
+        let element = match child.get_custom_property(SHARED_ELEMENT) {
+            Some(CustomProperty::Any(any)) => any.downcast_ref::<SharedElement>().cloned(),
+            _ => None,
+        };
+        if let Some(element) = element {
+            let scale_center = (transform.scale_center_x.get(), transform.scale_center_y.get());
+            shared_elements.insert(
+                element.name.clone(),
+                SharedElementState {
+                    transform,
+                    scale_center,
+                    element,
+                },
+            );
+        }
+        collect_shared_elements(&child, child_x, child_y, shared_elements);
+    }
+}
+
+fn tween(shared: &SharedF32, from: f32, to: f32, start: Duration, end: Duration, easing: CubicBezier) -> Motion {
+    KeyframeTrack::f32(shared)
+        .keyframe(start, from)
+        .keyframe_with(end, to, easing)
+        .into()
+}
+
+/// Runs `transition` from `outgoing` to `incoming` and calls `on_finish` when it's done.
+///
+/// The properties the transition animates are reset on the outgoing page once it finishes,
+/// so a page that stays in the stack is shown normally when it becomes visible again.
+pub(crate) fn start_page_transition(
+    window_context: &WindowContext,
+    transition: &PageTransition,
+    direction: TransitionDirection,
+    outgoing: PageState,
+    incoming: PageState,
+    on_finish: impl FnMut() + Send + 'static,
+) -> Option<MotionController> {
+    if *transition == PageTransition::None {
+        return None;
+    }
+    let (duration, easing) = {
+        let theme = window_context.theme().lock();
+        let duration = theme
+            .get_duration(motion::duration::MEDIUM2)
+            .unwrap_or(Duration::from_millis(300));
+        let easing = theme
+            .get_easing(motion::easing::STANDARD)
+            .unwrap_or(CubicBezier::new(0.2, 0.0, 0.0, 1.0));
+        (duration, easing)
+    };
+    // Material fades the outgoing content out during the first 35% of the transition.
+    let fade_out_end = duration.mul_f32(0.35);
+    let sign = match direction {
+        TransitionDirection::Forward => 1.0,
+        TransitionDirection::Backward => -1.0,
+    };
+
+    let out = &outgoing.transform;
+    let into = &incoming.transform;
+    let mut motions = match transition {
+        PageTransition::None => Vec::new(),
+        PageTransition::Fade => match direction {
+            TransitionDirection::Forward => vec![tween(&into.opacity, 0.0, 1.0, Duration::ZERO, duration, easing)],
+            TransitionDirection::Backward => vec![tween(&out.opacity, 1.0, 0.0, Duration::ZERO, duration, easing)],
+        },
+        PageTransition::FadeThrough => vec![
+            tween(&out.opacity, 1.0, 0.0, Duration::ZERO, fade_out_end, easing),
+            tween(&into.opacity, 0.0, 1.0, fade_out_end, duration, easing),
+            tween(&into.scale_x, 0.92, 1.0, fade_out_end, duration, easing),
+            tween(&into.scale_y, 0.92, 1.0, fade_out_end, duration, easing),
+        ],
+        PageTransition::SharedAxisX | PageTransition::SharedAxisY => {
+            let (out_offset, into_offset) = if *transition == PageTransition::SharedAxisX {
+                (&out.offset_x, &into.offset_x)
+            } else {
+                (&out.offset_y, &into.offset_y)
+            };
+            vec![
+                tween(out_offset, 0.0, -30.0 * sign, Duration::ZERO, duration, easing),
+                tween(&out.opacity, 1.0, 0.0, Duration::ZERO, fade_out_end, easing),
+                tween(into_offset, 30.0 * sign, 0.0, Duration::ZERO, duration, easing),
+                tween(&into.opacity, 0.0, 1.0, fade_out_end, duration, easing),
+            ]
+        }
+        PageTransition::SharedAxisZ => {
+            let (out_scale, into_scale) = match direction {
+                TransitionDirection::Forward => (1.1, 0.8),
+                TransitionDirection::Backward => (0.8, 1.1),
+            };
+            vec![
+                tween(&out.scale_x, 1.0, out_scale, Duration::ZERO, duration, easing),
+                tween(&out.scale_y, 1.0, out_scale, Duration::ZERO, duration, easing),
+                tween(&out.opacity, 1.0, 0.0, Duration::ZERO, fade_out_end, easing),
+                tween(&into.scale_x, into_scale, 1.0, Duration::ZERO, duration, easing),
+                tween(&into.scale_y, into_scale, 1.0, Duration::ZERO, duration, easing),
+                tween(&into.opacity, 0.0, 1.0, fade_out_end, duration, easing),
+            ]
+        }
+        PageTransition::ContainerTransform(name) => match direction {
+            TransitionDirection::Forward => match outgoing.shared_elements.get(name) {
+                Some(container) => vec![
+                    into.morph_from(container.transform.bounds, duration, easing),
+                    tween(&into.opacity, 0.0, 1.0, Duration::ZERO, fade_out_end, easing),
+                ],
+                None => vec![tween(&into.opacity, 0.0, 1.0, Duration::ZERO, duration, easing)],
+            },
+            TransitionDirection::Backward => match incoming.shared_elements.get(name) {
+                Some(container) => vec![
+                    out.morph_to(container.transform.bounds, duration, easing),
+                    tween(&out.opacity, 1.0, 0.0, duration - fade_out_end, duration, easing),
+                ],
+                None => vec![tween(&out.opacity, 1.0, 0.0, Duration::ZERO, duration, easing)],
+            },
+        },
+    };
+
+    let container = match transition {
+        PageTransition::ContainerTransform(name) => Some(name.as_str()),
+        _ => None,
+    };
+    let mut hidden = Vec::new();
+    let mut restored = Vec::new();
+    for (name, target) in incoming.shared_elements.iter() {
+        if Some(name.as_str()) == container {
+            continue;
+        }
+        let Some(source) = outgoing.shared_elements.get(name) else {
+            continue;
+        };
+        motions.push(target.transform.morph_from(source.transform.bounds, duration, easing));
+        if let (Some(from), Some(to)) = (&source.element.corner_radius, &target.element.corner_radius) {
+            motions.push(tween(to, from.get(), to.get(), Duration::ZERO, duration, easing));
+        }
+        if let (Some(from), Some(to)) = (&source.element.color, &target.element.color) {
+            motions.push(
+                KeyframeTrack::color(to)
+                    .keyframe(Duration::ZERO, from.get())
+                    .keyframe_with(duration, to.get(), easing)
+                    .into(),
+            );
+        }
+        // The element is drawn by its counterpart while the transition runs.
+        hidden.push((source.transform.opacity.clone(), source.transform.opacity.get()));
+        source.transform.opacity.set(0.0);
+        restored.push((target.transform.clone(), target.scale_center));
+    }
+
+    let outgoing_transform = outgoing.transform.clone();
+    let incoming_scale_center = (
+        incoming.transform.scale_center_x.clone(),
+        incoming.transform.scale_center_y.clone(),
+        incoming.transform.scale_center_x.get(),
+        incoming.transform.scale_center_y.get(),
+    );
+    let outgoing_scale_center = (
+        outgoing.transform.scale_center_x.get(),
+        outgoing.transform.scale_center_y.get(),
+    );
+    let mut on_finish = on_finish;
+    Some(
+        Motion::parallel(motions)
+            .start(window_context.event_loop_proxy())
+            .on_finish(move || {
+                outgoing_transform.reset();
+                outgoing_transform.scale_center_x.set(outgoing_scale_center.0);
+                outgoing_transform.scale_center_y.set(outgoing_scale_center.1);
+                incoming_scale_center.0.set(incoming_scale_center.2);
+                incoming_scale_center.1.set(incoming_scale_center.3);
+                for (opacity, value) in hidden.iter() {
+                    opacity.set(*value);
+                }
+                for (transform, (scale_center_x, scale_center_y)) in restored.iter() {
+                    transform.scale_center_x.set(*scale_center_x);
+                    transform.scale_center_y.set(*scale_center_y);
+                }
+                on_finish();
+            }),
+    )
+}
@@ -1,7 +1,7 @@
-use crate::shared::{Children, Settable, Shared, SharedDrawable};
+use crate::shared::{Children, Gettable, Settable, Shared, SharedDrawable};
 use crate::ui::app::{EventLoopProxy, WindowContext};
 use crate::ui::component::{ImageDrawable, ImageExt, RippleExt, ScaleMode};
-use crate::ui::item::{Alignment, ItemData};
+use crate::ui::item::{Alignment, ClickSource, ItemData};
 use crate::ui::layout::StackExt;
 use crate::ui::theme::color;
 use crate::ui::Item;
@@ -10,6 +10,7 @@ use proc_macro::item;
 use skia_safe::{Color, Path};
 use std::time::{Duration, Instant};
 use lazy_static::lazy_static;
+use winit::keyboard::{Key, NamedKey};
 
 const RADIO_BUTTON_CHECKED_ICON: &[u8] = include_bytes!("assets/icon/radio_button_checked.svg");
 const RADIO_BUTTON_UNCHECKED_ICON: &[u8] = include_bytes!("assets/icon/radio_button_unchecked.svg");
@@ -161,6 +162,25 @@ impl<T: Clone + PartialEq + Send + 'static> Radio<T> {
                 }
             });
 
+        item.data().set_focusable(true);
+        item.data().set_focus_ring_radius(f32::MAX);
+        item.data().set_keyboard_input(|item, input| {
+            if !item.get_focused().get() {
+                return false;
+            }
+            match input.key_event.logical_key {
+                Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) => {
+                    if input.key_event.state.is_pressed() {
+                        if let Some(on_click) = item.get_on_click().cloned() {
+                            on_click.lock()(ClickSource::Keyboard);
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            }
+        });
+
         let value = property.lock().value.clone();
         property
             .lock()
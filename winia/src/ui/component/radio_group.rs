@@ -1,4 +1,5 @@
 use tokio::time::Instant;
+use crate::core::next_id;
 use crate::shared::{Children, Shared};
 use crate::text::StyledText;
 use crate::ui::app::WindowContext;
@@ -22,9 +23,12 @@ impl<T: Clone + PartialEq + Send + 'static> RadioGroupExt<T> for WindowContext {
         on_selected: impl FnMut(&Shared<T>, T) + Clone + 'static,
     ) -> Children {
         let children = Children::new();
+        // The radios are one Tab stop, the arrow keys move between them.
+        let group = next_id();
         for (value, label) in values {
             let radio = self.radio(value.clone(), selected_value, Some(Box::new(on_selected.clone())))
                 .item()
+                .focus_group(group)
                 .margin_end(4);
             let mut on_click = radio.data().get_on_click().cloned();
             children.add_item(
@@ -1,7 +1,7 @@
 use std::ops::DerefMut;
 use crate::shared::{Children, Gettable, Observable, Shared, SharedColor, SharedF32};
 use crate::ui::app::WindowContext;
-use crate::ui::item::{ItemData, LayoutDirection, LogicalX};
+use crate::ui::item::{Brush, ItemData, LayoutDirection, LogicalX};
 use crate::ui::Item;
 use crate::{impl_property_layout, impl_property_redraw};
 use proc_macro::item;
@@ -11,13 +11,16 @@ use skia_safe::{Color, Path, RRect, Rect, Shader, Vector};
 #[derive(Clone)]
 struct RectangleProperty {
     color: SharedColor,
-    shader: Shared<Option<Shader>>,
+    /// Paints the rectangle in place of `color`.
+    brush: Shared<Option<Brush>>,
     radius_top_start: SharedF32,
     radius_top_end: SharedF32,
     radius_bottom_start: SharedF32,
     radius_bottom_end: SharedF32,
     outline_width: SharedF32,
     outline_color: SharedColor,
+    /// Paints the outline in place of `outline_color`.
+    outline_brush: Shared<Option<Brush>>,
     outline_offset: SharedF32,
 }
 
@@ -28,15 +31,53 @@ pub struct Rectangle {
 }
 
 impl_property_redraw!(Rectangle, color, SharedColor);
-impl_property_redraw!(Rectangle, shader, Shared<Option<Shader>>);
+impl_property_redraw!(Rectangle, brush, Shared<Option<Brush>>);
 impl_property_redraw!(Rectangle, radius_top_start, SharedF32);
 impl_property_redraw!(Rectangle, radius_top_end, SharedF32);
 impl_property_redraw!(Rectangle, radius_bottom_start, SharedF32);
 impl_property_redraw!(Rectangle, radius_bottom_end, SharedF32);
 impl_property_redraw!(Rectangle, outline_width, SharedF32);
 impl_property_redraw!(Rectangle, outline_color, SharedColor);
+impl_property_redraw!(Rectangle, outline_brush, Shared<Option<Brush>>);
 impl_property_redraw!(Rectangle, outline_offset, SharedF32);
 
+/// The rounded rectangle that is filled, from the parameters set in the layout.
+fn get_rrect(item: &mut ItemData) -> RRect {
+    let display_parameter = item.get_display_parameter();
+    let param = |key: &str| display_parameter.get_float_param(key).unwrap_or(0.0);
+    let rect = Rect::from_xywh(
+        display_parameter.x() + param("x"),
+        display_parameter.y() + param("y"),
+        param("width"),
+        param("height"),
+    );
+    let radius_top_start = param("radius_top_start");
+    let radius_top_end = param("radius_top_end");
+    let radius_bottom_end = param("radius_bottom_end");
+    let radius_bottom_start = param("radius_bottom_start");
+    if item.get_layout_direction().get() == LayoutDirection::LTR {
+        RRect::new_rect_radii(
+            rect,
+            &[
+                Vector::new(radius_top_start, radius_top_start),
+                Vector::new(radius_top_end, radius_top_end),
+                Vector::new(radius_bottom_end, radius_bottom_end),
+                Vector::new(radius_bottom_start, radius_bottom_start),
+            ],
+        )
+    } else {
+        RRect::new_rect_radii(
+            rect,
+            &[
+                Vector::new(radius_top_end, radius_top_end),
+                Vector::new(radius_top_start, radius_top_start),
+                Vector::new(radius_bottom_start, radius_bottom_start),
+                Vector::new(radius_bottom_end, radius_bottom_end),
+            ],
+        )
+    }
+}
+
 impl Rectangle {
     pub fn new(app_context: &WindowContext, color: impl Into<SharedColor>) -> Self {
         let item = Item::new(app_context, Children::new());
@@ -44,7 +85,7 @@ impl Rectangle {
         let event_loop_proxy = app_context.event_loop_proxy();
         let property = Shared::from(RectangleProperty {
             color: color.into().redraw_when_changed(&event_loop_proxy, id),
-            shader: Shared::from(None).redraw_when_changed(&event_loop_proxy, id),
+            brush: Shared::from(None).redraw_when_changed(&event_loop_proxy, id),
             radius_top_start: SharedF32::from(0.0).redraw_when_changed(&event_loop_proxy, id),
             radius_top_end: SharedF32::from(0.0).redraw_when_changed(&event_loop_proxy, id),
             radius_bottom_start: SharedF32::from(0.0).redraw_when_changed(&event_loop_proxy, id),
@@ -52,17 +93,36 @@ impl Rectangle {
             outline_width: SharedF32::from(0.0).redraw_when_changed(&event_loop_proxy, id),
             outline_color: SharedColor::from(Color::TRANSPARENT)
                 .redraw_when_changed(&event_loop_proxy, id),
+            outline_brush: Shared::from(None).redraw_when_changed(&event_loop_proxy, id),
             outline_offset: SharedF32::from(0.0).redraw_when_changed(&event_loop_proxy, id),
         });
 
+        {
+            // The shape is known before the rectangle is drawn, so the shadow and the clip follow
+            // it from the first frame.
+            let clip_shape: Box<dyn Fn(&mut ItemData) -> Path + Send> = Box::new(|item| {
+                let mut path = Path::new();
+                path.add_rrect(get_rrect(item), None);
+                path
+            });
+            item.data().set_clip_shape(clip_shape);
+        }
+
         item.data()
             .set_layout({
                 let property = property.clone();
                 move |item, width, height| {
                     let property = property.lock();
-                    let color = property.color.get();
+                    // A color is a solid brush, so colors and brushes animate into each other.
+                    let brush = property
+                        .brush
+                        .get()
+                        .unwrap_or_else(|| Brush::Solid(property.color.get()));
                     let outline_width = property.outline_width.get();
-                    let outline_color = property.outline_color.get();
+                    let outline_brush = property
+                        .outline_brush
+                        .get()
+                        .unwrap_or_else(|| Brush::Solid(property.outline_color.get()));
                     let outline_offset = property.outline_offset.get();
                     
                     let padding_start = item.get_padding_start().get();
@@ -83,7 +143,7 @@ impl Rectangle {
                     let x = LogicalX::new(layout_direction, padding_start, width);
                     
                     let target_parameter = item.get_target_parameter();
-                    target_parameter.set_color_param("color", color);
+                    target_parameter.set_brush_param("brush", brush);
                     target_parameter.set_float_param("width", r_width);
                     target_parameter.set_float_param("height", r_height);
                     target_parameter.set_float_param("x", x.physical_value(r_width));
@@ -93,19 +153,18 @@ impl Rectangle {
                     target_parameter.set_float_param("radius_bottom_end", radius_bottom_end);
                     target_parameter.set_float_param("radius_bottom_start", radius_bottom_start);
                     target_parameter.set_float_param("outline_width", outline_width);
-                    target_parameter.set_color_param("outline_color", outline_color);
+                    target_parameter.set_brush_param("outline_brush", outline_brush);
                     target_parameter.set_float_param("outline_offset", outline_offset);
                 }
             })
             .set_draw({
-                let property = property.clone();
                 move |item, canvas| {
-                    let property = property.lock();
                     let display_parameter = item.get_display_parameter().clone();
 
-                    let color = display_parameter
-                        .get_color_param("color")
-                        .unwrap_or(Color::TRANSPARENT);
+                    let transparent = Brush::Solid(Color::TRANSPARENT);
+                    let brush = display_parameter
+                        .get_brush_param("brush")
+                        .unwrap_or(&transparent);
                     let width = display_parameter.get_float_param("width").unwrap_or(0.0);
                     let height = display_parameter.get_float_param("height").unwrap_or(0.0);
                     let max_radius = width.min(height) / 2.0;
@@ -126,64 +185,21 @@ impl Rectangle {
                     let outline_width = display_parameter
                         .get_float_param("outline_width")
                         .unwrap_or(0.0);
-                    let outline_color = display_parameter
-                        .get_color_param("outline_color")
-                        .unwrap_or(Color::TRANSPARENT);
+                    let outline_brush = display_parameter
+                        .get_brush_param("outline_brush")
+                        .unwrap_or(&transparent);
                     let outline_offset = display_parameter
                         .get_float_param("outline_offset")
                         .unwrap_or(0.0);
-                    let rect = Rect::from_xywh(
-                        display_parameter.x() + x,
-                        display_parameter.y() + y,
-                        width,
-                        height,
-                    );
                     let layout_direction = item.get_layout_direction().get();
-                    let rrect = if layout_direction == LayoutDirection::LTR {
-                        RRect::new_rect_radii(
-                            &rect,
-                            &[
-                                Vector::new(radius_top_start, radius_top_start),
-                                Vector::new(radius_top_end, radius_top_end),
-                                Vector::new(radius_bottom_end, radius_bottom_end),
-                                Vector::new(radius_bottom_start, radius_bottom_start),
-                            ],
-                        )
-                    } else {
-                        RRect::new_rect_radii(
-                            &rect,
-                            &[
-                                Vector::new(radius_top_end, radius_top_end),
-                                Vector::new(radius_top_start, radius_top_start),
-                                Vector::new(radius_bottom_start, radius_bottom_start),
-                                Vector::new(radius_bottom_end, radius_bottom_end),
-                            ],
-                        )
-                    };
+                    let rrect = get_rrect(item);
 
-                    { 
-                        let mut clip_shape = item.get_clip_shape().lock();
-                        let new_clip_shape: Box<dyn Fn(&mut ItemData) -> Path + Send> = Box::new(
-                            move |_| {
-                                let mut path = Path::new();
-                                path.add_rrect(rrect, None);
-                                path
-                            }
-                        );
-                        *clip_shape = new_clip_shape;
-                    }
-                    
-                    let shader = property.shader.get();
                     let mut paint = skia_safe::Paint::default();
                     paint.set_anti_alias(true);
-                    paint.set_color(color);
-                    if let Some(shader) = shader {
-                        paint.set_shader(shader);
-                    }
+                    brush.apply(&mut paint, *rrect.rect());
                     canvas.draw_rrect(rrect, &paint);
                     
                     if outline_width > 0.0 {
-                        paint.set_color(outline_color);
                         paint.set_style(Style::Stroke);
                         paint.set_stroke_width(outline_width);
                         canvas.draw_rrect(
@@ -195,6 +211,8 @@ impl Rectangle {
                                     width - outline_width + outline_offset * 2.0,
                                     height - outline_width + outline_offset * 2.0,
                                 );
+                                // The brush spans the outer edge of the outline.
+                                outline_brush.apply(&mut paint, rect.with_outset((offset, offset)));
                                 let offset = offset * 2.0;
                                 if layout_direction == LayoutDirection::LTR {
                                     RRect::new_rect_radii(
@@ -233,4 +251,16 @@ impl Rectangle {
             .radius_bottom_end(radius.clone())
             .radius_bottom_start(radius)
     }
+
+    /// Paints the rectangle with a Skia shader in the coordinates of the canvas, in place of the
+    /// color.
+    #[deprecated(note = "use `brush`, a `Brush::Skia` paints a Skia shader")]
+    #[track_caller]
+    pub fn shader(self, shader: impl Into<Shared<Option<Shader>>>) -> Self {
+        let shader = shader.into();
+        let brush = Shared::from_dynamic([shader.to_observable()].into(), move || {
+            shader.get().map(Brush::Skia)
+        });
+        self.brush(brush)
+    }
 }
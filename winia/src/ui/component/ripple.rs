@@ -1,3 +1,4 @@
+use crate::core::log_target;
 use crate::impl_property_redraw;
 use crate::shared::{
     Children, Gettable, Observable, Settable, Shared, SharedAnimationTrait, SharedBool,
@@ -230,11 +231,23 @@ impl Ripple {
     /// # color = "0xff0000"
     /// borderless = true
     /// ```
+    /// Unknown colors are ignored and reported on the `winia::style` target.
+    ///
+    /// # Panics
+    /// If `string` is not valid TOML, use [`try_from_toml`](Ripple::try_from_toml) to handle it.
     pub fn from_toml(window_context: &WindowContext, string: &str) -> Self {
-        let mut ripple = Ripple::new(window_context);
-        let toml: Value = toml::from_str(string).unwrap_or_else(|err| {
+        Self::try_from_toml(window_context, string).unwrap_or_else(|err| {
             panic!("Failed to parse toml: {}", err);
-        });
+        })
+    }
+
+    /// Like [`from_toml`](Ripple::from_toml), returning the error if `string` is not valid TOML.
+    pub fn try_from_toml(
+        window_context: &WindowContext,
+        string: &str,
+    ) -> Result<Self, toml::de::Error> {
+        let toml: Value = toml::from_str(string)?;
+        let mut ripple = Ripple::new(window_context);
 
         if let Some(Value::Table(table)) = toml.get("ripple") {
             if let Some(Value::String(color)) = table.get("color") {
@@ -242,9 +255,15 @@ impl Ripple {
                     ripple = ripple.color(color);
                 } else {
                     let theme = window_context.theme();
-                    if let Some(color) = theme.lock().get_color(color) {
-                        ripple = ripple.color(*color);
-                    };
+                    let theme_color = theme.lock().get_color(color).copied();
+                    match theme_color {
+                        Some(color) => ripple = ripple.color(color),
+                        None => tracing::warn!(
+                            target: log_target::STYLE,
+                            %color,
+                            "The ripple color is neither a color nor a theme key"
+                        ),
+                    }
                 }
             }
             if let Some(Value::Boolean(borderless)) = table.get("borderless") {
@@ -252,7 +271,7 @@ impl Ripple {
             }
         }
 
-        ripple
+        Ok(ripple)
     }
 
     pub fn borderless(self, borderless: impl Into<Shared<bool>>) -> Self {
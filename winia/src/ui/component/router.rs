@@ -0,0 +1,625 @@
+use crate::core::{log_target, next_id};
+use crate::shared::{Observable, Shared, Store};
+use crate::ui::app::WindowContext;
+use crate::ui::component::{PageFn, PageManager, PageStack, PageTransition};
+use crate::ui::layout::StackExt;
+use crate::ui::Item;
+use proc_macro::item;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The arguments a page was opened with: the parameters of its route, the query of the location
+/// and an optional value passed with [`Navigator::push_with`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteArguments {
+    location: String,
+    route: String,
+    params: HashMap<String, String>,
+    query: HashMap<String, String>,
+    value: Option<Value>,
+}
+
+impl RouteArguments {
+    /// The location the page was opened with, e.g. `/users/42?tab=posts`.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// The pattern of the matched route, e.g. `/users/:id`.
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    /// Returns the path parameter `name`, e.g. `id` for the route `/users/:id`, parsed as `T`.
+    pub fn param<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.params.get(name)?.parse().ok()
+    }
+
+    /// Returns the query parameter `name` parsed as `T`.
+    pub fn query<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.query.get(name)?.parse().ok()
+    }
+
+    /// Returns the value passed with [`Navigator::push_with`].
+    pub fn value<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.value.clone()?).ok()
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 3;
+                    }
+                    None => {
+                        decoded.push(b'%');
+                        index += 1;
+                    }
+                }
+            }
+            b'+' => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Splits a location into its decoded path segments and query parameters.
+fn parse_location(location: &str) -> (Vec<String>, HashMap<String, String>) {
+    let location = location.split('#').next().unwrap_or_default();
+    let (path, query) = location.split_once('?').unwrap_or((location, ""));
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect();
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    (segments, query)
+}
+
+enum Segment {
+    Static(String),
+    /// `:name` matches one segment.
+    Param(String),
+    /// `*name` matches the rest of the path, it must be the last segment.
+    Rest(String),
+}
+
+type RouteBuilder = Arc<dyn Fn(&WindowContext, &RouteArguments, Navigator) -> Item + Send + Sync>;
+
+struct Route {
+    pattern: String,
+    segments: Vec<Segment>,
+    builder: RouteBuilder,
+}
+
+impl Route {
+    fn matches(&self, segments: &[String]) -> Option<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Static(name) => {
+                    if segments.get(index) != Some(name) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    params.insert(name.clone(), segments.get(index)?.clone());
+                }
+                Segment::Rest(name) => {
+                    params.insert(name.clone(), segments[index.min(segments.len())..].join("/"));
+                    return Some(params);
+                }
+            }
+        }
+        (segments.len() == self.segments.len()).then_some(params)
+    }
+}
+
+/// The routes of a [`Router`].
+///
+/// Patterns are paths whose segments are either literal, `:name` for a parameter
+/// or `*name` for the rest of the path. Routes are matched in the order they were added.
+pub struct Routes {
+    initial_location: String,
+    routes: Vec<Route>,
+    not_found: Option<RouteBuilder>,
+    transition: PageTransition,
+}
+
+impl Routes {
+    /// `initial_location` is opened like a deep link, see [`Navigator::open`].
+    pub fn new(initial_location: impl Into<String>) -> Self {
+        Self {
+            initial_location: initial_location.into(),
+            routes: Vec::new(),
+            not_found: None,
+            transition: PageTransition::default(),
+        }
+    }
+
+    pub fn route(
+        mut self,
+        pattern: impl Into<String>,
+        builder: impl Fn(&WindowContext, &RouteArguments, Navigator) -> Item + Send + Sync + 'static,
+    ) -> Self {
+        let pattern = pattern.into();
+        let segments = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                if let Some(name) = segment.strip_prefix(':') {
+                    Segment::Param(name.to_string())
+                } else if let Some(name) = segment.strip_prefix('*') {
+                    Segment::Rest(name.to_string())
+                } else {
+                    Segment::Static(segment.to_string())
+                }
+            })
+            .collect();
+        self.routes.push(Route {
+            pattern,
+            segments,
+            builder: Arc::new(builder),
+        });
+        self
+    }
+
+    /// The page shown for locations that match no route.
+    pub fn not_found(
+        mut self,
+        builder: impl Fn(&WindowContext, &RouteArguments, Navigator) -> Item + Send + Sync + 'static,
+    ) -> Self {
+        self.not_found = Some(Arc::new(builder));
+        self
+    }
+
+    pub fn transition(mut self, transition: PageTransition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    fn resolve(&self, location: &str, value: Option<Value>) -> Option<(RouteArguments, RouteBuilder)> {
+        let (segments, query) = parse_location(location);
+        let matched = self.routes.iter().find_map(|route| {
+            let params = route.matches(&segments)?;
+            Some((route.pattern.clone(), params, route.builder.clone()))
+        });
+        let (route, params, builder) = match matched {
+            Some(matched) => matched,
+            None => (String::new(), HashMap::new(), self.not_found.clone()?),
+        };
+        Some((
+            RouteArguments {
+                location: location.to_string(),
+                route,
+                params,
+                query,
+                value,
+            },
+            builder,
+        ))
+    }
+}
+
+struct Entry {
+    arguments: RouteArguments,
+    builder: RouteBuilder,
+    on_result: Option<Box<dyn FnOnce(Option<Value>) + Send>>,
+}
+
+impl Entry {
+    fn finish(self, result: Option<Value>) {
+        if let Some(on_result) = self.on_result {
+            on_result(result);
+        }
+    }
+}
+
+/// Navigates between the routes of a [`Router`].
+///
+/// The navigator keeps the back stack of the router: one entry per page,
+/// each with the [`RouteArguments`] of the page.
+#[derive(Clone)]
+pub struct Navigator {
+    routes: Arc<Routes>,
+    entries: Shared<Vec<Entry>>,
+    page_manager: Shared<Option<PageManager>>,
+}
+
+impl Navigator {
+    fn new(routes: Routes) -> Self {
+        Self {
+            routes: Arc::new(routes),
+            entries: Shared::from(Vec::new()),
+            page_manager: Shared::from(None),
+        }
+    }
+
+    fn page_fn(&self, entry: &Entry) -> PageFn {
+        let navigator = self.clone();
+        let arguments = entry.arguments.clone();
+        let builder = entry.builder.clone();
+        Box::new(move |window_context, _| builder(window_context, &arguments, navigator.clone()))
+    }
+
+    fn entry(&self, location: &str, value: Option<Value>) -> Option<Entry> {
+        match self.routes.resolve(location, value) {
+            Some((arguments, builder)) => Some(Entry {
+                arguments,
+                builder,
+                on_result: None,
+            }),
+            None => {
+                tracing::warn!(target: log_target::ROUTER, %location, "No route matches");
+                None
+            }
+        }
+    }
+
+    /// The entries for a deep link: every prefix of the path that matches a route, then the location itself.
+    fn deep_link_entries(&self, location: &str) -> Vec<Entry> {
+        let (segments, _) = parse_location(location);
+        let mut entries: Vec<Entry> = (0..segments.len())
+            .filter_map(|length| {
+                let prefix = format!("/{}", segments[..length].join("/"));
+                let (segments, _) = parse_location(&prefix);
+                self.routes
+                    .routes
+                    .iter()
+                    .any(|route| route.matches(&segments).is_some())
+                    .then(|| self.entry(&prefix, None))
+                    .flatten()
+            })
+            .collect();
+        entries.extend(self.entry(location, None));
+        entries
+    }
+
+    /// Builds the first page of the page stack of the router.
+    fn attach(&self, window_context: &WindowContext, page_manager: PageManager) -> Item {
+        page_manager.set_back_handler({
+            let navigator = self.clone();
+            move || navigator.pop()
+        });
+        self.page_manager.lock().replace(page_manager.clone());
+        let location = self.routes.initial_location.clone();
+        let entries = self.deep_link_entries(&location);
+        let first_page = match entries.first() {
+            Some(entry) => self.page_fn(entry)(window_context, page_manager.clone()),
+            None => window_context.stack(crate::shared::Children::new()).item(),
+        };
+        // The pages above the first one of a deep link are pushed at once.
+        let page_fns: Vec<PageFn> = entries.iter().skip(1).map(|entry| self.page_fn(entry)).collect();
+        *self.entries.lock() = entries;
+        self.entries.notify();
+        for page_fn in page_fns {
+            page_manager.push_with_transition(PageTransition::None, page_fn);
+        }
+        first_page
+    }
+
+    fn with_page_manager(&self, f: impl FnOnce(&PageManager)) {
+        if let Some(page_manager) = self.page_manager.lock().as_ref() {
+            f(page_manager);
+        }
+    }
+
+    fn push_entry(&self, entry: Entry, replace: bool) {
+        let page_fn = self.page_fn(&entry);
+        let replaced = {
+            let mut entries = self.entries.lock();
+            let replaced = if replace { entries.pop() } else { None };
+            entries.push(entry);
+            replaced
+        };
+        self.entries.notify();
+        self.with_page_manager(|page_manager| {
+            if replace {
+                page_manager.replace(page_fn);
+            } else {
+                page_manager.push(page_fn);
+            }
+        });
+        // Called once the page stack is updated, `on_result` may navigate again.
+        if let Some(replaced) = replaced {
+            replaced.finish(None);
+        }
+    }
+
+    /// Opens the page of the route that matches `location`, e.g. `/users/42?tab=posts`.
+    pub fn push(&self, location: &str) {
+        if let Some(entry) = self.entry(location, None) {
+            self.push_entry(entry, false);
+        }
+    }
+
+    /// Opens the page of `location` and passes `value` to it, see [`RouteArguments::value`].
+    pub fn push_with<T: Serialize>(&self, location: &str, value: &T) {
+        let value = serde_json::to_value(value).ok();
+        if let Some(entry) = self.entry(location, value) {
+            self.push_entry(entry, false);
+        }
+    }
+
+    /// Opens the page of `location` and calls `on_result` when it's popped, with the value passed to
+    /// [`pop_with_result`](Navigator::pop_with_result), or `None` if it was popped without a result.
+    pub fn push_for_result<R: DeserializeOwned>(
+        &self,
+        location: &str,
+        on_result: impl FnOnce(Option<R>) + Send + 'static,
+    ) {
+        if let Some(mut entry) = self.entry(location, None) {
+            entry.on_result = Some(Box::new(move |result| {
+                on_result(result.and_then(|result| serde_json::from_value(result).ok()));
+            }));
+            self.push_entry(entry, false);
+        }
+    }
+
+    /// Replaces the current page with the page of `location`.
+    pub fn replace(&self, location: &str) {
+        if let Some(entry) = self.entry(location, None) {
+            self.push_entry(entry, true);
+        }
+    }
+
+    fn pop_entry(&self, result: Option<Value>) -> bool {
+        let popped = {
+            let mut entries = self.entries.lock();
+            if entries.len() > 1 {
+                entries.pop()
+            } else {
+                None
+            }
+        };
+        let Some(popped) = popped else {
+            return false;
+        };
+        self.entries.notify();
+        // Called once the page stack is updated, `on_result` may navigate again.
+        self.with_page_manager(|page_manager| page_manager.pop());
+        popped.finish(result);
+        true
+    }
+
+    /// Closes the current page. Returns `false` if it's the first page, which can't be popped.
+    pub fn pop(&self) -> bool {
+        self.pop_entry(None)
+    }
+
+    /// Closes the current page and passes `result` to the page that opened it with
+    /// [`push_for_result`](Navigator::push_for_result).
+    pub fn pop_with_result<R: Serialize>(&self, result: &R) -> bool {
+        self.pop_entry(serde_json::to_value(result).ok())
+    }
+
+    /// Closes pages until `predicate` returns `true` for the current page.
+    /// Nothing happens if no page matches.
+    pub fn pop_until(&self, predicate: impl Fn(&RouteArguments) -> bool) {
+        let popped = {
+            let mut entries = self.entries.lock();
+            let Some(index) = entries.iter().rposition(|entry| predicate(&entry.arguments)) else {
+                return;
+            };
+            entries.split_off(index + 1)
+        };
+        if popped.is_empty() {
+            return;
+        }
+        self.entries.notify();
+        let depth = self.entries.lock().len();
+        self.with_page_manager(|page_manager| page_manager.pop_to(depth));
+        for entry in popped.into_iter().rev() {
+            entry.finish(None);
+        }
+    }
+
+    /// Opens `location` as a deep link: the back stack is replaced by the pages of every prefix
+    /// of the path that matches a route, e.g. `/`, `/users` and `/users/42` for `/users/42`.
+    pub fn open(&self, location: &str) {
+        let entries = self.deep_link_entries(location);
+        if !entries.is_empty() {
+            self.reset(entries);
+        }
+    }
+
+    fn reset(&self, entries: Vec<Entry>) {
+        let page_fns = entries.iter().map(|entry| self.page_fn(entry)).collect();
+        let previous = std::mem::replace(&mut *self.entries.lock(), entries);
+        self.entries.notify();
+        self.with_page_manager(|page_manager| page_manager.reset(page_fns));
+        for entry in previous.into_iter().rev() {
+            entry.finish(None);
+        }
+    }
+
+    pub fn current(&self) -> Option<RouteArguments> {
+        self.entries.lock().last().map(|entry| entry.arguments.clone())
+    }
+
+    pub fn depth(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn can_pop(&self) -> bool {
+        self.depth() > 1
+    }
+
+    /// Returns the back stack as a list of `{ "location": ..., "value": ... }` objects.
+    /// Result callbacks are not saved.
+    pub fn back_stack(&self) -> Value {
+        let entries = self.entries.lock();
+        Value::Array(
+            entries
+                .iter()
+                .map(|entry| {
+                    let mut object = Map::new();
+                    object.insert("location".to_string(), Value::from(entry.arguments.location.clone()));
+                    if let Some(value) = &entry.arguments.value {
+                        object.insert("value".to_string(), value.clone());
+                    }
+                    Value::Object(object)
+                })
+                .collect(),
+        )
+    }
+
+    /// Restores a back stack saved with [`back_stack`](Navigator::back_stack).
+    /// Entries whose location no longer matches a route are skipped.
+    /// Returns `false` and leaves the back stack unchanged if no entry could be restored.
+    pub fn restore_back_stack(&self, back_stack: &Value) -> bool {
+        let Some(saved) = back_stack.as_array() else {
+            return false;
+        };
+        let entries: Vec<Entry> = saved
+            .iter()
+            .filter_map(|saved| {
+                let location = saved.get("location")?.as_str()?;
+                self.entry(location, saved.get("value").cloned())
+            })
+            .collect();
+        if entries.is_empty() {
+            return false;
+        }
+        self.reset(entries);
+        true
+    }
+
+    /// Restores the back stack saved under `key` in `store`, and saves it there whenever it changes.
+    pub fn persist(&self, store: &Store, key: &str) {
+        if let Some(back_stack) = store.get::<Value>(key) {
+            self.restore_back_stack(&back_stack);
+        }
+        let navigator = self.clone();
+        let store = store.clone();
+        let key = key.to_string();
+        self.entries
+            .clone()
+            .add_observer(
+                next_id(),
+                Box::new(move || {
+                    if let Err(error) = store.set(&key, &navigator.back_stack()) {
+                        tracing::error!(
+                            target: log_target::PERSISTENCE,
+                            %key,
+                            %error,
+                            "Failed to persist the history"
+                        );
+                    }
+                }),
+            )
+            .drop();
+    }
+}
+
+/// A [`PageStack`] whose pages are opened by location through a [`Navigator`].
+///
+/// Escape and the back button of the mouse pop the current page.
+#[item(routes: Routes)]
+pub struct Router {
+    item: Item,
+    navigator: Navigator,
+}
+
+impl Router {
+    pub fn new(window_context: &WindowContext, routes: Routes) -> Self {
+        let transition = routes.transition.clone();
+        let navigator = Navigator::new(routes);
+        let item = PageStack::new(window_context, {
+            let navigator = navigator.clone();
+            move |window_context, page_manager| navigator.attach(window_context, page_manager)
+        })
+        .transition(transition)
+        .item();
+        Self { item, navigator }
+    }
+
+    pub fn navigator(&self) -> Navigator {
+        self.navigator.clone()
+    }
+}
+
+#[cfg(test)]
+mod router_test {
+    use super::{parse_location, percent_decode, Routes};
+    use std::collections::HashMap;
+
+    fn routes() -> Routes {
+        Routes::new("/")
+            .route("/", |_, _, _| unreachable!())
+            .route("/users", |_, _, _| unreachable!())
+            .route("/users/:id", |_, _, _| unreachable!())
+            .route("/files/*path", |_, _, _| unreachable!())
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let (segments, query) = parse_location("/users/42/?tab=posts&q=a%26b#top");
+        assert_eq!(segments, vec!["users", "42"]);
+        assert_eq!(query.get("tab").map(String::as_str), Some("posts"));
+        assert_eq!(query.get("q").map(String::as_str), Some("a&b"));
+    }
+
+    #[test]
+    fn test_static_and_param_routes() {
+        let routes = routes();
+        let (arguments, _) = routes.resolve("/users", None).unwrap();
+        assert_eq!(arguments.route(), "/users");
+        let (arguments, _) = routes.resolve("/users/42?tab=posts", None).unwrap();
+        assert_eq!(arguments.route(), "/users/:id");
+        assert_eq!(arguments.param::<u32>("id"), Some(42));
+        assert_eq!(arguments.query::<String>("tab"), Some("posts".to_string()));
+        assert!(routes.resolve("/users/42/posts", None).is_none());
+    }
+
+    #[test]
+    fn test_rest_route() {
+        let routes = routes();
+        let (arguments, _) = routes.resolve("/files/a/b.txt", None).unwrap();
+        assert_eq!(arguments.route(), "/files/*path");
+        assert_eq!(arguments.param::<String>("path"), Some("a/b.txt".to_string()));
+        let (arguments, _) = routes.resolve("/files", None).unwrap();
+        assert_eq!(arguments.param::<String>("path"), Some(String::new()));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let routes = routes().not_found(|_, _, _| unreachable!());
+        let (arguments, _) = routes.resolve("/missing", None).unwrap();
+        assert_eq!(arguments.route(), "");
+        assert_eq!(arguments.params, HashMap::new());
+    }
+}
@@ -1,9 +1,9 @@
 use crate::core::next_id;
-use crate::shared::{Gettable, Settable, Shared, SharedBool, SharedColor, SharedF32};
+use crate::shared::{Gettable, Settable, Shared, SharedBool, SharedF32};
 use crate::ui::animation::{AnimationExt, Target};
 use crate::ui::app::WindowContext;
 use crate::ui::component::RectangleExt;
-use crate::ui::item::{Alignment, ItemState, Pointer, PointerState, Size};
+use crate::ui::item::{Alignment, Pointer, PointerState, Size};
 use crate::ui::layout::StackExt;
 use crate::ui::theme::color;
 use crate::ui::Item;
@@ -64,8 +64,6 @@ impl Switch {
         let track_color = Shared::from_static(track_start_color);
         let track_outline_color = Shared::from_static(track_outline_start_color);
         
-        let focus_indicator_color = SharedColor::from(Color::TRANSPARENT);
-        
         let progress = SharedF32::from_static(0.0);
         let pressed = SharedBool::from_static(false);
         progress.add_specific_observer(
@@ -109,9 +107,8 @@ impl Switch {
             .background(
                 w.rectangle(&track_color).outline_color(&track_outline_color).outline_width(2).radius(18).item()
             )
-            .foreground(
-                w.rectangle(Color::TRANSPARENT).outline_color(&focus_indicator_color).outline_width(3).outline_offset(5).radius(18).item()
-            );
+            .focusable(true)
+            .focus_ring_radius(f32::MAX);
         
         let item_id = item.data().get_id();
 
@@ -196,17 +193,6 @@ impl Switch {
                     }
                 }
             }
-        }).set_focus_next(|item|{
-            if !item.get_enabled().get() {
-                return true;
-            }
-            let focused = item.get_focused();
-            if !focused.get() {
-                focused.set(true);
-                false
-            } else {
-                true
-            }
         }).set_keyboard_input({
             clone!(selected);
             move |item, input| {
@@ -234,25 +220,6 @@ impl Switch {
             }
         });
         
-        item.data().get_state().add_specific_observer(
-            item_id,
-            {
-                let focus_indicator_color = focus_indicator_color.clone();
-                let theme = theme.clone();
-                move |state| {
-                    if let ItemState::Focused = state {
-                        focus_indicator_color.set(
-                            *theme.lock().get_color(color::SECONDARY).unwrap()
-                        );
-                    } else {
-                        focus_indicator_color.set(
-                            Color::TRANSPARENT
-                        );
-                    }
-                }
-            }
-        );
-        
         selected.notify();
         Self {
             item,
@@ -4,15 +4,14 @@ use crate::shared::{Children, Gettable, LocalShared, Observable, Settable, Share
 use crate::text::{Paragraph, StyledText};
 use crate::ui::app::WindowContext;
 use crate::ui::item::{
-    ClickSource, DisplayParameter, HorizontalAlignment, ImeAction, LayoutDirection, LogicalX,
+    Brush, ClickSource, DisplayParameter, HorizontalAlignment, ImeAction, LayoutDirection, LogicalX,
     MeasureMode, Orientation, PointerState, VerticalAlignment,
 };
 use crate::ui::theme::color;
 use crate::ui::Item;
-use crate::impl_property_redraw;
 use proc_macro::item;
 use skia_safe::textlayout::{TextAlign, TextStyle};
-use skia_safe::{Canvas, Color, Drawable, Paint, PictureRecorder, Rect};
+use skia_safe::{BlendMode, Canvas, Color, Drawable, Paint, PictureRecorder, Rect};
 use std::cmp::{Ordering};
 use std::ops::{Not, Range};
 use std::string::ToString;
@@ -21,6 +20,7 @@ use arboard::Clipboard;
 use clonelet::clone;
 use winit::dpi::Size;
 use winit::event::{ElementState, MouseButton};
+use winit::window::CursorIcon;
 use winit::keyboard::{Key, NamedKey};
 
 pub mod text_style {
@@ -36,6 +36,8 @@ pub struct TextProperty {
     editable: SharedBool,
     selectable: SharedBool,
     color: SharedColor,
+    /// Paints all of the text in place of its colors.
+    foreground: Shared<Option<Brush>>,
     font_size: SharedF32,
 }
 
@@ -123,6 +125,7 @@ impl Text {
                 .get_color(color::ON_SURFACE)
                 .unwrap_or(&Color::BLACK))
                 .into(),
+            foreground: None.into(),
             font_size: 24.0.into(),
         });
 
@@ -263,11 +266,25 @@ impl Text {
                                 Rect::from_wh(text_layout.width(), text_layout.height()),
                                 None,
                             );
-                            text_layout.draw(
-                                canvas,
-                                0.0,
-                                0.0,
-                            );
+                            if let Some(foreground) = property.foreground.get() {
+                                // The brush is drawn over the glyphs and keeps only what covers them.
+                                let bounds =
+                                    Rect::from_wh(text_layout.width(), text_layout.height());
+                                canvas.save_layer_alpha_f(bounds, 1.0);
+                                text_layout.draw(canvas, 0.0, 0.0);
+                                let mut paint = Paint::default();
+                                paint.set_anti_alias(true);
+                                paint.set_blend_mode(BlendMode::SrcIn);
+                                foreground.apply(&mut paint, bounds);
+                                canvas.draw_rect(bounds, &paint);
+                                canvas.restore();
+                            } else {
+                                text_layout.draw(
+                                    canvas,
+                                    0.0,
+                                    0.0,
+                                );
+                            }
                             let picture = recorder.finish_recording_as_drawable().unwrap();
                             let mut draw_cache = context.draw_cache.lock();
                             draw_cache.add(picture, item.get_target_parameter());
@@ -540,7 +557,7 @@ impl Text {
                                 Paint::default().set_anti_alias(true).set_color(0xffff0000),
                             );
                             if item.get_focused().get() {
-                                item.get_window_context().window.lock().set_ime_cursor_area(
+                                item.get_window_context().window().set_ime_cursor_area(
                                     Position::Logical(LogicalPosition::new(x as f64, y as f64)),
                                     Size::Logical(LogicalSize::new(0.0, 0.0)),
                                 )
@@ -689,7 +706,7 @@ impl Text {
                             start_index = index;
                             selection.start = index;
                             selection.end = index;
-                            item.get_window_context().request_redraw();
+                            item.get_window_context().request_repaint(item.get_id());
                             *context.show_cursor.lock() = true;
                         }
                         PointerState::Moved => {
@@ -707,7 +724,7 @@ impl Text {
                                 _=> {}
                             }
 
-                            item.get_window_context().request_redraw();
+                            item.get_window_context().request_repaint(item.get_id());
                             *context.show_cursor.lock() = true;
                         }
                         PointerState::Ended => {}
@@ -747,7 +764,7 @@ impl Text {
                     if id == item.get_id() {
                         let mut show_cursor = context.show_cursor.lock();
                         *show_cursor = show_cursor.not();
-                        item.get_window_context().request_redraw();
+                        item.get_window_context().request_repaint(item.get_id());
                         if item.get_focused().get() {
                             item.get_window_context()
                                 .create_timer(item.get_id(), Duration::from_millis(500));
@@ -806,6 +823,32 @@ impl Text {
                 });
         }
 
+        // The cursor already shows where the focus is.
+        item.data().set_focus_ring(false);
+
+        // `editable` can be replaced, so the cursor icon follows it while the cursor moves.
+        // An icon set with `cursor_icon` is left alone.
+        let text_cursor_icon = Shared::from(CursorIcon::Text);
+        let cursor_move = item.data().get_cursor_move();
+        item.data().set_cursor_move({
+            let property = property.clone();
+            move |item, event| {
+                cursor_move.lock()(item, event);
+                let is_own_icon = item
+                    .get_cursor_icon()
+                    .is_none_or(|cursor_icon| cursor_icon.id() == text_cursor_icon.id());
+                if !is_own_icon {
+                    return;
+                }
+                let editable = property.lock().editable.get();
+                if editable && item.get_cursor_icon().is_none() {
+                    item.set_cursor_icon(text_cursor_icon.clone());
+                } else if !editable {
+                    item.clear_cursor_icon();
+                }
+            }
+        });
+
         Self {
             item,
             property,
@@ -863,6 +906,31 @@ impl Text {
         self
     }
 
+    pub fn foreground(self, foreground: impl Into<Shared<Option<Brush>>>) -> Self {
+        {
+            let id = self.item.data().get_id();
+            let mut property = self.property.lock();
+            property.foreground.remove_observer(id);
+
+            let text_context = self.text_context.clone();
+            let event_loop_proxy = self
+                .item
+                .data()
+                .get_window_context()
+                .event_loop_proxy()
+                .clone();
+            property.foreground = foreground.into();
+            property.foreground.add_observer(
+                id,
+                Box::new(move || {
+                    text_context.is_text_changed.set(true);
+                    event_loop_proxy.request_layout();
+                }),
+            );
+        }
+        self
+    }
+
     pub fn font_size(self, font_size: impl Into<SharedF32>) -> Self {
         {
             let id = self.item.data().get_id();
@@ -891,7 +959,31 @@ impl Text {
 
 // impl_property_layout!(Text, color, SharedColor);
 // impl_property_layout!(Text, font_size, SharedF32);
-impl_property_redraw!(Text, editable, SharedBool);
+
+impl Text {
+    /// Only editable text takes part in keyboard focus navigation, so `focusable` follows it.
+    #[track_caller]
+    pub fn editable(self, editable: impl Into<SharedBool>) -> Self {
+        let id = self.item.data().get_id();
+        let location = std::panic::Location::caller();
+        {
+            let mut property = self.property.lock();
+            property.editable.remove_observer(id);
+            let event_loop_proxy = self.item.data().get_window_context().event_loop_proxy().clone();
+            let focusable = self.item.data().get_focusable().clone();
+            property.editable = editable.into();
+            focusable.set(property.editable.get());
+            property
+                .editable
+                .add_specific_observer(id, move |editable: &mut bool| {
+                    focusable.set(*editable);
+                    event_loop_proxy.request_repaint_from(id, location);
+                });
+        }
+        self.property.notify();
+        self
+    }
+}
 
 fn get_text_style(property: &TextProperty) -> TextStyle {
     let color = property.color.get();
@@ -0,0 +1,710 @@
+use crate::core::log_target;
+use crate::shared::Shared;
+use crate::ui::animation::interpolate_color;
+use skia_safe::gradient_shader::GradientShaderColors;
+use skia_safe::prelude::NativeAccess;
+use skia_safe::{
+    shaders, Blender, Color, Data, Image, Matrix, Paint, Point, Rect, RuntimeEffect,
+    SamplingOptions, Shader, TileMode,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    /// Compiled SkSL by source, `None` for sources that failed to compile so they are reported once.
+    static RUNTIME_EFFECTS: RefCell<HashMap<String, Option<RuntimeEffect>>> = RefCell::new(HashMap::new());
+}
+
+fn runtime_effect(sksl: &str) -> Option<RuntimeEffect> {
+    RUNTIME_EFFECTS.with_borrow_mut(|effects| {
+        if let Some(effect) = effects.get(sksl) {
+            return effect.clone();
+        }
+        let effect = match RuntimeEffect::make_for_shader(sksl, None) {
+            Ok(effect) => Some(effect),
+            Err(error) => {
+                tracing::warn!(target: log_target::DRAW, %error, "Failed to compile the SkSL of a brush");
+                None
+            }
+        };
+        effects.insert(sksl.to_string(), effect.clone());
+        effect
+    })
+}
+
+/// How an area is painted: a flat color, a gradient, an image or a shader.
+///
+/// Points and radii of gradients are fractions of the painted bounds, `(0, 0)` is the top left
+/// and `(1, 1)` the bottom right corner, so the same brush fits items of any size.
+#[derive(Clone, Debug)]
+pub enum Brush {
+    Solid(Color),
+    Linear {
+        start: Point,
+        end: Point,
+        colors: Vec<Color>,
+        /// The positions of the colors from 0.0 to 1.0, evenly spaced if `None`.
+        positions: Option<Vec<f32>>,
+    },
+    Radial {
+        center: Point,
+        /// A fraction of the larger side of the bounds.
+        radius: f32,
+        colors: Vec<Color>,
+        positions: Option<Vec<f32>>,
+    },
+    Sweep {
+        center: Point,
+        colors: Vec<Color>,
+        positions: Option<Vec<f32>>,
+    },
+    /// An image at its own size, its top left corner at the top left of the bounds.
+    Image {
+        image: Image,
+        tile_x: TileMode,
+        tile_y: TileMode,
+    },
+    /// A shader written in SkSL, its coordinates start at the top left of the bounds. The float
+    /// uniforms of the shader are filled from `uniforms` in the order they are declared, except
+    /// for a `float2 size` uniform, which receives the size of the bounds and takes nothing from
+    /// `uniforms`.
+    Shader {
+        sksl: String,
+        uniforms: Vec<f32>,
+    },
+    /// A Skia shader painted as it is, in the coordinates of the canvas rather than the bounds.
+    Skia(Shader),
+    /// A crossfade from one brush to another, animations between brushes that can't be
+    /// interpolated stop by stop paint it.
+    Mix {
+        from: Box<Brush>,
+        to: Box<Brush>,
+        amount: f32,
+    },
+}
+
+impl Brush {
+    pub fn linear(start: impl Into<Point>, end: impl Into<Point>, colors: Vec<Color>) -> Self {
+        Brush::Linear {
+            start: start.into(),
+            end: end.into(),
+            colors,
+            positions: None,
+        }
+    }
+
+    /// A gradient from the left to the right side of the bounds.
+    pub fn horizontal(colors: Vec<Color>) -> Self {
+        Self::linear((0.0, 0.5), (1.0, 0.5), colors)
+    }
+
+    /// A gradient from the top to the bottom of the bounds.
+    pub fn vertical(colors: Vec<Color>) -> Self {
+        Self::linear((0.5, 0.0), (0.5, 1.0), colors)
+    }
+
+    pub fn radial(center: impl Into<Point>, radius: f32, colors: Vec<Color>) -> Self {
+        Brush::Radial {
+            center: center.into(),
+            radius,
+            colors,
+            positions: None,
+        }
+    }
+
+    pub fn sweep(center: impl Into<Point>, colors: Vec<Color>) -> Self {
+        Brush::Sweep {
+            center: center.into(),
+            colors,
+            positions: None,
+        }
+    }
+
+    /// Repeats the image in both directions.
+    pub fn image(image: Image) -> Self {
+        Brush::Image {
+            image,
+            tile_x: TileMode::Repeat,
+            tile_y: TileMode::Repeat,
+        }
+    }
+
+    pub fn shader(sksl: impl Into<String>, uniforms: Vec<f32>) -> Self {
+        Brush::Shader {
+            sksl: sksl.into(),
+            uniforms,
+        }
+    }
+
+    /// Sets the positions of the colors of a gradient, other brushes are returned unchanged.
+    pub fn positions(mut self, stops: Vec<f32>) -> Self {
+        match &mut self {
+            Brush::Linear { positions, .. }
+            | Brush::Radial { positions, .. }
+            | Brush::Sweep { positions, .. } => *positions = Some(stops),
+            _ => {}
+        }
+        self
+    }
+
+    fn is_gradient(&self) -> bool {
+        matches!(
+            self,
+            Brush::Linear { .. } | Brush::Radial { .. } | Brush::Sweep { .. }
+        )
+    }
+
+    /// The same gradient in a single color, the start of a transition from a solid brush.
+    fn with_color(&self, color: Color) -> Brush {
+        let mut brush = self.clone();
+        match &mut brush {
+            Brush::Linear {
+                colors, positions, ..
+            }
+            | Brush::Radial {
+                colors, positions, ..
+            }
+            | Brush::Sweep {
+                colors, positions, ..
+            } => {
+                *colors = vec![color];
+                *positions = None;
+            }
+            _ => {}
+        }
+        brush
+    }
+
+    pub fn to_shader(&self, bounds: Rect) -> Option<Shader> {
+        let point = |point: &Point| {
+            Point::new(
+                bounds.left + point.x * bounds.width(),
+                bounds.top + point.y * bounds.height(),
+            )
+        };
+        match self {
+            Brush::Solid(color) => Some(shaders::color(*color)),
+            Brush::Linear {
+                start,
+                end,
+                colors,
+                positions,
+            } => Shader::linear_gradient(
+                (point(start), point(end)),
+                GradientShaderColors::Colors(colors),
+                positions.as_deref(),
+                TileMode::Clamp,
+                None,
+                None,
+            ),
+            Brush::Radial {
+                center,
+                radius,
+                colors,
+                positions,
+            } => Shader::radial_gradient(
+                point(center),
+                radius * bounds.width().max(bounds.height()),
+                GradientShaderColors::Colors(colors),
+                positions.as_deref(),
+                TileMode::Clamp,
+                None,
+                None,
+            ),
+            Brush::Sweep {
+                center,
+                colors,
+                positions,
+            } => Shader::sweep_gradient(
+                point(center),
+                GradientShaderColors::Colors(colors),
+                positions.as_deref(),
+                TileMode::Clamp,
+                None,
+                None,
+                None,
+            ),
+            Brush::Image {
+                image,
+                tile_x,
+                tile_y,
+            } => image.to_shader(
+                (*tile_x, *tile_y),
+                SamplingOptions::default(),
+                &Matrix::translate((bounds.left, bounds.top)),
+            ),
+            Brush::Shader { sksl, uniforms } => {
+                let effect = runtime_effect(sksl)?;
+                let mut data = vec![0u8; effect.uniform_size()];
+                let mut values = uniforms.iter();
+                for uniform in effect.uniforms() {
+                    let offset = uniform.offset();
+                    let Some(bytes) = data.get_mut(offset..offset + uniform.size_in_bytes()) else {
+                        continue;
+                    };
+                    if uniform.name() == "size" && bytes.len() == 8 {
+                        bytes[..4].copy_from_slice(&bounds.width().to_ne_bytes());
+                        bytes[4..].copy_from_slice(&bounds.height().to_ne_bytes());
+                        continue;
+                    }
+                    for (bytes, value) in bytes.chunks_exact_mut(4).zip(values.by_ref()) {
+                        bytes.copy_from_slice(&value.to_ne_bytes());
+                    }
+                }
+                effect.make_shader(
+                    Data::new_copy(&data),
+                    &[],
+                    &Matrix::translate((bounds.left, bounds.top)),
+                )
+            }
+            Brush::Skia(shader) => Some(shader.clone()),
+            Brush::Mix { from, to, amount } => {
+                let amount = amount.clamp(0.0, 1.0);
+                let blender = Blender::arithmetic(0.0, amount, 1.0 - amount, 0.0, true)?;
+                Some(shaders::blend(
+                    blender,
+                    from.to_shader(bounds)?,
+                    to.to_shader(bounds)?,
+                ))
+            }
+        }
+    }
+
+    /// Sets the color or the shader of the paint to paint the bounds with this brush.
+    pub fn apply(&self, paint: &mut Paint, bounds: Rect) {
+        match self {
+            Brush::Solid(color) => {
+                paint.set_shader(None);
+                paint.set_color(*color);
+            }
+            _ => {
+                paint.set_color(Color::BLACK);
+                paint.set_shader(self.to_shader(bounds));
+            }
+        }
+    }
+
+    /// The brush `progress` of the way from this brush to `end`. Gradients of the same kind, and
+    /// solid colors into gradients, are interpolated stop by stop, other brushes crossfade.
+    pub fn interpolate(&self, end: &Brush, progress: f32) -> Brush {
+        if progress <= 0.0 {
+            return self.clone();
+        }
+        if progress >= 1.0 {
+            return end.clone();
+        }
+        let lerp = |start: f32, end: f32| start + (end - start) * progress;
+        let lerp_point =
+            |start: &Point, end: &Point| Point::new(lerp(start.x, end.x), lerp(start.y, end.y));
+        match (self, end) {
+            // An interrupted crossfade goes on from the brush it was fading to, so crossfades
+            // don't nest.
+            (Brush::Mix { to, .. }, _) => to.interpolate(end, progress),
+            (Brush::Solid(start), Brush::Solid(end)) => {
+                Brush::Solid(interpolate_color(start, end, progress))
+            }
+            (Brush::Solid(color), _) if end.is_gradient() => {
+                end.with_color(*color).interpolate(end, progress)
+            }
+            (_, Brush::Solid(color)) if self.is_gradient() => {
+                self.interpolate(&self.with_color(*color), progress)
+            }
+            (
+                Brush::Linear {
+                    start: start_a,
+                    end: end_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Linear {
+                    start: start_b,
+                    end: end_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => {
+                let (colors, positions) = interpolate_stops(
+                    &stops(colors_a, positions_a),
+                    &stops(colors_b, positions_b),
+                    progress,
+                );
+                Brush::Linear {
+                    start: lerp_point(start_a, start_b),
+                    end: lerp_point(end_a, end_b),
+                    colors,
+                    positions: Some(positions),
+                }
+            }
+            (
+                Brush::Radial {
+                    center: center_a,
+                    radius: radius_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Radial {
+                    center: center_b,
+                    radius: radius_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => {
+                let (colors, positions) = interpolate_stops(
+                    &stops(colors_a, positions_a),
+                    &stops(colors_b, positions_b),
+                    progress,
+                );
+                Brush::Radial {
+                    center: lerp_point(center_a, center_b),
+                    radius: lerp(*radius_a, *radius_b),
+                    colors,
+                    positions: Some(positions),
+                }
+            }
+            (
+                Brush::Sweep {
+                    center: center_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Sweep {
+                    center: center_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => {
+                let (colors, positions) = interpolate_stops(
+                    &stops(colors_a, positions_a),
+                    &stops(colors_b, positions_b),
+                    progress,
+                );
+                Brush::Sweep {
+                    center: lerp_point(center_a, center_b),
+                    colors,
+                    positions: Some(positions),
+                }
+            }
+            (
+                Brush::Shader {
+                    sksl: sksl_a,
+                    uniforms: uniforms_a,
+                },
+                Brush::Shader {
+                    sksl: sksl_b,
+                    uniforms: uniforms_b,
+                },
+            ) if sksl_a == sksl_b && uniforms_a.len() == uniforms_b.len() => Brush::Shader {
+                sksl: sksl_b.clone(),
+                uniforms: uniforms_a
+                    .iter()
+                    .zip(uniforms_b)
+                    .map(|(a, b)| lerp(*a, *b))
+                    .collect(),
+            },
+            _ if self == end => end.clone(),
+            _ => Brush::Mix {
+                from: Box::new(self.clone()),
+                to: Box::new(end.clone()),
+                amount: progress,
+            },
+        }
+    }
+}
+
+/// The colors of a gradient with their positions.
+fn stops(colors: &[Color], positions: &Option<Vec<f32>>) -> Vec<(f32, Color)> {
+    match positions {
+        Some(positions) => positions
+            .iter()
+            .copied()
+            .zip(colors.iter().copied())
+            .collect(),
+        None => {
+            let last = colors.len().saturating_sub(1).max(1) as f32;
+            colors
+                .iter()
+                .enumerate()
+                .map(|(index, color)| (index as f32 / last, *color))
+                .collect()
+        }
+    }
+}
+
+fn color_at(stops: &[(f32, Color)], position: f32) -> Color {
+    let Some(&(first_position, first_color)) = stops.first() else {
+        return Color::TRANSPARENT;
+    };
+    if position <= first_position {
+        return first_color;
+    }
+    for window in stops.windows(2) {
+        let (start, start_color) = window[0];
+        let (end, end_color) = window[1];
+        if position <= end {
+            if end - start <= f32::EPSILON {
+                return end_color;
+            }
+            return interpolate_color(&start_color, &end_color, (position - start) / (end - start));
+        }
+    }
+    stops.last().map_or(first_color, |(_, color)| *color)
+}
+
+/// Both gradients are sampled at the positions of either of them, then the colors are
+/// interpolated position by position.
+fn interpolate_stops(
+    start: &[(f32, Color)],
+    end: &[(f32, Color)],
+    progress: f32,
+) -> (Vec<Color>, Vec<f32>) {
+    let mut positions: Vec<f32> = start
+        .iter()
+        .chain(end)
+        .map(|(position, _)| position.clamp(0.0, 1.0))
+        .collect();
+    positions.sort_by(f32::total_cmp);
+    positions.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+    let colors = positions
+        .iter()
+        .map(|position| {
+            interpolate_color(
+                &color_at(start, *position),
+                &color_at(end, *position),
+                progress,
+            )
+        })
+        .collect();
+    (colors, positions)
+}
+
+impl PartialEq for Brush {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Brush::Solid(a), Brush::Solid(b)) => a == b,
+            (
+                Brush::Linear {
+                    start: start_a,
+                    end: end_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Linear {
+                    start: start_b,
+                    end: end_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => {
+                start_a == start_b
+                    && end_a == end_b
+                    && colors_a == colors_b
+                    && positions_a == positions_b
+            }
+            (
+                Brush::Radial {
+                    center: center_a,
+                    radius: radius_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Radial {
+                    center: center_b,
+                    radius: radius_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => {
+                center_a == center_b
+                    && radius_a == radius_b
+                    && colors_a == colors_b
+                    && positions_a == positions_b
+            }
+            (
+                Brush::Sweep {
+                    center: center_a,
+                    colors: colors_a,
+                    positions: positions_a,
+                },
+                Brush::Sweep {
+                    center: center_b,
+                    colors: colors_b,
+                    positions: positions_b,
+                },
+            ) => center_a == center_b && colors_a == colors_b && positions_a == positions_b,
+            // Images are compared by identity, decoding the same file twice gives two images.
+            (
+                Brush::Image {
+                    image: image_a,
+                    tile_x: tile_x_a,
+                    tile_y: tile_y_a,
+                },
+                Brush::Image {
+                    image: image_b,
+                    tile_x: tile_x_b,
+                    tile_y: tile_y_b,
+                },
+            ) => {
+                image_a.unique_id() == image_b.unique_id()
+                    && tile_x_a == tile_x_b
+                    && tile_y_a == tile_y_b
+            }
+            (
+                Brush::Shader {
+                    sksl: sksl_a,
+                    uniforms: uniforms_a,
+                },
+                Brush::Shader {
+                    sksl: sksl_b,
+                    uniforms: uniforms_b,
+                },
+            ) => sksl_a == sksl_b && uniforms_a == uniforms_b,
+            (Brush::Skia(a), Brush::Skia(b)) => std::ptr::eq(a.native(), b.native()),
+            (
+                Brush::Mix {
+                    from: from_a,
+                    to: to_a,
+                    amount: amount_a,
+                },
+                Brush::Mix {
+                    from: from_b,
+                    to: to_b,
+                    amount: amount_b,
+                },
+            ) => from_a == from_b && to_a == to_b && amount_a == amount_b,
+            _ => false,
+        }
+    }
+}
+
+impl From<Color> for Brush {
+    fn from(color: Color) -> Self {
+        Brush::Solid(color)
+    }
+}
+
+impl From<Color> for Shared<Brush> {
+    fn from(color: Color) -> Self {
+        Shared::from(Brush::Solid(color))
+    }
+}
+
+#[cfg(test)]
+mod brush_test {
+    use super::Brush;
+    use crate::ui::animation::interpolate_color;
+    use skia_safe::{Color, Point};
+
+    const RED: Color = Color::RED;
+    const BLUE: Color = Color::BLUE;
+    const GREEN: Color = Color::GREEN;
+
+    #[test]
+    fn test_interpolate_endpoints() {
+        let start = Brush::Solid(RED);
+        let end = Brush::horizontal(vec![GREEN, BLUE]);
+        assert_eq!(start.interpolate(&end, 0.0), start);
+        assert_eq!(start.interpolate(&end, 1.0), end);
+    }
+
+    #[test]
+    fn test_interpolate_solid() {
+        assert_eq!(
+            Brush::Solid(RED).interpolate(&Brush::Solid(BLUE), 0.25),
+            Brush::Solid(interpolate_color(&RED, &BLUE, 0.25))
+        );
+    }
+
+    #[test]
+    fn test_interpolate_linear() {
+        let start = Brush::linear((0.0, 0.0), (1.0, 0.0), vec![RED, BLUE]);
+        let end = Brush::linear((0.0, 0.0), (0.0, 1.0), vec![GREEN, RED]);
+        assert_eq!(
+            start.interpolate(&end, 0.5),
+            Brush::Linear {
+                start: Point::new(0.0, 0.0),
+                end: Point::new(0.5, 0.5),
+                colors: vec![
+                    interpolate_color(&RED, &GREEN, 0.5),
+                    interpolate_color(&BLUE, &RED, 0.5),
+                ],
+                positions: Some(vec![0.0, 1.0]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_interpolate_stops() {
+        // The middle stop of the end is sampled from the start, which has no stop there.
+        let start = Brush::horizontal(vec![RED, BLUE]);
+        let end = Brush::horizontal(vec![RED, GREEN, BLUE]).positions(vec![0.0, 0.5, 1.0]);
+        let Brush::Linear {
+            colors, positions, ..
+        } = start.interpolate(&end, 0.5)
+        else {
+            panic!("a linear gradient is expected");
+        };
+        assert_eq!(positions, Some(vec![0.0, 0.5, 1.0]));
+        let middle = interpolate_color(&RED, &BLUE, 0.5);
+        assert_eq!(
+            colors,
+            vec![
+                interpolate_color(&RED, &RED, 0.5),
+                interpolate_color(&middle, &GREEN, 0.5),
+                interpolate_color(&BLUE, &BLUE, 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolate_solid_into_gradient() {
+        let end = Brush::radial((0.5, 0.5), 0.5, vec![GREEN, BLUE]);
+        assert_eq!(
+            Brush::Solid(RED).interpolate(&end, 0.5),
+            Brush::Radial {
+                center: Point::new(0.5, 0.5),
+                radius: 0.5,
+                colors: vec![
+                    interpolate_color(&RED, &GREEN, 0.5),
+                    interpolate_color(&RED, &BLUE, 0.5),
+                ],
+                positions: Some(vec![0.0, 1.0]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_interpolate_shader() {
+        let start = Brush::shader("sksl", vec![0.0, 10.0]);
+        let end = Brush::shader("sksl", vec![1.0, 20.0]);
+        assert_eq!(
+            start.interpolate(&end, 0.5),
+            Brush::shader("sksl", vec![0.5, 15.0])
+        );
+        let other = Brush::shader("other sksl", vec![1.0, 20.0]);
+        assert!(matches!(start.interpolate(&other, 0.5), Brush::Mix { .. }));
+    }
+
+    #[test]
+    fn test_interpolate_crossfade() {
+        let linear = Brush::horizontal(vec![RED, BLUE]);
+        let sweep = Brush::sweep((0.5, 0.5), vec![RED, BLUE]);
+        assert_eq!(
+            linear.interpolate(&sweep, 0.25),
+            Brush::Mix {
+                from: Box::new(linear.clone()),
+                to: Box::new(sweep.clone()),
+                amount: 0.25,
+            }
+        );
+        // An interrupted crossfade starts again from the brush it was fading to.
+        let radial = Brush::radial((0.5, 0.5), 1.0, vec![GREEN, BLUE]);
+        assert_eq!(
+            linear.interpolate(&sweep, 0.25).interpolate(&radial, 0.5),
+            Brush::Mix {
+                from: Box::new(sweep),
+                to: Box::new(radial),
+                amount: 0.5,
+            }
+        );
+    }
+}
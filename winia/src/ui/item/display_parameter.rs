@@ -1,8 +1,8 @@
-use crate::ui::item::Orientation;
-use skia_safe::Color;
+use crate::ui::item::{Brush, Orientation};
+use skia_safe::{Color, M44};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DisplayParameter {
     pub visible: bool,
     pub margin_start: f32,
@@ -33,8 +33,13 @@ pub struct DisplayParameter {
     pub skew_y: f32,
     pub skew_center_x: f32,
     pub skew_center_y: f32,
+    /// Applied after rotation, skew and scale, around the transform center.
+    pub transform: M44,
+    pub transform_center_x: f32,
+    pub transform_center_y: f32,
     pub float_params: HashMap<String, f32>,
     pub color_params: HashMap<String, Color>,
+    pub brush_params: HashMap<String, Brush>,
 }
 
 impl DisplayParameter {
@@ -68,8 +73,12 @@ impl DisplayParameter {
         self.skew_y = other.skew_y;
         self.skew_center_x = other.skew_center_x;
         self.skew_center_y = other.skew_center_y;
+        self.transform = other.transform;
+        self.transform_center_x = other.transform_center_x;
+        self.transform_center_y = other.transform_center_y;
         self.float_params = other.float_params.clone();
         self.color_params = other.color_params.clone();
+        self.brush_params = other.brush_params.clone();
     }
 
     pub fn x(&self) -> f32 {
@@ -177,6 +186,11 @@ impl DisplayParameter {
         self.skew_center_y = y;
     }
 
+    pub fn set_transform_center(&mut self, x: f32, y: f32) {
+        self.transform_center_x = x;
+        self.transform_center_y = y;
+    }
+
     pub fn set_float_param(&mut self, key: impl Into<String>, value: f32) {
         self.float_params.insert(key.into(), value);
     }
@@ -185,6 +199,10 @@ impl DisplayParameter {
         self.color_params.insert(key.into(), value);
     }
 
+    pub fn set_brush_param(&mut self, key: impl Into<String>, value: Brush) {
+        self.brush_params.insert(key.into(), value);
+    }
+
     pub fn get_float_param(&self, key: &str) -> Option<f32> {
         self.float_params.get(key).copied()
     }
@@ -192,6 +210,10 @@ impl DisplayParameter {
     pub fn get_color_param(&self, key: &str) -> Option<Color> {
         self.color_params.get(key).copied()
     }
+
+    pub fn get_brush_param(&self, key: &str) -> Option<&Brush> {
+        self.brush_params.get(key)
+    }
     
     pub fn is_empty(&self) -> bool {
         self.width <= 0.0 && self.height <= 0.0
@@ -230,8 +252,12 @@ impl Default for DisplayParameter {
             skew_y: 0.0,
             skew_center_x: 0.0,
             skew_center_y: 0.0,
+            transform: M44::new_identity(),
+            transform_center_x: 0.0,
+            transform_center_y: 0.0,
             float_params: HashMap::new(),
             color_params: HashMap::new(),
+            brush_params: HashMap::new(),
         }
     }
 }
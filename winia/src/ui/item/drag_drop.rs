@@ -0,0 +1,1009 @@
+use crate::shared::{Children, Gettable, Settable, Shared, SharedBool, SharedF32};
+use crate::ui::animation::SpringAnimation;
+use crate::ui::app::{LayerController, WindowContext};
+use crate::ui::component::RectangleExt;
+use crate::ui::item::{CustomProperty, DragAxis, DragGestureRecognizer, ItemData, PointerState};
+use crate::ui::theme::color;
+use crate::ui::Item;
+use parking_lot::Mutex;
+use skia_safe::{Point, Rect};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use winit::window::WindowId;
+
+/// What is being dragged: a value of any type, or files dragged in from outside the application.
+#[derive(Clone, Default)]
+pub struct DragData {
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    files: Vec<PathBuf>,
+}
+
+impl DragData {
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self {
+            value: Some(Arc::new(value)),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn from_files(files: Vec<PathBuf>) -> Self {
+        Self { value: None, files }
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.value.as_ref()?.downcast_ref::<T>()
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn get_files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+type PreviewFn = Arc<dyn Fn(&WindowContext) -> Item + Send + Sync>;
+
+/// Makes an item draggable, added with [`Item::drag_source`].
+pub struct DragSource {
+    data: Box<dyn FnMut() -> DragData>,
+    preview: Option<PreviewFn>,
+    on_drag_start: Option<Box<dyn FnMut()>>,
+    on_drag_end: Option<Box<dyn FnMut(bool)>>,
+    touch_delay: Option<Duration>,
+}
+
+impl DragSource {
+    /// `data` is called when a drag starts.
+    pub fn new(data: impl FnMut() -> DragData + 'static) -> Self {
+        Self {
+            data: Box::new(data),
+            preview: None,
+            on_drag_start: None,
+            on_drag_end: None,
+            touch_delay: None,
+        }
+    }
+
+    /// How long a touch has to stay down before the item can be dragged, see
+    /// [`DragGestureRecognizer::touch_delay`]. Without it, a touch drags the item as soon as it moves.
+    pub fn touch_delay(mut self, touch_delay: Duration) -> Self {
+        self.touch_delay = Some(touch_delay);
+        self
+    }
+
+    /// The item following the pointer, it keeps the distance between the pointer and the top left
+    /// corner of the source. A translucent rectangle of the size of the source by default.
+    pub fn preview(
+        mut self,
+        preview: impl Fn(&WindowContext) -> Item + Send + Sync + 'static,
+    ) -> Self {
+        self.preview = Some(Arc::new(preview));
+        self
+    }
+
+    pub fn on_drag_start(mut self, on_drag_start: impl FnMut() + 'static) -> Self {
+        self.on_drag_start = Some(Box::new(on_drag_start));
+        self
+    }
+
+    /// Called with whether a drop target has accepted the data.
+    pub fn on_drag_end(mut self, on_drag_end: impl FnMut(bool) + 'static) -> Self {
+        self.on_drag_end = Some(Box::new(on_drag_end));
+        self
+    }
+}
+
+/// Receives dragged data, added with [`Item::drop_target`].
+/// When targets overlap, the topmost one under the pointer that accepts the data is used.
+/// Positions are in window coordinates.
+pub struct DropTarget {
+    accepts: Box<dyn Fn(&DragData) -> bool>,
+    hovered: SharedBool,
+    on_enter: Option<Box<dyn FnMut(&DragData)>>,
+    on_over: Option<Box<dyn FnMut(&DragData, f32, f32)>>,
+    on_leave: Option<Box<dyn FnMut()>>,
+    on_drop: Option<Box<dyn FnMut(&DragData, f32, f32) -> bool>>,
+}
+
+impl Default for DropTarget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DropTarget {
+    /// A target that accepts everything.
+    pub fn new() -> Self {
+        Self {
+            accepts: Box::new(|_| true),
+            hovered: false.into(),
+            on_enter: None,
+            on_over: None,
+            on_leave: None,
+            on_drop: None,
+        }
+    }
+
+    pub fn accepts(mut self, accepts: impl Fn(&DragData) -> bool + 'static) -> Self {
+        self.accepts = Box::new(accepts);
+        self
+    }
+
+    /// Only accepts values of type `T`.
+    pub fn accept<T: Any>(self) -> Self {
+        self.accepts(|data| data.is::<T>())
+    }
+
+    /// Only accepts files dropped from outside the application.
+    pub fn accept_files(self) -> Self {
+        self.accepts(|data| !data.get_files().is_empty())
+    }
+
+    /// Set to true while accepted data is dragged over the target, e.g. to highlight it.
+    pub fn hovered(mut self, hovered: impl Into<SharedBool>) -> Self {
+        self.hovered = hovered.into();
+        self
+    }
+
+    pub fn on_enter(mut self, on_enter: impl FnMut(&DragData) + 'static) -> Self {
+        self.on_enter = Some(Box::new(on_enter));
+        self
+    }
+
+    pub fn on_over(mut self, on_over: impl FnMut(&DragData, f32, f32) + 'static) -> Self {
+        self.on_over = Some(Box::new(on_over));
+        self
+    }
+
+    pub fn on_leave(mut self, on_leave: impl FnMut() + 'static) -> Self {
+        self.on_leave = Some(Box::new(on_leave));
+        self
+    }
+
+    /// Returns whether the data has been accepted.
+    pub fn on_drop(mut self, on_drop: impl FnMut(&DragData, f32, f32) -> bool + 'static) -> Self {
+        self.on_drop = Some(Box::new(on_drop));
+        self
+    }
+
+    fn enter(&mut self, data: &DragData) {
+        self.hovered.set(true);
+        if let Some(on_enter) = self.on_enter.as_mut() {
+            on_enter(data);
+        }
+    }
+
+    fn over(&mut self, data: &DragData, x: f32, y: f32) {
+        if let Some(on_over) = self.on_over.as_mut() {
+            on_over(data, x, y);
+        }
+    }
+
+    fn leave(&mut self) {
+        self.hovered.set(false);
+        if let Some(on_leave) = self.on_leave.as_mut() {
+            on_leave();
+        }
+    }
+
+    fn drop_data(&mut self, data: &DragData, x: f32, y: f32) -> bool {
+        self.on_drop
+            .as_mut()
+            .is_some_and(|on_drop| on_drop(data, x, y))
+    }
+}
+
+struct TargetEntry {
+    /// The id of the item.
+    id: usize,
+    window_context: WindowContext,
+    item: Weak<Mutex<ItemData>>,
+    target: Rc<RefCell<DropTarget>>,
+}
+
+/// The layer showing the preview of a drag.
+struct Preview {
+    window_id: WindowId,
+    x: SharedF32,
+    y: SharedF32,
+    controller: Shared<Option<LayerController>>,
+    removed: Shared<bool>,
+}
+
+impl Preview {
+    fn show(window_context: &WindowContext, source: &ActiveSource) -> Self {
+        let x = SharedF32::from(0.0);
+        let y = SharedF32::from(0.0);
+        let controller = Shared::from(None);
+        let removed = Shared::from(false);
+        let preview = source.preview.clone();
+        let (width, height) = source.size;
+        window_context.event_loop_proxy().new_layer({
+            let x = x.clone();
+            let y = y.clone();
+            let controller = controller.clone();
+            let removed = removed.clone();
+            move |w, layer_controller| {
+                controller.set(Some(layer_controller.clone()));
+                // The drag may have ended before the layer has been created.
+                if removed.get() {
+                    layer_controller.remove();
+                }
+                let item = match preview {
+                    Some(preview) => preview(w),
+                    None => {
+                        let color = *w.theme().lock().get_color(color::SECONDARY_CONTAINER).unwrap();
+                        w.rectangle(color.with_a(204))
+                            .radius(12)
+                            .item()
+                            .size(width.max(0.0), height.max(0.0))
+                    }
+                };
+                item.offset_x(&x).offset_y(&y).enabled(false)
+            }
+        });
+        Self {
+            window_id: window_context.window_id(),
+            x,
+            y,
+            controller,
+            removed,
+        }
+    }
+
+    fn remove(&self) {
+        self.removed.set(true);
+        if let Some(controller) = self.controller.get() {
+            controller.remove();
+        }
+    }
+}
+
+struct ActiveSource {
+    source: Rc<RefCell<DragSource>>,
+    preview: Option<PreviewFn>,
+    size: (f32, f32),
+    /// Where the source has been grabbed, relative to its top left corner.
+    grab: (f32, f32),
+}
+
+struct DragSession {
+    data: DragData,
+    /// The window reporting the pointer, it may be over another window.
+    window_context: WindowContext,
+    x: f32,
+    y: f32,
+    /// None for files dragged in from outside the application.
+    source: Option<ActiveSource>,
+    preview: Option<Preview>,
+    target: Option<usize>,
+    dropped: bool,
+    cancelled: bool,
+}
+
+thread_local! {
+    static SESSION: RefCell<Option<DragSession>> = const { RefCell::new(None) };
+    static TARGETS: RefCell<Vec<TargetEntry>> = const { RefCell::new(Vec::new()) };
+    /// The files of the last drop from outside on each window, which winit reports one by one.
+    static DROPPED_FILES: RefCell<HashMap<WindowId, Vec<PathBuf>>> = RefCell::new(HashMap::new());
+}
+
+fn find_target(id: usize) -> Option<Rc<RefCell<DropTarget>>> {
+    TARGETS.with_borrow(|targets| {
+        targets
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.target.clone())
+    })
+}
+
+fn screen_position(window_context: &WindowContext, x: f32, y: f32) -> Option<(f32, f32)> {
+    let scale_factor = window_context.scale_factor();
+    let position = window_context.window().inner_position().ok()?;
+    Some((
+        position.x as f32 + x * scale_factor,
+        position.y as f32 + y * scale_factor,
+    ))
+}
+
+fn is_inside_window(window_context: &WindowContext, x: f32, y: f32) -> bool {
+    let (width, height) = window_context.window_size();
+    x >= 0.0 && y >= 0.0 && x < width && y < height
+}
+
+/// Finds the window the pointer is over and the position in it.
+/// A pointer outside of the window reporting it may be over another window with drop targets.
+fn locate(window_context: &WindowContext, x: f32, y: f32) -> (WindowContext, f32, f32) {
+    if is_inside_window(window_context, x, y) {
+        return (window_context.clone(), x, y);
+    }
+    let Some((screen_x, screen_y)) = screen_position(window_context, x, y) else {
+        return (window_context.clone(), x, y);
+    };
+    let window_id = window_context.window_id();
+    let windows: Vec<WindowContext> = TARGETS.with_borrow(|targets| {
+        let mut windows: Vec<WindowContext> = Vec::new();
+        for entry in targets.iter() {
+            let id = entry.window_context.window_id();
+            if id != window_id && windows.iter().all(|window| window.window_id() != id) {
+                windows.push(entry.window_context.clone());
+            }
+        }
+        windows
+    });
+    for window in windows {
+        let Some((origin_x, origin_y)) = screen_position(&window, 0.0, 0.0) else {
+            continue;
+        };
+        let scale_factor = window.scale_factor();
+        let local_x = (screen_x - origin_x) / scale_factor;
+        let local_y = (screen_y - origin_y) / scale_factor;
+        if is_inside_window(&window, local_x, local_y) {
+            return (window, local_x, local_y);
+        }
+    }
+    (window_context.clone(), x, y)
+}
+
+/// The topmost drop target under the position that accepts the data, found with
+/// [`WindowContext::hit_test`] so that clipping, transformations and z-order are the same as for
+/// the pointer events.
+fn hit_test(window_context: &WindowContext, data: &DragData, x: f32, y: f32) -> Option<usize> {
+    let window_id = window_context.window_id();
+    let hits = window_context.hit_test(x, y);
+    TARGETS.with_borrow_mut(|targets| {
+        targets.retain(|entry| entry.item.strong_count() > 0);
+        hits.into_iter().find(|id| {
+            targets
+                .iter()
+                .filter(|entry| entry.id == *id && entry.window_context.window_id() == window_id)
+                .any(|entry| {
+                    let enabled = entry
+                        .item
+                        .upgrade()
+                        .and_then(|item| item.try_lock().map(|item| item.get_enabled().get()))
+                        .unwrap_or(false);
+                    enabled && (entry.target.borrow().accepts)(data)
+                })
+        })
+    })
+}
+
+/// Moves the preview, tells the targets about the drag and finishes it once it has ended.
+/// Returns whether the drag goes on.
+fn update() -> bool {
+    // Taken out so that the callbacks can't run into a borrowed session.
+    let Some(mut session) = SESSION.with_borrow_mut(|session| session.take()) else {
+        return false;
+    };
+    let (window_context, x, y) = locate(&session.window_context, session.x, session.y);
+    let target = if session.cancelled {
+        None
+    } else {
+        hit_test(&window_context, &session.data, x, y)
+    };
+    if target != session.target {
+        if let Some(old) = session.target.and_then(find_target) {
+            old.borrow_mut().leave();
+        }
+        if let Some(new) = target.and_then(find_target) {
+            new.borrow_mut().enter(&session.data);
+        }
+        session.target = target;
+    }
+    let target = target.and_then(find_target);
+
+    if session.dropped || session.cancelled {
+        let accepted = match target {
+            Some(target) => {
+                let accepted = session.dropped && target.borrow_mut().drop_data(&session.data, x, y);
+                target.borrow_mut().leave();
+                accepted
+            }
+            None => false,
+        };
+        if let Some(preview) = session.preview.take() {
+            preview.remove();
+        }
+        if let Some(active_source) = session.source {
+            let mut source = active_source.source.borrow_mut();
+            if let Some(on_drag_end) = source.on_drag_end.as_mut() {
+                on_drag_end(accepted);
+            }
+        }
+        return false;
+    }
+
+    if let Some(target) = target {
+        target.borrow_mut().over(&session.data, x, y);
+    }
+    if let Some(source) = session.source.as_ref() {
+        let window_id = window_context.window_id();
+        if session
+            .preview
+            .as_ref()
+            .is_some_and(|preview| preview.window_id != window_id)
+        {
+            if let Some(preview) = session.preview.take() {
+                preview.remove();
+            }
+        }
+        let preview = session
+            .preview
+            .get_or_insert_with(|| Preview::show(&window_context, source));
+        preview.x.set(x - source.grab.0);
+        preview.y.set(y - source.grab.1);
+    }
+    SESSION.with_borrow_mut(|current| {
+        if current.is_none() {
+            *current = Some(session);
+        }
+    });
+    true
+}
+
+fn is_dragging(source: &Rc<RefCell<DragSource>>) -> bool {
+    SESSION.with_borrow(|session| {
+        session
+            .as_ref()
+            .and_then(|session| session.source.as_ref())
+            .is_some_and(|active_source| Rc::ptr_eq(&active_source.source, source))
+    })
+}
+
+fn start_drag(
+    window_context: &WindowContext,
+    source: &Rc<RefCell<DragSource>>,
+    bounds: (f32, f32, f32, f32),
+    x: f32,
+    y: f32,
+) {
+    // One drag at a time.
+    if SESSION.with_borrow(|session| session.is_some()) {
+        return;
+    }
+    let (data, preview) = {
+        let mut source = source.borrow_mut();
+        if let Some(on_drag_start) = source.on_drag_start.as_mut() {
+            on_drag_start();
+        }
+        ((source.data)(), source.preview.clone())
+    };
+    let (source_x, source_y, width, height) = bounds;
+    SESSION.set(Some(DragSession {
+        data,
+        window_context: window_context.clone(),
+        x,
+        y,
+        source: Some(ActiveSource {
+            source: source.clone(),
+            preview,
+            size: (width, height),
+            grab: (x - source_x, y - source_y),
+        }),
+        preview: None,
+        target: None,
+        dropped: false,
+        cancelled: false,
+    }));
+    // Targets are looked up after the event has been dispatched, when no item is locked.
+    window_context.add_ui_hook(update);
+    window_context.request_redraw();
+}
+
+fn update_drag(source: &Rc<RefCell<DragSource>>, f: impl FnOnce(&mut DragSession)) {
+    if !is_dragging(source) {
+        return;
+    }
+    SESSION.with_borrow_mut(|session| {
+        if let Some(session) = session.as_mut() {
+            f(session);
+            session.window_context.request_redraw();
+        }
+    });
+}
+
+fn install_drag_source(item: &Item, source: DragSource) {
+    let touch_delay = source.touch_delay;
+    let source = Rc::new(RefCell::new(source));
+    // Where the item is when a pointer goes down, which is where a drag starts from.
+    let bounds = Rc::new(Cell::new((0.0, 0.0, 0.0, 0.0)));
+    let mut item = item.data();
+    let pointer_input = item.get_pointer_input();
+    item.set_pointer_input({
+        let bounds = bounds.clone();
+        move |item, input| {
+            if input.pointer_state == PointerState::Started {
+                let display_parameter = item.get_display_parameter();
+                bounds.set((
+                    display_parameter.x(),
+                    display_parameter.y(),
+                    display_parameter.width,
+                    display_parameter.height,
+                ));
+            }
+            pointer_input.lock()(item, input);
+        }
+    });
+    let window_context = item.get_window_context().clone();
+    let mut recognizer = DragGestureRecognizer::new(DragAxis::Both);
+    if let Some(touch_delay) = touch_delay {
+        recognizer = recognizer.touch_delay(touch_delay);
+    }
+    item.add_gesture(
+        recognizer
+            .on_start({
+                let source = source.clone();
+                move |start| start_drag(&window_context, &source, bounds.get(), start.x, start.y)
+            })
+            .on_update({
+                let source = source.clone();
+                move |update| {
+                    update_drag(&source, |session| {
+                        session.x = update.x;
+                        session.y = update.y;
+                    })
+                }
+            })
+            .on_end({
+                let source = source.clone();
+                move |_| update_drag(&source, |session| session.dropped = true)
+            })
+            .on_cancel(move || update_drag(&source, |session| session.cancelled = true)),
+    );
+}
+
+fn install_drop_target(item: &Item, target: DropTarget) {
+    let (id, window_context) = {
+        let data = item.data();
+        (data.get_id(), data.get_window_context().clone())
+    };
+    let entry = TargetEntry {
+        id,
+        window_context,
+        item: item.data_clone(),
+        target: Rc::new(RefCell::new(target)),
+    };
+    TARGETS.with_borrow_mut(|targets| {
+        targets.retain(|entry| entry.id != id);
+        targets.push(entry);
+    });
+}
+
+impl Item {
+    /// Lets the item be dragged with a pointer, carrying the data of `source` to a [`DropTarget`]
+    /// in any window of the application.
+    pub fn drag_source(self, source: DragSource) -> Self {
+        install_drag_source(&self, source);
+        self
+    }
+
+    pub fn drop_target(self, target: DropTarget) -> Self {
+        install_drop_target(&self, target);
+        self
+    }
+}
+
+/// A file from outside the application is dragged over the window.
+/// winit reports each file with its own event.
+pub(crate) fn hover_file(window_context: &WindowContext, path: PathBuf) {
+    let (x, y) = window_context.get_cursor_position();
+    SESSION.with_borrow_mut(|session| match session {
+        Some(session) => {
+            if session.source.is_none() {
+                session.data.files.push(path);
+                session.window_context = window_context.clone();
+                session.x = x;
+                session.y = y;
+            }
+        }
+        None => {
+            *session = Some(DragSession {
+                data: DragData::from_files(vec![path]),
+                window_context: window_context.clone(),
+                x,
+                y,
+                source: None,
+                preview: None,
+                target: None,
+                dropped: false,
+                cancelled: false,
+            })
+        }
+    });
+    update();
+}
+
+fn update_files(f: impl FnOnce(&mut DragSession)) -> bool {
+    SESSION.with_borrow_mut(|session| match session {
+        Some(session) if session.source.is_none() => {
+            f(session);
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Follows the pointer of a file drag over `window_context`. Called for every event of the window
+/// that carries a position, since platforms differ in which ones they send during a file drag.
+pub(crate) fn move_file_hover(window_context: &WindowContext, x: f32, y: f32) {
+    if update_files(|session| {
+        session.window_context = window_context.clone();
+        session.x = x;
+        session.y = y;
+    }) {
+        update();
+    }
+}
+
+pub(crate) fn cancel_file_hover() {
+    if update_files(|session| session.cancelled = true) {
+        update();
+    }
+}
+
+/// Files hovered before are dropped together with the first one that is dropped.
+/// The files already delivered are remembered per window, so that the following events of the
+/// same drop are ignored while another window may receive a drop of its own.
+pub(crate) fn drop_file(window_context: &WindowContext, path: PathBuf) {
+    let window_id = window_context.window_id();
+    let already_dropped = DROPPED_FILES.with_borrow_mut(|dropped_files| {
+        let Some(files) = dropped_files.get_mut(&window_id) else {
+            return false;
+        };
+        let index = files.iter().position(|file| *file == path);
+        let already_dropped = index.map(|index| files.remove(index)).is_some();
+        if files.is_empty() {
+            dropped_files.remove(&window_id);
+        }
+        already_dropped
+    });
+    if already_dropped {
+        return;
+    }
+    // The drop happens where the pointer was last seen in the window that received it.
+    let (x, y) = window_context.get_cursor_position();
+    if !update_files(|session| {
+        if !session.data.files.contains(&path) {
+            session.data.files.push(path.clone());
+        }
+        session.window_context = window_context.clone();
+        session.x = x;
+        session.y = y;
+        session.dropped = true;
+    }) {
+        hover_file(window_context, path.clone());
+        update_files(|session| session.dropped = true);
+    }
+    let files = SESSION.with_borrow(|session| {
+        session
+            .as_ref()
+            .map(|session| session.data.files.clone())
+            .unwrap_or_default()
+    });
+    let files: Vec<PathBuf> = files.into_iter().filter(|file| *file != path).collect();
+    DROPPED_FILES.with_borrow_mut(|dropped_files| {
+        if files.is_empty() {
+            dropped_files.remove(&window_id);
+        } else {
+            dropped_files.insert(window_id, files);
+        }
+    });
+    update();
+}
+
+/// The payload of the drags that reorder the children of a container.
+struct ReorderData {
+    container: usize,
+    child: usize,
+}
+
+const REORDER_SOURCE: &str = "reorder_source";
+const REORDER_TOUCH_DELAY: Duration = Duration::from_millis(400);
+
+/// The state of a container whose children can be reordered.
+struct Reorder {
+    children: Children,
+    animations: HashMap<usize, (SpringAnimation, SpringAnimation)>,
+}
+
+impl Reorder {
+    /// The position and size of each child without its offset, by child id.
+    fn slots(&self) -> Vec<(usize, Rect)> {
+        self.children
+            .lock()
+            .iter()
+            .map(|child| {
+                let mut child = child.data();
+                let display_parameter = child.get_display_parameter();
+                (
+                    child.get_id(),
+                    Rect::from_xywh(
+                        display_parameter.parent_x + display_parameter.relative_x,
+                        display_parameter.parent_y + display_parameter.relative_y,
+                        display_parameter.width,
+                        display_parameter.height,
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// The index of the dragged child and the index of the slot it is over.
+    fn positions(&self, data: &DragData, x: f32, y: f32) -> Option<(Vec<(usize, Rect)>, usize, usize)> {
+        let data = data.get::<ReorderData>()?;
+        let slots = self.slots();
+        let from = slots.iter().position(|(id, _)| *id == data.child)?;
+        let to = slot_at(&slots, x, y).unwrap_or(from);
+        Some((slots, from, to))
+    }
+
+    fn animate(&mut self, id: usize, offset_x: f32, offset_y: f32) {
+        if let Some((x, y)) = self.animations.get(&id) {
+            x.retarget(offset_x);
+            y.retarget(offset_y);
+            return;
+        }
+        let children = self.children.lock();
+        let Some(child) = children.iter().find(|child| child.data().get_id() == id) else {
+            return;
+        };
+        let child = child.data();
+        let event_loop_proxy = child.get_window_context().event_loop_proxy().clone();
+        let x = SpringAnimation::new(child.get_offset_x(), offset_x).start(&event_loop_proxy);
+        let y = SpringAnimation::new(child.get_offset_y(), offset_y).start(&event_loop_proxy);
+        drop(child);
+        drop(children);
+        self.animations.insert(id, (x, y));
+    }
+
+    /// Moves the children between the dragged child and the slot it is over into the place of
+    /// their neighbours, which leaves a gap where the dragged child would end up.
+    fn displace(&mut self, slots: &[(usize, Rect)], from: usize, to: usize) {
+        for (id, offset_x, offset_y) in displacement(slots, from, to) {
+            self.animate(id, offset_x, offset_y);
+        }
+    }
+
+    fn reset(&mut self) {
+        let ids: Vec<usize> = self.animations.keys().copied().collect();
+        for id in ids {
+            self.animate(id, 0.0, 0.0);
+        }
+    }
+
+    /// Removes the displacement at once, used when the children take their new places.
+    fn clear(&mut self) {
+        for (_, (x, y)) in self.animations.drain() {
+            x.stop();
+            y.stop();
+        }
+        for child in self.children.lock().iter() {
+            let child = child.data();
+            child.get_offset_x().set(0.0);
+            child.get_offset_y().set(0.0);
+        }
+    }
+}
+
+/// The index of the slot containing the position, or of the nearest one.
+fn slot_at(slots: &[(usize, Rect)], x: f32, y: f32) -> Option<usize> {
+    let point = Point::new(x, y);
+    slots
+        .iter()
+        .position(|(_, rect)| rect.contains(point))
+        .or_else(|| {
+            slots
+                .iter()
+                .enumerate()
+                .min_by(|(_, (_, a)), (_, (_, b))| {
+                    let a = (a.center() - point).length();
+                    let b = (b.center() - point).length();
+                    a.total_cmp(&b)
+                })
+                .map(|(index, _)| index)
+        })
+}
+
+/// The offset of each child while the child at `from` is dragged over the slot at `to`.
+/// The children in between take the slot of their neighbour towards `from`.
+fn displacement(slots: &[(usize, Rect)], from: usize, to: usize) -> Vec<(usize, f32, f32)> {
+    slots
+        .iter()
+        .enumerate()
+        .map(|(index, (id, rect))| {
+            let neighbour = if from < to && index > from && index <= to {
+                Some(index - 1)
+            } else if to < from && index >= to && index < from {
+                Some(index + 1)
+            } else {
+                None
+            };
+            let (offset_x, offset_y) = neighbour.map_or((0.0, 0.0), |neighbour| {
+                let neighbour = slots[neighbour].1;
+                (neighbour.left - rect.left, neighbour.top - rect.top)
+            });
+            (*id, offset_x, offset_y)
+        })
+        .collect()
+}
+
+/// Lets the children of `container` be reordered by dragging them. While a child is dragged, the
+/// children it passes move aside. `on_reorder` is called with the old and the new index of the
+/// dragged child among the children and is expected to move it.
+pub(crate) fn make_reorderable(container: &Item, on_reorder: impl FnMut(usize, usize) + 'static) {
+    let (id, children) = {
+        let data = container.data();
+        (data.get_id(), data.get_children().clone())
+    };
+    let reorder = Rc::new(RefCell::new(Reorder {
+        children,
+        animations: HashMap::new(),
+    }));
+    let on_reorder = Rc::new(RefCell::new(on_reorder));
+
+    // Children may be added at any time, so they become draggable when they are measured.
+    let measure = container.data().get_measure();
+    container.data().set_measure(move |item, width_mode, height_mode| {
+        measure.lock()(item, width_mode, height_mode);
+        for child in item.get_children().lock().iter() {
+            let (child_id, opacity) = {
+                let mut data = child.data();
+                if data.get_custom_property(REORDER_SOURCE).is_some() {
+                    continue;
+                }
+                data.custom_property(REORDER_SOURCE, CustomProperty::Bool(true.into()));
+                (data.get_id(), data.get_opacity().clone())
+            };
+            install_drag_source(
+                child,
+                DragSource::new(move || DragData::new(ReorderData { container: id, child: child_id }))
+                    // A touch that moves right away scrolls the container instead.
+                    .touch_delay(REORDER_TOUCH_DELAY)
+                    .on_drag_start({
+                        let opacity = opacity.clone();
+                        move || opacity.set(0.5)
+                    })
+                    .on_drag_end(move |_| opacity.set(1.0)),
+            );
+        }
+    });
+
+    install_drop_target(
+        container,
+        DropTarget::new()
+            .accepts(move |data| {
+                data.get::<ReorderData>()
+                    .is_some_and(|data| data.container == id)
+            })
+            .on_over({
+                let reorder = reorder.clone();
+                move |data, x, y| {
+                    let mut reorder = reorder.borrow_mut();
+                    if let Some((slots, from, to)) = reorder.positions(data, x, y) {
+                        reorder.displace(&slots, from, to);
+                    }
+                }
+            })
+            .on_leave({
+                let reorder = reorder.clone();
+                move || reorder.borrow_mut().reset()
+            })
+            .on_drop(move |data, x, y| {
+                let positions = {
+                    let mut reorder = reorder.borrow_mut();
+                    let positions = reorder.positions(data, x, y);
+                    reorder.clear();
+                    positions
+                };
+                if let Some((_, from, to)) = positions {
+                    if from != to {
+                        (on_reorder.borrow_mut())(from, to);
+                    }
+                }
+                true
+            }),
+    );
+}
+
+#[cfg(test)]
+mod drag_drop_test {
+    use super::{displacement, slot_at, DragData, DropTarget};
+    use crate::shared::{Gettable, SharedBool};
+    use skia_safe::Rect;
+    use std::path::PathBuf;
+
+    /// Three slots of 10x10 in a row, with the ids 1, 2 and 3.
+    fn row() -> Vec<(usize, Rect)> {
+        (0..3)
+            .map(|index| {
+                let rect = Rect::from_xywh(index as f32 * 10.0, 0.0, 10.0, 10.0);
+                (index + 1, rect)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drag_data() {
+        let data = DragData::new(42_u32);
+        assert_eq!(data.get::<u32>(), Some(&42));
+        assert!(!data.is::<i32>());
+        assert!(data.get_files().is_empty());
+        let files = DragData::from_files(vec![PathBuf::from("a.txt")]);
+        assert!(!files.is::<u32>());
+        assert_eq!(files.get_files(), [PathBuf::from("a.txt")]);
+    }
+
+    #[test]
+    fn test_drop_target() {
+        let hovered = SharedBool::from(false);
+        let mut target = DropTarget::new().accept::<u32>().hovered(hovered.clone());
+        assert!((target.accepts)(&DragData::new(1_u32)));
+        assert!(!(target.accepts)(&DragData::new("text")));
+        let files = DropTarget::new().accept_files();
+        assert!(!(files.accepts)(&DragData::new(1_u32)));
+        let dropped = DragData::from_files(vec![PathBuf::from("a.txt")]);
+        assert!((files.accepts)(&dropped));
+        target.enter(&DragData::new(1_u32));
+        assert!(hovered.get());
+        target.leave();
+        assert!(!hovered.get());
+        // Without on_drop the data is not accepted.
+        assert!(!target.drop_data(&DragData::new(1_u32), 0.0, 0.0));
+        let mut target = DropTarget::new().on_drop(|data, x, _| data.is::<u32>() && x > 0.0);
+        assert!(target.drop_data(&DragData::new(1_u32), 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_slot_at() {
+        let slots = row();
+        assert_eq!(slot_at(&slots, 5.0, 5.0), Some(0));
+        assert_eq!(slot_at(&slots, 25.0, 5.0), Some(2));
+        // Outside of every slot, the nearest one.
+        assert_eq!(slot_at(&slots, 14.0, 40.0), Some(1));
+        assert_eq!(slot_at(&slots, 100.0, 5.0), Some(2));
+        assert_eq!(slot_at(&[], 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_displacement() {
+        let slots = row();
+        assert_eq!(
+            displacement(&slots, 0, 2),
+            vec![(1, 0.0, 0.0), (2, -10.0, 0.0), (3, -10.0, 0.0)]
+        );
+        assert_eq!(
+            displacement(&slots, 2, 1),
+            vec![(1, 0.0, 0.0), (2, 10.0, 0.0), (3, 0.0, 0.0)]
+        );
+        assert_eq!(
+            displacement(&slots, 1, 1),
+            vec![(1, 0.0, 0.0), (2, 0.0, 0.0), (3, 0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_displacement_column() {
+        let slots: Vec<(usize, Rect)> = [(1, 20.0), (2, 30.0), (3, 10.0)]
+            .into_iter()
+            .scan(0.0, |top, (id, height)| {
+                let rect = Rect::from_xywh(0.0, *top, 50.0, height);
+                *top += height;
+                Some((id, rect))
+            })
+            .collect();
+        // The children move into the place of their neighbours, whatever their size.
+        assert_eq!(
+            displacement(&slots, 2, 0),
+            vec![(1, 0.0, 20.0), (2, 0.0, 30.0), (3, 0.0, 0.0)]
+        );
+        assert_eq!(slot_at(&slots, 25.0, 45.0), Some(1));
+    }
+}
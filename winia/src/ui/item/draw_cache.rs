@@ -0,0 +1,53 @@
+use crate::ui::item::{Brush, DisplayParameter};
+use skia_safe::{Canvas, Color, Picture, PictureRecorder, Rect};
+use std::collections::HashMap;
+
+/// The drawing of an item recorded at the origin, so that it can be replayed wherever the item
+/// moves until one of its properties changes.
+pub(crate) struct DrawCache {
+    picture: Picture,
+    width: f32,
+    height: f32,
+    float_params: HashMap<String, f32>,
+    color_params: HashMap<String, Color>,
+    brush_params: HashMap<String, Brush>,
+}
+
+impl DrawCache {
+    pub fn record(
+        display_parameter: &DisplayParameter,
+        draw: impl FnOnce(&Canvas),
+    ) -> Option<Self> {
+        let mut recorder = PictureRecorder::new();
+        // The item may draw outside of its bounds, so the recording is not culled.
+        let canvas = recorder.begin_recording(Rect::new(-1e5, -1e5, 1e5, 1e5), None);
+        canvas.translate((-display_parameter.x(), -display_parameter.y()));
+        draw(canvas);
+        let picture = recorder.finish_recording_as_picture(None)?;
+        Some(Self {
+            picture,
+            width: display_parameter.width,
+            height: display_parameter.height,
+            float_params: display_parameter.float_params.clone(),
+            color_params: display_parameter.color_params.clone(),
+            brush_params: display_parameter.brush_params.clone(),
+        })
+    }
+
+    /// Whether the recording still shows the item. Animations change the display parameter
+    /// without notifying the properties, so it is compared too.
+    pub fn is_valid(&self, display_parameter: &DisplayParameter) -> bool {
+        self.width == display_parameter.width
+            && self.height == display_parameter.height
+            && self.float_params == display_parameter.float_params
+            && self.color_params == display_parameter.color_params
+            && self.brush_params == display_parameter.brush_params
+    }
+
+    pub fn draw(&self, canvas: &Canvas, display_parameter: &DisplayParameter) {
+        canvas.save();
+        canvas.translate((display_parameter.x(), display_parameter.y()));
+        canvas.draw_picture(&self.picture, None, None);
+        canvas.restore();
+    }
+}
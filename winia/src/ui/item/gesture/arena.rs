@@ -0,0 +1,410 @@
+use crate::core::next_id;
+use crate::ui::app::WindowContext;
+use crate::ui::item::{GestureDecision, GestureRecognizer, Pointer};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Weak;
+
+pub(crate) type RecognizerRef = Weak<RefCell<dyn GestureRecognizer>>;
+
+struct Member {
+    id: usize,
+    recognizer: RecognizerRef,
+}
+
+struct Arena {
+    pointer: Pointer,
+    /// In the order the members have joined, parents before their children.
+    members: Vec<Member>,
+    /// Members still join while the arena is open, which lasts until the pointer-down event has
+    /// been dispatched to every item.
+    is_open: bool,
+    is_pointer_up: bool,
+    /// Whether the pointer has been lifted and the arena is going to be swept.
+    sweep_scheduled: bool,
+    /// The members holding the arena open after the pointer has been lifted.
+    held: Vec<usize>,
+    winner: Option<usize>,
+}
+
+impl Arena {
+    fn is_finished(&self) -> bool {
+        self.is_pointer_up && (self.winner.is_some() || self.members.is_empty())
+    }
+
+    /// Makes the member win and removes the other members, returns the calls to make.
+    fn resolve(&mut self, winner: usize) -> Vec<(RecognizerRef, bool)> {
+        self.winner = Some(winner);
+        self.held.clear();
+        let mut calls = Vec::new();
+        self.members.retain(|member| {
+            let is_winner = member.id == winner;
+            calls.push((member.recognizer.clone(), is_winner));
+            is_winner
+        });
+        // The winner learns about its victory after the losers have been told.
+        calls.sort_by_key(|(_, is_winner)| *is_winner);
+        calls
+    }
+
+    /// Resolves the arena once it is clear who wins.
+    fn try_resolve(&mut self) -> Vec<(RecognizerRef, bool)> {
+        if self.is_open || self.winner.is_some() {
+            return Vec::new();
+        }
+        if self.members.len() == 1 {
+            return self.resolve(self.members[0].id);
+        }
+        // Nobody has accepted, the innermost member wins.
+        if self.is_pointer_up && self.held.is_empty() {
+            if let Some(member) = self.members.last() {
+                return self.resolve(member.id);
+            }
+        }
+        Vec::new()
+    }
+}
+
+thread_local! {
+    static ARENAS: RefCell<HashMap<usize, Arena>> = RefCell::new(HashMap::new());
+    /// The arena each pointer that is down belongs to.
+    static CURRENT_ARENAS: RefCell<HashMap<Pointer, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Tells the recognizers about the outcome. Called without borrowing the arenas,
+/// so recognizers can make decisions from their callbacks.
+fn run(pointer: Pointer, calls: Vec<(RecognizerRef, bool)>) {
+    for (recognizer, accepted) in calls {
+        if let Some(recognizer) = recognizer.upgrade() {
+            let mut recognizer = recognizer.borrow_mut();
+            if accepted {
+                recognizer.accept_gesture(pointer);
+            } else {
+                recognizer.reject_gesture(pointer);
+            }
+        }
+    }
+}
+
+fn update(arena_id: usize, f: impl FnOnce(&mut Arena) -> Vec<(RecognizerRef, bool)>) {
+    let calls = ARENAS.with_borrow_mut(|arenas| {
+        let arena = arenas.get_mut(&arena_id)?;
+        let pointer = arena.pointer;
+        let calls = f(arena);
+        if arena.is_finished() {
+            arenas.remove(&arena_id);
+        }
+        Some((pointer, calls))
+    });
+    if let Some((pointer, calls)) = calls {
+        run(pointer, calls);
+    }
+}
+
+/// Adds the member to the open arena of the pointer and returns the id of the arena.
+pub(crate) fn enter(
+    window_context: &WindowContext,
+    pointer: Pointer,
+    member_id: usize,
+    recognizer: RecognizerRef,
+) -> usize {
+    let current = CURRENT_ARENAS.with_borrow(|current| current.get(&pointer).copied());
+    let open = current.filter(|arena_id| {
+        ARENAS.with_borrow(|arenas| arenas.get(arena_id).is_some_and(|arena| arena.is_open))
+    });
+    let arena_id = match open {
+        Some(arena_id) => arena_id,
+        None => {
+            let arena_id = create(pointer);
+            // Closes the arena once the event has reached every item.
+            window_context.add_ui_hook(move || {
+                close(arena_id);
+                false
+            });
+            window_context.request_redraw();
+            arena_id
+        }
+    };
+    join(arena_id, member_id, recognizer);
+    arena_id
+}
+
+/// Opens a new arena and makes it the current one of the pointer.
+fn create(pointer: Pointer) -> usize {
+    let arena_id = next_id();
+    ARENAS.with_borrow_mut(|arenas| {
+        arenas.insert(
+            arena_id,
+            Arena {
+                pointer,
+                members: Vec::new(),
+                is_open: true,
+                is_pointer_up: false,
+                sweep_scheduled: false,
+                held: Vec::new(),
+                winner: None,
+            },
+        )
+    });
+    CURRENT_ARENAS.with_borrow_mut(|current| current.insert(pointer, arena_id));
+    arena_id
+}
+
+fn join(arena_id: usize, member_id: usize, recognizer: RecognizerRef) {
+    ARENAS.with_borrow_mut(|arenas| {
+        if let Some(arena) = arenas.get_mut(&arena_id) {
+            arena.members.push(Member {
+                id: member_id,
+                recognizer,
+            });
+        }
+    });
+}
+
+pub(crate) fn close(arena_id: usize) {
+    update(arena_id, |arena| {
+        if !arena.is_open {
+            return Vec::new();
+        }
+        arena.is_open = false;
+        arena.try_resolve()
+    });
+}
+
+/// Marks the pointer as lifted once the event has reached every item.
+/// If nobody has won the arena by then, it is swept.
+pub(crate) fn pointer_up(window_context: &WindowContext, arena_id: usize) {
+    let is_new = ARENAS.with_borrow_mut(|arenas| {
+        arenas
+            .get_mut(&arena_id)
+            .is_some_and(|arena| !std::mem::replace(&mut arena.sweep_scheduled, true))
+    });
+    if !is_new {
+        return;
+    }
+    window_context.add_ui_hook(move || {
+        sweep(arena_id);
+        false
+    });
+    window_context.request_redraw();
+}
+
+/// Lets the innermost member win if nobody has won the arena and nobody holds it.
+fn sweep(arena_id: usize) {
+    close(arena_id);
+    CURRENT_ARENAS.with_borrow_mut(|current| {
+        current.retain(|_, current_id| *current_id != arena_id);
+    });
+    update(arena_id, |arena| {
+        arena.is_pointer_up = true;
+        arena.try_resolve()
+    });
+}
+
+pub(crate) fn is_member(arena_id: usize, member_id: usize) -> bool {
+    ARENAS.with_borrow(|arenas| {
+        arenas
+            .get(&arena_id)
+            .is_some_and(|arena| arena.members.iter().any(|member| member.id == member_id))
+    })
+}
+
+pub(crate) fn resolve(arena_id: usize, member_id: usize, decision: GestureDecision) {
+    update(arena_id, |arena| {
+        if !arena.members.iter().any(|member| member.id == member_id) {
+            return Vec::new();
+        }
+        match decision {
+            GestureDecision::Pending => Vec::new(),
+            GestureDecision::Hold => {
+                if arena.winner.is_none() && !arena.held.contains(&member_id) {
+                    arena.held.push(member_id);
+                }
+                Vec::new()
+            }
+            GestureDecision::Accept => {
+                if arena.winner.is_some() {
+                    return Vec::new();
+                }
+                arena.resolve(member_id)
+            }
+            GestureDecision::Reject => {
+                // The arena stays resolved when the winner gives up.
+                if arena.winner == Some(member_id) {
+                    return Vec::new();
+                }
+                let index = arena
+                    .members
+                    .iter()
+                    .position(|member| member.id == member_id)
+                    .unwrap();
+                let member = arena.members.remove(index);
+                arena.held.retain(|id| *id != member_id);
+                let mut calls = vec![(member.recognizer, false)];
+                calls.extend(arena.try_resolve());
+                calls
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod arena_test {
+    use super::{close, create, is_member, join, resolve, sweep};
+    use crate::core::next_id;
+    use crate::ui::item::{GestureDecision, GestureRecognizer, Pointer, PointerInput};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<(&'static str, bool)>>>;
+
+    struct Recorder {
+        name: &'static str,
+        log: Log,
+    }
+
+    impl GestureRecognizer for Recorder {
+        fn add_pointer(&mut self, _input: &PointerInput) -> bool {
+            true
+        }
+
+        fn handle_pointer(&mut self, _input: &PointerInput) -> GestureDecision {
+            GestureDecision::Pending
+        }
+
+        fn accept_gesture(&mut self, _pointer: Pointer) {
+            self.log.borrow_mut().push((self.name, true));
+        }
+
+        fn reject_gesture(&mut self, _pointer: Pointer) {
+            self.log.borrow_mut().push((self.name, false));
+        }
+    }
+
+    struct Member {
+        id: usize,
+        _recognizer: Rc<RefCell<dyn GestureRecognizer>>,
+    }
+
+    /// Opens an arena for a new pointer and joins the members, outermost first.
+    fn arena(names: &[&'static str], log: &Log) -> (usize, Vec<Member>) {
+        let arena_id = create(Pointer::Touch {
+            id: next_id() as u64,
+        });
+        let members = names
+            .iter()
+            .map(|name| {
+                let recognizer: Rc<RefCell<dyn GestureRecognizer>> =
+                    Rc::new(RefCell::new(Recorder {
+                        name: *name,
+                        log: log.clone(),
+                    }));
+                let id = next_id();
+                join(arena_id, id, Rc::downgrade(&recognizer));
+                Member {
+                    id,
+                    _recognizer: recognizer,
+                }
+            })
+            .collect();
+        (arena_id, members)
+    }
+
+    #[test]
+    fn test_single_member_wins_when_closed() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["a"], &log);
+        assert!(log.borrow().is_empty());
+        close(arena_id);
+        assert_eq!(*log.borrow(), vec![("a", true)]);
+        assert!(is_member(arena_id, members[0].id));
+        sweep(arena_id);
+        assert!(!is_member(arena_id, members[0].id));
+    }
+
+    #[test]
+    fn test_innermost_wins_sweep() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["outer", "inner"], &log);
+        close(arena_id);
+        assert!(log.borrow().is_empty());
+        sweep(arena_id);
+        assert_eq!(*log.borrow(), vec![("outer", false), ("inner", true)]);
+        assert!(!is_member(arena_id, members[1].id));
+    }
+
+    #[test]
+    fn test_accept_wins_before_pointer_up() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["outer", "inner"], &log);
+        close(arena_id);
+        resolve(arena_id, members[0].id, GestureDecision::Accept);
+        // The losers are told first.
+        assert_eq!(*log.borrow(), vec![("inner", false), ("outer", true)]);
+        // A later accept doesn't change the winner.
+        resolve(arena_id, members[1].id, GestureDecision::Accept);
+        assert_eq!(log.borrow().len(), 2);
+        assert!(is_member(arena_id, members[0].id));
+        assert!(!is_member(arena_id, members[1].id));
+    }
+
+    #[test]
+    fn test_last_member_left_wins() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["a", "b", "c"], &log);
+        close(arena_id);
+        resolve(arena_id, members[2].id, GestureDecision::Reject);
+        assert_eq!(*log.borrow(), vec![("c", false)]);
+        resolve(arena_id, members[0].id, GestureDecision::Reject);
+        assert_eq!(*log.borrow(), vec![("c", false), ("a", false), ("b", true)]);
+    }
+
+    #[test]
+    fn test_open_arena_waits_for_members() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["a", "b"], &log);
+        resolve(arena_id, members[1].id, GestureDecision::Reject);
+        // "a" is alone, but more members may still join the open arena.
+        assert_eq!(*log.borrow(), vec![("b", false)]);
+        close(arena_id);
+        assert_eq!(*log.borrow(), vec![("b", false), ("a", true)]);
+    }
+
+    #[test]
+    fn test_hold_keeps_arena_after_pointer_up() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["tap", "double_tap"], &log);
+        close(arena_id);
+        resolve(arena_id, members[0].id, GestureDecision::Hold);
+        sweep(arena_id);
+        assert!(log.borrow().is_empty());
+        assert!(is_member(arena_id, members[1].id));
+        // The holder gives up, the arena is swept as it would have been.
+        resolve(arena_id, members[0].id, GestureDecision::Reject);
+        assert_eq!(*log.borrow(), vec![("tap", false), ("double_tap", true)]);
+        assert!(!is_member(arena_id, members[1].id));
+    }
+
+    #[test]
+    fn test_holder_accepts() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["tap", "double_tap"], &log);
+        close(arena_id);
+        resolve(arena_id, members[1].id, GestureDecision::Hold);
+        sweep(arena_id);
+        assert!(log.borrow().is_empty());
+        resolve(arena_id, members[1].id, GestureDecision::Accept);
+        assert_eq!(*log.borrow(), vec![("tap", false), ("double_tap", true)]);
+    }
+
+    #[test]
+    fn test_winner_rejecting_keeps_arena_resolved() {
+        let log = Log::default();
+        let (arena_id, members) = arena(&["a", "b"], &log);
+        close(arena_id);
+        resolve(arena_id, members[1].id, GestureDecision::Accept);
+        resolve(arena_id, members[1].id, GestureDecision::Reject);
+        assert_eq!(*log.borrow(), vec![("a", false), ("b", true)]);
+        assert!(is_member(arena_id, members[1].id));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::core::next_id;
+use crate::shared::Shared;
+use crate::ui::app::{Timer, WindowContext};
+use crate::ui::item::gesture::arena;
+use crate::ui::item::{GestureDecision, GestureRecognizer, Pointer, PointerInput, PointerState};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+/// Connects a recognizer to the pointer events of an item and to the gesture arenas.
+pub(crate) struct GestureDetector {
+    id: usize,
+    recognizer: Rc<RefCell<dyn GestureRecognizer>>,
+    /// The arenas the recognizer has joined, with the pointer of each.
+    arenas: Vec<(Pointer, usize)>,
+    /// The deadline a wake-up has been scheduled for.
+    scheduled_deadline: Option<Instant>,
+    is_polling: bool,
+    this: Weak<RefCell<GestureDetector>>,
+}
+
+impl GestureDetector {
+    pub(crate) fn new(recognizer: impl GestureRecognizer + 'static) -> Rc<RefCell<Self>> {
+        Rc::new_cyclic(|this| {
+            RefCell::new(Self {
+                id: next_id(),
+                recognizer: Rc::new(RefCell::new(recognizer)),
+                arenas: Vec::new(),
+                scheduled_deadline: None,
+                is_polling: false,
+                this: this.clone(),
+            })
+        })
+    }
+
+    pub(crate) fn handle(
+        detector: &Rc<RefCell<Self>>,
+        window_context: &WindowContext,
+        input: &PointerInput,
+    ) {
+        let mut detector = detector.borrow_mut();
+        let id = detector.id;
+        match input.pointer_state {
+            PointerState::Started => {
+                if !detector.recognizer.borrow_mut().add_pointer(input) {
+                    return;
+                }
+                let recognizer = Rc::downgrade(&detector.recognizer);
+                let arena_id = arena::enter(window_context, input.pointer, id, recognizer);
+                detector.arenas.push((input.pointer, arena_id));
+            }
+            _ => {
+                // The latest arena of the pointer, an earlier one may still be held.
+                let Some(arena_id) = detector
+                    .arenas
+                    .iter()
+                    .rev()
+                    .find(|(pointer, _)| *pointer == input.pointer)
+                    .map(|(_, arena_id)| *arena_id)
+                else {
+                    return;
+                };
+                arena::close(arena_id);
+                if !arena::is_member(arena_id, id) {
+                    detector.clean_up();
+                    return;
+                }
+                let decision = detector.recognizer.borrow_mut().handle_pointer(input);
+                detector.apply(arena_id, decision);
+                if matches!(
+                    input.pointer_state,
+                    PointerState::Ended | PointerState::Cancelled
+                ) {
+                    arena::pointer_up(window_context, arena_id);
+                }
+            }
+        }
+        detector.clean_up();
+        detector.schedule_poll(window_context);
+    }
+
+    /// Holds only the arena of the event, accepting and rejecting applies to all of them.
+    fn apply(&self, arena_id: usize, decision: GestureDecision) {
+        match decision {
+            GestureDecision::Pending => {}
+            GestureDecision::Hold => arena::resolve(arena_id, self.id, decision),
+            GestureDecision::Accept | GestureDecision::Reject => {
+                for (_, arena_id) in self.arenas.clone() {
+                    arena::resolve(arena_id, self.id, decision);
+                }
+            }
+        }
+    }
+
+    fn clean_up(&mut self) {
+        let id = self.id;
+        self.arenas
+            .retain(|(_, arena_id)| arena::is_member(*arena_id, id));
+    }
+
+    fn schedule_poll(&mut self, window_context: &WindowContext) {
+        let Some(deadline) = self.recognizer.borrow().deadline() else {
+            return;
+        };
+        let timers = window_context.timers.clone();
+        self.wake_at(&timers, deadline);
+        if self.is_polling {
+            return;
+        }
+        self.is_polling = true;
+        let this = self.this.clone();
+        window_context.add_ui_hook(move || {
+            let Some(detector) = this.upgrade() else {
+                return false;
+            };
+            let mut detector = detector.borrow_mut();
+            detector.is_polling = detector.poll(&timers);
+            detector.is_polling
+        });
+    }
+
+    /// Calls the recognizer once its deadline has passed. Returns whether to keep polling.
+    fn poll(&mut self, timers: &Shared<Vec<Timer>>) -> bool {
+        let Some(deadline) = self.recognizer.borrow().deadline() else {
+            return false;
+        };
+        if Instant::now() < deadline {
+            return true;
+        }
+        let decision = self.recognizer.borrow_mut().poll();
+        if let Some((_, arena_id)) = self.arenas.last() {
+            self.apply(*arena_id, decision);
+        }
+        self.clean_up();
+        let deadline = self.recognizer.borrow().deadline();
+        match deadline {
+            Some(deadline) => {
+                self.wake_at(timers, deadline);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes sure an event arrives after the deadline, so that the hooks run.
+    /// The event loop wakes the window once the timer is due.
+    fn wake_at(&mut self, timers: &Shared<Vec<Timer>>, deadline: Instant) {
+        if self.scheduled_deadline == Some(deadline) {
+            return;
+        }
+        self.scheduled_deadline = Some(deadline);
+        let start_time = Instant::now();
+        timers.lock().push(Timer {
+            id: next_id(),
+            start_time,
+            duration: deadline.saturating_duration_since(start_time),
+        });
+    }
+}
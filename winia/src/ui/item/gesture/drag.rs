@@ -0,0 +1,458 @@
+use crate::shared::{Gettable, Shared};
+use crate::ui::animation::VelocityTracker;
+use crate::ui::item::{
+    GestureDecision, GestureRecognizer, Pointer, PointerInput, PointerKind, PointerState,
+    TOUCH_SLOP,
+};
+use std::time::{Duration, Instant};
+
+/// The directions a drag follows. A drag along one axis only starts once the pointer has moved
+/// far enough along that axis, so it loses against a recognizer of the other axis when the
+/// pointer moves the other way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DragAxis {
+    #[default]
+    Both,
+    Horizontal,
+    Vertical,
+}
+
+impl DragAxis {
+    fn exceeds_slop(&self, delta_x: f32, delta_y: f32) -> bool {
+        match self {
+            DragAxis::Both => delta_x.hypot(delta_y) > TOUCH_SLOP,
+            DragAxis::Horizontal => delta_x.abs() > TOUCH_SLOP,
+            DragAxis::Vertical => delta_y.abs() > TOUCH_SLOP,
+        }
+    }
+
+    /// Drops the part of the vector that is not along the axis.
+    fn project(&self, x: f32, y: f32) -> (f32, f32) {
+        match self {
+            DragAxis::Both => (x, y),
+            DragAxis::Horizontal => (x, 0.0),
+            DragAxis::Vertical => (0.0, y),
+        }
+    }
+}
+
+/// Positions are in window coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct DragStart {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DragUpdate {
+    pub x: f32,
+    pub y: f32,
+    pub delta_x: f32,
+    pub delta_y: f32,
+}
+
+/// The velocity in pixels per second when the pointer was lifted.
+#[derive(Clone, Copy, Debug)]
+pub struct DragEnd {
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+}
+
+/// Follows one pointer, shared by drags and swipes.
+struct DragTracker {
+    axis: Shared<DragAxis>,
+    pointer_kind: PointerKind,
+    pointer: Option<Pointer>,
+    start: (f32, f32),
+    last: (f32, f32),
+    velocity_x: VelocityTracker,
+    velocity_y: VelocityTracker,
+    won: bool,
+}
+
+impl DragTracker {
+    fn new(axis: Shared<DragAxis>) -> Self {
+        Self {
+            axis,
+            pointer_kind: PointerKind::Any,
+            pointer: None,
+            start: (0.0, 0.0),
+            last: (0.0, 0.0),
+            velocity_x: VelocityTracker::new(),
+            velocity_y: VelocityTracker::new(),
+            won: false,
+        }
+    }
+
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if self.pointer.is_some() || !self.pointer_kind.accepts(&input.pointer) {
+            return false;
+        }
+        self.pointer = Some(input.pointer);
+        self.start = (input.x, input.y);
+        self.last = (input.x, input.y);
+        self.velocity_x.clear();
+        self.velocity_y.clear();
+        true
+    }
+
+    fn tracks(&self, pointer: &Pointer) -> bool {
+        self.pointer.as_ref() == Some(pointer)
+    }
+
+    /// Records the movement and returns the delta.
+    fn move_to(&mut self, x: f32, y: f32) -> (f32, f32) {
+        let delta = (x - self.last.0, y - self.last.1);
+        self.last = (x, y);
+        self.velocity_x.add_movement(delta.0);
+        self.velocity_y.add_movement(delta.1);
+        delta
+    }
+
+    fn exceeds_slop(&self) -> bool {
+        self.axis
+            .get()
+            .exceeds_slop(self.last.0 - self.start.0, self.last.1 - self.start.1)
+    }
+
+    fn velocity(&self) -> (f32, f32) {
+        self.axis
+            .get()
+            .project(self.velocity_x.velocity(), self.velocity_y.velocity())
+    }
+
+    fn reset(&mut self) {
+        self.pointer = None;
+        self.won = false;
+    }
+}
+
+/// Recognizes a pointer that moves along an axis, e.g. to scroll or to move something around.
+pub struct DragGestureRecognizer {
+    tracker: DragTracker,
+    dragging: bool,
+    touch_delay: Option<Duration>,
+    /// When a touch that has stayed down may start dragging.
+    touch_deadline: Option<Instant>,
+    /// Whether a touch has stayed down for the delay, the drag then starts without the slop.
+    pressed: bool,
+    on_start: Option<Box<dyn FnMut(DragStart)>>,
+    on_update: Option<Box<dyn FnMut(DragUpdate)>>,
+    on_end: Option<Box<dyn FnMut(DragEnd)>>,
+    on_cancel: Option<Box<dyn FnMut()>>,
+}
+
+impl DragGestureRecognizer {
+    pub fn new(axis: impl Into<Shared<DragAxis>>) -> Self {
+        Self {
+            tracker: DragTracker::new(axis.into()),
+            dragging: false,
+            touch_delay: None,
+            touch_deadline: None,
+            pressed: false,
+            on_start: None,
+            on_update: None,
+            on_end: None,
+            on_cancel: None,
+        }
+    }
+
+    pub fn pointer_kind(mut self, pointer_kind: PointerKind) -> Self {
+        self.tracker.pointer_kind = pointer_kind;
+        self
+    }
+
+    /// Makes a touch stay down without moving for `touch_delay` before it can drag, like a long
+    /// press. A touch that moves earlier is left to the other recognizers, e.g. to scroll a list
+    /// along the same axis. Mouse pointers drag right away.
+    pub fn touch_delay(mut self, touch_delay: Duration) -> Self {
+        self.touch_delay = Some(touch_delay);
+        self
+    }
+
+    pub fn on_start(mut self, on_start: impl FnMut(DragStart) + 'static) -> Self {
+        self.on_start = Some(Box::new(on_start));
+        self
+    }
+
+    pub fn on_update(mut self, on_update: impl FnMut(DragUpdate) + 'static) -> Self {
+        self.on_update = Some(Box::new(on_update));
+        self
+    }
+
+    pub fn on_end(mut self, on_end: impl FnMut(DragEnd) + 'static) -> Self {
+        self.on_end = Some(Box::new(on_end));
+        self
+    }
+
+    /// Called when a started drag loses its pointer without ending.
+    pub fn on_cancel(mut self, on_cancel: impl FnMut() + 'static) -> Self {
+        self.on_cancel = Some(Box::new(on_cancel));
+        self
+    }
+
+    fn update(&mut self, delta_x: f32, delta_y: f32) {
+        let (delta_x, delta_y) = self.tracker.axis.get().project(delta_x, delta_y);
+        if delta_x == 0.0 && delta_y == 0.0 {
+            return;
+        }
+        let (x, y) = self.tracker.last;
+        if let Some(on_update) = self.on_update.as_mut() {
+            on_update(DragUpdate {
+                x,
+                y,
+                delta_x,
+                delta_y,
+            });
+        }
+    }
+
+    /// Starts at the position the pointer went down and catches up with the movement since.
+    fn start(&mut self) {
+        self.dragging = true;
+        let (x, y) = self.tracker.start;
+        if let Some(on_start) = self.on_start.as_mut() {
+            on_start(DragStart { x, y });
+        }
+        let (last_x, last_y) = self.tracker.last;
+        self.update(last_x - x, last_y - y);
+    }
+
+    fn reset(&mut self) {
+        self.tracker.reset();
+        self.touch_deadline = None;
+        self.pressed = false;
+    }
+
+    /// Whether the drag may start once it has won the arena.
+    fn can_start(&self) -> bool {
+        self.touch_deadline.is_none() && (self.pressed || self.tracker.exceeds_slop())
+    }
+
+    fn cancel(&mut self) {
+        self.reset();
+        if std::mem::take(&mut self.dragging) {
+            if let Some(on_cancel) = self.on_cancel.as_mut() {
+                on_cancel();
+            }
+        }
+    }
+}
+
+impl GestureRecognizer for DragGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if !self.tracker.add_pointer(input) {
+            return false;
+        }
+        if let (Some(touch_delay), Pointer::Touch { .. }) = (self.touch_delay, input.pointer) {
+            self.touch_deadline = Some(Instant::now() + touch_delay);
+        }
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        if !self.tracker.tracks(&input.pointer) {
+            return GestureDecision::Pending;
+        }
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                let (delta_x, delta_y) = self.tracker.move_to(input.x, input.y);
+                if self.dragging {
+                    self.update(delta_x, delta_y);
+                    GestureDecision::Pending
+                } else if self.touch_deadline.is_some() {
+                    // The touch moved before the delay has passed.
+                    if self.tracker.exceeds_slop() {
+                        self.cancel();
+                        GestureDecision::Reject
+                    } else {
+                        GestureDecision::Pending
+                    }
+                } else if !self.can_start() {
+                    // Even a drag that won the arena alone waits for the slop,
+                    // so that a pointer that wobbles while clicking doesn't drag.
+                    GestureDecision::Pending
+                } else if self.tracker.won {
+                    self.start();
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Accept
+                }
+            }
+            PointerState::Ended => {
+                self.tracker.move_to(input.x, input.y);
+                if !self.dragging {
+                    self.reset();
+                    return GestureDecision::Reject;
+                }
+                let (velocity_x, velocity_y) = self.tracker.velocity();
+                self.reset();
+                self.dragging = false;
+                if let Some(on_end) = self.on_end.as_mut() {
+                    on_end(DragEnd {
+                        velocity_x,
+                        velocity_y,
+                    });
+                }
+                GestureDecision::Pending
+            }
+            PointerState::Cancelled => {
+                self.cancel();
+                GestureDecision::Reject
+            }
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.touch_deadline
+    }
+
+    fn poll(&mut self) -> GestureDecision {
+        match self.touch_deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.touch_deadline = None;
+                self.pressed = true;
+                if self.tracker.won {
+                    self.start();
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Accept
+                }
+            }
+            _ => GestureDecision::Pending,
+        }
+    }
+
+    fn accept_gesture(&mut self, pointer: Pointer) {
+        if !self.tracker.tracks(&pointer) {
+            return;
+        }
+        self.tracker.won = true;
+        if !self.dragging && self.can_start() {
+            self.start();
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        if self.tracker.tracks(&pointer) {
+            self.cancel();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Recognizes a quick movement that ends with the pointer being lifted, e.g. to dismiss
+/// something. Like a drag, it only competes for the pointer once it has moved along its axis.
+pub struct SwipeGestureRecognizer {
+    tracker: DragTracker,
+    min_velocity: f32,
+    on_swipe: Option<Box<dyn FnMut(SwipeDirection, f32)>>,
+}
+
+impl SwipeGestureRecognizer {
+    pub fn new(axis: impl Into<Shared<DragAxis>>) -> Self {
+        Self {
+            tracker: DragTracker::new(axis.into()),
+            min_velocity: 800.0,
+            on_swipe: None,
+        }
+    }
+
+    pub fn pointer_kind(mut self, pointer_kind: PointerKind) -> Self {
+        self.tracker.pointer_kind = pointer_kind;
+        self
+    }
+
+    /// The speed in pixels per second the pointer must have when it is lifted, 800 by default.
+    pub fn min_velocity(mut self, min_velocity: f32) -> Self {
+        self.min_velocity = min_velocity;
+        self
+    }
+
+    /// Called with the direction and the speed of the swipe.
+    pub fn on_swipe(mut self, on_swipe: impl FnMut(SwipeDirection, f32) + 'static) -> Self {
+        self.on_swipe = Some(Box::new(on_swipe));
+        self
+    }
+
+    fn swipe(&mut self) {
+        let (velocity_x, velocity_y) = self.tracker.velocity();
+        let (direction, speed) = if velocity_x.abs() >= velocity_y.abs() {
+            if velocity_x < 0.0 {
+                (SwipeDirection::Left, -velocity_x)
+            } else {
+                (SwipeDirection::Right, velocity_x)
+            }
+        } else if velocity_y < 0.0 {
+            (SwipeDirection::Up, -velocity_y)
+        } else {
+            (SwipeDirection::Down, velocity_y)
+        };
+        if speed < self.min_velocity {
+            return;
+        }
+        if let Some(on_swipe) = self.on_swipe.as_mut() {
+            on_swipe(direction, speed);
+        }
+    }
+}
+
+impl GestureRecognizer for SwipeGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        self.tracker.add_pointer(input)
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        if !self.tracker.tracks(&input.pointer) {
+            return GestureDecision::Pending;
+        }
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                self.tracker.move_to(input.x, input.y);
+                if !self.tracker.won && self.tracker.exceeds_slop() {
+                    GestureDecision::Accept
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended => {
+                self.tracker.move_to(input.x, input.y);
+                let won = self.tracker.won;
+                if won {
+                    self.swipe();
+                }
+                self.tracker.reset();
+                if won {
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Reject
+                }
+            }
+            PointerState::Cancelled => {
+                self.tracker.reset();
+                GestureDecision::Reject
+            }
+        }
+    }
+
+    fn accept_gesture(&mut self, pointer: Pointer) {
+        if self.tracker.tracks(&pointer) {
+            self.tracker.won = true;
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        if self.tracker.tracks(&pointer) {
+            self.tracker.reset();
+        }
+    }
+}
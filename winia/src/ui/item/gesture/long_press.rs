@@ -0,0 +1,157 @@
+use crate::ui::item::gesture::distance;
+use crate::ui::item::{
+    GestureDecision, GestureRecognizer, Pointer, PointerInput, PointerKind, PointerState,
+    TOUCH_SLOP,
+};
+use std::time::{Duration, Instant};
+
+/// Recognizes a pointer that stays down without moving for a while.
+/// Positions are in window coordinates.
+pub struct LongPressGestureRecognizer {
+    pointer_kind: PointerKind,
+    duration: Duration,
+    on_long_press: Option<Box<dyn FnMut(f32, f32)>>,
+    on_long_press_end: Option<Box<dyn FnMut(f32, f32)>>,
+    /// The tracked pointer, where and when it went down.
+    down: Option<(Pointer, f32, f32, Instant)>,
+    elapsed: bool,
+    won: bool,
+    /// Whether the long press has been reported.
+    pressed: bool,
+}
+
+impl Default for LongPressGestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LongPressGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            pointer_kind: PointerKind::Any,
+            duration: Duration::from_millis(500),
+            on_long_press: None,
+            on_long_press_end: None,
+            down: None,
+            elapsed: false,
+            won: false,
+            pressed: false,
+        }
+    }
+
+    pub fn pointer_kind(mut self, pointer_kind: PointerKind) -> Self {
+        self.pointer_kind = pointer_kind;
+        self
+    }
+
+    /// How long the pointer has to stay down, 500ms by default.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn on_long_press(mut self, on_long_press: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_long_press = Some(Box::new(on_long_press));
+        self
+    }
+
+    /// Called when the pointer of a recognized long press is lifted.
+    pub fn on_long_press_end(mut self, on_long_press_end: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_long_press_end = Some(Box::new(on_long_press_end));
+        self
+    }
+
+    fn press(&mut self) {
+        self.pressed = true;
+        if let (Some((_, x, y, _)), Some(on_long_press)) = (self.down, self.on_long_press.as_mut()) {
+            on_long_press(x, y);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.down = None;
+        self.elapsed = false;
+        self.won = false;
+        self.pressed = false;
+    }
+}
+
+impl GestureRecognizer for LongPressGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if self.down.is_some() || !self.pointer_kind.accepts(&input.pointer) {
+            return false;
+        }
+        self.down = Some((input.pointer, input.x, input.y, Instant::now()));
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        let Some((pointer, x, y, _)) = self.down else {
+            return GestureDecision::Pending;
+        };
+        if pointer != input.pointer {
+            return GestureDecision::Pending;
+        }
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                if !self.pressed && distance(x, y, input.x, input.y) > TOUCH_SLOP {
+                    self.reset();
+                    GestureDecision::Reject
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended => {
+                let pressed = self.pressed;
+                self.reset();
+                if pressed {
+                    if let Some(on_long_press_end) = self.on_long_press_end.as_mut() {
+                        on_long_press_end(input.x, input.y);
+                    }
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Reject
+                }
+            }
+            PointerState::Cancelled => {
+                self.reset();
+                GestureDecision::Reject
+            }
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        if self.elapsed {
+            return None;
+        }
+        self.down.map(|(_, _, _, time)| time + self.duration)
+    }
+
+    fn poll(&mut self) -> GestureDecision {
+        self.elapsed = true;
+        if self.won {
+            self.press();
+            GestureDecision::Pending
+        } else {
+            GestureDecision::Accept
+        }
+    }
+
+    fn accept_gesture(&mut self, pointer: Pointer) {
+        if self.down.is_none_or(|(down, _, _, _)| down != pointer) {
+            return;
+        }
+        self.won = true;
+        if self.elapsed && !self.pressed {
+            self.press();
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        if self.down.is_some_and(|(down, _, _, _)| down == pointer) {
+            self.reset();
+        }
+    }
+}
@@ -0,0 +1,408 @@
+mod arena;
+mod detector;
+mod drag;
+mod long_press;
+mod tap;
+mod transform;
+
+pub use drag::*;
+pub use long_press::*;
+pub use tap::*;
+pub use transform::*;
+
+use crate::ui::item::{ItemData, Pointer, PointerInput};
+use crate::ui::Item;
+use std::time::Instant;
+use winit::event::MouseButton;
+
+/// How far a pointer may move before it is no longer a tap or a long press,
+/// and how far it must move before it becomes a drag.
+pub const TOUCH_SLOP: f32 = 8.0;
+
+/// What a recognizer wants to happen in the gesture arena of a pointer.
+///
+/// Every pointer that goes down gets an arena, which all recognizers tracking the pointer join.
+/// Only one of them wins it: the first one that accepts, the last one left after the others have
+/// rejected, or the innermost one when the pointer is lifted and nobody has decided yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GestureDecision {
+    /// Nothing has been decided yet.
+    Pending,
+    /// The gesture has been recognized, the recognizer wins the arena and the others lose it.
+    Accept,
+    /// The gesture is impossible, the recognizer leaves the arena.
+    Reject,
+    /// Keeps the arena open after the pointer has been lifted, e.g. to wait for a second tap.
+    /// It is released when the recognizer accepts or rejects.
+    Hold,
+}
+
+/// Which pointers a recognizer tracks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointerKind {
+    /// Touches and the left mouse button.
+    #[default]
+    Any,
+    Touch,
+    Mouse,
+}
+
+impl PointerKind {
+    pub fn accepts(&self, pointer: &Pointer) -> bool {
+        match (self, pointer) {
+            (PointerKind::Any | PointerKind::Touch, Pointer::Touch { .. }) => true,
+            (PointerKind::Any | PointerKind::Mouse, Pointer::Mouse { button }) => {
+                *button == MouseButton::Left
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Turns the pointer events of an item into a gesture. Added to an item with [`Item::gesture`].
+///
+/// Decisions are returned from [`handle_pointer`](GestureRecognizer::handle_pointer) and
+/// [`poll`](GestureRecognizer::poll), the outcome of the arena arrives through
+/// [`accept_gesture`](GestureRecognizer::accept_gesture) and
+/// [`reject_gesture`](GestureRecognizer::reject_gesture). A recognizer that accepts or rejects
+/// does so for all pointers it tracks. A recognizer may win an arena without accepting, so it
+/// should only report its gesture once the gesture has been recognized and the arena has been won.
+pub trait GestureRecognizer {
+    /// Called when a pointer goes down on the item. Returns whether the pointer is tracked,
+    /// which makes the recognizer join the arena of the pointer.
+    fn add_pointer(&mut self, input: &PointerInput) -> bool;
+    /// Called for the following events of the tracked pointers.
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision;
+    /// When [`poll`](GestureRecognizer::poll) should be called next, used for timeouts.
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+    fn poll(&mut self) -> GestureDecision {
+        GestureDecision::Pending
+    }
+    /// The recognizer has won the arena of `pointer`.
+    fn accept_gesture(&mut self, pointer: Pointer);
+    /// The recognizer has lost the arena of `pointer` and should stop tracking it.
+    fn reject_gesture(&mut self, pointer: Pointer);
+}
+
+impl ItemData {
+    /// Recognizes a gesture on the item, see [`Item::gesture`].
+    pub fn add_gesture(&mut self, recognizer: impl GestureRecognizer + 'static) -> &mut Self {
+        let detector = detector::GestureDetector::new(recognizer);
+        let pointer_input = self.get_pointer_input();
+        self.set_pointer_input(move |item, input| {
+            pointer_input.lock()(item, input);
+            detector::GestureDetector::handle(&detector, item.get_window_context(), input);
+        })
+    }
+}
+
+impl Item {
+    /// Recognizes a gesture on the item. The item keeps receiving its pointer events as before,
+    /// so recognizers can be added to components that handle pointers themselves.
+    pub fn gesture(self, recognizer: impl GestureRecognizer + 'static) -> Self {
+        self.data().add_gesture(recognizer);
+        self
+    }
+}
+
+fn distance(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
+    (x2 - x1).hypot(y2 - y1)
+}
+
+#[cfg(test)]
+mod gesture_test {
+    use super::*;
+    use crate::ui::item::PointerState;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+    use winit::event::DeviceId;
+
+    const TOUCH: Pointer = Pointer::Touch { id: 1 };
+
+    fn input(pointer_state: PointerState, x: f32, y: f32) -> PointerInput {
+        PointerInput {
+            device_id: unsafe { DeviceId::dummy() },
+            pointer: TOUCH,
+            x,
+            y,
+            pointer_state,
+            force: None,
+        }
+    }
+
+    #[test]
+    fn test_pointer_kind() {
+        let left = Pointer::Mouse {
+            button: MouseButton::Left,
+        };
+        let right = Pointer::Mouse {
+            button: MouseButton::Right,
+        };
+        assert!(PointerKind::Any.accepts(&TOUCH));
+        assert!(PointerKind::Any.accepts(&left));
+        assert!(!PointerKind::Any.accepts(&right));
+        assert!(!PointerKind::Mouse.accepts(&TOUCH));
+        assert!(!PointerKind::Touch.accepts(&left));
+    }
+
+    #[test]
+    fn test_tap_within_slop() {
+        let taps = Rc::new(RefCell::new(Vec::new()));
+        let mut tap = TapGestureRecognizer::new().on_tap({
+            let taps = taps.clone();
+            move |x, y| taps.borrow_mut().push((x, y))
+        });
+        assert!(tap.add_pointer(&input(PointerState::Started, 10.0, 10.0)));
+        let moved = input(PointerState::Moved, 10.0 + TOUCH_SLOP, 10.0);
+        assert_eq!(tap.handle_pointer(&moved), GestureDecision::Pending);
+        let ended = input(PointerState::Ended, 12.0, 10.0);
+        assert_eq!(tap.handle_pointer(&ended), GestureDecision::Pending);
+        // The tap is only reported once the arena has been won.
+        assert!(taps.borrow().is_empty());
+        tap.accept_gesture(TOUCH);
+        assert_eq!(*taps.borrow(), vec![(12.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_tap_beyond_slop() {
+        let cancelled = Rc::new(Cell::new(false));
+        let mut tap = TapGestureRecognizer::new().on_tap_cancel({
+            let cancelled = cancelled.clone();
+            move || cancelled.set(true)
+        });
+        tap.add_pointer(&input(PointerState::Started, 10.0, 10.0));
+        let moved = input(PointerState::Moved, 10.0, 11.0 + TOUCH_SLOP);
+        assert_eq!(tap.handle_pointer(&moved), GestureDecision::Reject);
+        assert!(cancelled.get());
+        // The recognizer is free for the next pointer.
+        assert!(tap.add_pointer(&input(PointerState::Started, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let double_taps = Rc::new(RefCell::new(Vec::new()));
+        let mut double_tap = DoubleTapGestureRecognizer::new().on_double_tap({
+            let double_taps = double_taps.clone();
+            move |x, y| double_taps.borrow_mut().push((x, y))
+        });
+        assert!(double_tap.add_pointer(&input(PointerState::Started, 10.0, 10.0)));
+        assert_eq!(double_tap.deadline(), None);
+        let ended = input(PointerState::Ended, 10.0, 10.0);
+        assert_eq!(double_tap.handle_pointer(&ended), GestureDecision::Hold);
+        let deadline = double_tap.deadline().unwrap();
+        assert!(deadline > Instant::now());
+        assert!(deadline <= Instant::now() + DOUBLE_TAP_TIMEOUT);
+        // A second tap too far away is not tracked.
+        let far = input(PointerState::Started, 20.0 + DOUBLE_TAP_SLOP, 10.0);
+        assert!(!double_tap.add_pointer(&far));
+        assert!(double_tap.add_pointer(&input(PointerState::Started, 20.0, 10.0)));
+        assert_eq!(double_tap.deadline(), None);
+        let ended = input(PointerState::Ended, 20.0, 10.0);
+        assert_eq!(double_tap.handle_pointer(&ended), GestureDecision::Accept);
+        double_tap.accept_gesture(TOUCH);
+        assert_eq!(*double_taps.borrow(), vec![(10.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_double_tap_timeout() {
+        let mut double_tap = DoubleTapGestureRecognizer::new();
+        double_tap.add_pointer(&input(PointerState::Started, 10.0, 10.0));
+        double_tap.handle_pointer(&input(PointerState::Ended, 10.0, 10.0));
+        assert_eq!(double_tap.poll(), GestureDecision::Reject);
+        assert_eq!(double_tap.deadline(), None);
+    }
+
+    #[test]
+    fn test_long_press() {
+        let presses = Rc::new(RefCell::new(Vec::new()));
+        let mut long_press = LongPressGestureRecognizer::new()
+            .duration(Duration::ZERO)
+            .on_long_press({
+                let presses = presses.clone();
+                move |x, y| presses.borrow_mut().push((x, y))
+            });
+        long_press.add_pointer(&input(PointerState::Started, 10.0, 10.0));
+        assert!(long_press.deadline().unwrap() <= Instant::now());
+        assert_eq!(long_press.poll(), GestureDecision::Accept);
+        assert_eq!(long_press.deadline(), None);
+        assert!(presses.borrow().is_empty());
+        long_press.accept_gesture(TOUCH);
+        assert_eq!(*presses.borrow(), vec![(10.0, 10.0)]);
+        // Once pressed, the pointer may move.
+        let moved = input(PointerState::Moved, 50.0, 50.0);
+        assert_eq!(long_press.handle_pointer(&moved), GestureDecision::Pending);
+    }
+
+    #[test]
+    fn test_long_press_beyond_slop() {
+        let mut long_press = LongPressGestureRecognizer::new();
+        long_press.add_pointer(&input(PointerState::Started, 10.0, 10.0));
+        let moved = input(PointerState::Moved, 11.0 + TOUCH_SLOP, 10.0);
+        assert_eq!(long_press.handle_pointer(&moved), GestureDecision::Reject);
+        assert_eq!(long_press.deadline(), None);
+    }
+
+    #[test]
+    fn test_long_press_lifted_early() {
+        let mut long_press = LongPressGestureRecognizer::new();
+        long_press.add_pointer(&input(PointerState::Started, 10.0, 10.0));
+        let ended = input(PointerState::Ended, 10.0, 10.0);
+        assert_eq!(long_press.handle_pointer(&ended), GestureDecision::Reject);
+    }
+
+    /// The drag callbacks as (event, x, y) for starts and (event, delta_x, delta_y) for updates.
+    type Events = Rc<RefCell<Vec<(&'static str, f32, f32)>>>;
+
+    fn recording_drag(axis: DragAxis) -> (DragGestureRecognizer, Events) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let drag = DragGestureRecognizer::new(axis)
+            .on_start({
+                let events = events.clone();
+                move |start| events.borrow_mut().push(("start", start.x, start.y))
+            })
+            .on_update({
+                let events = events.clone();
+                move |update| {
+                    events
+                        .borrow_mut()
+                        .push(("update", update.delta_x, update.delta_y))
+                }
+            })
+            .on_end({
+                let events = events.clone();
+                move |_| events.borrow_mut().push(("end", 0.0, 0.0))
+            })
+            .on_cancel({
+                let events = events.clone();
+                move || events.borrow_mut().push(("cancel", 0.0, 0.0))
+            });
+        (drag, events)
+    }
+
+    #[test]
+    fn test_drag_waits_for_slop() {
+        let (mut drag, events) = recording_drag(DragAxis::Both);
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        let moved = input(PointerState::Moved, 4.0, 4.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Pending);
+        // Winning the arena alone doesn't start the drag before the slop.
+        drag.accept_gesture(TOUCH);
+        assert!(events.borrow().is_empty());
+        let moved = input(PointerState::Moved, 8.0, 8.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Pending);
+        // The drag starts where the pointer went down and catches up.
+        assert_eq!(
+            *events.borrow(),
+            vec![("start", 0.0, 0.0), ("update", 8.0, 8.0)]
+        );
+        let moved = input(PointerState::Moved, 10.0, 8.0);
+        drag.handle_pointer(&moved);
+        let ended = input(PointerState::Ended, 10.0, 8.0);
+        assert_eq!(drag.handle_pointer(&ended), GestureDecision::Pending);
+        assert_eq!(events.borrow()[2], ("update", 2.0, 0.0));
+        assert_eq!(events.borrow()[3].0, "end");
+    }
+
+    #[test]
+    fn test_drag_accepts_after_slop() {
+        let (mut drag, events) = recording_drag(DragAxis::Both);
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        let moved = input(PointerState::Moved, TOUCH_SLOP + 1.0, 0.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Accept);
+        assert!(events.borrow().is_empty());
+        drag.accept_gesture(TOUCH);
+        assert_eq!(
+            *events.borrow(),
+            vec![("start", 0.0, 0.0), ("update", TOUCH_SLOP + 1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_drag_axis() {
+        let (mut drag, events) = recording_drag(DragAxis::Horizontal);
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        // Moving across the axis doesn't make it compete.
+        let moved = input(PointerState::Moved, 2.0, 50.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Pending);
+        let moved = input(PointerState::Moved, 20.0, 60.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Accept);
+        drag.accept_gesture(TOUCH);
+        // Only the movement along the axis is reported.
+        assert_eq!(
+            *events.borrow(),
+            vec![("start", 0.0, 0.0), ("update", 20.0, 0.0)]
+        );
+        let moved = input(PointerState::Moved, 20.0, 80.0);
+        drag.handle_pointer(&moved);
+        assert_eq!(events.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_drag_lost_arena() {
+        let (mut drag, events) = recording_drag(DragAxis::Both);
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        drag.accept_gesture(TOUCH);
+        drag.handle_pointer(&input(PointerState::Moved, 20.0, 0.0));
+        drag.reject_gesture(TOUCH);
+        assert_eq!(events.borrow().last().unwrap().0, "cancel");
+        // Lifting a pointer that never dragged rejects.
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        let ended = input(PointerState::Ended, 1.0, 0.0);
+        assert_eq!(drag.handle_pointer(&ended), GestureDecision::Reject);
+    }
+
+    #[test]
+    fn test_drag_touch_delay() {
+        let (drag, _) = recording_drag(DragAxis::Vertical);
+        let mut drag = drag.touch_delay(Duration::from_secs(60));
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        assert!(drag.deadline().is_some());
+        assert_eq!(drag.poll(), GestureDecision::Pending);
+        // Moving before the delay leaves the pointer to the others, even along the axis.
+        let moved = input(PointerState::Moved, 0.0, TOUCH_SLOP + 1.0);
+        assert_eq!(drag.handle_pointer(&moved), GestureDecision::Reject);
+        assert_eq!(drag.deadline(), None);
+    }
+
+    #[test]
+    fn test_drag_touch_delay_elapsed() {
+        let (drag, events) = recording_drag(DragAxis::Vertical);
+        let mut drag = drag.touch_delay(Duration::ZERO);
+        drag.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        assert_eq!(drag.poll(), GestureDecision::Accept);
+        assert_eq!(drag.deadline(), None);
+        // After the delay the drag starts without the slop.
+        drag.accept_gesture(TOUCH);
+        assert_eq!(*events.borrow(), vec![("start", 0.0, 0.0)]);
+        drag.handle_pointer(&input(PointerState::Moved, 0.0, 1.0));
+        assert_eq!(events.borrow()[1], ("update", 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_swipe_needs_arena() {
+        let swipes = Rc::new(Cell::new(0));
+        let mut swipe = SwipeGestureRecognizer::new(DragAxis::Horizontal)
+            .min_velocity(0.0)
+            .on_swipe({
+                let swipes = swipes.clone();
+                move |_, _| swipes.set(swipes.get() + 1)
+            });
+        swipe.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        let moved = input(PointerState::Moved, 0.0, 50.0);
+        assert_eq!(swipe.handle_pointer(&moved), GestureDecision::Pending);
+        let ended = input(PointerState::Ended, 0.0, 60.0);
+        assert_eq!(swipe.handle_pointer(&ended), GestureDecision::Reject);
+        assert_eq!(swipes.get(), 0);
+
+        swipe.add_pointer(&input(PointerState::Started, 0.0, 0.0));
+        let moved = input(PointerState::Moved, 50.0, 0.0);
+        assert_eq!(swipe.handle_pointer(&moved), GestureDecision::Accept);
+        swipe.accept_gesture(TOUCH);
+        let ended = input(PointerState::Ended, 60.0, 0.0);
+        assert_eq!(swipe.handle_pointer(&ended), GestureDecision::Pending);
+        assert_eq!(swipes.get(), 1);
+    }
+}
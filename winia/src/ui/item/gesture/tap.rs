@@ -0,0 +1,287 @@
+use crate::ui::item::gesture::distance;
+use crate::ui::item::{
+    GestureDecision, GestureRecognizer, Pointer, PointerInput, PointerKind, PointerState,
+    TOUCH_SLOP,
+};
+use std::time::{Duration, Instant};
+
+/// The longest time between the first tap ending and the second tap starting.
+pub const DOUBLE_TAP_TIMEOUT: Duration = Duration::from_millis(300);
+/// How far the second tap may be from the first one.
+pub const DOUBLE_TAP_SLOP: f32 = 100.0;
+
+/// Recognizes a pointer that goes down and up without moving.
+/// Positions are in window coordinates.
+pub struct TapGestureRecognizer {
+    pointer_kind: PointerKind,
+    on_tap_down: Option<Box<dyn FnMut(f32, f32)>>,
+    on_tap: Option<Box<dyn FnMut(f32, f32)>>,
+    on_tap_cancel: Option<Box<dyn FnMut()>>,
+    /// The tracked pointer and where it went down.
+    down: Option<(Pointer, f32, f32)>,
+    /// Where the pointer has been lifted, while the arena has not been won yet.
+    up: Option<(f32, f32)>,
+    won: bool,
+}
+
+impl Default for TapGestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TapGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            pointer_kind: PointerKind::Any,
+            on_tap_down: None,
+            on_tap: None,
+            on_tap_cancel: None,
+            down: None,
+            up: None,
+            won: false,
+        }
+    }
+
+    pub fn pointer_kind(mut self, pointer_kind: PointerKind) -> Self {
+        self.pointer_kind = pointer_kind;
+        self
+    }
+
+    /// Called as soon as the pointer goes down, before it is known whether it becomes a tap.
+    pub fn on_tap_down(mut self, on_tap_down: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_tap_down = Some(Box::new(on_tap_down));
+        self
+    }
+
+    pub fn on_tap(mut self, on_tap: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_tap = Some(Box::new(on_tap));
+        self
+    }
+
+    /// Called when a pointer reported by `on_tap_down` does not become a tap.
+    pub fn on_tap_cancel(mut self, on_tap_cancel: impl FnMut() + 'static) -> Self {
+        self.on_tap_cancel = Some(Box::new(on_tap_cancel));
+        self
+    }
+
+    fn tap(&mut self, x: f32, y: f32) {
+        self.reset();
+        if let Some(on_tap) = self.on_tap.as_mut() {
+            on_tap(x, y);
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.reset();
+        if let Some(on_tap_cancel) = self.on_tap_cancel.as_mut() {
+            on_tap_cancel();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.down = None;
+        self.up = None;
+        self.won = false;
+    }
+}
+
+impl GestureRecognizer for TapGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if self.down.is_some() || !self.pointer_kind.accepts(&input.pointer) {
+            return false;
+        }
+        self.down = Some((input.pointer, input.x, input.y));
+        if let Some(on_tap_down) = self.on_tap_down.as_mut() {
+            on_tap_down(input.x, input.y);
+        }
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        let Some((pointer, x, y)) = self.down else {
+            return GestureDecision::Pending;
+        };
+        if pointer != input.pointer {
+            return GestureDecision::Pending;
+        }
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                if distance(x, y, input.x, input.y) > TOUCH_SLOP {
+                    self.cancel();
+                    GestureDecision::Reject
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended => {
+                if self.won {
+                    self.tap(input.x, input.y);
+                } else {
+                    self.up = Some((input.x, input.y));
+                }
+                GestureDecision::Pending
+            }
+            PointerState::Cancelled => {
+                self.cancel();
+                GestureDecision::Reject
+            }
+        }
+    }
+
+    fn accept_gesture(&mut self, pointer: Pointer) {
+        if self.down.is_none_or(|(down, _, _)| down != pointer) {
+            return;
+        }
+        self.won = true;
+        if let Some((x, y)) = self.up {
+            self.tap(x, y);
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        if self.down.is_some_and(|(down, _, _)| down == pointer) {
+            self.cancel();
+        }
+    }
+}
+
+/// Recognizes two taps in quick succession. It keeps the arena of the first tap open,
+/// so a [`TapGestureRecognizer`] competing with it only reports the tap after
+/// [`DOUBLE_TAP_TIMEOUT`]. Positions are in window coordinates.
+pub struct DoubleTapGestureRecognizer {
+    pointer_kind: PointerKind,
+    on_double_tap: Option<Box<dyn FnMut(f32, f32)>>,
+    /// Where the first tap went down and when it ended.
+    first_tap: Option<(f32, f32, Instant)>,
+    /// The tracked pointer and where it went down.
+    down: Option<(Pointer, f32, f32)>,
+    /// Whether the second tap has ended and is waiting for the arena.
+    second_tap_up: bool,
+    /// Whether the arena of the tracked pointer has been won.
+    won: bool,
+}
+
+impl Default for DoubleTapGestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DoubleTapGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            pointer_kind: PointerKind::Any,
+            on_double_tap: None,
+            first_tap: None,
+            down: None,
+            second_tap_up: false,
+            won: false,
+        }
+    }
+
+    pub fn pointer_kind(mut self, pointer_kind: PointerKind) -> Self {
+        self.pointer_kind = pointer_kind;
+        self
+    }
+
+    /// Called with the position of the first tap.
+    pub fn on_double_tap(mut self, on_double_tap: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_double_tap = Some(Box::new(on_double_tap));
+        self
+    }
+
+    fn double_tap(&mut self) {
+        let first_tap = self.first_tap;
+        self.reset();
+        if let (Some((x, y, _)), Some(on_double_tap)) = (first_tap, self.on_double_tap.as_mut()) {
+            on_double_tap(x, y);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.first_tap = None;
+        self.down = None;
+        self.second_tap_up = false;
+        self.won = false;
+    }
+}
+
+impl GestureRecognizer for DoubleTapGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if self.down.is_some() || !self.pointer_kind.accepts(&input.pointer) {
+            return false;
+        }
+        if let Some((x, y, _)) = self.first_tap {
+            if distance(x, y, input.x, input.y) > DOUBLE_TAP_SLOP {
+                return false;
+            }
+        }
+        self.down = Some((input.pointer, input.x, input.y));
+        self.won = false;
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        let Some((pointer, x, y)) = self.down else {
+            return GestureDecision::Pending;
+        };
+        if pointer != input.pointer {
+            return GestureDecision::Pending;
+        }
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                if distance(x, y, input.x, input.y) > TOUCH_SLOP {
+                    self.reset();
+                    GestureDecision::Reject
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended => {
+                if self.first_tap.is_none() {
+                    self.first_tap = Some((x, y, Instant::now()));
+                    self.down = None;
+                    GestureDecision::Hold
+                } else if self.won {
+                    self.double_tap();
+                    GestureDecision::Pending
+                } else {
+                    self.second_tap_up = true;
+                    GestureDecision::Accept
+                }
+            }
+            PointerState::Cancelled => {
+                self.reset();
+                GestureDecision::Reject
+            }
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        if self.down.is_some() {
+            return None;
+        }
+        self.first_tap
+            .map(|(_, _, time)| time + DOUBLE_TAP_TIMEOUT)
+    }
+
+    fn poll(&mut self) -> GestureDecision {
+        self.reset();
+        GestureDecision::Reject
+    }
+
+    fn accept_gesture(&mut self, pointer: Pointer) {
+        if self.second_tap_up {
+            self.double_tap();
+        } else if self.down.is_some_and(|(down, _, _)| down == pointer) {
+            self.won = true;
+        }
+    }
+
+    fn reject_gesture(&mut self, _pointer: Pointer) {
+        self.reset();
+    }
+}
@@ -0,0 +1,440 @@
+use crate::ui::item::{GestureDecision, GestureRecognizer, Pointer, PointerInput, PointerState, TOUCH_SLOP};
+use std::f32::consts::PI;
+
+/// How far two fingers must turn before it is a rotation, in radians.
+pub const ROTATION_SLOP: f32 = PI / 36.0;
+
+/// Positions are in window coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct ScaleUpdate {
+    /// The center of the touches.
+    pub focal_x: f32,
+    pub focal_y: f32,
+    /// The scale since the gesture started.
+    pub scale: f32,
+}
+
+/// Positions are in window coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct RotateUpdate {
+    /// The center of the touches.
+    pub focal_x: f32,
+    pub focal_y: f32,
+    /// The clockwise rotation since the gesture started, in degrees.
+    pub rotation: f32,
+}
+
+/// The touches of a multi-finger gesture.
+#[derive(Default)]
+struct Touches {
+    touches: Vec<(Pointer, f32, f32)>,
+    won: bool,
+}
+
+impl Touches {
+    fn add(&mut self, input: &PointerInput) -> bool {
+        if !matches!(input.pointer, Pointer::Touch { .. }) {
+            return false;
+        }
+        self.touches.push((input.pointer, input.x, input.y));
+        true
+    }
+
+    fn move_to(&mut self, input: &PointerInput) {
+        if let Some(touch) = self
+            .touches
+            .iter_mut()
+            .find(|(pointer, _, _)| *pointer == input.pointer)
+        {
+            touch.1 = input.x;
+            touch.2 = input.y;
+        }
+    }
+
+    fn remove(&mut self, pointer: &Pointer) {
+        self.touches.retain(|(touch, _, _)| touch != pointer);
+        if self.touches.is_empty() {
+            self.won = false;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.touches.len()
+    }
+
+    fn focal_point(&self) -> (f32, f32) {
+        let count = self.touches.len().max(1) as f32;
+        let (x, y) = self
+            .touches
+            .iter()
+            .fold((0.0, 0.0), |(x, y), (_, touch_x, touch_y)| (x + touch_x, y + touch_y));
+        (x / count, y / count)
+    }
+
+    /// The average distance of the touches to the focal point.
+    fn span(&self) -> f32 {
+        let (focal_x, focal_y) = self.focal_point();
+        let count = self.touches.len().max(1) as f32;
+        self.touches
+            .iter()
+            .map(|(_, x, y)| (x - focal_x).hypot(y - focal_y))
+            .sum::<f32>()
+            / count
+    }
+
+    /// The angle of the line from the first to the second touch.
+    fn angle(&self) -> f32 {
+        match self.touches.as_slice() {
+            [(_, x1, y1), (_, x2, y2), ..] => (y2 - y1).atan2(x2 - x1),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Recognizes two or more fingers moving apart or together.
+pub struct ScaleGestureRecognizer {
+    touches: Touches,
+    /// The span the current scale is relative to, it changes when fingers are added or lifted.
+    initial_span: f32,
+    /// The scale reached before the fingers last changed.
+    base_scale: f32,
+    scale: f32,
+    scaling: bool,
+    on_start: Option<Box<dyn FnMut(f32, f32)>>,
+    on_update: Option<Box<dyn FnMut(ScaleUpdate)>>,
+    on_end: Option<Box<dyn FnMut(f32)>>,
+}
+
+impl Default for ScaleGestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScaleGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            touches: Touches::default(),
+            initial_span: 0.0,
+            base_scale: 1.0,
+            scale: 1.0,
+            scaling: false,
+            on_start: None,
+            on_update: None,
+            on_end: None,
+        }
+    }
+
+    /// Called with the focal point when the fingers have moved far enough.
+    pub fn on_start(mut self, on_start: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_start = Some(Box::new(on_start));
+        self
+    }
+
+    pub fn on_update(mut self, on_update: impl FnMut(ScaleUpdate) + 'static) -> Self {
+        self.on_update = Some(Box::new(on_update));
+        self
+    }
+
+    /// Called with the final scale when fewer than two fingers are left.
+    pub fn on_end(mut self, on_end: impl FnMut(f32) + 'static) -> Self {
+        self.on_end = Some(Box::new(on_end));
+        self
+    }
+
+    fn rebase(&mut self) {
+        self.base_scale = self.scale;
+        self.initial_span = self.touches.span();
+    }
+
+    fn exceeds_slop(&self) -> bool {
+        self.touches.len() >= 2 && (self.touches.span() - self.initial_span).abs() > TOUCH_SLOP
+    }
+
+    fn start(&mut self) {
+        self.scaling = true;
+        let (focal_x, focal_y) = self.touches.focal_point();
+        if let Some(on_start) = self.on_start.as_mut() {
+            on_start(focal_x, focal_y);
+        }
+        self.update();
+    }
+
+    fn update(&mut self) {
+        if self.initial_span > 0.0 {
+            self.scale = self.base_scale * self.touches.span() / self.initial_span;
+        }
+        let (focal_x, focal_y) = self.touches.focal_point();
+        let scale = self.scale;
+        if let Some(on_update) = self.on_update.as_mut() {
+            on_update(ScaleUpdate {
+                focal_x,
+                focal_y,
+                scale,
+            });
+        }
+    }
+
+    fn end(&mut self) {
+        if std::mem::take(&mut self.scaling) {
+            let scale = self.scale;
+            if let Some(on_end) = self.on_end.as_mut() {
+                on_end(scale);
+            }
+        }
+        self.base_scale = 1.0;
+        self.scale = 1.0;
+    }
+}
+
+impl GestureRecognizer for ScaleGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if !self.touches.add(input) {
+            return false;
+        }
+        self.rebase();
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                self.touches.move_to(input);
+                if self.scaling {
+                    self.update();
+                    GestureDecision::Pending
+                } else if self.exceeds_slop() {
+                    if self.touches.won {
+                        self.start();
+                        GestureDecision::Pending
+                    } else {
+                        GestureDecision::Accept
+                    }
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended | PointerState::Cancelled => {
+                let scaling = self.scaling;
+                self.touches.remove(&input.pointer);
+                if self.touches.len() < 2 {
+                    self.end();
+                }
+                self.rebase();
+                if scaling || self.touches.len() > 0 {
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Reject
+                }
+            }
+        }
+    }
+
+    fn accept_gesture(&mut self, _pointer: Pointer) {
+        self.touches.won = true;
+        if !self.scaling && self.exceeds_slop() {
+            self.start();
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        self.touches.remove(&pointer);
+        self.end();
+        self.rebase();
+    }
+}
+
+/// Recognizes two fingers turning around each other.
+pub struct RotateGestureRecognizer {
+    touches: Touches,
+    /// The angle the current rotation is relative to, it changes when fingers are added or lifted.
+    initial_angle: f32,
+    /// The rotation reached before the fingers last changed, in radians.
+    base_rotation: f32,
+    rotation: f32,
+    rotating: bool,
+    on_start: Option<Box<dyn FnMut(f32, f32)>>,
+    on_update: Option<Box<dyn FnMut(RotateUpdate)>>,
+    on_end: Option<Box<dyn FnMut(f32)>>,
+}
+
+impl Default for RotateGestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RotateGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            touches: Touches::default(),
+            initial_angle: 0.0,
+            base_rotation: 0.0,
+            rotation: 0.0,
+            rotating: false,
+            on_start: None,
+            on_update: None,
+            on_end: None,
+        }
+    }
+
+    /// Called with the focal point when the fingers have turned far enough.
+    pub fn on_start(mut self, on_start: impl FnMut(f32, f32) + 'static) -> Self {
+        self.on_start = Some(Box::new(on_start));
+        self
+    }
+
+    pub fn on_update(mut self, on_update: impl FnMut(RotateUpdate) + 'static) -> Self {
+        self.on_update = Some(Box::new(on_update));
+        self
+    }
+
+    /// Called with the final rotation in degrees when fewer than two fingers are left.
+    pub fn on_end(mut self, on_end: impl FnMut(f32) + 'static) -> Self {
+        self.on_end = Some(Box::new(on_end));
+        self
+    }
+
+    fn rebase(&mut self) {
+        self.base_rotation = self.rotation;
+        self.initial_angle = self.touches.angle();
+    }
+
+    /// The rotation since the last rebase, wrapped into -PI..PI.
+    fn turned(&self) -> f32 {
+        let turned = self.touches.angle() - self.initial_angle;
+        (turned + PI).rem_euclid(2.0 * PI) - PI
+    }
+
+    fn exceeds_slop(&self) -> bool {
+        self.touches.len() >= 2 && self.turned().abs() > ROTATION_SLOP
+    }
+
+    fn start(&mut self) {
+        self.rotating = true;
+        let (focal_x, focal_y) = self.touches.focal_point();
+        if let Some(on_start) = self.on_start.as_mut() {
+            on_start(focal_x, focal_y);
+        }
+        self.update();
+    }
+
+    fn update(&mut self) {
+        self.rotation = self.base_rotation + self.turned();
+        let (focal_x, focal_y) = self.touches.focal_point();
+        let rotation = self.rotation.to_degrees();
+        if let Some(on_update) = self.on_update.as_mut() {
+            on_update(RotateUpdate {
+                focal_x,
+                focal_y,
+                rotation,
+            });
+        }
+    }
+
+    fn end(&mut self) {
+        if std::mem::take(&mut self.rotating) {
+            let rotation = self.rotation.to_degrees();
+            if let Some(on_end) = self.on_end.as_mut() {
+                on_end(rotation);
+            }
+        }
+        self.base_rotation = 0.0;
+        self.rotation = 0.0;
+    }
+}
+
+impl GestureRecognizer for RotateGestureRecognizer {
+    fn add_pointer(&mut self, input: &PointerInput) -> bool {
+        if !self.touches.add(input) {
+            return false;
+        }
+        self.rebase();
+        true
+    }
+
+    fn handle_pointer(&mut self, input: &PointerInput) -> GestureDecision {
+        match input.pointer_state {
+            PointerState::Started => GestureDecision::Pending,
+            PointerState::Moved => {
+                self.touches.move_to(input);
+                if self.rotating {
+                    self.update();
+                    GestureDecision::Pending
+                } else if self.exceeds_slop() {
+                    if self.touches.won {
+                        self.start();
+                        GestureDecision::Pending
+                    } else {
+                        GestureDecision::Accept
+                    }
+                } else {
+                    GestureDecision::Pending
+                }
+            }
+            PointerState::Ended | PointerState::Cancelled => {
+                let rotating = self.rotating;
+                self.touches.remove(&input.pointer);
+                if self.touches.len() < 2 {
+                    self.end();
+                }
+                self.rebase();
+                if rotating || self.touches.len() > 0 {
+                    GestureDecision::Pending
+                } else {
+                    GestureDecision::Reject
+                }
+            }
+        }
+    }
+
+    fn accept_gesture(&mut self, _pointer: Pointer) {
+        self.touches.won = true;
+        if !self.rotating && self.exceeds_slop() {
+            self.start();
+        }
+    }
+
+    fn reject_gesture(&mut self, pointer: Pointer) {
+        self.touches.remove(&pointer);
+        self.end();
+        self.rebase();
+    }
+}
+
+#[cfg(test)]
+mod transform_test {
+    use super::Touches;
+    use crate::ui::item::{Pointer, PointerInput, PointerState};
+    use std::f32::consts::FRAC_PI_2;
+    use winit::event::DeviceId;
+
+    fn touch(id: u64, x: f32, y: f32) -> PointerInput {
+        PointerInput {
+            device_id: unsafe { DeviceId::dummy() },
+            pointer: Pointer::Touch { id },
+            x,
+            y,
+            pointer_state: PointerState::Started,
+            force: None,
+        }
+    }
+
+    #[test]
+    fn test_touches() {
+        let mut touches = Touches::default();
+        assert!(touches.add(&touch(1, 0.0, 0.0)));
+        assert!(touches.add(&touch(2, 0.0, 10.0)));
+        assert_eq!(touches.focal_point(), (0.0, 5.0));
+        assert_eq!(touches.span(), 5.0);
+        assert!((touches.angle() - FRAC_PI_2).abs() < 1e-6);
+        touches.move_to(&touch(2, 0.0, 20.0));
+        assert_eq!(touches.focal_point(), (0.0, 10.0));
+        assert_eq!(touches.span(), 10.0);
+        touches.remove(&Pointer::Touch { id: 1 });
+        assert_eq!(touches.len(), 1);
+        assert_eq!(touches.span(), 0.0);
+        assert_eq!(touches.angle(), 0.0);
+    }
+}
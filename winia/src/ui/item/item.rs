@@ -1,85 +1,93 @@
-use crate::core::{bind_str_to_id, next_id, unbind_id};
-use crate::shared::{Children, Gettable, LocalObservable, Observable, Settable, Shared, SharedAlignment, SharedBool, SharedColor, SharedF32, SharedInnerPosition, SharedItem, SharedSize, SharedUsize};
+use crate::core::{bind_str_to_id, log_target, next_id, unbind_id};
+use crate::shared::{owner_detached, Children, Gettable, LocalObservable, Observable, Settable, Shared, SharedAlignment, SharedBool, SharedColor, SharedF32, SharedInnerPosition, SharedItem, SharedSize, SharedUsize};
 use crate::ui::animation::{Target, Animation};
-use crate::ui::app::WindowContext;
-use crate::ui::item::{DisplayParameter, InnerPosition, Size};
+use crate::ui::app::{is_hit, FocusDirection, WindowContext};
+use crate::ui::item::draw_cache::DrawCache;
+use crate::ui::item::shadow::{draw_background_blur, draw_content_shadow, draw_shadow};
+use crate::ui::item::{transform, Brush, DisplayParameter, InnerPosition, Size};
 use crate::ui::theme::color;
 use crate::ui::{LayoutAnimation, Theme};
 use parking_lot::{Mutex, MutexGuard};
 use proc_macro::AsRef;
-use skia_safe::image_filters::CropRect;
-use skia_safe::{
-    image_filters, surfaces, BlendMode, Canvas, Color, IRect, Paint, Path, Point, Rect, Surface,
-    TileMode, Vector,
-};
+use skia_safe::{Canvas, Color, Matrix, Path, Point, Rect, M44};
 use std::any::Any;
 use std::collections::{HashMap, HashSet, LinkedList};
 use std::ops::{Add, DerefMut, Not};
+use std::panic::Location;
 use std::sync::{Arc, Weak};
 use std::time::Instant;
 use winit::event::{DeviceId, Force, KeyEvent, Modifiers, MouseButton, TouchPhase};
+use winit::window::CursorIcon;
 
+#[track_caller]
 pub fn layout<T: Send>(
     mut property: Shared<T>,
     id: usize,
     window_context: &WindowContext,
 ) -> Shared<T> {
     let event_loop_proxy = window_context.event_loop_proxy().clone();
+    let location = Location::caller();
     property
         .add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_layout();
+                event_loop_proxy.request_layout_from(location);
             }),
         )
         .drop();
     property
 }
 
+#[track_caller]
 pub fn init_property_layout<T: Send>(
     property: &mut Shared<T>,
     id: usize,
     window_context: &WindowContext,
 ) {
     let event_loop_proxy = window_context.event_loop_proxy().clone();
+    let location = Location::caller();
     property
         .add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_layout();
+                event_loop_proxy.request_layout_from(location);
             }),
         )
         .drop();
 }
 
+#[track_caller]
 pub fn redraw<T: Send>(
     mut property: Shared<T>,
     id: usize,
     window_context: &WindowContext,
 ) -> Shared<T> {
     let event_loop_proxy = window_context.event_loop_proxy().clone();
+    let location = Location::caller();
     property
         .add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_redraw();
+                event_loop_proxy.request_repaint_from(id, location);
             }),
         )
         .drop();
     property
 }
 
+#[track_caller]
 pub fn init_property_redraw<T: Send>(
     property: &mut Shared<T>,
     id: usize,
     window_context: &WindowContext,
 ) {
     let event_loop_proxy = window_context.event_loop_proxy().clone();
+    let location = Location::caller();
     property
         .add_observer(
             id,
             Box::new(move || {
-                event_loop_proxy.request_redraw();
+                event_loop_proxy.request_repaint_from(id, location);
             }),
         )
         .drop();
@@ -89,6 +97,7 @@ macro_rules! impl_property_layout {
     ($property_name:ident, $set_property_name:ident, $get_property_name:ident, $property_type:ty, $doc:expr) => {
         impl ItemData {
             #[doc=$doc]
+            #[track_caller]
             pub fn $set_property_name(&mut self, $property_name: impl Into<$property_type>) {
                 self.$property_name.remove_observer(self.id);
                 self.$property_name = $property_name.into();
@@ -102,6 +111,7 @@ macro_rules! impl_property_layout {
 
         impl Item {
             #[doc=$doc]
+            #[track_caller]
             pub fn $property_name(self, $property_name: impl Into<$property_type>) -> Self {
                 let mut item = self.data.lock();
                 item.$set_property_name($property_name);
@@ -116,6 +126,7 @@ macro_rules! impl_property_redraw {
     ($property_name:ident, $set_property_name:ident, $get_property_name:ident, $property_type:ty, $doc:expr) => {
         impl ItemData {
             #[doc=$doc]
+            #[track_caller]
             pub fn $set_property_name(&mut self, $property_name: impl Into<$property_type>) {
                 self.$property_name.remove_observer(self.id);
                 self.$property_name = $property_name.into();
@@ -129,6 +140,7 @@ macro_rules! impl_property_redraw {
 
         impl Item {
             #[doc=$doc]
+            #[track_caller]
             pub fn $property_name(self, $property_name: impl Into<$property_type>) -> Self {
                 let mut item = self.data.lock();
                 item.$set_property_name($property_name);
@@ -415,6 +427,8 @@ pub enum ClickSource {
     Mouse(MouseButton),
     Touch,
     LongTouch,
+    /// Enter or Space pressed while the item is focused.
+    Keyboard,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, AsRef)]
@@ -502,8 +516,12 @@ struct Animations {
     skew_y: AnimationOption,
     skew_center_x: AnimationOption,
     skew_center_y: AnimationOption,
+    transform: Option<(M44, M44, Box<dyn Animation>)>,
+    transform_center_x: AnimationOption,
+    transform_center_y: AnimationOption,
     float_params: HashMap<String, (f32, f32, Box<dyn Animation>)>,
     color_params: HashMap<String, (Color, Color, Box<dyn Animation>)>,
+    brush_params: HashMap<String, (Brush, Brush, Box<dyn Animation>)>,
 }
 
 impl Animations {
@@ -526,8 +544,40 @@ impl Animations {
             || self.skew_y.is_some()
             || self.skew_center_x.is_some()
             || self.skew_center_y.is_some()
+            || self.transform.is_some()
+            || self.transform_center_x.is_some()
+            || self.transform_center_y.is_some()
             || !self.float_params.is_empty()
             || !self.color_params.is_empty()
+            || !self.brush_params.is_empty()
+    }
+}
+
+/// Where an item was last drawn, pointer events are matched against it.
+pub(crate) struct HitRegion {
+    /// Maps window coordinates to the coordinates the item was drawn in.
+    to_local: Matrix,
+    shape: Path,
+    opacity: f32,
+}
+
+impl HitRegion {
+    /// `to_window` maps the coordinates the shape is drawn in to window coordinates.
+    pub(crate) fn new(to_window: &Matrix, shape: Path, opacity: f32) -> Option<Self> {
+        to_window.invert().map(|to_local| Self {
+            to_local,
+            shape,
+            opacity,
+        })
+    }
+
+    pub(crate) fn contains(&self, x: f32, y: f32) -> bool {
+        self.is_shown() && self.shape.contains(self.to_local.map_point((x, y)))
+    }
+
+    /// Whether anything was drawn, a fully transparent item hides its children too.
+    pub(crate) fn is_shown(&self) -> bool {
+        self.opacity > 0.0
     }
 }
 
@@ -554,16 +604,25 @@ pub struct ItemData {
     children: Children,
     clip: Shared<bool>,
     clip_shape: Shared<Box<dyn Fn(&mut ItemData) -> Path + Send>>,
+    /// Whether the clip shape was set, otherwise the shadow follows what the item draws.
+    has_clip_shape: bool,
+    cursor_icon: Option<Shared<CursorIcon>>,
     custom_properties: HashMap<String, CustomProperty>,
     display_parameter_out: Shared<DisplayParameter>,
+    draw_cache: Option<DrawCache>,
     elevation: SharedF32,
     enabled: SharedBool,
     enable_background_blur: SharedBool,
     focusable: Shared<bool>,
     focused: Shared<bool>,
     focused_when_clicked: Shared<bool>,
+    focus_group: Option<usize>,
+    focus_ring: bool,
+    focus_ring_radius: Option<SharedF32>,
+    focus_scope: bool,
     foreground: SharedItem,
     height: SharedSize,
+    hit_region: Option<HitRegion>,
     id: usize,
     layout_direction: Shared<LayoutDirection>,
     margin_bottom: SharedF32,
@@ -606,7 +665,11 @@ pub struct ItemData {
     skew_x: SharedF32,
     skew_y: SharedF32,
     state: Shared<ItemState>,
+    tab_index: Option<i32>,
     target_parameter: DisplayParameter,
+    transform: Shared<M44>,
+    transform_center_x: SharedInnerPosition,
+    transform_center_y: SharedInnerPosition,
     visible: SharedBool,
     width: SharedSize,
 
@@ -614,7 +677,7 @@ pub struct ItemData {
     cursor_move: Arc<Mutex<dyn FnMut(&mut ItemData, &CursorMove)>>,
     dispatch_apply_theme: Arc<Mutex<dyn FnMut(&mut ItemData, &Theme)>>,
     dispatch_cursor_move: Arc<Mutex<dyn FnMut(&mut ItemData, &CursorMove)>>,
-    dispatch_draw: Arc<Mutex<dyn FnMut(&mut ItemData, &mut Surface, f32, f32)>>,
+    dispatch_draw: Arc<Mutex<dyn FnMut(&mut ItemData, &Canvas, f32, f32)>>,
     dispatch_focus: Arc<Mutex<dyn FnMut(&mut ItemData)>>,
     dispatch_keyboard_input: Arc<Mutex<dyn FnMut(&mut ItemData, &KeyboardInput) -> bool>>,
     dispatch_layout: Arc<Mutex<dyn FnMut(&mut ItemData, f32, f32, f32, f32)>>,
@@ -684,8 +747,11 @@ impl ItemData {
                 id,
                 window_context,
             ),
+            has_clip_shape: false,
+            cursor_icon: None,
             custom_properties: HashMap::new(),
             display_parameter_out: DisplayParameter::default().into(),
+            draw_cache: None,
             elevation: redraw(0.0.into(), id, window_context),
             enabled: {
                 let enabled: SharedBool = true.into();
@@ -716,6 +782,10 @@ impl ItemData {
             focusable: layout(false.into(), id, window_context),
             focused: layout(false.into(), id, window_context),
             focused_when_clicked: layout(false.into(), id, window_context),
+            focus_group: None,
+            focus_ring: true,
+            focus_ring_radius: None,
+            focus_scope: false,
             foreground: {
                 let mut item = SharedItem::none();
                 let event_loop_proxy = window_context.event_loop_proxy().clone();
@@ -729,6 +799,7 @@ impl ItemData {
                 item
             },
             height: redraw(Size::Auto.into(), id, window_context),
+            hit_region: None,
             id,
             layout_direction: layout(LayoutDirection::LTR.into(), id, window_context),
             margin_bottom: layout(0.0.into(), id, window_context),
@@ -771,7 +842,11 @@ impl ItemData {
             skew_x: redraw(0.0.into(), id, window_context),
             skew_y: redraw(0.0.into(), id, window_context),
             state,
+            tab_index: None,
             target_parameter: Default::default(),
+            transform: layout(M44::new_identity().into(), id, window_context),
+            transform_center_x: layout(InnerPosition::default().into(), id, window_context),
+            transform_center_y: layout(InnerPosition::default().into(), id, window_context),
             visible: layout(true.into(), id, window_context),
             width: layout(Size::Auto.into(), id, window_context),
 
@@ -824,11 +899,15 @@ impl ItemData {
 
                     item.get_cursor_move().lock()(item, cursor_move);
 
-                    if item
-                        .get_display_parameter()
-                        .is_inside(cursor_move.x, cursor_move.y)
+                    if item.contains_point(cursor_move.x, cursor_move.y)
                         && !cursor_move.is_left_window
                     {
+                        // Children are dispatched later, so the innermost item has the last word.
+                        if let Some(cursor_icon) = item.get_cursor_icon() {
+                            let window_context = item.get_window_context();
+                            window_context.hovered_cursor_icon.set(Some(cursor_icon.get()));
+                            window_context.hovered_cursor_item.set(Some(item.get_id()));
+                        }
                         if !is_hovered {
                             is_hovered = true;
                             item.get_hover_event().lock()(item, true);
@@ -882,12 +961,7 @@ impl ItemData {
                 }
             })),
             dispatch_draw: Arc::new(Mutex::new({
-                let mut image_filter_paint = Paint::default();
-                let mut shadow_paint = Paint::default();
-                shadow_paint
-                    .set_anti_alias(true)
-                    .set_blend_mode(BlendMode::SrcIn);
-                move |item: &mut ItemData, surface: &mut Surface, parent_x: f32, parent_y: f32| {
+                move |item: &mut ItemData, canvas: &Canvas, parent_x: f32, parent_y: f32| {
                     {
                         // Set the parent position of the target parameter of the item.
                         // It's child lock can use the parent position to calculate their own position.
@@ -911,75 +985,25 @@ impl ItemData {
                         let x_overlap = x < window_width && x + width > 0.0;
                         let y_overlap = y < window_height && y + height > 0.0;
                         if !x_overlap || !y_overlap {
+                            item.hit_region = None;
                             return;
                         }
                     }
 
+                    let clip_shape = item.get_clip_shape().clone();
+                    let shape = clip_shape.lock().as_ref()(item);
+                    let id = item.get_id();
+                    let damage = item.get_window_context().damage.clone();
+
                     {
                         // Draw the background blur effect.
-                        let blur = /*35.0*/item.get_blur().get();
-                        let margin = blur * 2.0;
+                        let blur = item.get_blur().get();
                         if item.get_enable_background_blur().get() && !display_parameter.is_empty()
                         {
                             let scale_factor = item.get_window_context().scale_factor();
-                            let left = (display_parameter.x() * scale_factor - margin) as i32;
-                            let top = (display_parameter.y() * scale_factor - margin) as i32;
-                            let right = ((display_parameter.x() + display_parameter.width)
-                                * scale_factor
-                                + margin) as i32;
-                            let bottom = ((display_parameter.y() + display_parameter.height)
-                                * scale_factor
-                                + margin) as i32;
-
-                            let background = surface
-                                .image_snapshot_with_bounds(IRect::from_ltrb(
-                                    left, top, right, bottom,
-                                ))
-                                .unwrap();
-
-                            let (width, height) = {
-                                let image_info = background.image_info();
-                                (image_info.width(), image_info.height())
-                            };
-
-                            let canvas = surface.canvas();
-                            image_filter_paint.set_image_filter(image_filters::blur(
-                                (blur, blur),
-                                TileMode::Clamp,
-                                None,
-                                CropRect::from(Rect::from_wh(width as f32, height as f32)),
-                            ));
-
-                            let d = margin / scale_factor;
-                            let mut x = display_parameter.x() - d;
-                            let mut y = display_parameter.y() - d;
-                            if x < 0.0 {
-                                x = 0.0;
-                            }
-                            if y < 0.0 {
-                                y = 0.0;
-                            }
-
-                            canvas.save();
-
-                            canvas.clip_rect(
-                                Rect::from_xywh(
-                                    display_parameter.x(),
-                                    display_parameter.y(),
-                                    display_parameter.width,
-                                    display_parameter.height,
-                                ),
-                                None,
-                                None,
-                            );
-                            canvas.translate(Vector::new(x, y));
-                            canvas.scale((1.0 / scale_factor, 1.0 / scale_factor));
-                            canvas.draw_image(
-                                background,
-                                Point::new(0.0, 0.0),
-                                Some(&image_filter_paint),
-                            );
-                            canvas.restore();
+                            draw_background_blur(canvas, &shape, blur / scale_factor);
+                            let bounds = canvas.local_to_device_as_3x3().map_rect(shape.bounds()).0;
+                            damage.lock().add_backdrop(bounds);
                         }
                     }
 
@@ -1003,7 +1027,6 @@ impl ItemData {
 
                     {
                         // Apply the transformation matrix to the canvas.
-                        let canvas = surface.canvas();
                         if display_parameter.opacity < 1.0 {
                             // canvas.save();
                             canvas.save_layer_alpha_f(
@@ -1037,12 +1060,27 @@ impl ItemData {
                             -(scale_x - 1.0) * scale_center_x / scale_x,
                             -(scale_y - 1.0) * scale_center_y / scale_y,
                         ));
+
+                        let transform_center_x = display_parameter.transform_center_x + x;
+                        let transform_center_y = display_parameter.transform_center_y + y;
+                        canvas.translate((transform_center_x, transform_center_y));
+                        canvas.concat_44(&display_parameter.transform);
+                        canvas.translate((-transform_center_x, -transform_center_y));
                         // if item.get_name() == "blue" {
                         //     canvas.scale((scale_x, scale_y));
                         //     canvas.translate((-(scale_x * 150.0 - 150.0) / scale_x, 0.0));
                         // }
                     }
 
+                    {
+                        // Pointer events find the item where it is drawn.
+                        let scale_factor = item.get_window_context().scale_factor();
+                        let mut to_window = Matrix::scale((1.0 / scale_factor, 1.0 / scale_factor));
+                        to_window.pre_concat(&canvas.local_to_device_as_3x3());
+                        item.hit_region =
+                            HitRegion::new(&to_window, shape.clone(), display_parameter.opacity);
+                    }
+
                     {
                         // Draw the shadow
                         let elevation = item.get_elevation().get() / 2.0;
@@ -1054,70 +1092,91 @@ impl ItemData {
                                 .get_color(color::SHADOW)
                                 .unwrap()
                                 .with_a((0.5 * 255.0) as u8);
-                            shadow_paint.set_color(shadow_color);
                             drop(theme);
-                            let blur_sigma = elevation * 1.5;
-                            let shadow_offset = elevation;
-
-                            let mut shadow_surface = surfaces::raster_n32_premul((
-                                display_parameter.width.ceil() as i32,
-                                display_parameter.height.ceil() as i32,
-                            ))
-                            .unwrap();
-
-                            let shadow_canvas = shadow_surface.canvas();
-                            shadow_canvas
-                                .translate((-display_parameter.x(), -display_parameter.y()));
-                            item.draw(shadow_canvas);
-
-                            shadow_canvas.draw_paint(&shadow_paint);
+                            if item.has_clip_shape {
+                                let scale_factor = item.get_window_context().scale_factor();
+                                draw_shadow(
+                                    canvas,
+                                    &shape,
+                                    elevation,
+                                    shadow_color,
+                                    scale_factor,
+                                );
+                            } else {
+                                draw_content_shadow(canvas, elevation, shadow_color, |canvas| {
+                                    item.draw(canvas)
+                                });
+                            }
+                        }
+                    }
 
-                            let shadow_image = shadow_surface.image_snapshot();
+                    let subtree_changed = {
+                        // Record the item again only if it changed, otherwise its last recording
+                        // is replayed.
+                        let repaint = damage.lock().needs_repaint(id)
+                            || !item
+                                .draw_cache
+                                .as_ref()
+                                .is_some_and(|cache| cache.is_valid(&display_parameter));
+                        if repaint {
+                            let profiling = item.get_window_context().is_profiling();
+                            let start = profiling.then(Instant::now);
+                            let _span = tracing::trace_span!(
+                                target: log_target::DRAW,
+                                "record",
+                                id,
+                                name = item.get_name()
+                            )
+                            .entered();
+                            let draw_cache =
+                                DrawCache::record(&display_parameter, |canvas| item.draw(canvas));
+                            item.draw_cache = draw_cache;
+                            if profiling {
+                                let profiler = item.get_window_context().profiler.clone();
+                                profiler.lock().record_item(id, item.get_name(), start);
+                            }
+                        }
 
-                            let rect = Rect::from_xywh(
-                                display_parameter.x() - elevation * 6.0,
-                                display_parameter.y() - elevation * 6.0,
-                                display_parameter.width + elevation * 12.0,
-                                display_parameter.height + elevation * 12.0,
-                            );
-                            image_filter_paint.set_image_filter(image_filters::blur(
-                                (blur_sigma, blur_sigma),
-                                TileMode::Clamp,
-                                None,
-                                CropRect::from(rect),
-                            ));
-
-                            let canvas = surface.canvas();
-                            canvas.draw_image(
-                                shadow_image,
-                                Point::new(
-                                    display_parameter.x(),
-                                    display_parameter.y() + shadow_offset,
-                                ),
-                                Some(&image_filter_paint),
-                            );
+                        // The shadow reaches three sigmas around the item, moved down by the
+                        // elevation.
+                        let elevation = item.get_elevation().get() / 2.0;
+                        let outset = 1.0 + elevation.max(0.0) * 5.5;
+                        let rect = Rect::from_xywh(x, y, display_parameter.width, display_parameter.height)
+                            .with_outset((outset, outset));
+                        let bounds = canvas.local_to_device_as_3x3().map_rect(rect).0;
+                        let bounds = canvas
+                            .device_clip_bounds()
+                            .and_then(|clip| Rect::intersect(&bounds, &Rect::from_irect(clip)))
+                            .unwrap_or_default();
+                        let subtree_changed = damage.lock().record_item(
+                            id,
+                            bounds,
+                            display_parameter.opacity,
+                            repaint,
+                        );
+                        if subtree_changed {
+                            damage.lock().enter_changed_subtree();
                         }
-                    }
+                        subtree_changed
+                    };
 
                     let clip = item.get_clip().get();
                     if clip {
-                        let canvas = surface.canvas();
                         canvas.save();
-                        let clip_shape = item.get_clip_shape().clone();
-                        let path = clip_shape.lock().as_ref()(item);
-                        canvas.clip_path(&path, None, true);
+                        canvas.clip_path(&shape, None, true);
                     }
 
                     {
                         // Draw the background
                         if let Some(background) = item.get_background().lock().as_mut() {
-                            background.data().dispatch_draw(surface, x, y);
+                            background.data().dispatch_draw(canvas, x, y);
                         }
                     }
                     {
                         // Draw the item itself.
-                        let canvas = surface.canvas();
-                        item.draw(canvas);
+                        if let Some(draw_cache) = &item.draw_cache {
+                            draw_cache.draw(canvas, &display_parameter);
+                        }
                     }
 
                     // Draw the children of the item.
@@ -1127,25 +1186,31 @@ impl ItemData {
                         .iter_mut()
                         // .iter_visible_item(window_size)
                         .for_each(|child| {
-                            child.data().dispatch_draw(surface, x, y);
+                            child.data().dispatch_draw(canvas, x, y);
                         });
 
                     {
                         // Draw the foreground
                         if let Some(foreground) = item.get_foreground().lock().as_mut() {
-                            foreground.data().dispatch_draw(surface, x, y);
+                            foreground.data().dispatch_draw(canvas, x, y);
                         }
                     }
 
                     if clip {
                         // Restore the transformation matrix of the canvas.
-                        let canvas = surface.canvas();
                         canvas.restore();
                     }
 
+                    if item.get_focused().get() {
+                        item.get_window_context().draw_focus_ring(item, canvas);
+                    }
+
+                    if subtree_changed {
+                        damage.lock().exit_changed_subtree();
+                    }
+
                     {
                         // Restore the transformation matrix of the canvas.
-                        let canvas = surface.canvas();
                         canvas.restore();
                     }
                 }
@@ -1243,6 +1308,7 @@ impl ItemData {
                     };
                     let skew_x = item.get_skew_x().get();
                     let skew_y = item.get_skew_y().get();
+                    let transform = item.get_transform().get();
 
                     fn center(inner_position: InnerPosition, size: f32) -> f32 {
                         match inner_position {
@@ -1261,6 +1327,10 @@ impl ItemData {
                         let scale_center_y = center(item.get_scale_center_y().get(), height);
                         let skew_center_x = center(item.get_skew_center_x().get(), width);
                         let skew_center_y = center(item.get_skew_center_y().get(), height);
+                        let transform_center_x =
+                            center(item.get_transform_center_x().get(), width);
+                        let transform_center_y =
+                            center(item.get_transform_center_y().get(), height);
 
                         {
                             let target_parameter = item.get_target_parameter();
@@ -1276,6 +1346,9 @@ impl ItemData {
                             target_parameter.set_offset(offset_x, offset_y);
                             target_parameter.set_skew(skew_x, skew_y);
                             target_parameter.set_skew_center(skew_center_x, skew_center_y);
+                            target_parameter.transform = transform;
+                            target_parameter
+                                .set_transform_center(transform_center_x, transform_center_y);
                             // item.set_target_parameter(target_parameter);
                         }
                     }
@@ -1304,12 +1377,21 @@ impl ItemData {
                 let mut captured_mouse_button: HashSet<(usize, MouseButton)> = HashSet::new();
                 // The source of the click event.
                 let mut click_source: Option<ClickSource> = None;
+                // The mouse buttons captured by another item, which this item has been told about.
+                let mut lost_mouse_buttons: HashSet<MouseButton> = HashSet::new();
                 move |item: &mut ItemData, mouse_input: &MouseInput| {
                     if !item.get_enabled().get() {
                         return;
                     }
                     let x = mouse_input.x;
                     let y = mouse_input.y;
+                    let pointer = Pointer::Mouse {
+                        button: mouse_input.button,
+                    };
+                    let captured_by_other = item
+                        .get_window_context()
+                        .get_pointer_capture(&pointer)
+                        .is_some_and(|id| id != item.get_id());
 
                     // If the item captures the mouse button,
                     // the foreground and background of the item can receive all mouse input events.
@@ -1323,26 +1405,46 @@ impl ItemData {
                         background.data().dispatch_mouse_input(mouse_input);
                     }
 
-                    match mouse_input.pointer_state {
-                        PointerState::Started => {
-                            let item_state = item.get_state();
-                            item_state.set(ItemState::Pressed);
-                        }
-                        PointerState::Ended | PointerState::Cancelled => {
-                            let item_state = item.get_state();
-                            if item_state.get() == ItemState::Pressed && item.get_enabled().get() {
-                                item_state.set(ItemState::Enabled);
+                    // When another item captures the button, this item gets a cancel event
+                    // once and then only passes the events on to its children.
+                    let own_input = if captured_by_other {
+                        lost_mouse_buttons.insert(mouse_input.button).then(|| {
+                            click_source.take();
+                            MouseInput {
+                                pointer_state: PointerState::Cancelled,
+                                ..*mouse_input
                             }
-                        }
-                        _=> {}
+                        })
+                    } else {
+                        Some(*mouse_input)
+                    };
+                    if matches!(
+                        mouse_input.pointer_state,
+                        PointerState::Ended | PointerState::Cancelled
+                    ) {
+                        lost_mouse_buttons.remove(&mouse_input.button);
                     }
 
-                    // Call the on_mouse_input event of the item.
-                    if let Some(on_mouse_input) = item.get_on_mouse_input() {
-                        on_mouse_input(mouse_input);
-                    }
+                    if let Some(mouse_input) = own_input.as_ref() {
+                        match mouse_input.pointer_state {
+                            PointerState::Started => {
+                                let item_state = item.get_state();
+                                item_state.set(ItemState::Pressed);
+                            }
+                            PointerState::Ended | PointerState::Cancelled => {
+                                let item_state = item.get_state();
+                                if item_state.get() == ItemState::Pressed && item.get_enabled().get() {
+                                    item_state.set(ItemState::Enabled);
+                                }
+                            }
+                            _=> {}
+                        }
+
+                        // Call the on_mouse_input event of the item.
+                        if let Some(on_mouse_input) = item.get_on_mouse_input() {
+                            on_mouse_input(mouse_input);
+                        }
 
-                    {
                         // Call the mouse_input event of the item_event.
                         // Why there are two mouse_input events?
                         // Because winia don't want to expose item object to the user.
@@ -1360,11 +1462,11 @@ impl ItemData {
                         // Dispatch the mouse input events to the child lock.
                         let children = item.get_children();
                         for child in children.lock().iter_mut().rev() {
-                            let display_parameter = child.data().get_display_parameter();
                             match mouse_input.pointer_state {
                                 PointerState::Started => {
-                                    // If the mouse pointer is inside the child item,
-                                    if display_parameter.is_inside(x, y) {
+                                    // If the mouse pointer hits the child item or one of its
+                                    // descendants, the same way `WindowContext::hit_test` does,
+                                    if is_hit(child.data().deref_mut(), x, y) {
                                         // The child item captures the mouse button.
                                         captured_mouse_button
                                             .insert((child.data().get_id(), mouse_input.button));
@@ -1402,13 +1504,13 @@ impl ItemData {
                     }
 
                     // Handle the click event.
-                    if !click_consumed {
+                    if !click_consumed && !captured_by_other {
                         match mouse_input.pointer_state {
                             PointerState::Started => {
                                 click_source.replace(ClickSource::Mouse(mouse_input.button));
                             }
                             PointerState::Ended => {
-                                if item.get_display_parameter().is_inside(x, y) {
+                                if item.contains_point(x, y) {
                                     let is_clicked = {
                                         click_source == Some(ClickSource::Mouse(mouse_input.button))
                                     };
@@ -1437,8 +1539,7 @@ impl ItemData {
                     let children = item.get_children();
                     let (cursor_x, cursor_y) = item.get_window_context().get_cursor_position();
                     for child in children.lock().iter_mut().rev() {
-                        let display_parameter = child.data().get_display_parameter();
-                        if display_parameter.is_inside(cursor_x, cursor_y) {
+                        if is_hit(child.data().deref_mut(), cursor_x, cursor_y) {
                             let dispatch_mouse_wheel = child.data().get_dispatch_mouse_wheel_x();
                             let r = dispatch_mouse_wheel.lock()(child.data().deref_mut(), mouse_wheel);
                             if r {
@@ -1460,8 +1561,7 @@ impl ItemData {
                     let children = item.get_children();
                     let (cursor_x, cursor_y) = item.get_window_context().get_cursor_position();
                     for child in children.lock().iter_mut().rev() {
-                        let display_parameter = child.data().get_display_parameter();
-                        if display_parameter.is_inside(cursor_x, cursor_y) {
+                        if is_hit(child.data().deref_mut(), cursor_x, cursor_y) {
                             let dispatch_mouse_wheel = child.data().get_dispatch_mouse_wheel_y();
                             let r = dispatch_mouse_wheel.lock()(child.data().deref_mut(), mouse_wheel);
                             if r {
@@ -1490,12 +1590,18 @@ impl ItemData {
                 // item_id, touch_id
                 let mut captured_touch_pointer: HashSet<(usize, u64)> = HashSet::new();
                 let mut touch_start_time = Instant::now();
+                // The touches captured by another item, which this item has been told about.
+                let mut lost_touches: HashSet<u64> = HashSet::new();
                 move |item: &mut ItemData, touch_input: &TouchInput| {
                     if !item.get_enabled().get() {
                         return;
                     }
                     let x = touch_input.x;
                     let y = touch_input.y;
+                    let captured_by_other = item
+                        .get_window_context()
+                        .get_pointer_capture(&Pointer::Touch { id: touch_input.id })
+                        .is_some_and(|id| id != item.get_id());
 
                     let foreground = item.get_foreground();
                     if let Some(foreground) = foreground.lock().as_mut() {
@@ -1506,14 +1612,34 @@ impl ItemData {
                         background.data().dispatch_touch_input(touch_input);
                     }
 
-                    if let Some(on_touch) = &mut item.get_on_touch_input() {
-                        on_touch(touch_input);
+                    // When another item captures the touch, this item gets a cancel event
+                    // once and then only passes the events on to its children.
+                    let own_input = if captured_by_other {
+                        lost_touches.insert(touch_input.id).then_some(TouchInput {
+                            pointer_state: PointerState::Cancelled,
+                            ..*touch_input
+                        })
+                    } else {
+                        Some(*touch_input)
+                    };
+                    if matches!(
+                        touch_input.pointer_state,
+                        PointerState::Ended | PointerState::Cancelled
+                    ) {
+                        lost_touches.remove(&touch_input.id);
                     }
 
-                    let pointer_input = PointerInput::from(touch_input);
-                    item.get_pointer_input().lock()(item, &pointer_input);
-                    if let Some(on_pointer_input) = item.get_on_pointer_input() {
-                        on_pointer_input(&pointer_input)
+                    if let Some(touch_input) = own_input.as_ref() {
+                        item.get_touch_input().lock()(item, touch_input);
+                        if let Some(on_touch) = &mut item.get_on_touch_input() {
+                            on_touch(touch_input);
+                        }
+
+                        let pointer_input = PointerInput::from(touch_input);
+                        item.get_pointer_input().lock()(item, &pointer_input);
+                        if let Some(on_pointer_input) = item.get_on_pointer_input() {
+                            on_pointer_input(&pointer_input)
+                        }
                     }
 
                     let mut click_consumed = false;
@@ -1521,10 +1647,9 @@ impl ItemData {
                     {
                         let children = item.get_children();
                         for child in children.lock().iter_mut().rev() {
-                            let display_parameter = child.data().get_display_parameter();
                             match touch_input.pointer_state {
                                 PointerState::Started => {
-                                    if display_parameter.is_inside(x, y) {
+                                    if is_hit(child.data().deref_mut(), x, y) {
                                         captured_touch_pointer
                                             .insert((child.data().get_id(), touch_input.id));
                                         child.data().dispatch_touch_input(touch_input);
@@ -1557,7 +1682,7 @@ impl ItemData {
                         }
                     }
 
-                    if !click_consumed {
+                    if !click_consumed && !captured_by_other {
                         match touch_input.pointer_state {
                             PointerState::Started => {
                                 touch_start_time = Instant::now();
@@ -1580,30 +1705,15 @@ impl ItemData {
                 }
             })),
             draw: Arc::new(Mutex::new(|_item: &mut ItemData, _canvas: &Canvas| {})),
-            focus_next: Arc::new(Mutex::new({
-                let mut last_focused_index = 0_usize;
-                move |item: &mut ItemData| {
-                    let children = item.get_children().lock();
-                    if children.is_empty() {
-                        return true
-                    }
-                    loop {
-                        if last_focused_index >= children.len() {
-                            last_focused_index = 0;
-                            return true;
-                        }
-                        if let Some(child) = children.get(last_focused_index) {
-                            if child.data().focus_next() {
-                                last_focused_index += 1;
-                            } else { 
-                                return false;
-                            }
-                        } else {
-                            last_focused_index = 0;
-                            return true;
-                        }
-                    }
-                }
+            focus_next: Arc::new(Mutex::new(|item: &mut ItemData| {
+                // The root may be locked by the caller, so the focus moves in a UI hook.
+                let window_context = item.get_window_context().clone();
+                item.get_window_context().add_ui_hook(move || {
+                    window_context.move_focus(FocusDirection::Next);
+                    false
+                });
+                item.get_window_context().request_redraw();
+                true
             })),
             ime_input: Arc::new(Mutex::new(|_item: &mut ItemData, _action: &ImeAction| {})),
             keyboard_input: Arc::new(Mutex::new(
@@ -1666,12 +1776,15 @@ impl ItemData {
             touch_input: Arc::new(Mutex::new(|_item: &mut ItemData, _event: &TouchInput| {})),
         };
         item.set_focused(false);
+        window_context.damage.lock().add_item(id);
         item
     }
 }
 
 impl Drop for ItemData {
     fn drop(&mut self) {
+        self.window_context.damage.lock().remove_item(self.id);
+        owner_detached(self.id);
         unbind_id(self.id);
         self.window_context.set_ime_allowed(self.id, false);
     }
@@ -1741,13 +1854,35 @@ impl_property_redraw!(
     SharedBool,
     "Whether to clip the content of the item to its bounds. If this is set to true, the content will not be drawn outside the bounds of the item."
 );
-impl_property_redraw!(
-    clip_shape,
-    set_clip_shape,
-    get_clip_shape,
-    Shared<Box<dyn Fn(&mut ItemData) -> Path + Send>>,
-    "The shape used to clip the content of the item. If this is set, the content will be clipped to the shape."
-);
+impl ItemData {
+    /// The shape used to clip the content of the item. If this is set, the content will be
+    /// clipped to the shape and the shadow follows the shape instead of what the item draws.
+    #[track_caller]
+    pub fn set_clip_shape(
+        &mut self,
+        clip_shape: impl Into<Shared<Box<dyn Fn(&mut ItemData) -> Path + Send>>>,
+    ) {
+        self.clip_shape.remove_observer(self.id);
+        self.clip_shape = clip_shape.into();
+        self.has_clip_shape = true;
+        init_property_redraw(&mut self.clip_shape, self.id, &self.window_context);
+    }
+
+    pub fn get_clip_shape(&self) -> &Shared<Box<dyn Fn(&mut ItemData) -> Path + Send>> {
+        &self.clip_shape
+    }
+}
+impl Item {
+    /// See [`ItemData::set_clip_shape`].
+    #[track_caller]
+    pub fn clip_shape(
+        self,
+        clip_shape: impl Into<Shared<Box<dyn Fn(&mut ItemData) -> Path + Send>>>,
+    ) -> Self {
+        self.data().set_clip_shape(clip_shape);
+        self
+    }
+}
 // impl_property_redraw!(
 //     enabled,
 //     set_enabled,
@@ -2053,6 +2188,29 @@ impl_property_layout!(
     SharedF32,
     "The skew in the y direction in degrees."
 );
+impl_property_layout!(
+    transform,
+    set_transform,
+    get_transform,
+    Shared<M44>,
+    "A transformation applied after the rotation, skew and scale, around the transform center. \
+    Perspective and rotations around the x and y axes are supported, see \
+    [`transform`](crate::ui::item::transform)."
+);
+impl_property_layout!(
+    transform_center_x,
+    set_transform_center_x,
+    get_transform_center_x,
+    SharedInnerPosition,
+    "The center of the transformation in the x direction."
+);
+impl_property_layout!(
+    transform_center_y,
+    set_transform_center_y,
+    get_transform_center_y,
+    SharedInnerPosition,
+    "The center of the transformation in the y direction."
+);
 impl_property_layout!(
     visible,
     set_visible,
@@ -2107,11 +2265,11 @@ impl_get_set!(
 impl_get_set!(
     dispatch_draw,
     set_dispatch_draw,
-    impl FnMut(&mut ItemData, &mut Surface, f32, f32) + 'static,
-    "item, surface, x, y",
+    impl FnMut(&mut ItemData, &Canvas, f32, f32) + 'static,
+    "item, canvas, x, y",
     get_dispatch_draw,
-    dyn FnMut(&mut ItemData, &mut Surface, f32, f32),
-    "item, surface, x, y"
+    dyn FnMut(&mut ItemData, &Canvas, f32, f32),
+    "item, canvas, x, y"
 );
 impl_get_set!(
     dispatch_focus,
@@ -2203,15 +2361,6 @@ impl_get_set!(
     dyn FnMut(&mut ItemData, &Canvas),
     "item, canvas"
 );
-impl_get_set!(
-    focus_next,
-    set_focus_next,
-    impl FnMut(&mut ItemData) -> bool + 'static,
-    "item",
-    get_focus_next,
-    dyn FnMut(&mut ItemData) -> bool,
-    "item"
-);
 impl_get_set!(
     ime_input,
     set_ime_input,
@@ -2360,6 +2509,99 @@ impl ItemData {
         self
     }
 
+    /// The shape of the mouse cursor while it is over the item.
+    /// The innermost hovered item with a cursor icon wins, the default cursor is used if there is none.
+    /// A change of the icon shows up at once if the item is hovered.
+    pub fn set_cursor_icon(&mut self, cursor_icon: impl Into<Shared<CursorIcon>>) {
+        self.clear_cursor_icon();
+        let cursor_icon = cursor_icon.into();
+        let id = self.get_id();
+        let window_context = self.get_window_context();
+        let hovered_cursor_icon = window_context.hovered_cursor_icon.clone();
+        let hovered_cursor_item = window_context.hovered_cursor_item.clone();
+        let event_loop_proxy = window_context.event_loop_proxy().clone();
+        cursor_icon.add_specific_observer(id, move |cursor_icon| {
+            if hovered_cursor_item.get() == Some(id) {
+                hovered_cursor_icon.set(Some(*cursor_icon));
+                event_loop_proxy.request_cursor_icon_update();
+            }
+        });
+        self.cursor_icon = Some(cursor_icon);
+    }
+
+    pub fn clear_cursor_icon(&mut self) {
+        if let Some(cursor_icon) = self.cursor_icon.take() {
+            cursor_icon.remove_observer(self.get_id());
+        }
+    }
+
+    /// Makes the item a single Tab stop for all the focusable items sharing `group`, the arrow
+    /// keys move the focus inside the group. Tab returns to the member focused last.
+    pub fn set_focus_group(&mut self, group: usize) {
+        self.focus_group = Some(group);
+    }
+
+    pub fn clear_focus_group(&mut self) {
+        self.focus_group = None;
+    }
+
+    /// Groups all the focusable descendants without an explicit focus group into one Tab stop.
+    pub fn set_focus_scope(&mut self, focus_scope: bool) {
+        self.focus_scope = focus_scope;
+    }
+
+    /// Overrides the Tab order. Items with a positive index come first in ascending order, then
+    /// the items without an index in tree order. Items with a negative index are skipped by Tab
+    /// but can still be focused by the arrow keys or programmatically.
+    pub fn set_tab_index(&mut self, tab_index: i32) {
+        self.tab_index = Some(tab_index);
+    }
+
+    pub fn clear_tab_index(&mut self) {
+        self.tab_index = None;
+    }
+
+    /// Whether the window draws the theme's focus ring around the item when it is focused by the
+    /// keyboard.
+    pub fn set_focus_ring(&mut self, focus_ring: bool) {
+        self.focus_ring = focus_ring;
+    }
+
+    /// The corner radius of the focus ring, the theme's
+    /// [`focus::RING_RADIUS`](crate::ui::theme::focus::RING_RADIUS) is used if it is not set.
+    pub fn set_focus_ring_radius(&mut self, radius: impl Into<SharedF32>) {
+        let mut radius = radius.into();
+        // The ring is drawn by the window over every item.
+        let event_loop_proxy = self.window_context.event_loop_proxy().clone();
+        radius
+            .add_observer(
+                self.id,
+                Box::new(move || {
+                    event_loop_proxy.request_redraw();
+                }),
+            )
+            .drop();
+        self.focus_ring_radius = Some(radius);
+    }
+
+    /// Makes the item receive all events of `pointer`, wherever it goes, until it is lifted or
+    /// [`release_pointer`](ItemData::release_pointer) is called. The other items receiving the
+    /// pointer get a [`PointerState::Cancelled`] event. Only items that have received the pointer
+    /// going down can capture it.
+    pub fn capture_pointer(&self, pointer: Pointer) {
+        self.window_context.capture_pointer(self.id, pointer);
+    }
+
+    pub fn release_pointer(&self, pointer: Pointer) {
+        if self.has_pointer_capture(&pointer) {
+            self.window_context.release_pointer(&pointer);
+        }
+    }
+
+    pub fn has_pointer_capture(&self, pointer: &Pointer) -> bool {
+        self.window_context.get_pointer_capture(pointer) == Some(self.id)
+    }
+
     pub fn custom_property(&mut self, name: impl Into<String>, property: CustomProperty) {
         self.custom_properties.insert(name.into(), property);
     }
@@ -2427,9 +2669,20 @@ impl ItemData {
         });
     }
 
+    /// Names the item, so it can be found with [`get_id_by_name`](crate::core::get_id_by_name).
+    /// The name is kept only if it isn't empty or bound to another item.
     pub fn set_name(&mut self, name: impl Into<String>) {
-        self.name = name.into();
-        bind_str_to_id(&self.name, self.id);
+        let name = name.into();
+        match bind_str_to_id(&name, self.id) {
+            Ok(()) => self.name = name,
+            // The item still works, it just can't be found by its name.
+            Err(error) => tracing::warn!(
+                target: log_target::NAME,
+                id = self.id,
+                %error,
+                "Failed to bind the name of an item"
+            ),
+        }
     }
 
     pub fn set_on_click<F>(&mut self, f: F)
@@ -2508,6 +2761,30 @@ impl ItemData {
         &mut self.children
     }
 
+    pub fn get_cursor_icon(&self) -> Option<&Shared<CursorIcon>> {
+        self.cursor_icon.as_ref()
+    }
+
+    pub fn get_focus_group(&self) -> Option<usize> {
+        self.focus_group
+    }
+
+    pub fn get_focus_scope(&self) -> bool {
+        self.focus_scope
+    }
+
+    pub fn get_tab_index(&self) -> Option<i32> {
+        self.tab_index
+    }
+
+    pub fn get_focus_ring(&self) -> bool {
+        self.focus_ring
+    }
+
+    pub fn get_focus_ring_radius(&self) -> Option<&SharedF32> {
+        self.focus_ring_radius.as_ref()
+    }
+
     pub fn get_custom_property(&self, name: &str) -> Option<&CustomProperty> {
         self.custom_properties.get(name)
     }
@@ -2538,6 +2815,17 @@ impl ItemData {
         calculate_animation_value!(skew_y, self, display_parameter);
         calculate_animation_value!(skew_center_x, self, display_parameter);
         calculate_animation_value!(skew_center_y, self, display_parameter);
+        calculate_animation_value!(transform_center_x, self, display_parameter);
+        calculate_animation_value!(transform_center_y, self, display_parameter);
+        if let Some((start, _, animation)) = &self.animations.transform {
+            if !animation.is_finished() {
+                let progress = animation.interpolate_f32(0.0, 1.0);
+                display_parameter.transform =
+                    transform::interpolate(start, &display_parameter.transform, progress);
+            } else {
+                self.animations.transform = None;
+            }
+        }
         self.animations
             .float_params
             .retain(|_, (_, _, animation)| !animation.is_finished());
@@ -2564,11 +2852,52 @@ impl ItemData {
                         .insert(key.clone(), animation.interpolate_color(start, end));
                 }
             });
+        self.animations
+            .brush_params
+            .retain(|_, (_, _, animation)| !animation.is_finished());
+        self.animations
+            .brush_params
+            .iter()
+            .for_each(|(key, (start, _, animation))| {
+                if let Some(end) = display_parameter.brush_params.get(key) {
+                    // Brushes are interpolated with the eased progress of the animation.
+                    let progress = animation.interpolate_f32(0.0, 1.0);
+                    let brush = start.interpolate(end, progress);
+                    display_parameter.brush_params.insert(key.clone(), brush);
+                }
+            });
         self.display_parameter_out
             .set_static(display_parameter.clone());
         display_parameter
     }
 
+    /// Whether the point in window coordinates is inside the clip shape of the item as it was
+    /// last drawn, with all of its transformations and those of its parents. Hidden and fully
+    /// transparent items contain no point.
+    pub fn contains_point(&mut self, x: f32, y: f32) -> bool {
+        if !self.visible.get() {
+            return false;
+        }
+        match &self.hit_region {
+            Some(hit_region) => hit_region.contains(x, y),
+            None => self.get_display_parameter().is_inside(x, y),
+        }
+    }
+
+    /// The clip shape of the item in window coordinates as it was last drawn, with all of its
+    /// transformations and those of its parents.
+    pub(crate) fn drawn_shape(&self) -> Option<Path> {
+        let hit_region = self.hit_region.as_ref()?;
+        let to_window = hit_region.to_local.invert()?;
+        Some(hit_region.shape.with_transform(&to_window))
+    }
+
+    /// Whether the children of the item are shown at all, an item that is hidden or fully
+    /// transparent hides them too.
+    pub(crate) fn is_shown(&self) -> bool {
+        self.visible.get() && self.hit_region.as_ref().is_none_or(HitRegion::is_shown)
+    }
+
     pub fn get_focused(&self) -> &Shared<bool> {
         &self.focused
     }
@@ -2773,9 +3102,25 @@ impl ItemData {
                     skew_x,
                     skew_y,
                     skew_center_x,
-                    skew_center_y
+                    skew_center_y,
+                    transform_center_x,
+                    transform_center_y
                 );
 
+                if recorded_parameter.transform != target_parameter.transform
+                    && self
+                        .animations
+                        .transform
+                        .as_ref()
+                        .map_or(true, |(_, end, _)| *end != target_parameter.transform)
+                {
+                    self.animations.transform = Some((
+                        recorded_parameter.transform,
+                        target_parameter.transform,
+                        animation.clone_boxed(),
+                    ));
+                }
+
                 {
                     target_parameter
                         .float_params
@@ -2839,6 +3184,37 @@ impl ItemData {
                             }
                         });
                 }
+
+                {
+                    target_parameter
+                        .brush_params
+                        .iter()
+                        .for_each(|(key, end)| {
+                            let target_changed = self
+                                .animations
+                                .brush_params
+                                .get(key)
+                                .map_or(true, |(_, animating_to, _)| animating_to != end);
+
+                            if let Some(start) = recorded_parameter.brush_params.get(key) {
+                                if start != end && target_changed {
+                                    self.animations.brush_params.insert(
+                                        key.clone(),
+                                        (start.clone(), end.clone(), animation.clone_boxed()),
+                                    );
+                                }
+                            } else if target_changed {
+                                self.animations.brush_params.insert(
+                                    key.clone(),
+                                    (
+                                        Brush::Solid(Color::TRANSPARENT),
+                                        end.clone(),
+                                        animation.clone_boxed(),
+                                    ),
+                                );
+                            }
+                        });
+                }
             }
         }
 
@@ -2868,9 +3244,9 @@ impl ItemData {
         f.lock()(self, theme);
     }
 
-    pub fn dispatch_draw(&mut self, surface: &mut Surface, parent_x: f32, parent_y: f32) {
+    pub fn dispatch_draw(&mut self, canvas: &Canvas, parent_x: f32, parent_y: f32) {
         let f = self.get_dispatch_draw();
-        f.lock()(self, surface, parent_x, parent_y);
+        f.lock()(self, canvas, parent_x, parent_y);
     }
 
     pub fn dispatch_focus(&mut self) {
@@ -2949,6 +3325,20 @@ impl ItemData {
         }
     }
 
+    /// The ids of the items from this item down to the item with `id`, both included.
+    pub fn path_to(&self, id: usize) -> Option<Vec<usize>> {
+        if self.id == id {
+            return Some(vec![id]);
+        }
+        for child in self.children.lock().iter() {
+            if let Some(mut path) = child.data().path_to(id) {
+                path.insert(0, self.id);
+                return Some(path);
+            }
+        }
+        None
+    }
+
     pub fn find_item_mut(&mut self, id: usize, f: &mut impl FnMut(&mut ItemData)) {
         if self.id == id {
             f(self);
@@ -2959,13 +3349,31 @@ impl ItemData {
         }
     }
 
-    /// Returns true if it can focus next item.
+    /// Moves the focus to the next Tab stop of the window before the next window event is
+    /// handled, or runs the function set with [`set_focus_next`](ItemData::set_focus_next).
+    #[deprecated(note = "use `WindowContext::move_focus` with `FocusDirection::Next`")]
     pub fn focus_next(&mut self) -> bool {
         let focus_next = self.focus_next.clone();
         let r = focus_next.lock()(self);
         r
     }
 
+    /// The Tab order is computed by the window, see [`ItemData::set_tab_index`],
+    /// [`ItemData::set_focus_group`] and [`ItemData::set_focus_scope`].
+    #[deprecated(note = "the window moves the focus, use `set_tab_index` or `set_focus_group`")]
+    pub fn set_focus_next(
+        &mut self,
+        focus_next: impl FnMut(&mut ItemData) -> bool + 'static,
+    ) -> &mut Self {
+        self.focus_next = Arc::new(Mutex::new(focus_next));
+        self
+    }
+
+    #[deprecated(note = "the window moves the focus, use `WindowContext::move_focus`")]
+    pub fn get_focus_next(&self) -> Arc<Mutex<dyn FnMut(&mut ItemData) -> bool>> {
+        self.focus_next.clone()
+    }
+
     pub fn for_each_child<F>(&self, mut f: F)
     where
         F: FnMut(&Item),
@@ -3169,6 +3577,42 @@ impl Item {
         self
     }
 
+    /// See [`ItemData::set_cursor_icon`].
+    pub fn cursor_icon(self, cursor_icon: impl Into<Shared<CursorIcon>>) -> Self {
+        self.data().set_cursor_icon(cursor_icon);
+        self
+    }
+
+    /// See [`ItemData::set_focus_group`].
+    pub fn focus_group(self, group: usize) -> Self {
+        self.data().set_focus_group(group);
+        self
+    }
+
+    /// See [`ItemData::set_focus_scope`].
+    pub fn focus_scope(self, focus_scope: bool) -> Self {
+        self.data().set_focus_scope(focus_scope);
+        self
+    }
+
+    /// See [`ItemData::set_tab_index`].
+    pub fn tab_index(self, tab_index: i32) -> Self {
+        self.data().set_tab_index(tab_index);
+        self
+    }
+
+    /// See [`ItemData::set_focus_ring`].
+    pub fn focus_ring(self, focus_ring: bool) -> Self {
+        self.data().set_focus_ring(focus_ring);
+        self
+    }
+
+    /// See [`ItemData::set_focus_ring_radius`].
+    pub fn focus_ring_radius(self, radius: impl Into<SharedF32>) -> Self {
+        self.data().set_focus_ring_radius(radius);
+        self
+    }
+
     pub fn on_click<F>(self, f: F) -> Self
     where
         F: FnMut(ClickSource) + 'static,
@@ -1,14 +1,23 @@
+mod brush;
 mod display_parameter;
+mod drag_drop;
+mod gesture;
 mod inner_position;
 mod item;
 mod logical_x;
 mod scroller;
+mod shadow;
 mod size;
+pub mod transform;
 mod dimension;
+mod draw_cache;
 
 pub use item::*;
 
+pub use brush::*;
 pub use display_parameter::*;
+pub use drag_drop::*;
+pub use gesture::*;
 pub use inner_position::*;
 pub use logical_x::*;
 pub use scroller::*;
@@ -18,9 +27,11 @@ pub use size::*;
 macro_rules! impl_property_redraw {
     ($struct_name:ident, $property_name:ident, $property_type:ty) => {
         impl $struct_name {
+            #[track_caller]
             pub fn $property_name(self, $property_name: impl Into<$property_type>) -> Self {
                 use $crate::shared::Observable;
                 let id = self.item.data().get_id();
+                let location = ::std::panic::Location::caller();
                 {
                     let mut property = self.property.lock();
                     property.$property_name.remove_observer(id);
@@ -29,7 +40,7 @@ macro_rules! impl_property_redraw {
                     property.$property_name.add_observer(
                         id,
                         Box::new(move || {
-                            event_loop_proxy.request_redraw();
+                            event_loop_proxy.request_repaint_from(id, location);
                         }),
                     );
                 }
@@ -44,9 +55,11 @@ macro_rules! impl_property_redraw {
 macro_rules! impl_property_layout {
     ($struct_name:ident, $property_name:ident, $property_type:ty) => {
         impl $struct_name {
+            #[track_caller]
             pub fn $property_name(self, $property_name: impl Into<$property_type>) -> Self {
                 use $crate::shared::Observable;
                 let id = self.item.data().get_id();
+                let location = ::std::panic::Location::caller();
                 {
                     let mut property = self.property.lock();
                     property.$property_name.remove_observer(id);
@@ -55,7 +68,7 @@ macro_rules! impl_property_layout {
                     property.$property_name.add_observer(
                         id,
                         Box::new(move || {
-                            event_loop_proxy.request_layout();
+                            event_loop_proxy.request_layout_from(location);
                         }),
                     );
                 }
@@ -1,9 +1,69 @@
-use crate::shared::{Gettable, SharedF32};
+use crate::core::next_id;
+use crate::shared::{Gettable, Shared, SharedAnimationTrait, SharedF32};
+use crate::ui::animation::{FlingAnimation, VelocityTracker};
 use crate::ui::app::{EventLoopProxy, WindowContext};
-use crate::ui::item::{DisplayParameter, MouseScrollDelta, MouseWheel};
+use crate::ui::item::{
+    DisplayParameter, DragAxis, DragGestureRecognizer, MouseScrollDelta, MouseWheel, PointerKind,
+    PointerState,
+};
 use crate::ui::theme::color;
 use skia_safe::{Canvas, RRect, Rect};
 
+/// The kinetic scrolling state of one axis.
+struct Fling {
+    /// The distance travelled by the current fling, animated by [`FlingAnimation`].
+    offset: SharedF32,
+    /// The part of `offset` that has already been turned into deltas.
+    consumed: f32,
+    animation: Option<FlingAnimation>,
+    velocity_tracker: VelocityTracker,
+}
+
+impl Fling {
+    fn new(event_loop_proxy: &EventLoopProxy) -> Self {
+        Self {
+            offset: SharedF32::from(0.0).layout_when_changed(event_loop_proxy, next_id()),
+            consumed: 0.0,
+            animation: None,
+            velocity_tracker: VelocityTracker::new(),
+        }
+    }
+
+    fn take_delta(&mut self) -> f32 {
+        if self.animation.as_ref().is_some_and(FlingAnimation::is_finished) {
+            self.animation = None;
+        }
+        let offset = self.offset.get();
+        let delta = offset - self.consumed;
+        self.consumed = offset;
+        delta
+    }
+
+    fn is_flinging(&self) -> bool {
+        self.animation
+            .as_ref()
+            .is_some_and(|animation| !animation.is_finished())
+    }
+
+    fn stop(&mut self) {
+        if let Some(animation) = self.animation.take() {
+            animation.stop();
+        }
+        self.velocity_tracker.clear();
+    }
+
+    fn start(&mut self, event_loop_proxy: &EventLoopProxy) {
+        let velocity = self.velocity_tracker.velocity();
+        self.stop();
+        if velocity.abs() < 50.0 {
+            return;
+        }
+        self.offset.set_static(0.0);
+        self.consumed = 0.0;
+        self.animation = Some(FlingAnimation::new(&self.offset, velocity).start(event_loop_proxy));
+    }
+}
+
 pub struct Scroller {
     animated_f32: SharedF32,
     event_loop_proxy: EventLoopProxy,
@@ -12,6 +72,8 @@ pub struct Scroller {
     x_deltas: f32,
     y_deltas: f32,
     thumb_opacity: SharedF32,
+    x_fling: Fling,
+    y_fling: Fling,
 }
 
 impl Scroller {
@@ -27,6 +89,8 @@ impl Scroller {
             x_deltas: 0.0,
             y_deltas: 0.0,
             thumb_opacity: 1.0.into(),
+            x_fling: Fling::new(event_loop_proxy),
+            y_fling: Fling::new(event_loop_proxy),
         }
     }
     
@@ -38,23 +102,89 @@ impl Scroller {
     }
     
     pub fn x_deltas(&mut self) -> &mut f32 {
+        self.x_deltas += self.x_fling.take_delta();
         &mut self.x_deltas
     }
     
     pub fn y_deltas(&mut self) -> &mut f32 {
+        self.y_deltas += self.y_fling.take_delta();
         &mut self.y_deltas
     }
-    
+
+    /// Stops kinetic scrolling, e.g. because the content can't be scrolled any further.
+    pub fn stop_fling(&mut self) {
+        self.x_fling.stop();
+        self.y_fling.stop();
+    }
+
+    pub fn is_flinging(&self) -> bool {
+        self.x_fling.is_flinging() || self.y_fling.is_flinging()
+    }
+
+    /// Scrolls by the movement of a drag and records it for the fling that follows.
+    pub fn drag(&mut self, delta_x: f32, delta_y: f32) {
+        if self.scroll_enabled.0 {
+            self.x_deltas += delta_x;
+            self.x_fling.velocity_tracker.add_movement(delta_x);
+        }
+        if self.scroll_enabled.1 {
+            self.y_deltas += delta_y;
+            self.y_fling.velocity_tracker.add_movement(delta_y);
+        }
+    }
+
+    /// Continues scrolling with the velocity of the drag that has just ended.
+    pub fn fling(&mut self) {
+        self.x_fling.start(&self.event_loop_proxy);
+        self.y_fling.start(&self.event_loop_proxy);
+    }
+
+    /// A drag recognizer that scrolls with touches. Joining the gesture arena lets nested
+    /// recognizers of the other axis, e.g. a horizontal swipe, take over the touch.
+    pub fn drag_gesture(
+        scroller: &Shared<Scroller>,
+        axis: &Shared<DragAxis>,
+    ) -> DragGestureRecognizer {
+        let event_loop_proxy = scroller.lock().event_loop_proxy.clone();
+        DragGestureRecognizer::new(axis.clone())
+            .pointer_kind(PointerKind::Touch)
+            .on_start({
+                let scroller = scroller.clone();
+                move |_| scroller.lock().stop_fling()
+            })
+            .on_update({
+                let scroller = scroller.clone();
+                let event_loop_proxy = event_loop_proxy.clone();
+                move |update| {
+                    scroller.lock().drag(update.delta_x, update.delta_y);
+                    event_loop_proxy.request_layout();
+                }
+            })
+            .on_end({
+                let scroller = scroller.clone();
+                move |_| {
+                    scroller.lock().fling();
+                    event_loop_proxy.request_layout();
+                }
+            })
+            .on_cancel({
+                let scroller = scroller.clone();
+                move || scroller.lock().stop_fling()
+            })
+    }
+
     pub fn update_by_mouse_wheel_x(&mut self, mouse_wheel: &MouseWheel) {
         match mouse_wheel.delta {
             MouseScrollDelta::LineDelta(x) => {
                 if self.scroll_enabled.0 {
+                    self.x_fling.stop();
                     self.x_deltas += x * self.mouse_scroll_speed;
                 }
             }
             MouseScrollDelta::LogicalDelta(x) => {
                 if self.scroll_enabled.0 {
                     self.x_deltas += x;
+                    update_fling(&mut self.x_fling, mouse_wheel.state, x, &self.event_loop_proxy);
                 }
             }
         }
@@ -64,12 +194,14 @@ impl Scroller {
         match mouse_wheel.delta {
             MouseScrollDelta::LineDelta(y) => {
                 if self.scroll_enabled.1 {
+                    self.y_fling.stop();
                     self.y_deltas += y * self.mouse_scroll_speed;
                 }
             }
             MouseScrollDelta::LogicalDelta(y) => {
                 if self.scroll_enabled.1 {
                     self.y_deltas += y;
+                    update_fling(&mut self.y_fling, mouse_wheel.state, y, &self.event_loop_proxy);
                 }
             }
         }
@@ -129,3 +261,16 @@ impl Scroller {
         }
     }
 }
+
+/// Trackpad gestures report their phase, the content keeps moving after the fingers are lifted.
+fn update_fling(fling: &mut Fling, state: PointerState, delta: f32, event_loop_proxy: &EventLoopProxy) {
+    match state {
+        PointerState::Started => {
+            fling.stop();
+            fling.velocity_tracker.add_movement(delta);
+        }
+        PointerState::Moved => fling.velocity_tracker.add_movement(delta),
+        PointerState::Ended => fling.start(event_loop_proxy),
+        PointerState::Cancelled => fling.stop(),
+    }
+}
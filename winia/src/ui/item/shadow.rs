@@ -0,0 +1,249 @@
+use skia_safe::canvas::SaveLayerRec;
+use skia_safe::image_filters::CropRect;
+use skia_safe::rrect::Corner;
+use skia_safe::{
+    image_filters, surfaces, BlurStyle, Canvas, Color, Color4f, FilterMode, IRect, Image, ImageInfo,
+    MaskFilter, Paint, Path, RRect, Rect, TileMode, Vector,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The number of shadow masks kept, the least recently used ones are dropped first.
+const SHADOW_CACHE_CAPACITY: usize = 64;
+
+/// Shadow geometry is rounded to a quarter of a pixel so that animated values still hit the cache.
+fn quantize(value: f32) -> u32 {
+    (value * 4.0).round().max(0.0) as u32
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct ShadowKey {
+    /// `(0, 0)` for nine-patch masks, which are stretched to any size.
+    size: (u32, u32),
+    /// The x and y radii of the top left, top right, bottom right and bottom left corners.
+    radii: [u32; 8],
+    sigma: u32,
+    scale: u32,
+}
+
+struct ShadowMask {
+    image: Image,
+    /// The stretchable center of a nine-patch mask, in pixels of the image.
+    center: Option<IRect>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct ShadowCache {
+    masks: HashMap<ShadowKey, ShadowMask>,
+    clock: u64,
+}
+
+impl ShadowCache {
+    fn get_or_create(
+        &mut self,
+        key: ShadowKey,
+        create: impl FnOnce() -> Option<(Image, Option<IRect>)>,
+    ) -> Option<(Image, Option<IRect>)> {
+        self.clock += 1;
+        if let Some(mask) = self.masks.get_mut(&key) {
+            mask.last_used = self.clock;
+            return Some((mask.image.clone(), mask.center));
+        }
+        let (image, center) = create()?;
+        if self.masks.len() >= SHADOW_CACHE_CAPACITY {
+            let oldest = self
+                .masks
+                .iter()
+                .min_by_key(|(_, mask)| mask.last_used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.masks.remove(&oldest);
+            }
+        }
+        self.masks.insert(
+            key,
+            ShadowMask {
+                image: image.clone(),
+                center,
+                last_used: self.clock,
+            },
+        );
+        Some((image, center))
+    }
+}
+
+thread_local! {
+    static SHADOW_CACHE: RefCell<ShadowCache> = RefCell::new(ShadowCache::default());
+}
+
+/// Renders the blurred coverage of a rounded rectangle of `width` x `height` into an alpha mask,
+/// leaving `padding` around it for the blur.
+fn render_mask(
+    width: f32,
+    height: f32,
+    radii: &[Vector; 4],
+    sigma: f32,
+    padding: f32,
+    scale: f32,
+) -> Option<Image> {
+    let image_width = ((width + padding * 2.0) * scale).ceil() as i32;
+    let image_height = ((height + padding * 2.0) * scale).ceil() as i32;
+    let mut surface = surfaces::raster(&ImageInfo::new_a8((image_width, image_height)), None, None)?;
+    let canvas = surface.canvas();
+    canvas.scale((scale, scale));
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(Color::BLACK);
+    paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, sigma, true));
+    canvas.draw_rrect(
+        RRect::new_rect_radii(Rect::from_xywh(padding, padding, width, height), radii),
+        &paint,
+    );
+    Some(surface.image_snapshot())
+}
+
+/// Draws the shadow of `shape` for `elevation` with a cached mask.
+///
+/// Rounded rectangles that are large enough use a nine-patch mask shared by every size, smaller
+/// ones use a mask keyed by their size. Other shapes are blurred directly.
+pub(crate) fn draw_shadow(canvas: &Canvas, shape: &Path, elevation: f32, color: Color, scale: f32) {
+    let sigma = elevation * 1.5;
+    let offset = elevation;
+    let Some(rrect) = shape
+        .is_rrect()
+        .or_else(|| shape.is_rect().map(|(rect, _, _)| RRect::new_rect(rect)))
+        .or_else(|| shape.is_oval().map(|oval| RRect::new_oval(oval)))
+    else {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(color);
+        paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, sigma, true));
+        canvas.save();
+        canvas.translate((0.0, offset));
+        canvas.draw_path(shape, &paint);
+        canvas.restore();
+        return;
+    };
+
+    let rect = *rrect.rect();
+    let radii = [
+        rrect.radii(Corner::UpperLeft),
+        rrect.radii(Corner::UpperRight),
+        rrect.radii(Corner::LowerRight),
+        rrect.radii(Corner::LowerLeft),
+    ];
+    // The blur reaches three sigmas to both sides of an edge.
+    let extent = (sigma * 3.0).ceil();
+    let left = radii[0].x.max(radii[3].x) + extent;
+    let right = radii[1].x.max(radii[2].x) + extent;
+    let top = radii[0].y.max(radii[1].y) + extent;
+    let bottom = radii[2].y.max(radii[3].y) + extent;
+    let nine_patch = rect.width() >= left + right + 1.0 && rect.height() >= top + bottom + 1.0;
+
+    let key = ShadowKey {
+        size: if nine_patch {
+            (0, 0)
+        } else {
+            (quantize(rect.width()), quantize(rect.height()))
+        },
+        radii: [
+            quantize(radii[0].x),
+            quantize(radii[0].y),
+            quantize(radii[1].x),
+            quantize(radii[1].y),
+            quantize(radii[2].x),
+            quantize(radii[2].y),
+            quantize(radii[3].x),
+            quantize(radii[3].y),
+        ],
+        sigma: quantize(sigma),
+        scale: quantize(scale),
+    };
+    let mask = SHADOW_CACHE.with_borrow_mut(|cache| {
+        cache.get_or_create(key, || {
+            if nine_patch {
+                // The edges are the same everywhere between the corners, so one pixel of them is
+                // enough.
+                let width = left + right + 1.0;
+                let height = top + bottom + 1.0;
+                let image = render_mask(width, height, &radii, sigma, extent, scale)?;
+                let center = IRect::from_xywh(
+                    ((extent + left) * scale).floor() as i32,
+                    ((extent + top) * scale).floor() as i32,
+                    scale.ceil() as i32,
+                    scale.ceil() as i32,
+                );
+                Some((image, Some(center)))
+            } else {
+                let image = render_mask(rect.width(), rect.height(), &radii, sigma, extent, scale)?;
+                Some((image, None))
+            }
+        })
+    });
+    let Some((image, center)) = mask else {
+        return;
+    };
+
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(color);
+    // The mask is drawn in pixels, so the corners keep their size whatever the scale factor is.
+    canvas.save();
+    canvas.scale((1.0 / scale, 1.0 / scale));
+    let dst = Rect::from_xywh(
+        (rect.left - extent) * scale,
+        (rect.top - extent + offset) * scale,
+        (rect.width() + extent * 2.0) * scale,
+        (rect.height() + extent * 2.0) * scale,
+    );
+    match center {
+        Some(center) => {
+            canvas.draw_image_nine(&image, center, dst, FilterMode::Linear, Some(&paint));
+        }
+        None => {
+            canvas.draw_image_rect(&image, None, dst, &paint);
+        }
+    }
+    canvas.restore();
+}
+
+/// Draws the shadow of what `draw` draws, for items without a clip shape. The content is drawn
+/// again each time, so it is not cached.
+pub(crate) fn draw_content_shadow(
+    canvas: &Canvas,
+    elevation: f32,
+    color: Color,
+    draw: impl FnOnce(&Canvas),
+) {
+    let sigma = elevation * 1.5;
+    let Some(filter) = image_filters::drop_shadow_only(
+        (0.0, elevation),
+        (sigma, sigma),
+        Color4f::from(color),
+        None,
+        None,
+        CropRect::NO_CROP_RECT,
+    ) else {
+        return;
+    };
+    let mut paint = Paint::default();
+    paint.set_image_filter(filter);
+    canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+    draw(canvas);
+    canvas.restore();
+}
+
+/// Blurs what is already drawn under `shape`. Only the region under the shape is read back.
+pub(crate) fn draw_background_blur(canvas: &Canvas, shape: &Path, sigma: f32) {
+    let Some(filter) =
+        image_filters::blur((sigma, sigma), TileMode::Clamp, None, CropRect::NO_CROP_RECT)
+    else {
+        return;
+    };
+    canvas.save();
+    canvas.clip_path(shape, None, true);
+    canvas.save_layer(&SaveLayerRec::default().bounds(shape.bounds()).backdrop(&filter));
+    canvas.restore();
+    canvas.restore();
+}
@@ -0,0 +1,369 @@
+//! Matrices for [`Item::transform`](crate::ui::Item::transform). They compose with
+//! [`M44::concat`], the right-hand matrix is applied first.
+
+use skia_safe::{M44, V3};
+use std::array;
+
+/// A perspective as seen by a viewer `distance` logical pixels in front of the item, put it
+/// first so the rotations after it are projected.
+pub fn perspective(distance: f32) -> M44 {
+    if distance <= 0.0 {
+        return M44::new_identity();
+    }
+    #[rustfmt::skip]
+    let values = [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, -1.0 / distance, 1.0,
+    ];
+    M44::row_major(&values)
+}
+
+/// Rotates around the horizontal axis in degrees, use it after a [`perspective`].
+pub fn rotation_x(degrees: f32) -> M44 {
+    M44::rotate(V3::new(1.0, 0.0, 0.0), degrees.to_radians())
+}
+
+/// Rotates around the vertical axis in degrees, use it after a [`perspective`].
+pub fn rotation_y(degrees: f32) -> M44 {
+    M44::rotate(V3::new(0.0, 1.0, 0.0), degrees.to_radians())
+}
+
+/// Rotates in the plane of the window in degrees, clockwise.
+pub fn rotation_z(degrees: f32) -> M44 {
+    M44::rotate(V3::new(0.0, 0.0, 1.0), degrees.to_radians())
+}
+
+/// Moves by `x`, `y` and `z`, a positive `z` moves towards the viewer.
+pub fn translation(x: f32, y: f32, z: f32) -> M44 {
+    M44::translate(x, y, z)
+}
+
+/// Interpolates between two matrices the way CSS transforms do. The matrices are decomposed into
+/// a perspective, a translation, a rotation, a skew and a scale, which are interpolated one by one,
+/// the rotation along the shortest arc. Matrices that can't be decomposed switch halfway.
+pub fn interpolate(start: &M44, end: &M44, progress: f32) -> M44 {
+    match (Decomposed::new(start), Decomposed::new(end)) {
+        (Some(start), Some(end)) => start.interpolate(&end, progress).compose(),
+        _ if progress < 0.5 => *start,
+        _ => *end,
+    }
+}
+
+/// A matrix in rows, which are indexed first.
+type Rows = [[f32; 4]; 4];
+
+fn to_rows(matrix: &M44) -> Rows {
+    array::from_fn(|row| array::from_fn(|column| matrix.rc(row, column)))
+}
+
+fn from_rows(rows: &Rows) -> M44 {
+    let values: [f32; 16] = array::from_fn(|index| rows[index / 4][index % 4]);
+    M44::row_major(&values)
+}
+
+fn identity() -> Rows {
+    array::from_fn(|row| array::from_fn(|column| if row == column { 1.0 } else { 0.0 }))
+}
+
+fn multiply(a: &Rows, b: &Rows) -> Rows {
+    array::from_fn(|row| {
+        array::from_fn(|column| (0..4).map(|index| a[row][index] * b[index][column]).sum())
+    })
+}
+
+fn dot<const N: usize>(a: &[f32; N], b: &[f32; N]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn cross(a: &[f32; 3], b: &[f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn lerp<const N: usize>(start: &[f32; N], end: &[f32; N], progress: f32) -> [f32; N] {
+    array::from_fn(|index| start[index] + (end[index] - start[index]) * progress)
+}
+
+/// Interpolates between unit quaternions along the shortest arc.
+fn slerp(start: &[f32; 4], end: &[f32; 4], progress: f32) -> [f32; 4] {
+    let mut end = *end;
+    let mut cos = dot(start, &end);
+    if cos < 0.0 {
+        end = end.map(|value| -value);
+        cos = -cos;
+    }
+    let quaternion = if cos > 0.9995 {
+        // The arc is too short to divide by its sine.
+        lerp(start, &end, progress)
+    } else {
+        let angle = cos.acos();
+        let start_weight = ((1.0 - progress) * angle).sin() / angle.sin();
+        let end_weight = (progress * angle).sin() / angle.sin();
+        array::from_fn(|index| start[index] * start_weight + end[index] * end_weight)
+    };
+    let length = dot(&quaternion, &quaternion).sqrt();
+    quaternion.map(|value| value / length)
+}
+
+/// The parts of a matrix that is the product of a perspective, a translation, a rotation, a skew
+/// and a scale, applied in reverse order.
+#[derive(Clone, Copy, Debug)]
+struct Decomposed {
+    /// The bottom row of the perspective.
+    perspective: [f32; 4],
+    translation: [f32; 3],
+    /// A unit quaternion `[x, y, z, w]`.
+    rotation: [f32; 4],
+    /// The xy, xz and yz shears.
+    skew: [f32; 3],
+    scale: [f32; 3],
+}
+
+impl Decomposed {
+    /// Returns `None` if the matrix is singular.
+    fn new(matrix: &M44) -> Option<Self> {
+        let mut rows = to_rows(matrix);
+        let w = rows[3][3];
+        if w == 0.0 {
+            return None;
+        }
+        rows = rows.map(|row| row.map(|value| value / w));
+
+        // The affine part is the matrix without the perspective.
+        let columns: [[f32; 3]; 3] =
+            array::from_fn(|column| array::from_fn(|row| rows[row][column]));
+        let determinant = dot(&columns[0], &cross(&columns[1], &columns[2]));
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let translation = [rows[0][3], rows[1][3], rows[2][3]];
+
+        // The bottom row is the perspective times the affine part.
+        let perspective = if rows[3] == [0.0, 0.0, 0.0, 1.0] {
+            [0.0, 0.0, 0.0, 1.0]
+        } else {
+            // The inverse of the linear part is its adjugate over the determinant, the rows of
+            // the adjugate are the cross products of the columns.
+            let inverse = [
+                cross(&columns[1], &columns[2]),
+                cross(&columns[2], &columns[0]),
+                cross(&columns[0], &columns[1]),
+            ]
+            .map(|row| row.map(|value| value / determinant));
+            let bottom = [rows[3][0], rows[3][1], rows[3][2]];
+            let linear: [f32; 3] = array::from_fn(|column| {
+                (0..3)
+                    .map(|index| bottom[index] * inverse[index][column])
+                    .sum()
+            });
+            [
+                linear[0],
+                linear[1],
+                linear[2],
+                rows[3][3] - dot(&linear, &translation),
+            ]
+        };
+
+        // Gram-Schmidt turns the columns into the rotation, the shears and the scale.
+        let normalize = |vector: [f32; 3]| {
+            let length = dot(&vector, &vector).sqrt();
+            (length, vector.map(|value| value / length))
+        };
+        let (scale_x, axis_x) = normalize(columns[0]);
+        let xy = dot(&axis_x, &columns[1]);
+        let (scale_y, axis_y) = normalize(array::from_fn(|index| {
+            columns[1][index] - xy * axis_x[index]
+        }));
+        let xz = dot(&axis_x, &columns[2]);
+        let yz = dot(&axis_y, &columns[2]);
+        let (scale_z, axis_z) = normalize(array::from_fn(|index| {
+            columns[2][index] - xz * axis_x[index] - yz * axis_y[index]
+        }));
+        let mut scale = [scale_x, scale_y, scale_z];
+        let skew = [xy / scale_y, xz / scale_z, yz / scale_z];
+        let mut axes = [axis_x, axis_y, axis_z];
+        if determinant < 0.0 {
+            // A mirrored matrix is a rotation with a negative scale.
+            scale = scale.map(|value| -value);
+            axes = axes.map(|axis| axis.map(|value| -value));
+        }
+
+        Some(Self {
+            perspective,
+            translation,
+            rotation: quaternion(&axes),
+            skew,
+            scale,
+        })
+    }
+
+    fn interpolate(&self, end: &Self, progress: f32) -> Self {
+        Self {
+            perspective: lerp(&self.perspective, &end.perspective, progress),
+            translation: lerp(&self.translation, &end.translation, progress),
+            rotation: slerp(&self.rotation, &end.rotation, progress),
+            skew: lerp(&self.skew, &end.skew, progress),
+            scale: lerp(&self.scale, &end.scale, progress),
+        }
+    }
+
+    fn compose(&self) -> M44 {
+        let mut perspective = identity();
+        perspective[3] = self.perspective;
+        let mut translation = identity();
+        for (row, value) in self.translation.iter().enumerate() {
+            translation[row][3] = *value;
+        }
+        let [x, y, z, w] = self.rotation;
+        let mut rotation = identity();
+        rotation[0][..3].copy_from_slice(&[
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - z * w),
+            2.0 * (x * z + y * w),
+        ]);
+        rotation[1][..3].copy_from_slice(&[
+            2.0 * (x * y + z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - x * w),
+        ]);
+        rotation[2][..3].copy_from_slice(&[
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ]);
+        let mut skew = identity();
+        skew[0][1] = self.skew[0];
+        skew[0][2] = self.skew[1];
+        skew[1][2] = self.skew[2];
+        let mut scale = identity();
+        for (index, value) in self.scale.iter().enumerate() {
+            scale[index][index] = *value;
+        }
+        let rows = [translation, rotation, skew, scale]
+            .iter()
+            .fold(perspective, |rows, matrix| multiply(&rows, matrix));
+        from_rows(&rows)
+    }
+}
+
+/// The quaternion of a rotation given by the images of the axes, which are its columns.
+fn quaternion(axes: &[[f32; 3]; 3]) -> [f32; 4] {
+    let r = |row: usize, column: usize| axes[column][row];
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    // Dividing by the largest component keeps it precise near half turns.
+    if trace > 0.0 {
+        let s = 0.5 / (trace + 1.0).sqrt();
+        [
+            (r(2, 1) - r(1, 2)) * s,
+            (r(0, 2) - r(2, 0)) * s,
+            (r(1, 0) - r(0, 1)) * s,
+            0.25 / s,
+        ]
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = 2.0 * (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt();
+        [
+            0.25 * s,
+            (r(0, 1) + r(1, 0)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+            (r(2, 1) - r(1, 2)) / s,
+        ]
+    } else if r(1, 1) > r(2, 2) {
+        let s = 2.0 * (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt();
+        [
+            (r(0, 1) + r(1, 0)) / s,
+            0.25 * s,
+            (r(1, 2) + r(2, 1)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+        ]
+    } else {
+        let s = 2.0 * (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt();
+        [
+            (r(0, 2) + r(2, 0)) / s,
+            (r(1, 2) + r(2, 1)) / s,
+            0.25 * s,
+            (r(1, 0) - r(0, 1)) / s,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod transform_test {
+    use super::{interpolate, perspective, rotation_y, rotation_z, translation, Decomposed};
+    use skia_safe::M44;
+
+    fn assert_close(a: &M44, b: &M44) {
+        for row in 0..4 {
+            for column in 0..4 {
+                let (a, b) = (a.rc(row, column), b.rc(row, column));
+                assert!((a - b).abs() < 1e-4, "{a} != {b} at ({row}, {column})");
+            }
+        }
+    }
+
+    fn scale(x: f32, y: f32) -> M44 {
+        M44::scale(x, y, 1.0)
+    }
+
+    #[test]
+    fn test_decompose_round_trip() {
+        let matrices = [
+            M44::new_identity(),
+            M44::concat(&translation(10.0, -20.0, 5.0), &rotation_z(30.0)),
+            M44::concat(&rotation_y(170.0), &scale(2.0, 0.5)),
+            M44::concat(&perspective(500.0), &rotation_y(45.0)),
+            M44::concat(&rotation_z(180.0), &scale(-1.0, 1.0)),
+            M44::concat(
+                &M44::concat(&perspective(800.0), &translation(0.0, 0.0, -50.0)),
+                &M44::concat(&rotation_z(-90.0), &scale(3.0, 3.0)),
+            ),
+        ];
+        for matrix in &matrices {
+            assert_close(&Decomposed::new(matrix).unwrap().compose(), matrix);
+        }
+        assert!(Decomposed::new(&scale(0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_interpolate_rotation() {
+        assert_close(
+            &interpolate(&rotation_z(0.0), &rotation_z(90.0), 0.5),
+            &rotation_z(45.0),
+        );
+        // The rotation takes the shortest way.
+        assert_close(
+            &interpolate(&rotation_z(10.0), &rotation_z(350.0), 0.5),
+            &M44::new_identity(),
+        );
+        let start = M44::concat(&perspective(500.0), &rotation_y(0.0));
+        let end = M44::concat(&perspective(500.0), &rotation_y(60.0));
+        assert_close(
+            &interpolate(&start, &end, 0.5),
+            &M44::concat(&perspective(500.0), &rotation_y(30.0)),
+        );
+    }
+
+    #[test]
+    fn test_interpolate_translation_and_scale() {
+        let start = translation(10.0, 0.0, 0.0);
+        let end = M44::concat(&translation(30.0, 0.0, 0.0), &scale(3.0, 3.0));
+        assert_close(
+            &interpolate(&start, &end, 0.5),
+            &M44::concat(&translation(20.0, 0.0, 0.0), &scale(2.0, 2.0)),
+        );
+        assert_close(&interpolate(&start, &end, 0.0), &start);
+        assert_close(&interpolate(&start, &end, 1.0), &end);
+    }
+
+    #[test]
+    fn test_interpolate_singular() {
+        let start = scale(0.0, 0.0);
+        let end = scale(2.0, 2.0);
+        assert_eq!(interpolate(&start, &end, 0.25), start);
+        assert_eq!(interpolate(&start, &end, 0.75), end);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::shared::{Children, Gettable, Observable, Shared, SharedUsize};
 use crate::ui::app::WindowContext;
-use crate::ui::item::{CustomProperty, ItemData, LogicalX, MeasureMode, Orientation, Size};
+use crate::ui::item::{make_reorderable, CustomProperty, ItemData, LogicalX, MeasureMode, Orientation, Size};
 use crate::ui::Item;
 use proc_macro::item;
 use std::ops::Not;
@@ -1331,6 +1331,22 @@ impl Flex {
         }
     }
 
+    /// Lets the children be reordered by dragging them. `on_reorder` is called with the old and the
+    /// new index of the dragged child after it has been moved.
+    pub fn reorderable(self, mut on_reorder: impl FnMut(usize, usize) + 'static) -> Self {
+        let children = self.item.data().get_children().clone();
+        make_reorderable(&self.item, move |from, to| {
+            {
+                let mut children = children.lock();
+                let child = children.remove(from);
+                children.insert(to, child);
+            }
+            children.notify();
+            on_reorder(from, to);
+        });
+        self
+    }
+
     pub fn flex_direction(self, direction: impl Into<Shared<FlexDirection>>) -> Self {
         {
             let id = self.item.data().get_id();
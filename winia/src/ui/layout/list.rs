@@ -2,7 +2,7 @@ use crate::collection::Operation;
 use crate::collection::WVec;
 use crate::shared::{Children, Gettable, Settable, Shared, SharedUsize};
 use crate::ui::app::WindowContext;
-use crate::ui::item::{CustomProperty, MeasureMode, Scroller};
+use crate::ui::item::{make_reorderable, CustomProperty, DragAxis, MeasureMode, Scroller};
 use crate::ui::Item;
 use clonelet::clone;
 
@@ -141,6 +141,34 @@ impl<T: Send + 'static> List<T> {
                                             );
                                     }
                                 }
+                                Operation::Move(from, to) => {
+                                    let end = start_index + children_len;
+                                    let from_visible = from >= start_index && from < end;
+                                    let to_visible = to >= start_index && to < end;
+                                    let mut children = children.lock();
+                                    match (from_visible, to_visible) {
+                                        // The item keeps its child, only its place changes.
+                                        (true, true) => {
+                                            let child = children.remove(from - start_index);
+                                            children.insert(to - start_index, child);
+                                        }
+                                        (true, false) => {
+                                            children.remove(from - start_index);
+                                        }
+                                        (false, true) => {
+                                            children.insert(
+                                                to - start_index,
+                                                build_item(
+                                                    &item_builder,
+                                                    item.get_window_context(),
+                                                    &property.items,
+                                                    to,
+                                                ),
+                                            );
+                                        }
+                                        (false, false) => {}
+                                    }
+                                }
                                 _ => children.clear(),
                             }
                         }
@@ -406,9 +434,27 @@ impl<T: Send + 'static> List<T> {
                 }
             });
 
+        let item = item.gesture(Scroller::drag_gesture(&scroller, &Shared::from(DragAxis::Vertical)));
         Self { item, property }
     }
 
+    /// Lets the items be reordered by dragging them. `on_reorder` is called with the old and the
+    /// new index of the dragged item after it has been moved in `items`.
+    pub fn reorderable(self, mut on_reorder: impl FnMut(usize, usize) + 'static) -> Self {
+        let property = self.property.clone();
+        make_reorderable(&self.item, move |from, to| {
+            let (items, start_index) = {
+                let property = property.lock();
+                (property.items.clone(), property.start_index.get())
+            };
+            let (from, to) = (start_index + from, start_index + to);
+            // Recorded as a move, so the list keeps the child of the item instead of rebuilding it.
+            items.write(|items| items.move_item(from, to));
+            on_reorder(from, to);
+        });
+        self
+    }
+
     pub fn item(self) -> Item {
         self.item
     }
@@ -2,7 +2,7 @@ use crate::impl_property_layout;
 use crate::shared::{Children, Gettable, Settable, Shared};
 use crate::ui::app::WindowContext;
 use crate::ui::item::{
-    HorizontalAlignment, LogicalX, MeasureMode, MouseScrollDelta, Orientation, Scroller,
+    DragAxis, HorizontalAlignment, LogicalX, MeasureMode, MouseScrollDelta, Orientation, Scroller,
     VerticalAlignment,
 };
 use crate::ui::Item;
@@ -45,10 +45,12 @@ impl ScrollArea {
             window_context.event_loop_proxy(),
             (true, true),
         ));
+        let drag_axis = Shared::from(DragAxis::Vertical);
         item.data()
             .set_measure({
                 let property = property.clone();
                 let scroller = scroller.clone();
+                let drag_axis = drag_axis.clone();
                 move |item, width_mode, height_mode| {
                     if item.get_children().lock().len() > 1 {
                         panic!("ScrollArea can only have one child");
@@ -58,6 +60,11 @@ impl ScrollArea {
                     let vertical_scrollable = property.vertical_scrollable.get();
                     let mut scroller = scroller.lock();
                     scroller.set_scroll_enabled((horizontal_scrollable, vertical_scrollable));
+                    drag_axis.set(match (horizontal_scrollable, vertical_scrollable) {
+                        (true, true) => DragAxis::Both,
+                        (true, false) => DragAxis::Horizontal,
+                        _ => DragAxis::Vertical,
+                    });
 
                     item.measure_children(width_mode, height_mode);
                     let (child_max_width, child_max_height) = item
@@ -125,8 +132,13 @@ impl ScrollArea {
                                 *scroller.y_deltas() = 0.0;
                             }
 
+                            let unclamped_scroll_position = scroll_position;
                             scroll_position.0 = scroll_position.0.clamp(0.0, 1.0);
                             scroll_position.1 = scroll_position.1.clamp(0.0, 1.0);
+                            if scroll_position != unclamped_scroll_position {
+                                // The edge has been reached, a fling has nothing left to move.
+                                scroller.stop_fling();
+                            }
                             property.scroll_position.set(scroll_position);
                         }
 
@@ -242,9 +254,9 @@ impl ScrollArea {
         item.data().set_dispatch_draw({
             let scroller = scroller.clone();
             let property = property.clone();
-            move |item, surface, x, y| {
+            move |item, canvas, x, y| {
                 // let window_context = item.get_window_context();
-                dispatch_draw.lock()(item, surface, x, y);
+                dispatch_draw.lock()(item, canvas, x, y);
                 let display_parameter = item.get_display_parameter();
                 let child_display_parameter = item
                     .get_children()
@@ -258,7 +270,6 @@ impl ScrollArea {
                         child_display_parameter.width,
                         child_display_parameter.height,
                     );
-                    let canvas = surface.canvas();
                     let property = property.lock();
                     // scroller.draw(
                     //     item.get_window_context(),
@@ -271,6 +282,7 @@ impl ScrollArea {
                 }
             }
         });
+        let item = item.gesture(Scroller::drag_gesture(&scroller, &drag_axis));
         Self { item, property }
     }
 
@@ -0,0 +1,8 @@
+//! The focus ring drawn around the focused item after keyboard navigation.
+
+pub static RING_COLOR: &str = "focus_ring_color";
+pub static RING_WIDTH: &str = "focus_ring_width";
+/// The gap between the item and the ring.
+pub static RING_OFFSET: &str = "focus_ring_offset";
+/// The corner radius of the ring for items that don't set their own.
+pub static RING_RADIUS: &str = "focus_ring_radius";
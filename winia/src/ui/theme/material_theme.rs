@@ -1,7 +1,9 @@
 use crate::ui::theme::shape;
 use crate::ui::theme::shape::Corner;
 use crate::ui::theme::typescale::TypeScale;
-use crate::ui::theme::{color, elevation, typescale};
+use crate::ui::theme::{color, elevation, focus, motion, typescale};
+use crate::ui::animation::interpolator::CubicBezier;
+use std::time::Duration;
 use crate::ui::Theme;
 use material_colors::color::Argb;
 use material_colors::theme::ThemeBuilder;
@@ -150,6 +152,8 @@ pub fn material_theme(color: Color, is_dark: bool) -> Theme {
         .set_color(color::WINDOW_BACKGROUND_COLOR, color::BACKGROUND);
 
     add_elevation(&mut theme);
+    add_focus(&mut theme);
+    add_motion(&mut theme);
     add_shape_corner(&mut theme);
     add_typescale(&mut theme);
 
@@ -167,6 +171,56 @@ fn add_elevation(theme: &mut Theme) {
     theme.set_dimension(elevation::LEVEL_5, 12.0);
 }
 
+fn add_focus(theme: &mut Theme) {
+    theme
+        .set_color(focus::RING_COLOR, color::SECONDARY)
+        .set_dimension(focus::RING_WIDTH, 3.0)
+        .set_dimension(focus::RING_OFFSET, 2.0)
+        .set_dimension(focus::RING_RADIUS, 8.0);
+}
+
+fn add_motion(theme: &mut Theme) {
+    use motion::{duration, easing};
+    let easings = [
+        // The emphasized easing is a two-segment path in the spec, this is its usual single-curve approximation.
+        (easing::EMPHASIZED, CubicBezier::new(0.2, 0.0, 0.0, 1.0)),
+        (easing::EMPHASIZED_DECELERATE, CubicBezier::new(0.05, 0.7, 0.1, 1.0)),
+        (easing::EMPHASIZED_ACCELERATE, CubicBezier::new(0.3, 0.0, 0.8, 0.15)),
+        (easing::STANDARD, CubicBezier::new(0.2, 0.0, 0.0, 1.0)),
+        (easing::STANDARD_DECELERATE, CubicBezier::new(0.0, 0.0, 0.0, 1.0)),
+        (easing::STANDARD_ACCELERATE, CubicBezier::new(0.3, 0.0, 1.0, 1.0)),
+        (easing::LEGACY, CubicBezier::new(0.4, 0.0, 0.2, 1.0)),
+        (easing::LEGACY_DECELERATE, CubicBezier::new(0.0, 0.0, 0.2, 1.0)),
+        (easing::LEGACY_ACCELERATE, CubicBezier::new(0.4, 0.0, 1.0, 1.0)),
+        (easing::LINEAR, CubicBezier::new(0.0, 0.0, 1.0, 1.0)),
+    ];
+    for (key, cubic_bezier) in easings {
+        theme.set_style(key, Box::new(cubic_bezier));
+    }
+
+    let durations = [
+        (duration::SHORT1, 50),
+        (duration::SHORT2, 100),
+        (duration::SHORT3, 150),
+        (duration::SHORT4, 200),
+        (duration::MEDIUM1, 250),
+        (duration::MEDIUM2, 300),
+        (duration::MEDIUM3, 350),
+        (duration::MEDIUM4, 400),
+        (duration::LONG1, 450),
+        (duration::LONG2, 500),
+        (duration::LONG3, 550),
+        (duration::LONG4, 600),
+        (duration::EXTRA_LONG1, 700),
+        (duration::EXTRA_LONG2, 800),
+        (duration::EXTRA_LONG3, 900),
+        (duration::EXTRA_LONG4, 1000),
+    ];
+    for (key, milliseconds) in durations {
+        theme.set_style(key, Box::new(Duration::from_millis(milliseconds)));
+    }
+}
+
 fn add_shape_corner(theme: &mut Theme) {
     fn add_a_shape(
         theme: &mut Theme,
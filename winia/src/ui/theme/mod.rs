@@ -5,6 +5,8 @@ pub mod color;
 mod material_theme;
 pub mod styles;
 pub mod elevation;
+pub mod focus;
+pub mod motion;
 pub mod shape;
 pub mod typescale;
 
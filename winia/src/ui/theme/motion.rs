@@ -0,0 +1,37 @@
+//! Material 3 motion tokens.
+//!
+//! Easings are stored as [`CubicBezier`](crate::ui::animation::interpolator::CubicBezier) styles
+//! and durations as [`Duration`](std::time::Duration) styles,
+//! see [`Theme::get_easing`](crate::ui::Theme::get_easing) and [`Theme::get_duration`](crate::ui::Theme::get_duration).
+
+pub mod easing {
+    pub static EMPHASIZED: &str = "motion_easing_emphasized";
+    pub static EMPHASIZED_DECELERATE: &str = "motion_easing_emphasized_decelerate";
+    pub static EMPHASIZED_ACCELERATE: &str = "motion_easing_emphasized_accelerate";
+    pub static STANDARD: &str = "motion_easing_standard";
+    pub static STANDARD_DECELERATE: &str = "motion_easing_standard_decelerate";
+    pub static STANDARD_ACCELERATE: &str = "motion_easing_standard_accelerate";
+    pub static LEGACY: &str = "motion_easing_legacy";
+    pub static LEGACY_DECELERATE: &str = "motion_easing_legacy_decelerate";
+    pub static LEGACY_ACCELERATE: &str = "motion_easing_legacy_accelerate";
+    pub static LINEAR: &str = "motion_easing_linear";
+}
+
+pub mod duration {
+    pub static SHORT1: &str = "motion_duration_short1";
+    pub static SHORT2: &str = "motion_duration_short2";
+    pub static SHORT3: &str = "motion_duration_short3";
+    pub static SHORT4: &str = "motion_duration_short4";
+    pub static MEDIUM1: &str = "motion_duration_medium1";
+    pub static MEDIUM2: &str = "motion_duration_medium2";
+    pub static MEDIUM3: &str = "motion_duration_medium3";
+    pub static MEDIUM4: &str = "motion_duration_medium4";
+    pub static LONG1: &str = "motion_duration_long1";
+    pub static LONG2: &str = "motion_duration_long2";
+    pub static LONG3: &str = "motion_duration_long3";
+    pub static LONG4: &str = "motion_duration_long4";
+    pub static EXTRA_LONG1: &str = "motion_duration_extra_long1";
+    pub static EXTRA_LONG2: &str = "motion_duration_extra_long2";
+    pub static EXTRA_LONG3: &str = "motion_duration_extra_long3";
+    pub static EXTRA_LONG4: &str = "motion_duration_extra_long4";
+}
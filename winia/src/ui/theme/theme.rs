@@ -1,5 +1,8 @@
 use std::any::Any;
+use std::time::Duration;
+use crate::ui::animation::interpolator::CubicBezier;
 use crate::ui::app::WindowContext;
+use crate::ui::item::Brush;
 use crate::ui::Item;
 use parking_lot::Mutex;
 use skia_safe::Color;
@@ -82,6 +85,18 @@ impl From<Arc<Mutex<dyn Fn(WindowContext) -> Item + Send>>>
     }
 }
 
+impl From<&str> for ThemeValue<Brush> {
+    fn from(s: &str) -> Self {
+        ThemeValue::Ref(s.to_string())
+    }
+}
+
+impl From<Brush> for ThemeValue<Brush> {
+    fn from(brush: Brush) -> Self {
+        ThemeValue::Direct(brush)
+    }
+}
+
 impl From<&str> for ThemeValue<Corner> {
     fn from(s: &str) -> Self {
         ThemeValue::Ref(s.to_string())
@@ -96,6 +111,7 @@ impl From<Corner> for ThemeValue<Corner> {
 
 pub struct Theme {
     colors: HashMap<String, ThemeValue<Color>>,
+    brushes: HashMap<String, ThemeValue<Brush>>,
     dimensions: HashMap<String, ThemeValue<f32>>,
     bools: HashMap<String, ThemeValue<bool>>,
     strings: HashMap<String, ThemeValue<String>>,
@@ -113,6 +129,7 @@ impl Theme {
     pub(crate) fn new() -> Self {
         Self {
             colors: HashMap::new(),
+            brushes: HashMap::new(),
             dimensions: HashMap::new(),
             bools: HashMap::new(),
             strings: HashMap::new(),
@@ -130,6 +147,15 @@ impl Theme {
         self
     }
 
+    pub fn set_brush(
+        &mut self,
+        key: impl Into<String>,
+        brush: impl Into<ThemeValue<Brush>>,
+    ) -> &mut Self {
+        self.brushes.insert(key.into(), brush.into());
+        self
+    }
+
     pub fn set_dimension(
         &mut self,
         key: impl Into<String>,
@@ -205,6 +231,16 @@ impl Theme {
         Self::get_value(&self.colors, key)
     }
 
+    /// Returns the brush registered under the key, or a solid brush of the color registered under
+    /// it, so components painted with brushes still follow the color keys of the theme.
+    pub fn get_brush(&self, key: impl Into<String>) -> Option<Brush> {
+        let key = key.into();
+        match Self::get_value(&self.brushes, key.as_str()) {
+            Some(brush) => Some(brush.clone()),
+            None => self.get_color(key).map(|color| Brush::Solid(*color)),
+        }
+    }
+
     pub fn get_dimension(&self, key: impl Into<String>) -> Option<&f32> {
         Self::get_value(&self.dimensions, key)
     }
@@ -230,6 +266,41 @@ impl Theme {
         let style = self.styles.get(&key.into())?;
         Some(style.downcast_ref::<T>()?)
     }
+
+    /// The keys of the colors in the theme, in no particular order.
+    pub fn color_keys(&self) -> impl Iterator<Item = &str> {
+        self.colors.keys().map(String::as_str)
+    }
+
+    /// The keys of the brushes in the theme, in no particular order.
+    pub fn brush_keys(&self) -> impl Iterator<Item = &str> {
+        self.brushes.keys().map(String::as_str)
+    }
+
+    /// The keys of the dimensions in the theme, in no particular order.
+    pub fn dimension_keys(&self) -> impl Iterator<Item = &str> {
+        self.dimensions.keys().map(String::as_str)
+    }
+
+    /// The keys of the booleans in the theme, in no particular order.
+    pub fn bool_keys(&self) -> impl Iterator<Item = &str> {
+        self.bools.keys().map(String::as_str)
+    }
+
+    /// The keys of the strings in the theme, in no particular order.
+    pub fn string_keys(&self) -> impl Iterator<Item = &str> {
+        self.strings.keys().map(String::as_str)
+    }
+
+    /// Returns an easing registered under one of the [`motion::easing`](crate::ui::theme::motion::easing) keys.
+    pub fn get_easing(&self, key: impl Into<String>) -> Option<CubicBezier> {
+        self.get_style::<CubicBezier>(key).copied()
+    }
+
+    /// Returns a duration registered under one of the [`motion::duration`](crate::ui::theme::motion::duration) keys.
+    pub fn get_duration(&self, key: impl Into<String>) -> Option<Duration> {
+        self.get_style::<Duration>(key).copied()
+    }
 }
 
 #[derive(Clone)]